@@ -0,0 +1,532 @@
+use std::path::PathBuf;
+
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::solana_program::instruction::Instruction;
+use anchor_lang::{system_program, Discriminator};
+use anchor_spl::associated_token::{self, get_associated_token_address_with_program_id};
+use anyhow::{anyhow, bail, Result};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use clap::{Parser, Subcommand};
+use gotsol::state::{Merchant, RefundRecord};
+use gotsol_client::events::{parse_logged_events, LoggedEvent};
+use gotsol_client::rpc::{account_keys, inner_instruction_events, string_list, transaction_events, RpcClient};
+use gotsol_client::{accounts, instructions, pda, HOUSE, PROGRAM_ID};
+use serde_json::{json, Map, Value};
+use solana_keypair::{read_keypair_file, Keypair};
+use solana_signer::Signer;
+use solana_transaction::Transaction;
+
+#[derive(Parser)]
+#[command(name = "gotsol", version, about = "Admin and merchant operations for the GotSOL program")]
+struct Cli {
+    /// JSON-RPC endpoint
+    #[arg(long, short = 'u', env = "GOTSOL_RPC_URL", default_value = "http://127.0.0.1:8899", global = true)]
+    url: String,
+
+    /// Signing keypair (merchant owner, or an AUTH key for admin commands); defaults to the Solana CLI wallet
+    #[arg(long, short = 'k', env = "GOTSOL_KEYPAIR", global = true)]
+    keypair: Option<PathBuf>,
+
+    /// Keypair of an approved fee payer that sponsors fees and rent
+    #[arg(long, env = "GOTSOL_FEE_PAYER", global = true)]
+    fee_payer: Option<PathBuf>,
+
+    #[arg(long, default_value = "confirmed", global = true)]
+    commitment: String,
+
+    /// Simulate the transaction instead of sending it
+    #[arg(long, global = true)]
+    dry_run: bool,
+
+    /// Print machine-readable JSON
+    #[arg(long, global = true)]
+    json: bool,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Create a merchant owned by the signing keypair
+    CreateMerchant {
+        #[arg(long)]
+        name: String,
+    },
+    /// Withdraw SOL from the vault, or a stablecoin when --mint is given
+    Withdraw {
+        #[arg(long)]
+        merchant: Pubkey,
+        #[arg(long)]
+        mint: Option<Pubkey>,
+        /// Base units to withdraw; everything available when omitted
+        #[arg(long)]
+        amount: Option<u64>,
+    },
+    /// Refund SOL from the vault, or a stablecoin when --mint is given
+    Refund {
+        #[arg(long)]
+        merchant: Pubkey,
+        /// Signature of the payment being refunded
+        #[arg(long)]
+        original_tx_sig: String,
+        #[arg(long)]
+        recipient: Pubkey,
+        #[arg(long)]
+        amount: u64,
+        #[arg(long)]
+        mint: Option<Pubkey>,
+    },
+    /// Set a merchant's fee eligibility (AUTH only)
+    SetStatus {
+        #[arg(long)]
+        merchant: Pubkey,
+        #[arg(long, action = clap::ArgAction::Set)]
+        fee_eligible: bool,
+    },
+    /// Close a merchant account owned by the signing keypair
+    CloseMerchant {
+        #[arg(long)]
+        merchant: Pubkey,
+    },
+    /// Close a refund record and return its rent to whoever funded it (AUTH only)
+    CloseRefund {
+        #[arg(long)]
+        original_tx_sig: String,
+    },
+    /// List merchants owned by an address (the signing keypair by default)
+    ListMerchants {
+        #[arg(long)]
+        owner: Option<Pubkey>,
+    },
+    /// Decode the GotSOL events emitted by a transaction
+    DecodeEvents {
+        signature: String,
+    },
+}
+
+struct App {
+    rpc: RpcClient,
+    keypair: Option<PathBuf>,
+    fee_payer: Option<Keypair>,
+    dry_run: bool,
+}
+
+fn main() {
+    let cli = Cli::parse();
+    let json = cli.json;
+
+    match run(cli) {
+        Ok(report) => print_report(json, &report),
+        Err(error) => {
+            if json {
+                println!("{}", json!({ "error": error.to_string() }));
+            } else {
+                eprintln!("error: {error:#}");
+            }
+            std::process::exit(1);
+        }
+    }
+}
+
+fn run(cli: Cli) -> Result<Value> {
+    let app = App {
+        rpc: RpcClient::new(cli.url, cli.commitment),
+        keypair: cli.keypair,
+        fee_payer: cli.fee_payer.as_ref().map(load_keypair).transpose()?,
+        dry_run: cli.dry_run,
+    };
+
+    match cli.command {
+        Command::CreateMerchant { name } => app.create_merchant(name),
+        Command::Withdraw { merchant, mint: None, amount } => app.withdraw_sol(merchant, amount),
+        Command::Withdraw { merchant, mint: Some(mint), amount } => app.withdraw_spl(merchant, mint, amount),
+        Command::Refund { merchant, original_tx_sig, recipient, amount, mint: None } => {
+            app.refund_sol(merchant, original_tx_sig, recipient, amount)
+        }
+        Command::Refund { merchant, original_tx_sig, recipient, amount, mint: Some(mint) } => {
+            app.refund_spl(merchant, mint, original_tx_sig, recipient, amount)
+        }
+        Command::SetStatus { merchant, fee_eligible } => app.set_status(merchant, fee_eligible),
+        Command::CloseMerchant { merchant } => app.close_merchant(merchant),
+        Command::CloseRefund { original_tx_sig } => app.close_refund(original_tx_sig),
+        Command::ListMerchants { owner } => app.list_merchants(owner),
+        Command::DecodeEvents { signature } => app.decode_events(&signature),
+    }
+}
+
+impl App {
+    fn signer(&self) -> Result<Keypair> {
+        let path = match &self.keypair {
+            Some(path) => path.clone(),
+            None => {
+                let home = std::env::var("HOME").map_err(|_| anyhow!("HOME is not set; pass --keypair"))?;
+                PathBuf::from(home).join(".config/solana/id.json")
+            }
+        };
+        load_keypair(&path)
+    }
+
+    /// Accounts every sponsored instruction needs once our fee payer signs:
+    /// (fee_payer, sponsorship_budget, global)
+    fn sponsorship(&self, merchant: &Pubkey) -> (Option<Pubkey>, Option<Pubkey>, Option<Pubkey>) {
+        match &self.fee_payer {
+            Some(fee_payer) => (
+                Some(fee_payer.pubkey()),
+                Some(pda::sponsorship_budget(merchant).0),
+                Some(pda::global().0),
+            ),
+            None => (None, None, None),
+        }
+    }
+
+    fn token_program_for(&self, mint: &Pubkey) -> Result<Pubkey> {
+        let account = self.rpc.account(mint)?.ok_or_else(|| anyhow!("mint {mint} not found"))?;
+        if account.owner != anchor_spl::token::ID && account.owner != anchor_spl::token_2022::ID {
+            bail!("{mint} is not a token mint (owner {})", account.owner);
+        }
+        Ok(account.owner)
+    }
+
+    fn create_merchant(&self, name: String) -> Result<Value> {
+        let owner = self.signer()?;
+        let merchant = pda::merchant(&name, &owner.pubkey()).0;
+
+        let ix = instructions::create_merchant(
+            gotsol::accounts::CreateMerchant {
+                owner: owner.pubkey(),
+                merchant,
+                vault: pda::vault(&merchant).0,
+                associated_token_program: associated_token::ID,
+                token_program: anchor_spl::token::ID,
+                system_program: system_program::ID,
+            },
+            name,
+        );
+
+        let mut report = self.submit(ix, &owner)?;
+        report["merchant"] = json!(merchant.to_string());
+        Ok(report)
+    }
+
+    fn withdraw_sol(&self, merchant: Pubkey, amount: Option<u64>) -> Result<Value> {
+        let owner = self.signer()?;
+
+        let accounts = gotsol::accounts::WithdrawSol {
+            owner: owner.pubkey(),
+            merchant,
+            vault: pda::vault(&merchant).0,
+            withdrawal_limit: pda::sol_withdrawal_limit(&merchant).0,
+            house: HOUSE,
+            system_program: system_program::ID,
+            event_authority: pda::event_authority().0,
+            program: PROGRAM_ID,
+        };
+        let ix = match amount {
+            Some(amount) => instructions::withdraw_sol(accounts, amount),
+            None => instructions::withdraw_sol_all(accounts),
+        };
+
+        self.submit(ix, &owner)
+    }
+
+    fn withdraw_spl(&self, merchant: Pubkey, mint: Pubkey, amount: Option<u64>) -> Result<Value> {
+        let owner = self.signer()?;
+        let token_program = self.token_program_for(&mint)?;
+        let (fee_payer, sponsorship_budget, global) = self.sponsorship(&merchant);
+
+        let accounts = gotsol::accounts::WithdrawSpl {
+            fee_payer,
+            owner: owner.pubkey(),
+            merchant,
+            sponsorship_budget,
+            stablecoin_mint: mint,
+            merchant_stablecoin_ata: get_associated_token_address_with_program_id(&merchant, &mint, &token_program),
+            withdrawal_limit: pda::spl_withdrawal_limit(&merchant, &mint).0,
+            owner_stablecoin_ata: get_associated_token_address_with_program_id(&owner.pubkey(), &mint, &token_program),
+            house: HOUSE,
+            house_stablecoin_ata: get_associated_token_address_with_program_id(&HOUSE, &mint, &token_program),
+            global,
+            fee_payer_stablecoin_ata: None,
+            associated_token_program: associated_token::ID,
+            token_program,
+            system_program: system_program::ID,
+            event_authority: pda::event_authority().0,
+            program: PROGRAM_ID,
+        };
+        let ix = match amount {
+            Some(amount) => instructions::withdraw_spl(accounts, amount),
+            None => instructions::withdraw_spl_all(accounts),
+        };
+
+        self.submit(ix, &owner)
+    }
+
+    fn refund_sol(&self, merchant: Pubkey, original_tx_sig: String, recipient: Pubkey, amount: u64) -> Result<Value> {
+        let owner = self.signer()?;
+        let (fee_payer, sponsorship_budget, global) = self.sponsorship(&merchant);
+        let refund_record = pda::refund(&original_tx_sig).0;
+
+        let ix = instructions::refund_sol(
+            gotsol::accounts::RefundSol {
+                fee_payer,
+                owner: owner.pubkey(),
+                merchant,
+                sponsorship_budget,
+                global,
+                vault: pda::vault(&merchant).0,
+                withdrawal_limit: pda::sol_withdrawal_limit(&merchant).0,
+                refund_record,
+                recipient,
+                system_program: system_program::ID,
+                event_authority: pda::event_authority().0,
+                program: PROGRAM_ID,
+            },
+            original_tx_sig,
+            amount,
+        );
+
+        let mut report = self.submit(ix, &owner)?;
+        report["refund_record"] = json!(refund_record.to_string());
+        Ok(report)
+    }
+
+    fn refund_spl(&self, merchant: Pubkey, mint: Pubkey, original_tx_sig: String, recipient: Pubkey, amount: u64) -> Result<Value> {
+        let owner = self.signer()?;
+        let token_program = self.token_program_for(&mint)?;
+        let (fee_payer, sponsorship_budget, global) = self.sponsorship(&merchant);
+        let refund_record = pda::refund(&original_tx_sig).0;
+
+        let ix = instructions::refund_spl(
+            gotsol::accounts::RefundSpl {
+                fee_payer,
+                owner: owner.pubkey(),
+                merchant,
+                sponsorship_budget,
+                stablecoin_mint: mint,
+                merchant_stablecoin_ata: get_associated_token_address_with_program_id(&merchant, &mint, &token_program),
+                withdrawal_limit: pda::spl_withdrawal_limit(&merchant, &mint).0,
+                recipient_stablecoin_ata: get_associated_token_address_with_program_id(&recipient, &mint, &token_program),
+                refund_record,
+                recipient,
+                global,
+                fee_payer_stablecoin_ata: None,
+                associated_token_program: associated_token::ID,
+                token_program,
+                system_program: system_program::ID,
+                event_authority: pda::event_authority().0,
+                program: PROGRAM_ID,
+            },
+            original_tx_sig,
+            amount,
+        );
+
+        let mut report = self.submit(ix, &owner)?;
+        report["refund_record"] = json!(refund_record.to_string());
+        Ok(report)
+    }
+
+    fn set_status(&self, merchant: Pubkey, fee_eligible: bool) -> Result<Value> {
+        let auth = self.signer()?;
+
+        let ix = instructions::set_merchant_status(
+            gotsol::accounts::SetMerchantStatus {
+                auth: auth.pubkey(),
+                merchant,
+                system_program: system_program::ID,
+            },
+            fee_eligible,
+        );
+
+        self.submit(ix, &auth)
+    }
+
+    fn close_merchant(&self, merchant: Pubkey) -> Result<Value> {
+        let owner = self.signer()?;
+
+        let ix = instructions::close_merchant(gotsol::accounts::CloseMerchant {
+            owner: owner.pubkey(),
+            merchant,
+            system_program: system_program::ID,
+            event_authority: pda::event_authority().0,
+            program: PROGRAM_ID,
+        });
+
+        self.submit(ix, &owner)
+    }
+
+    fn close_refund(&self, original_tx_sig: String) -> Result<Value> {
+        let auth = self.signer()?;
+        let refund_record = pda::refund(&original_tx_sig).0;
+        let account = self
+            .rpc
+            .account(&refund_record)?
+            .ok_or_else(|| anyhow!("no refund record for {original_tx_sig}"))?;
+
+        // Records from before the rent payer was stored don't name it, so find who funded them
+        let ix = if account.data.len() == RefundRecord::LEGACY_LEN {
+            instructions::close_legacy_refund(
+                gotsol::accounts::CloseLegacyRefund {
+                    auth: auth.pubkey(),
+                    refund_record,
+                    rent_payer: self.legacy_refund_funder(&refund_record)?,
+                    system_program: system_program::ID,
+                },
+                original_tx_sig,
+            )
+        } else {
+            let record = accounts::decode_refund_record(&account.data)?;
+            instructions::close_refund(gotsol::accounts::CloseRefund {
+                auth: auth.pubkey(),
+                refund_record,
+                rent_payer: record.rent_payer,
+                system_program: system_program::ID,
+            })
+        };
+
+        self.submit(ix, &auth)
+    }
+
+    /// The fee payer of the GotSOL transaction that created a legacy refund record: our node's
+    /// fee payer when it sponsored the refund, otherwise the merchant owner
+    fn legacy_refund_funder(&self, refund_record: &Pubkey) -> Result<Pubkey> {
+        for signature in self.rpc.signatures_since(refund_record, None)? {
+            let Some(transaction) = self.rpc.transaction(&signature)? else {
+                continue;
+            };
+            if !transaction["meta"]["err"].is_null() {
+                continue;
+            }
+
+            // Skip plain transfers into the address; only the refund itself invokes GotSOL
+            let keys = account_keys(&transaction)?;
+            let invokes_gotsol = transaction["transaction"]["message"]["instructions"]
+                .as_array()
+                .into_iter()
+                .flatten()
+                .any(|ix| ix["programIdIndex"].as_u64().and_then(|index| keys.get(index as usize)) == Some(&PROGRAM_ID));
+            if invokes_gotsol {
+                return keys.first().copied().ok_or_else(|| anyhow!("transaction {signature} has no fee payer"));
+            }
+        }
+
+        bail!("can't find the transaction that created refund record {refund_record}")
+    }
+
+    fn list_merchants(&self, owner: Option<Pubkey>) -> Result<Value> {
+        let owner = match owner {
+            Some(owner) => owner,
+            None => self.signer()?.pubkey(),
+        };
+
+        let filters = json!([
+            { "memcmp": { "offset": 0, "bytes": STANDARD.encode(Merchant::DISCRIMINATOR), "encoding": "base64" } },
+            { "memcmp": { "offset": 8, "bytes": owner.to_string() } },
+        ]);
+
+        let merchants = self
+            .rpc
+            .program_accounts(&PROGRAM_ID, filters)?
+            .into_iter()
+            .map(|(address, account)| {
+                let merchant = accounts::decode_merchant(&account.data)?;
+                Ok(json!({
+                    "address": address.to_string(),
+                    "entity_name": merchant.entity_name,
+                    "fee_eligible": merchant.fee_eligible,
+                    "vault": pda::vault(&address).0.to_string(),
+                }))
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(json!({ "owner": owner.to_string(), "merchants": merchants }))
+    }
+
+    fn decode_events(&self, signature: &str) -> Result<Value> {
+        let transaction = self.rpc.transaction(signature)?.ok_or_else(|| anyhow!("transaction {signature} not found"))?;
+        Ok(json!({ "signature": signature, "events": events_json(&transaction_events(&transaction)?) }))
+    }
+
+    /// Signs with the wallet (and our fee payer, who then pays the fee) and either
+    /// simulates or sends the transaction, reporting the events it emitted
+    fn submit(&self, ix: Instruction, signer: &Keypair) -> Result<Value> {
+        let payer = self.fee_payer.as_ref().unwrap_or(signer);
+        let mut signers = vec![signer];
+        if payer.pubkey() != signer.pubkey() {
+            signers.push(payer);
+        }
+
+        let blockhash = self.rpc.latest_blockhash()?;
+        let transaction = Transaction::new_signed_with_payer(&[ix], Some(&payer.pubkey()), &signers, blockhash);
+        let wire = bincode::serialize(&transaction)?;
+
+        if self.dry_run {
+            let simulation = self.rpc.simulate(&wire)?;
+            let logs = string_list(&simulation["logs"]);
+            let mut events = parse_logged_events(&logs);
+            events.extend(inner_instruction_events(&simulation["innerInstructions"], &transaction.message.account_keys));
+            events.sort_by_key(|logged| logged.instruction_index);
+
+            return Ok(json!({
+                "dry_run": true,
+                "error": simulation["err"],
+                "units_consumed": simulation["unitsConsumed"],
+                "events": events_json(&events),
+                "logs": logs,
+            }));
+        }
+
+        let signature = self.rpc.send_and_confirm(&wire)?;
+        let transaction = self.rpc.transaction(&signature)?.ok_or_else(|| anyhow!("transaction {signature} not found"))?;
+        Ok(json!({ "signature": signature, "events": events_json(&transaction_events(&transaction)?) }))
+    }
+}
+
+fn load_keypair(path: &PathBuf) -> Result<Keypair> {
+    read_keypair_file(path).map_err(|e| anyhow!("failed to read keypair {}: {e}", path.display()))
+}
+
+fn events_json(events: &[LoggedEvent]) -> Value {
+    events
+        .iter()
+        .map(|LoggedEvent { event, .. }| event.to_json())
+        .collect()
+}
+
+fn print_report(json: bool, report: &Value) {
+    if json {
+        println!("{report:#}");
+        return;
+    }
+
+    if let Value::Object(fields) = report {
+        print_fields(fields, 0);
+    }
+}
+
+fn print_fields(fields: &Map<String, Value>, indent: usize) {
+    let pad = " ".repeat(indent);
+    for (key, value) in fields {
+        match value {
+            Value::Null => {}
+            Value::Array(items) => {
+                println!("{pad}{key}:");
+                for item in items {
+                    match item {
+                        Value::Object(inner) => {
+                            println!("{pad}  -");
+                            print_fields(inner, indent + 4);
+                        }
+                        Value::String(text) => println!("{pad}  {text}"),
+                        other => println!("{pad}  {other}"),
+                    }
+                }
+            }
+            Value::String(text) => println!("{pad}{key}: {text}"),
+            other => println!("{pad}{key}: {other}"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests;
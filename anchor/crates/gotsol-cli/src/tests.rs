@@ -0,0 +1,370 @@
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::TcpListener;
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+use anchor_lang::event::EVENT_IX_TAG_LE;
+use anchor_lang::solana_program::hash::Hash;
+use anchor_lang::{AccountSerialize, Event, InstructionData};
+use anchor_spl::token::spl_token::native_mint;
+use gotsol::events::{EventHeader, WithdrawSolProcessed};
+use solana_keypair::write_keypair_file;
+
+use super::*;
+
+const ORIGINAL_TX_SIG: &str = "5VERv8NM";
+
+/// Stand-in JSON-RPC node: answers every call with `respond(method, params)` and keeps
+/// the requests so tests can inspect the transaction the CLI built
+struct StubRpc {
+    url: String,
+    requests: Arc<Mutex<Vec<Value>>>,
+}
+
+impl StubRpc {
+    fn start(respond: impl Fn(&str, &Value) -> Value + Send + 'static) -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let requests = Arc::new(Mutex::new(vec![]));
+        let seen = requests.clone();
+
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                let mut stream = stream.unwrap();
+                let mut reader = BufReader::new(stream.try_clone().unwrap());
+                let mut content_length = 0;
+                loop {
+                    let mut line = String::new();
+                    reader.read_line(&mut line).unwrap();
+                    if line.trim().is_empty() {
+                        break;
+                    }
+                    if let Some((name, value)) = line.split_once(':') {
+                        if name.eq_ignore_ascii_case("content-length") {
+                            content_length = value.trim().parse().unwrap();
+                        }
+                    }
+                }
+                let mut body = vec![0; content_length];
+                reader.read_exact(&mut body).unwrap();
+
+                let request: Value = serde_json::from_slice(&body).unwrap();
+                let result = respond(request["method"].as_str().unwrap(), &request["params"]);
+                seen.lock().unwrap().push(request);
+
+                let response = json!({ "jsonrpc": "2.0", "id": 1, "result": result }).to_string();
+                write!(
+                    stream,
+                    "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{response}",
+                    response.len()
+                )
+                .unwrap();
+            }
+        });
+
+        Self { url, requests }
+    }
+
+    /// The transaction the CLI passed to `simulateTransaction`
+    fn simulated(&self) -> Transaction {
+        let requests = self.requests.lock().unwrap();
+        let request = requests
+            .iter()
+            .find(|request| request["method"] == "simulateTransaction")
+            .expect("no transaction was simulated");
+        decode_transaction(&request["params"])
+    }
+}
+
+fn decode_transaction(params: &Value) -> Transaction {
+    let wire = STANDARD.decode(params[0].as_str().unwrap()).unwrap();
+    bincode::deserialize(&wire).unwrap()
+}
+
+fn blockhash() -> Value {
+    json!({ "value": { "blockhash": Hash::default().to_string(), "lastValidBlockHeight": 0 } })
+}
+
+/// A successful simulation, optionally carrying an `emit_cpi!` event as an inner
+/// instruction of the first instruction
+fn simulation(params: &Value, event: Option<&dyn Fn() -> Vec<u8>>) -> Value {
+    let transaction = decode_transaction(params);
+    let program_index = transaction.message.account_keys.iter().position(|key| *key == PROGRAM_ID).unwrap();
+    let inner_instructions = match event {
+        Some(event) => {
+            let data = [EVENT_IX_TAG_LE, &event()].concat();
+            json!([{ "index": 0, "instructions": [
+                { "programIdIndex": program_index, "accounts": [], "data": bs58::encode(data).into_string() }
+            ] }])
+        }
+        None => json!([]),
+    };
+    json!({ "value": { "err": null, "logs": [], "unitsConsumed": 5000, "innerInstructions": inner_instructions } })
+}
+
+fn account_info(data: &[u8]) -> Value {
+    json!({ "value": {
+        "owner": PROGRAM_ID.to_string(),
+        "data": [STANDARD.encode(data), "base64"],
+        "lamports": 1_000_000,
+        "executable": false,
+    } })
+}
+
+/// Runs the CLI with `--dry-run` against the stub, signing with `keypair`
+fn dry_run(rpc: &StubRpc, keypair: &Keypair, fee_payer: Option<&Keypair>, args: &[&str]) -> Result<Value> {
+    let keypair_path = keypair_file(keypair);
+    let fee_payer_path = fee_payer.map(keypair_file);
+
+    let mut argv = vec!["gotsol", "--url", &rpc.url, "--keypair", keypair_path.to_str().unwrap(), "--dry-run"];
+    if let Some(path) = &fee_payer_path {
+        argv.extend(["--fee-payer", path.to_str().unwrap()]);
+    }
+    argv.extend(args);
+    let report = run(Cli::try_parse_from(argv).unwrap());
+
+    for path in [Some(keypair_path), fee_payer_path].into_iter().flatten() {
+        let _ = std::fs::remove_file(path);
+    }
+    report
+}
+
+fn keypair_file(keypair: &Keypair) -> PathBuf {
+    let path = std::env::temp_dir().join(format!("gotsol-cli-test-{}.json", keypair.pubkey()));
+    write_keypair_file(keypair, &path).unwrap();
+    path
+}
+
+fn instruction_keys(transaction: &Transaction) -> Vec<Pubkey> {
+    let keys = &transaction.message.account_keys;
+    transaction.message.instructions[0].accounts.iter().map(|&index| keys[index as usize]).collect()
+}
+
+#[test]
+fn withdraw_without_amount_withdraws_everything_and_reports_events() {
+    let owner = Keypair::new();
+    let merchant = Pubkey::new_unique();
+    let actor = owner.pubkey();
+    let rpc = StubRpc::start(move |method, params| match method {
+        "getLatestBlockhash" => blockhash(),
+        "simulateTransaction" => simulation(
+            params,
+            Some(&|| {
+                WithdrawSolProcessed {
+                    header: EventHeader {
+                        version: 1,
+                        merchant: Some(merchant),
+                        actor,
+                        mint: Some(native_mint::ID),
+                        timestamp: 1_700_000_000,
+                        slot: 42,
+                        balance: Some(0),
+                    },
+                    amount: 1_000,
+                    owner_amount: 990,
+                    house_amount: 10,
+                }
+                .data()
+            }),
+        ),
+        other => panic!("unexpected {other} call"),
+    });
+
+    let report = dry_run(&rpc, &owner, None, &["withdraw", "--merchant", &merchant.to_string()]).unwrap();
+
+    let transaction = rpc.simulated();
+    assert_eq!(transaction.message.account_keys[0], owner.pubkey());
+    assert_eq!(transaction.message.instructions[0].data, gotsol::instruction::WithdrawSolAll {}.data());
+    let keys = instruction_keys(&transaction);
+    assert!(keys.contains(&pda::vault(&merchant).0));
+    assert!(keys.contains(&pda::sol_withdrawal_limit(&merchant).0));
+    assert!(keys.contains(&HOUSE));
+
+    assert_eq!(report["dry_run"], true);
+    assert_eq!(report["events"][0]["type"], "WithdrawSolProcessed");
+    assert_eq!(report["events"][0]["merchant"], merchant.to_string());
+    assert_eq!(report["events"][0]["data"]["owner_amount"], 990);
+    assert_eq!(report["events"][0]["data"]["house_amount"], 10);
+}
+
+#[test]
+fn withdraw_with_amount_sends_it() {
+    let owner = Keypair::new();
+    let merchant = Pubkey::new_unique();
+    let rpc = StubRpc::start(|method, params| match method {
+        "getLatestBlockhash" => blockhash(),
+        "simulateTransaction" => simulation(params, None),
+        other => panic!("unexpected {other} call"),
+    });
+
+    dry_run(&rpc, &owner, None, &["withdraw", "--merchant", &merchant.to_string(), "--amount", "500"]).unwrap();
+
+    let transaction = rpc.simulated();
+    assert_eq!(transaction.message.instructions[0].data, gotsol::instruction::WithdrawSol { amount: 500 }.data());
+}
+
+#[test]
+fn fee_payer_pays_and_sponsors_refunds() {
+    let owner = Keypair::new();
+    let fee_payer = Keypair::new();
+    let merchant = Pubkey::new_unique();
+    let recipient = Pubkey::new_unique();
+    let rpc = StubRpc::start(|method, params| match method {
+        "getLatestBlockhash" => blockhash(),
+        "simulateTransaction" => simulation(params, None),
+        other => panic!("unexpected {other} call"),
+    });
+
+    let report = dry_run(
+        &rpc,
+        &owner,
+        Some(&fee_payer),
+        &[
+            "refund",
+            "--merchant",
+            &merchant.to_string(),
+            "--original-tx-sig",
+            ORIGINAL_TX_SIG,
+            "--recipient",
+            &recipient.to_string(),
+            "--amount",
+            "250",
+        ],
+    )
+    .unwrap();
+
+    let transaction = rpc.simulated();
+    assert_eq!(transaction.message.account_keys[0], fee_payer.pubkey());
+    assert_eq!(transaction.message.header.num_required_signatures, 2);
+    assert_eq!(
+        transaction.message.instructions[0].data,
+        gotsol::instruction::RefundSol { original_tx_sig: ORIGINAL_TX_SIG.to_string(), amount: 250 }.data()
+    );
+    let keys = instruction_keys(&transaction);
+    assert!(keys.contains(&pda::sponsorship_budget(&merchant).0));
+    assert!(keys.contains(&pda::global().0));
+    assert!(keys.contains(&pda::sol_withdrawal_limit(&merchant).0));
+    assert_eq!(report["refund_record"], pda::refund(ORIGINAL_TX_SIG).0.to_string());
+}
+
+#[test]
+fn close_refund_returns_rent_to_the_recorded_payer() {
+    let auth = Keypair::new();
+    let rent_payer = Pubkey::new_unique();
+    let mut data = vec![];
+    RefundRecord { rent_payer, original_tx_sig: ORIGINAL_TX_SIG.to_string(), bump: 255 }
+        .try_serialize(&mut data)
+        .unwrap();
+    data.resize(RefundRecord::LEN, 0);
+    let rpc = StubRpc::start(move |method, params| match method {
+        "getAccountInfo" => account_info(&data),
+        "getLatestBlockhash" => blockhash(),
+        "simulateTransaction" => simulation(params, None),
+        other => panic!("unexpected {other} call"),
+    });
+
+    dry_run(&rpc, &auth, None, &["close-refund", "--original-tx-sig", ORIGINAL_TX_SIG]).unwrap();
+
+    let transaction = rpc.simulated();
+    assert_eq!(transaction.message.instructions[0].data, gotsol::instruction::CloseRefund {}.data());
+    assert!(instruction_keys(&transaction).contains(&rent_payer));
+}
+
+fn legacy_refund_record() -> Vec<u8> {
+    let mut data = RefundRecord::DISCRIMINATOR.to_vec();
+    data.extend_from_slice(&(ORIGINAL_TX_SIG.len() as u32).to_le_bytes());
+    data.extend_from_slice(ORIGINAL_TX_SIG.as_bytes());
+    data.push(255);
+    data.resize(RefundRecord::LEGACY_LEN, 0);
+    data
+}
+
+/// A `getTransaction` result paid for by `fee_payer` whose only instruction calls `program`
+fn transaction_paid_by(fee_payer: Pubkey, program: Pubkey, err: Value) -> Value {
+    json!({
+        "slot": 1,
+        "meta": { "err": err, "innerInstructions": [], "logMessages": [] },
+        "transaction": {
+            "signatures": ["creation"],
+            "message": {
+                "accountKeys": [fee_payer.to_string(), pda::refund(ORIGINAL_TX_SIG).0.to_string(), program.to_string()],
+                "instructions": [{ "programIdIndex": 2, "accounts": [0, 1], "data": "" }],
+            },
+        },
+    })
+}
+
+#[test]
+fn close_refund_returns_legacy_rent_to_whoever_paid_for_the_creating_transaction() {
+    let auth = Keypair::new();
+    let funder = Pubkey::new_unique();
+    let data = legacy_refund_record();
+    let rpc = StubRpc::start(move |method, params| match method {
+        "getAccountInfo" => account_info(&data),
+        // newest first: a failed attempt and a transfer came before the refund that created the record
+        "getSignaturesForAddress" => json!([
+            { "signature": "later" },
+            { "signature": "creation" },
+            { "signature": "transfer" },
+            { "signature": "failed" },
+        ]),
+        "getTransaction" => match params[0].as_str().unwrap() {
+            "failed" => transaction_paid_by(Pubkey::new_unique(), PROGRAM_ID, json!({ "InstructionError": [0, "Custom"] })),
+            "transfer" => transaction_paid_by(Pubkey::new_unique(), system_program::ID, Value::Null),
+            "creation" => transaction_paid_by(funder, PROGRAM_ID, Value::Null),
+            other => panic!("looked past the creating transaction to {other}"),
+        },
+        "getLatestBlockhash" => blockhash(),
+        "simulateTransaction" => simulation(params, None),
+        other => panic!("unexpected {other} call"),
+    });
+
+    dry_run(&rpc, &auth, None, &["close-refund", "--original-tx-sig", ORIGINAL_TX_SIG]).unwrap();
+
+    let transaction = rpc.simulated();
+    assert_eq!(
+        transaction.message.instructions[0].data,
+        gotsol::instruction::CloseLegacyRefund { original_tx_sig: ORIGINAL_TX_SIG.to_string() }.data()
+    );
+    assert_eq!(instruction_keys(&transaction)[2], funder);
+}
+
+#[test]
+fn close_refund_refuses_legacy_records_it_cannot_trace() {
+    let auth = Keypair::new();
+    let data = legacy_refund_record();
+    let rpc = StubRpc::start(move |method, _| match method {
+        "getAccountInfo" => account_info(&data),
+        "getSignaturesForAddress" => json!([]),
+        other => panic!("unexpected {other} call"),
+    });
+
+    let error = dry_run(&rpc, &auth, None, &["close-refund", "--original-tx-sig", ORIGINAL_TX_SIG]).unwrap_err();
+
+    assert_eq!(
+        error.to_string(),
+        format!("can't find the transaction that created refund record {}", pda::refund(ORIGINAL_TX_SIG).0)
+    );
+}
+
+#[test]
+fn close_refund_without_a_record_fails_before_signing() {
+    let auth = Keypair::new();
+    let rpc = StubRpc::start(|method, _| match method {
+        "getAccountInfo" => json!({ "value": null }),
+        other => panic!("unexpected {other} call"),
+    });
+
+    let error = dry_run(&rpc, &auth, None, &["close-refund", "--original-tx-sig", ORIGINAL_TX_SIG]).unwrap_err();
+
+    assert_eq!(error.to_string(), format!("no refund record for {ORIGINAL_TX_SIG}"));
+}
+
+#[test]
+fn refund_requires_an_amount() {
+    let merchant = Pubkey::new_unique().to_string();
+    let recipient = Pubkey::new_unique().to_string();
+    let args = ["gotsol", "refund", "--merchant", &merchant, "--original-tx-sig", ORIGINAL_TX_SIG, "--recipient", &recipient];
+
+    assert!(Cli::try_parse_from(args).is_err());
+}
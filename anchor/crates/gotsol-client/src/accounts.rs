@@ -0,0 +1,16 @@
+use anchor_lang::{AccountDeserialize, Result};
+use gotsol::state::{Merchant, RefundRecord};
+
+/// Deserializes any GotSOL account, checking its 8-byte discriminator
+pub fn decode<T: AccountDeserialize>(data: &[u8]) -> Result<T> {
+    let mut data = data;
+    T::try_deserialize(&mut data)
+}
+
+pub fn decode_merchant(data: &[u8]) -> Result<Merchant> {
+    decode(data)
+}
+
+pub fn decode_refund_record(data: &[u8]) -> Result<RefundRecord> {
+    decode(data)
+}
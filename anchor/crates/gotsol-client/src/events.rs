@@ -0,0 +1,766 @@
+use anchor_lang::prelude::borsh::BorshDeserialize;
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::event::EVENT_IX_TAG_LE;
+use anchor_lang::Discriminator;
+use base64::{engine::general_purpose::STANDARD, Engine};
+use gotsol::events::*;
+use gotsol::state::CouponDiscount;
+use serde_json::{json, Value};
+
+const PROGRAM_DATA_PREFIX: &str = "Program data: ";
+
+pub enum GotsolEvent {
+    RefundProcessed(RefundProcessed),
+    MerchantClosed(MerchantClosed),
+    WithdrawSplProcessed(WithdrawSplProcessed),
+    WithdrawSolProcessed(WithdrawSolProcessed),
+    InvoiceCreated(InvoiceCreated),
+    InvoicePaid(InvoicePaid),
+    InvoiceCancelled(InvoiceCancelled),
+    PaymentProcessed(PaymentProcessed),
+    TipsDistributed(TipsDistributed),
+    PaymentAuthorized(PaymentAuthorized),
+    PaymentCaptured(PaymentCaptured),
+    AuthorizationVoided(AuthorizationVoided),
+    SubscriptionCreated(SubscriptionCreated),
+    SubscriptionCharged(SubscriptionCharged),
+    SubscriptionChargeFailed(SubscriptionChargeFailed),
+    SubscriptionCancelled(SubscriptionCancelled),
+    GiftCardPurchased(GiftCardPurchased),
+    GiftCardRedeemed(GiftCardRedeemed),
+    GiftCardClosed(GiftCardClosed),
+    LoyaltyProgramCreated(LoyaltyProgramCreated),
+    LoyaltyProgramUpdated(LoyaltyProgramUpdated),
+    LoyaltyPointsEarned(LoyaltyPointsEarned),
+    LoyaltyPointsRedeemed(LoyaltyPointsRedeemed),
+    CouponCreated(CouponCreated),
+    CouponRedeemed(CouponRedeemed),
+    ManagerAdded(ManagerAdded),
+    ManagerRemoved(ManagerRemoved),
+    ProductUpdated(ProductUpdated),
+    ProductDeleted(ProductDeleted),
+    OrderPaid(OrderPaid),
+    OrderItemsRefunded(OrderItemsRefunded),
+    SolPaymentProcessed(SolPaymentProcessed),
+    SwapRouterUpdated(SwapRouterUpdated),
+    WithdrawSolAsSplProcessed(WithdrawSolAsSplProcessed),
+    PayoutScheduleSet(PayoutScheduleSet),
+    PayoutScheduleCancelled(PayoutScheduleCancelled),
+    PayoutCranked(PayoutCranked),
+    WithdrawalLimitUpdated(WithdrawalLimitUpdated),
+    RentRecoupRateUpdated(RentRecoupRateUpdated),
+    AtaRentSponsored(AtaRentSponsored),
+    SponsorshipBudgetUpdated(SponsorshipBudgetUpdated),
+    SponsorshipCharged(SponsorshipCharged),
+    FeePayerApprovalChanged(FeePayerApprovalChanged),
+}
+
+impl GotsolEvent {
+    pub fn name(&self) -> &'static str {
+        match self {
+            GotsolEvent::RefundProcessed(_) => "RefundProcessed",
+            GotsolEvent::MerchantClosed(_) => "MerchantClosed",
+            GotsolEvent::WithdrawSplProcessed(_) => "WithdrawSplProcessed",
+            GotsolEvent::WithdrawSolProcessed(_) => "WithdrawSolProcessed",
+            GotsolEvent::InvoiceCreated(_) => "InvoiceCreated",
+            GotsolEvent::InvoicePaid(_) => "InvoicePaid",
+            GotsolEvent::InvoiceCancelled(_) => "InvoiceCancelled",
+            GotsolEvent::PaymentProcessed(_) => "PaymentProcessed",
+            GotsolEvent::TipsDistributed(_) => "TipsDistributed",
+            GotsolEvent::PaymentAuthorized(_) => "PaymentAuthorized",
+            GotsolEvent::PaymentCaptured(_) => "PaymentCaptured",
+            GotsolEvent::AuthorizationVoided(_) => "AuthorizationVoided",
+            GotsolEvent::SubscriptionCreated(_) => "SubscriptionCreated",
+            GotsolEvent::SubscriptionCharged(_) => "SubscriptionCharged",
+            GotsolEvent::SubscriptionChargeFailed(_) => "SubscriptionChargeFailed",
+            GotsolEvent::SubscriptionCancelled(_) => "SubscriptionCancelled",
+            GotsolEvent::GiftCardPurchased(_) => "GiftCardPurchased",
+            GotsolEvent::GiftCardRedeemed(_) => "GiftCardRedeemed",
+            GotsolEvent::GiftCardClosed(_) => "GiftCardClosed",
+            GotsolEvent::LoyaltyProgramCreated(_) => "LoyaltyProgramCreated",
+            GotsolEvent::LoyaltyProgramUpdated(_) => "LoyaltyProgramUpdated",
+            GotsolEvent::LoyaltyPointsEarned(_) => "LoyaltyPointsEarned",
+            GotsolEvent::LoyaltyPointsRedeemed(_) => "LoyaltyPointsRedeemed",
+            GotsolEvent::CouponCreated(_) => "CouponCreated",
+            GotsolEvent::CouponRedeemed(_) => "CouponRedeemed",
+            GotsolEvent::ManagerAdded(_) => "ManagerAdded",
+            GotsolEvent::ManagerRemoved(_) => "ManagerRemoved",
+            GotsolEvent::ProductUpdated(_) => "ProductUpdated",
+            GotsolEvent::ProductDeleted(_) => "ProductDeleted",
+            GotsolEvent::OrderPaid(_) => "OrderPaid",
+            GotsolEvent::OrderItemsRefunded(_) => "OrderItemsRefunded",
+            GotsolEvent::SolPaymentProcessed(_) => "SolPaymentProcessed",
+            GotsolEvent::SwapRouterUpdated(_) => "SwapRouterUpdated",
+            GotsolEvent::WithdrawSolAsSplProcessed(_) => "WithdrawSolAsSplProcessed",
+            GotsolEvent::PayoutScheduleSet(_) => "PayoutScheduleSet",
+            GotsolEvent::PayoutScheduleCancelled(_) => "PayoutScheduleCancelled",
+            GotsolEvent::PayoutCranked(_) => "PayoutCranked",
+            GotsolEvent::WithdrawalLimitUpdated(_) => "WithdrawalLimitUpdated",
+            GotsolEvent::RentRecoupRateUpdated(_) => "RentRecoupRateUpdated",
+            GotsolEvent::AtaRentSponsored(_) => "AtaRentSponsored",
+            GotsolEvent::SponsorshipBudgetUpdated(_) => "SponsorshipBudgetUpdated",
+            GotsolEvent::SponsorshipCharged(_) => "SponsorshipCharged",
+            GotsolEvent::FeePayerApprovalChanged(_) => "FeePayerApprovalChanged",
+        }
+    }
+
+    /// The fields every event carries: merchant, actor, mint, time, slot and resulting balance
+    pub fn header(&self) -> &EventHeader {
+        match self {
+            GotsolEvent::RefundProcessed(event) => &event.header,
+            GotsolEvent::MerchantClosed(event) => &event.header,
+            GotsolEvent::WithdrawSplProcessed(event) => &event.header,
+            GotsolEvent::WithdrawSolProcessed(event) => &event.header,
+            GotsolEvent::InvoiceCreated(event) => &event.header,
+            GotsolEvent::InvoicePaid(event) => &event.header,
+            GotsolEvent::InvoiceCancelled(event) => &event.header,
+            GotsolEvent::PaymentProcessed(event) => &event.header,
+            GotsolEvent::TipsDistributed(event) => &event.header,
+            GotsolEvent::PaymentAuthorized(event) => &event.header,
+            GotsolEvent::PaymentCaptured(event) => &event.header,
+            GotsolEvent::AuthorizationVoided(event) => &event.header,
+            GotsolEvent::SubscriptionCreated(event) => &event.header,
+            GotsolEvent::SubscriptionCharged(event) => &event.header,
+            GotsolEvent::SubscriptionChargeFailed(event) => &event.header,
+            GotsolEvent::SubscriptionCancelled(event) => &event.header,
+            GotsolEvent::GiftCardPurchased(event) => &event.header,
+            GotsolEvent::GiftCardRedeemed(event) => &event.header,
+            GotsolEvent::GiftCardClosed(event) => &event.header,
+            GotsolEvent::LoyaltyProgramCreated(event) => &event.header,
+            GotsolEvent::LoyaltyProgramUpdated(event) => &event.header,
+            GotsolEvent::LoyaltyPointsEarned(event) => &event.header,
+            GotsolEvent::LoyaltyPointsRedeemed(event) => &event.header,
+            GotsolEvent::CouponCreated(event) => &event.header,
+            GotsolEvent::CouponRedeemed(event) => &event.header,
+            GotsolEvent::ManagerAdded(event) => &event.header,
+            GotsolEvent::ManagerRemoved(event) => &event.header,
+            GotsolEvent::ProductUpdated(event) => &event.header,
+            GotsolEvent::ProductDeleted(event) => &event.header,
+            GotsolEvent::OrderPaid(event) => &event.header,
+            GotsolEvent::OrderItemsRefunded(event) => &event.header,
+            GotsolEvent::SolPaymentProcessed(event) => &event.header,
+            GotsolEvent::SwapRouterUpdated(event) => &event.header,
+            GotsolEvent::WithdrawSolAsSplProcessed(event) => &event.header,
+            GotsolEvent::PayoutScheduleSet(event) => &event.header,
+            GotsolEvent::PayoutScheduleCancelled(event) => &event.header,
+            GotsolEvent::PayoutCranked(event) => &event.header,
+            GotsolEvent::WithdrawalLimitUpdated(event) => &event.header,
+            GotsolEvent::RentRecoupRateUpdated(event) => &event.header,
+            GotsolEvent::AtaRentSponsored(event) => &event.header,
+            GotsolEvent::SponsorshipBudgetUpdated(event) => &event.header,
+            GotsolEvent::SponsorshipCharged(event) => &event.header,
+            GotsolEvent::FeePayerApprovalChanged(event) => &event.header,
+        }
+    }
+
+    /// The merchant the event belongs to, `None` for program-wide admin events
+    pub fn merchant(&self) -> Option<Pubkey> {
+        self.header().merchant
+    }
+
+    /// The event as JSON: its name under `type`, the header fields at the top level
+    /// and the event's own fields under `data`
+    pub fn to_json(&self) -> Value {
+        let header = self.header();
+        json!({
+            "type": self.name(),
+            "version": header.version,
+            "merchant": header.merchant.map(|merchant| merchant.to_string()),
+            "actor": header.actor.to_string(),
+            "mint": header.mint.map(|mint| mint.to_string()),
+            "balance": header.balance,
+            "timestamp": header.timestamp,
+            "slot": header.slot,
+            "data": self.data(),
+        })
+    }
+
+    /// The event's own fields, header excluded. Public keys are base58 strings and
+    /// amounts are base units of the header's `mint` (lamports for SOL).
+    pub fn data(&self) -> Value {
+        match self {
+            GotsolEvent::RefundProcessed(event) => json!({
+                "original_tx_sig": event.original_tx_sig,
+                "amount": event.amount,
+                "recipient": event.recipient.to_string(),
+                "sponsor": event.sponsor.map(|key| key.to_string()),
+            }),
+            GotsolEvent::MerchantClosed(event) => json!({
+                "entity_name": event.entity_name,
+            }),
+            GotsolEvent::WithdrawSplProcessed(event) => json!({
+                "amount": event.amount,
+                "owner_amount": event.owner_amount,
+                "house_amount": event.house_amount,
+            }),
+            GotsolEvent::WithdrawSolProcessed(event) => json!({
+                "amount": event.amount,
+                "owner_amount": event.owner_amount,
+                "house_amount": event.house_amount,
+            }),
+            GotsolEvent::InvoiceCreated(event) => json!({
+                "invoice": event.invoice.to_string(),
+                "invoice_id": event.invoice_id,
+                "amount": event.amount,
+                "due_date": event.due_date,
+            }),
+            GotsolEvent::InvoicePaid(event) => json!({
+                "invoice": event.invoice.to_string(),
+                "invoice_id": event.invoice_id,
+                "amount": event.amount,
+                "payer": event.payer.to_string(),
+                "discount": event.discount,
+            }),
+            GotsolEvent::InvoiceCancelled(event) => json!({
+                "invoice": event.invoice.to_string(),
+                "invoice_id": event.invoice_id,
+            }),
+            GotsolEvent::PaymentProcessed(event) => json!({
+                "payer": event.payer.to_string(),
+                "amount": event.amount,
+                "tip": event.tip,
+                "employee": event.employee.map(|key| key.to_string()),
+                "discount": event.discount,
+                "gift_card_amount": event.gift_card_amount,
+            }),
+            GotsolEvent::TipsDistributed(event) => json!({
+                "employee": event.employee.to_string(),
+                "amount": event.amount,
+            }),
+            GotsolEvent::PaymentAuthorized(event) => json!({
+                "customer": event.customer.to_string(),
+                "authorization": event.authorization.to_string(),
+                "amount": event.amount,
+                "expires_at": event.expires_at,
+            }),
+            GotsolEvent::PaymentCaptured(event) => json!({
+                "customer": event.customer.to_string(),
+                "authorization": event.authorization.to_string(),
+                "captured": event.captured,
+                "released": event.released,
+            }),
+            GotsolEvent::AuthorizationVoided(event) => json!({
+                "customer": event.customer.to_string(),
+                "authorization": event.authorization.to_string(),
+                "amount": event.amount,
+                "expired": event.expired,
+            }),
+            GotsolEvent::SubscriptionCreated(event) => json!({
+                "customer": event.customer.to_string(),
+                "subscription": event.subscription.to_string(),
+                "amount": event.amount,
+                "interval": event.interval,
+                "allowance": event.allowance,
+            }),
+            GotsolEvent::SubscriptionCharged(event) => json!({
+                "customer": event.customer.to_string(),
+                "subscription": event.subscription.to_string(),
+                "amount": event.amount,
+                "next_charge_at": event.next_charge_at,
+                "cranker": event.cranker.to_string(),
+            }),
+            GotsolEvent::SubscriptionChargeFailed(event) => json!({
+                "customer": event.customer.to_string(),
+                "subscription": event.subscription.to_string(),
+                "amount": event.amount,
+                "reason": charge_failure_reason(event.reason),
+                "retry_after": event.retry_after,
+            }),
+            GotsolEvent::SubscriptionCancelled(event) => json!({
+                "customer": event.customer.to_string(),
+                "subscription": event.subscription.to_string(),
+                "cancelled_by": event.cancelled_by.to_string(),
+            }),
+            GotsolEvent::GiftCardPurchased(event) => json!({
+                "gift_card": event.gift_card.to_string(),
+                "purchaser": event.purchaser.to_string(),
+                "amount": event.amount,
+                "expires_at": event.expires_at,
+            }),
+            GotsolEvent::GiftCardRedeemed(event) => json!({
+                "gift_card": event.gift_card.to_string(),
+                "redeemer": event.redeemer.to_string(),
+                "amount": event.amount,
+                "remaining_balance": event.remaining_balance,
+            }),
+            GotsolEvent::GiftCardClosed(event) => json!({
+                "gift_card": event.gift_card.to_string(),
+                "unredeemed_balance": event.unredeemed_balance,
+            }),
+            GotsolEvent::LoyaltyProgramCreated(event) => json!({
+                "points_mint": event.points_mint.to_string(),
+                "earn_basis_points": event.earn_basis_points,
+                "redemption_basis_points": event.redemption_basis_points,
+                "non_transferable": event.non_transferable,
+            }),
+            GotsolEvent::LoyaltyProgramUpdated(event) => json!({
+                "previous_earn_basis_points": event.previous_earn_basis_points,
+                "previous_redemption_basis_points": event.previous_redemption_basis_points,
+                "earn_basis_points": event.earn_basis_points,
+                "redemption_basis_points": event.redemption_basis_points,
+            }),
+            GotsolEvent::LoyaltyPointsEarned(event) => json!({
+                "customer": event.customer.to_string(),
+                "points": event.points,
+            }),
+            GotsolEvent::LoyaltyPointsRedeemed(event) => json!({
+                "customer": event.customer.to_string(),
+                "points": event.points,
+                "discount": event.discount,
+            }),
+            GotsolEvent::CouponCreated(event) => json!({
+                "coupon": event.coupon.to_string(),
+                "discount": coupon_discount(&event.discount),
+                "max_redemptions": event.max_redemptions,
+                "expires_at": event.expires_at,
+            }),
+            GotsolEvent::CouponRedeemed(event) => json!({
+                "coupon": event.coupon.to_string(),
+                "customer": event.customer.to_string(),
+                "discount": event.discount,
+                "redemption_count": event.redemption_count,
+            }),
+            GotsolEvent::ManagerAdded(event) => json!({
+                "manager": event.manager.to_string(),
+            }),
+            GotsolEvent::ManagerRemoved(event) => json!({
+                "manager": event.manager.to_string(),
+            }),
+            GotsolEvent::ProductUpdated(event) => json!({
+                "product": event.product.to_string(),
+                "sku": event.sku,
+                "price": event.price,
+                "stock": event.stock,
+                "tax_basis_points": event.tax_basis_points,
+                "active": event.active,
+            }),
+            GotsolEvent::ProductDeleted(event) => json!({
+                "product": event.product.to_string(),
+                "sku": event.sku,
+            }),
+            GotsolEvent::OrderPaid(event) => json!({
+                "order": event.order.to_string(),
+                "customer": event.customer.to_string(),
+                "subtotal": event.subtotal,
+                "tax": event.tax,
+                "tip": event.tip,
+                "line_items": event.line_items,
+                "gift_card_amount": event.gift_card_amount,
+                "discount": event.discount,
+            }),
+            GotsolEvent::OrderItemsRefunded(event) => json!({
+                "order": event.order.to_string(),
+                "customer": event.customer.to_string(),
+                "amount": event.amount,
+                "refunded_amount": event.refunded_amount,
+            }),
+            GotsolEvent::SolPaymentProcessed(event) => json!({
+                "payment": event.payment.to_string(),
+                "payer": event.payer.to_string(),
+                "usd_amount": event.usd_amount,
+                "lamports": event.lamports,
+                "price": event.price,
+                "price_expo": event.price_expo,
+            }),
+            GotsolEvent::SwapRouterUpdated(event) => json!({
+                "swap_router": event.swap_router.to_string(),
+            }),
+            GotsolEvent::WithdrawSolAsSplProcessed(event) => json!({
+                "amount": event.amount,
+                "amount_out": event.amount_out,
+                "owner_amount": event.owner_amount,
+                "house_amount": event.house_amount,
+                "output_mint": event.output_mint.to_string(),
+            }),
+            GotsolEvent::PayoutScheduleSet(event) => json!({
+                "destination": event.destination.to_string(),
+                "interval": event.interval,
+                "minimum_threshold": event.minimum_threshold,
+                "bounty_basis_points": event.bounty_basis_points,
+                "next_payout_at": event.next_payout_at,
+            }),
+            GotsolEvent::PayoutScheduleCancelled(_) => json!({}),
+            GotsolEvent::PayoutCranked(event) => json!({
+                "destination": event.destination.to_string(),
+                "cranker": event.cranker.to_string(),
+                "amount": event.amount,
+                "owner_amount": event.owner_amount,
+                "house_amount": event.house_amount,
+                "bounty": event.bounty,
+                "next_payout_at": event.next_payout_at,
+            }),
+            GotsolEvent::WithdrawalLimitUpdated(event) => json!({
+                "max_per_withdrawal": event.max_per_withdrawal,
+                "max_per_day": event.max_per_day,
+                "pending_max_per_withdrawal": event.pending_max_per_withdrawal,
+                "pending_max_per_day": event.pending_max_per_day,
+                "pending_effective_at": event.pending_effective_at,
+            }),
+            GotsolEvent::RentRecoupRateUpdated(event) => json!({
+                "rent_recoup_rate": event.rent_recoup_rate,
+            }),
+            GotsolEvent::AtaRentSponsored(event) => json!({
+                "fee_payer": event.fee_payer.to_string(),
+                "lamports": event.lamports,
+                "recouped": event.recouped,
+            }),
+            GotsolEvent::SponsorshipBudgetUpdated(event) => json!({
+                "allowance_per_period": event.allowance_per_period,
+                "period": event.period,
+                "consumed": event.consumed,
+            }),
+            GotsolEvent::SponsorshipCharged(event) => json!({
+                "sponsor": event.sponsor.to_string(),
+                "lamports": event.lamports,
+                "consumed": event.consumed,
+            }),
+            GotsolEvent::FeePayerApprovalChanged(event) => json!({
+                "fee_payer": event.fee_payer.to_string(),
+                "approved": event.approved,
+            }),
+        }
+    }
+}
+
+fn coupon_discount(discount: &CouponDiscount) -> Value {
+    match discount {
+        CouponDiscount::Percent { basis_points } => json!({ "percent_basis_points": basis_points }),
+        CouponDiscount::Fixed { amount } => json!({ "fixed_amount": amount }),
+    }
+}
+
+fn charge_failure_reason(reason: ChargeFailureReason) -> &'static str {
+    match reason {
+        ChargeFailureReason::DelegateRevoked => "DelegateRevoked",
+        ChargeFailureReason::AllowanceExhausted => "AllowanceExhausted",
+        ChargeFailureReason::InsufficientBalance => "InsufficientBalance",
+    }
+}
+
+/// Decodes a single `emit!` payload (discriminator followed by borsh data).
+/// Returns `None` for payloads that aren't GotSOL events or fail to parse.
+pub fn decode_event(data: &[u8]) -> Option<GotsolEvent> {
+    let discriminator_len = RefundProcessed::DISCRIMINATOR.len();
+    if data.len() < discriminator_len {
+        return None;
+    }
+    let (discriminator, payload) = data.split_at(discriminator_len);
+
+    let event = match discriminator {
+        RefundProcessed::DISCRIMINATOR => RefundProcessed::try_from_slice(payload).map(GotsolEvent::RefundProcessed),
+        MerchantClosed::DISCRIMINATOR => MerchantClosed::try_from_slice(payload).map(GotsolEvent::MerchantClosed),
+        WithdrawSplProcessed::DISCRIMINATOR => WithdrawSplProcessed::try_from_slice(payload).map(GotsolEvent::WithdrawSplProcessed),
+        WithdrawSolProcessed::DISCRIMINATOR => WithdrawSolProcessed::try_from_slice(payload).map(GotsolEvent::WithdrawSolProcessed),
+        InvoiceCreated::DISCRIMINATOR => InvoiceCreated::try_from_slice(payload).map(GotsolEvent::InvoiceCreated),
+        InvoicePaid::DISCRIMINATOR => InvoicePaid::try_from_slice(payload).map(GotsolEvent::InvoicePaid),
+        InvoiceCancelled::DISCRIMINATOR => InvoiceCancelled::try_from_slice(payload).map(GotsolEvent::InvoiceCancelled),
+        PaymentProcessed::DISCRIMINATOR => PaymentProcessed::try_from_slice(payload).map(GotsolEvent::PaymentProcessed),
+        TipsDistributed::DISCRIMINATOR => TipsDistributed::try_from_slice(payload).map(GotsolEvent::TipsDistributed),
+        PaymentAuthorized::DISCRIMINATOR => PaymentAuthorized::try_from_slice(payload).map(GotsolEvent::PaymentAuthorized),
+        PaymentCaptured::DISCRIMINATOR => PaymentCaptured::try_from_slice(payload).map(GotsolEvent::PaymentCaptured),
+        AuthorizationVoided::DISCRIMINATOR => AuthorizationVoided::try_from_slice(payload).map(GotsolEvent::AuthorizationVoided),
+        SubscriptionCreated::DISCRIMINATOR => SubscriptionCreated::try_from_slice(payload).map(GotsolEvent::SubscriptionCreated),
+        SubscriptionCharged::DISCRIMINATOR => SubscriptionCharged::try_from_slice(payload).map(GotsolEvent::SubscriptionCharged),
+        SubscriptionChargeFailed::DISCRIMINATOR => SubscriptionChargeFailed::try_from_slice(payload).map(GotsolEvent::SubscriptionChargeFailed),
+        SubscriptionCancelled::DISCRIMINATOR => SubscriptionCancelled::try_from_slice(payload).map(GotsolEvent::SubscriptionCancelled),
+        GiftCardPurchased::DISCRIMINATOR => GiftCardPurchased::try_from_slice(payload).map(GotsolEvent::GiftCardPurchased),
+        GiftCardRedeemed::DISCRIMINATOR => GiftCardRedeemed::try_from_slice(payload).map(GotsolEvent::GiftCardRedeemed),
+        GiftCardClosed::DISCRIMINATOR => GiftCardClosed::try_from_slice(payload).map(GotsolEvent::GiftCardClosed),
+        LoyaltyProgramCreated::DISCRIMINATOR => LoyaltyProgramCreated::try_from_slice(payload).map(GotsolEvent::LoyaltyProgramCreated),
+        LoyaltyProgramUpdated::DISCRIMINATOR => LoyaltyProgramUpdated::try_from_slice(payload).map(GotsolEvent::LoyaltyProgramUpdated),
+        LoyaltyPointsEarned::DISCRIMINATOR => LoyaltyPointsEarned::try_from_slice(payload).map(GotsolEvent::LoyaltyPointsEarned),
+        LoyaltyPointsRedeemed::DISCRIMINATOR => LoyaltyPointsRedeemed::try_from_slice(payload).map(GotsolEvent::LoyaltyPointsRedeemed),
+        CouponCreated::DISCRIMINATOR => CouponCreated::try_from_slice(payload).map(GotsolEvent::CouponCreated),
+        CouponRedeemed::DISCRIMINATOR => CouponRedeemed::try_from_slice(payload).map(GotsolEvent::CouponRedeemed),
+        ManagerAdded::DISCRIMINATOR => ManagerAdded::try_from_slice(payload).map(GotsolEvent::ManagerAdded),
+        ManagerRemoved::DISCRIMINATOR => ManagerRemoved::try_from_slice(payload).map(GotsolEvent::ManagerRemoved),
+        ProductUpdated::DISCRIMINATOR => ProductUpdated::try_from_slice(payload).map(GotsolEvent::ProductUpdated),
+        ProductDeleted::DISCRIMINATOR => ProductDeleted::try_from_slice(payload).map(GotsolEvent::ProductDeleted),
+        OrderPaid::DISCRIMINATOR => OrderPaid::try_from_slice(payload).map(GotsolEvent::OrderPaid),
+        OrderItemsRefunded::DISCRIMINATOR => OrderItemsRefunded::try_from_slice(payload).map(GotsolEvent::OrderItemsRefunded),
+        SolPaymentProcessed::DISCRIMINATOR => SolPaymentProcessed::try_from_slice(payload).map(GotsolEvent::SolPaymentProcessed),
+        SwapRouterUpdated::DISCRIMINATOR => SwapRouterUpdated::try_from_slice(payload).map(GotsolEvent::SwapRouterUpdated),
+        WithdrawSolAsSplProcessed::DISCRIMINATOR => WithdrawSolAsSplProcessed::try_from_slice(payload).map(GotsolEvent::WithdrawSolAsSplProcessed),
+        PayoutScheduleSet::DISCRIMINATOR => PayoutScheduleSet::try_from_slice(payload).map(GotsolEvent::PayoutScheduleSet),
+        PayoutScheduleCancelled::DISCRIMINATOR => PayoutScheduleCancelled::try_from_slice(payload).map(GotsolEvent::PayoutScheduleCancelled),
+        PayoutCranked::DISCRIMINATOR => PayoutCranked::try_from_slice(payload).map(GotsolEvent::PayoutCranked),
+        WithdrawalLimitUpdated::DISCRIMINATOR => WithdrawalLimitUpdated::try_from_slice(payload).map(GotsolEvent::WithdrawalLimitUpdated),
+        RentRecoupRateUpdated::DISCRIMINATOR => RentRecoupRateUpdated::try_from_slice(payload).map(GotsolEvent::RentRecoupRateUpdated),
+        AtaRentSponsored::DISCRIMINATOR => AtaRentSponsored::try_from_slice(payload).map(GotsolEvent::AtaRentSponsored),
+        SponsorshipBudgetUpdated::DISCRIMINATOR => SponsorshipBudgetUpdated::try_from_slice(payload).map(GotsolEvent::SponsorshipBudgetUpdated),
+        SponsorshipCharged::DISCRIMINATOR => SponsorshipCharged::try_from_slice(payload).map(GotsolEvent::SponsorshipCharged),
+        FeePayerApprovalChanged::DISCRIMINATOR => FeePayerApprovalChanged::try_from_slice(payload).map(GotsolEvent::FeePayerApprovalChanged),
+        _ => return None,
+    };
+
+    event.ok()
+}
+
+/// Decodes the data of the self-CPI made by `emit_cpi!` (event tag, then the event itself)
+pub fn decode_cpi_event(data: &[u8]) -> Option<GotsolEvent> {
+    decode_event(data.strip_prefix(EVENT_IX_TAG_LE)?)
+}
+
+/// An event together with the top-level instruction that emitted it
+pub struct LoggedEvent {
+    pub instruction_index: usize,
+    pub event: GotsolEvent,
+}
+
+/// Extracts GotSOL events from a transaction's log messages, keeping only
+/// `Program data:` lines written while GotSOL itself is executing so other
+/// programs can't inject look-alike events
+pub fn parse_logged_events<S: AsRef<str>>(logs: &[S]) -> Vec<LoggedEvent> {
+    let program_id = gotsol::ID.to_string();
+    let mut invocations: Vec<&str> = vec![];
+    let mut top_level_invokes = 0;
+    let mut events = vec![];
+
+    for log in logs {
+        let log = log.as_ref();
+
+        if let Some(encoded) = log.strip_prefix(PROGRAM_DATA_PREFIX) {
+            if invocations.last() != Some(&program_id.as_str()) {
+                continue;
+            }
+            let event = STANDARD.decode(encoded.trim()).ok().and_then(|data| decode_event(&data));
+            if let Some(event) = event {
+                events.push(LoggedEvent {
+                    instruction_index: top_level_invokes - 1,
+                    event,
+                });
+            }
+        } else if let Some((program, status)) = log.strip_prefix("Program ").and_then(|rest| rest.split_once(' ')) {
+            if status.starts_with("invoke [") {
+                if invocations.is_empty() {
+                    top_level_invokes += 1;
+                }
+                invocations.push(program);
+            } else if status == "success" || status.starts_with("failed") {
+                invocations.pop();
+            }
+        }
+    }
+
+    events
+}
+
+/// Extracts every GotSOL event from a transaction's log messages
+pub fn parse_logs<S: AsRef<str>>(logs: &[S]) -> Vec<GotsolEvent> {
+    parse_logged_events(logs).into_iter().map(|logged| logged.event).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use anchor_lang::prelude::borsh;
+    use anchor_lang::Event;
+
+    use super::*;
+
+    const OTHER_PROGRAM: &str = "7obNCw8woiubFYHDvyuUMU641RGDUNwdCEEAjJeziG1Z";
+
+    fn header() -> EventHeader {
+        EventHeader {
+            version: EVENT_SCHEMA_VERSION,
+            merchant: Some(Pubkey::new_from_array([1; 32])),
+            actor: Pubkey::new_from_array([2; 32]),
+            mint: Some(Pubkey::new_from_array([3; 32])),
+            timestamp: 1_700_000_000,
+            slot: 42,
+            balance: Some(7),
+        }
+    }
+
+    /// An event of type `T` carrying `header()` and zeroed fields otherwise
+    fn sample<T: BorshDeserialize>() -> T {
+        let mut bytes = borsh::to_vec(&header()).unwrap();
+        bytes.extend([0; 256]);
+        T::deserialize(&mut bytes.as_slice()).unwrap()
+    }
+
+    fn round_trip<T: BorshDeserialize + Event + Discriminator>(name: &str) -> &'static [u8] {
+        let data = sample::<T>().data();
+        assert!(data.starts_with(T::DISCRIMINATOR), "{name}");
+
+        let event = decode_event(&data).unwrap_or_else(|| panic!("{name} didn't decode"));
+        assert_eq!(event.name(), name);
+        assert!(event.header() == &header(), "{name} header");
+        assert_eq!(event.to_json()["type"], name);
+
+        let tagged = [EVENT_IX_TAG_LE, &data].concat();
+        assert_eq!(decode_cpi_event(&tagged).map(|event| event.name()), Some(name));
+        assert!(decode_cpi_event(&data).is_none(), "{name} decoded without the event tag");
+
+        T::DISCRIMINATOR
+    }
+
+    macro_rules! round_trip_all {
+        ($($event:ident),* $(,)?) => {
+            vec![$(round_trip::<$event>(stringify!($event))),*]
+        };
+    }
+
+    #[test]
+    fn every_event_round_trips_through_its_discriminator() {
+        let discriminators = round_trip_all![
+            RefundProcessed,
+            MerchantClosed,
+            WithdrawSplProcessed,
+            WithdrawSolProcessed,
+            InvoiceCreated,
+            InvoicePaid,
+            InvoiceCancelled,
+            PaymentProcessed,
+            TipsDistributed,
+            PaymentAuthorized,
+            PaymentCaptured,
+            AuthorizationVoided,
+            SubscriptionCreated,
+            SubscriptionCharged,
+            SubscriptionChargeFailed,
+            SubscriptionCancelled,
+            GiftCardPurchased,
+            GiftCardRedeemed,
+            GiftCardClosed,
+            LoyaltyProgramCreated,
+            LoyaltyProgramUpdated,
+            LoyaltyPointsEarned,
+            LoyaltyPointsRedeemed,
+            CouponCreated,
+            CouponRedeemed,
+            ManagerAdded,
+            ManagerRemoved,
+            ProductUpdated,
+            ProductDeleted,
+            OrderPaid,
+            OrderItemsRefunded,
+            SolPaymentProcessed,
+            SwapRouterUpdated,
+            WithdrawSolAsSplProcessed,
+            PayoutScheduleSet,
+            PayoutScheduleCancelled,
+            PayoutCranked,
+            WithdrawalLimitUpdated,
+            RentRecoupRateUpdated,
+            AtaRentSponsored,
+            SponsorshipBudgetUpdated,
+            SponsorshipCharged,
+            FeePayerApprovalChanged,
+        ];
+
+        assert_eq!(discriminators.len(), 43);
+        assert_eq!(discriminators.iter().collect::<HashSet<_>>().len(), 43);
+    }
+
+    #[test]
+    fn rejects_unknown_and_truncated_payloads() {
+        let data = sample::<RefundProcessed>().data();
+
+        assert!(decode_event(&data[..data.len() - 1]).is_none());
+        assert!(decode_event(&data[..4]).is_none());
+        assert!(decode_event(&[[0xff; 8].as_slice(), &data[8..]].concat()).is_none());
+    }
+
+    #[test]
+    fn encodes_event_fields_as_json() {
+        let event = CouponCreated {
+            header: header(),
+            coupon: Pubkey::new_from_array([4; 32]),
+            discount: CouponDiscount::Percent { basis_points: 1_500 },
+            max_redemptions: 10,
+            expires_at: 1_800_000_000,
+        };
+        let json = decode_event(&event.data()).unwrap().to_json();
+
+        assert_eq!(json["merchant"], Pubkey::new_from_array([1; 32]).to_string());
+        assert_eq!(json["balance"], 7);
+        assert_eq!(json["data"]["coupon"], Pubkey::new_from_array([4; 32]).to_string());
+        assert_eq!(json["data"]["discount"]["percent_basis_points"], 1_500);
+        assert_eq!(json["data"]["max_redemptions"], 10);
+
+        let failed = SubscriptionChargeFailed {
+            reason: ChargeFailureReason::AllowanceExhausted,
+            ..sample()
+        };
+        assert_eq!(decode_event(&failed.data()).unwrap().data()["reason"], "AllowanceExhausted");
+    }
+
+    fn program_data<T: Event>(event: &T) -> String {
+        format!("{PROGRAM_DATA_PREFIX}{}", STANDARD.encode(event.data()))
+    }
+
+    fn logged_names(logs: &[String]) -> Vec<(usize, &'static str)> {
+        parse_logged_events(logs)
+            .iter()
+            .map(|logged| (logged.instruction_index, logged.event.name()))
+            .collect()
+    }
+
+    #[test]
+    fn keeps_events_logged_by_gotsol_per_top_level_instruction() {
+        let gotsol = gotsol::ID.to_string();
+        let logs = vec![
+            format!("Program {OTHER_PROGRAM} invoke [1]"),
+            format!("Program {OTHER_PROGRAM} success"),
+            format!("Program {gotsol} invoke [1]"),
+            "Program log: Instruction: CloseMerchant".to_string(),
+            program_data(&sample::<MerchantClosed>()),
+            format!("Program {gotsol} consumed 5000 of 200000 compute units"),
+            format!("Program {gotsol} success"),
+        ];
+
+        assert_eq!(logged_names(&logs), vec![(1, "MerchantClosed")]);
+    }
+
+    #[test]
+    fn ignores_program_data_from_other_programs() {
+        let gotsol = gotsol::ID.to_string();
+        let logs = vec![
+            // a look-alike logged by another top-level instruction
+            format!("Program {OTHER_PROGRAM} invoke [1]"),
+            program_data(&sample::<RefundProcessed>()),
+            format!("Program {OTHER_PROGRAM} success"),
+            // gotsol calling out to another program that logs the same bytes
+            format!("Program {gotsol} invoke [1]"),
+            format!("Program {OTHER_PROGRAM} invoke [2]"),
+            program_data(&sample::<WithdrawSolProcessed>()),
+            format!("Program {OTHER_PROGRAM} success"),
+            program_data(&sample::<MerchantClosed>()),
+            format!("Program {gotsol} success"),
+        ];
+
+        assert_eq!(logged_names(&logs), vec![(1, "MerchantClosed")]);
+    }
+
+    #[test]
+    fn keeps_gotsol_events_when_invoked_through_another_program() {
+        let gotsol = gotsol::ID.to_string();
+        let logs = vec![
+            format!("Program {OTHER_PROGRAM} invoke [1]"),
+            format!("Program {gotsol} invoke [2]"),
+            program_data(&sample::<MerchantClosed>()),
+            format!("Program {gotsol} success"),
+            // back in the caller: its own data lines don't count
+            program_data(&sample::<RefundProcessed>()),
+            format!("Program {OTHER_PROGRAM} success"),
+        ];
+
+        assert_eq!(logged_names(&logs), vec![(0, "MerchantClosed")]);
+    }
+
+    #[test]
+    fn stops_attributing_lines_to_gotsol_after_it_fails() {
+        let gotsol = gotsol::ID.to_string();
+        let logs = vec![
+            format!("Program {OTHER_PROGRAM} invoke [1]"),
+            format!("Program {gotsol} invoke [2]"),
+            format!("Program {gotsol} failed: custom program error: 0x1770"),
+            program_data(&sample::<RefundProcessed>()),
+            format!("Program {OTHER_PROGRAM} failed: custom program error: 0x1770"),
+        ];
+
+        assert!(parse_logged_events(&logs).is_empty());
+    }
+}
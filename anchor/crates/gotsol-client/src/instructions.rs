@@ -0,0 +1,232 @@
+// One builder per `#[program]` entry point. Account structs come from `gotsol::accounts`;
+// entry points that read `ctx.remaining_accounts` take the extra metas as a trailing argument.
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::instruction::Instruction;
+use anchor_lang::InstructionData;
+use gotsol::state::{CouponDiscount, OrderItemRefund};
+
+/// Helper function to reduce code duplication when assembling instructions
+fn build(accounts: impl ToAccountMetas, data: impl InstructionData, remaining_accounts: Vec<AccountMeta>) -> Instruction {
+    let mut metas = accounts.to_account_metas(None);
+    metas.extend(remaining_accounts);
+
+    Instruction {
+        program_id: gotsol::ID,
+        accounts: metas,
+        data: data.data(),
+    }
+}
+
+pub fn create_merchant(accounts: gotsol::accounts::CreateMerchant, name: String) -> Instruction {
+    build(accounts, gotsol::instruction::CreateMerchant { name }, vec![])
+}
+
+pub fn withdraw_spl(accounts: gotsol::accounts::WithdrawSpl, amount: u64) -> Instruction {
+    build(accounts, gotsol::instruction::WithdrawSpl { amount }, vec![])
+}
+
+pub fn withdraw_sol(accounts: gotsol::accounts::WithdrawSol, amount: u64) -> Instruction {
+    build(accounts, gotsol::instruction::WithdrawSol { amount }, vec![])
+}
+
+pub fn withdraw_spl_all(accounts: gotsol::accounts::WithdrawSpl) -> Instruction {
+    build(accounts, gotsol::instruction::WithdrawSplAll {}, vec![])
+}
+
+pub fn withdraw_sol_all(accounts: gotsol::accounts::WithdrawSol) -> Instruction {
+    build(accounts, gotsol::instruction::WithdrawSolAll {}, vec![])
+}
+
+pub fn refund_spl(accounts: gotsol::accounts::RefundSpl, original_tx_sig: String, amount: u64) -> Instruction {
+    build(accounts, gotsol::instruction::RefundSpl { original_tx_sig, amount }, vec![])
+}
+
+pub fn refund_sol(accounts: gotsol::accounts::RefundSol, original_tx_sig: String, amount: u64) -> Instruction {
+    build(accounts, gotsol::instruction::RefundSol { original_tx_sig, amount }, vec![])
+}
+
+pub fn set_merchant_status(accounts: gotsol::accounts::SetMerchantStatus, fee_eligible: bool) -> Instruction {
+    build(accounts, gotsol::instruction::SetMerchantStatus { fee_eligible }, vec![])
+}
+
+pub fn close_merchant(accounts: gotsol::accounts::CloseMerchant) -> Instruction {
+    build(accounts, gotsol::instruction::CloseMerchant {}, vec![])
+}
+
+pub fn close_refund(accounts: gotsol::accounts::CloseRefund) -> Instruction {
+    build(accounts, gotsol::instruction::CloseRefund {}, vec![])
+}
+
+pub fn close_legacy_refund(accounts: gotsol::accounts::CloseLegacyRefund, original_tx_sig: String) -> Instruction {
+    build(accounts, gotsol::instruction::CloseLegacyRefund { original_tx_sig }, vec![])
+}
+
+pub fn create_invoice(accounts: gotsol::accounts::CreateInvoice, invoice_id: u64, amount: u64, due_date: i64, memo_hash: [u8; 32]) -> Instruction {
+    build(accounts, gotsol::instruction::CreateInvoice { invoice_id, amount, due_date, memo_hash }, vec![])
+}
+
+pub fn pay_invoice(accounts: gotsol::accounts::PayInvoice, amount: u64, coupon_code: Option<Vec<u8>>) -> Instruction {
+    build(accounts, gotsol::instruction::PayInvoice { amount, coupon_code }, vec![])
+}
+
+pub fn cancel_invoice(accounts: gotsol::accounts::CancelInvoice) -> Instruction {
+    build(accounts, gotsol::instruction::CancelInvoice {}, vec![])
+}
+
+pub fn close_invoice(accounts: gotsol::accounts::CloseInvoice) -> Instruction {
+    build(accounts, gotsol::instruction::CloseInvoice {}, vec![])
+}
+
+pub fn create_tip_pool(accounts: gotsol::accounts::CreateTipPool) -> Instruction {
+    build(accounts, gotsol::instruction::CreateTipPool {}, vec![])
+}
+
+pub fn pay_spl(accounts: gotsol::accounts::PaySpl, amount: u64, tip: u64, employee: Option<Pubkey>, points_to_redeem: u64, coupon_code: Option<Vec<u8>>) -> Instruction {
+    build(accounts, gotsol::instruction::PaySpl { amount, tip, employee, points_to_redeem, coupon_code }, vec![])
+}
+
+pub fn distribute_tips(accounts: gotsol::accounts::DistributeTips, amounts: Vec<u64>, remaining_accounts: Vec<AccountMeta>) -> Instruction {
+    build(accounts, gotsol::instruction::DistributeTips { amounts }, remaining_accounts)
+}
+
+pub fn authorize_payment(accounts: gotsol::accounts::AuthorizePayment, authorization_id: u64, amount: u64, expires_at: i64) -> Instruction {
+    build(accounts, gotsol::instruction::AuthorizePayment { authorization_id, amount, expires_at }, vec![])
+}
+
+pub fn capture_payment(accounts: gotsol::accounts::CapturePayment, amount: u64) -> Instruction {
+    build(accounts, gotsol::instruction::CapturePayment { amount }, vec![])
+}
+
+pub fn void_authorization(accounts: gotsol::accounts::VoidAuthorization) -> Instruction {
+    build(accounts, gotsol::instruction::VoidAuthorization {}, vec![])
+}
+
+pub fn create_subscription(accounts: gotsol::accounts::CreateSubscription, amount: u64, interval: i64, max_charges: u64) -> Instruction {
+    build(accounts, gotsol::instruction::CreateSubscription { amount, interval, max_charges }, vec![])
+}
+
+pub fn charge_subscription(accounts: gotsol::accounts::ChargeSubscription) -> Instruction {
+    build(accounts, gotsol::instruction::ChargeSubscription {}, vec![])
+}
+
+pub fn cancel_subscription(accounts: gotsol::accounts::CancelSubscription) -> Instruction {
+    build(accounts, gotsol::instruction::CancelSubscription {}, vec![])
+}
+
+pub fn purchase_gift_card(accounts: gotsol::accounts::PurchaseGiftCard, card_id: u64, amount: u64, holder: Option<Pubkey>, code_key: Option<Pubkey>, expires_at: i64) -> Instruction {
+    build(accounts, gotsol::instruction::PurchaseGiftCard { card_id, amount, holder, code_key, expires_at }, vec![])
+}
+
+pub fn close_gift_card(accounts: gotsol::accounts::CloseGiftCard) -> Instruction {
+    build(accounts, gotsol::instruction::CloseGiftCard {}, vec![])
+}
+
+pub fn create_loyalty_program(accounts: gotsol::accounts::CreateLoyaltyProgram, earn_basis_points: u64, redemption_basis_points: u64, decimals: u8, non_transferable: bool) -> Instruction {
+    build(accounts, gotsol::instruction::CreateLoyaltyProgram { earn_basis_points, redemption_basis_points, decimals, non_transferable }, vec![])
+}
+
+pub fn update_loyalty_program(accounts: gotsol::accounts::UpdateLoyaltyProgram, earn_basis_points: u64, redemption_basis_points: u64) -> Instruction {
+    build(accounts, gotsol::instruction::UpdateLoyaltyProgram { earn_basis_points, redemption_basis_points }, vec![])
+}
+
+pub fn create_coupon(accounts: gotsol::accounts::CreateCoupon, code_hash: [u8; 32], discount: CouponDiscount, max_redemptions: u32, expires_at: i64) -> Instruction {
+    build(accounts, gotsol::instruction::CreateCoupon { code_hash, discount, max_redemptions, expires_at }, vec![])
+}
+
+pub fn close_coupon(accounts: gotsol::accounts::CloseCoupon) -> Instruction {
+    build(accounts, gotsol::instruction::CloseCoupon {}, vec![])
+}
+
+pub fn add_manager(accounts: gotsol::accounts::AddManager, manager: Pubkey) -> Instruction {
+    build(accounts, gotsol::instruction::AddManager { manager }, vec![])
+}
+
+pub fn remove_manager(accounts: gotsol::accounts::RemoveManager) -> Instruction {
+    build(accounts, gotsol::instruction::RemoveManager {}, vec![])
+}
+
+pub fn create_product(accounts: gotsol::accounts::CreateProduct, sku: String, price: u64, stock: u64, tax_basis_points: u16) -> Instruction {
+    build(accounts, gotsol::instruction::CreateProduct { sku, price, stock, tax_basis_points }, vec![])
+}
+
+pub fn update_product(accounts: gotsol::accounts::UpdateProduct, price: Option<u64>, stock: Option<u64>, tax_basis_points: Option<u16>, active: Option<bool>) -> Instruction {
+    build(accounts, gotsol::instruction::UpdateProduct { price, stock, tax_basis_points, active }, vec![])
+}
+
+pub fn delete_product(accounts: gotsol::accounts::DeleteProduct) -> Instruction {
+    build(accounts, gotsol::instruction::DeleteProduct {}, vec![])
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn pay_order(accounts: gotsol::accounts::PayOrder, order_id: u64, quantities: Vec<u64>, expected_total: u64, tip: u64, coupon_code: Option<Vec<u8>>, remaining_accounts: Vec<AccountMeta>) -> Instruction {
+    build(accounts, gotsol::instruction::PayOrder { order_id, quantities, expected_total, tip, coupon_code }, remaining_accounts)
+}
+
+pub fn refund_order_items(accounts: gotsol::accounts::RefundOrderItems, items: Vec<OrderItemRefund>) -> Instruction {
+    build(accounts, gotsol::instruction::RefundOrderItems { items }, vec![])
+}
+
+pub fn close_order(accounts: gotsol::accounts::CloseOrder) -> Instruction {
+    build(accounts, gotsol::instruction::CloseOrder {}, vec![])
+}
+
+pub fn pay_sol_usd(accounts: gotsol::accounts::PaySolUsd, payment_id: u64, usd_amount: u64, max_lamports: u64) -> Instruction {
+    build(accounts, gotsol::instruction::PaySolUsd { payment_id, usd_amount, max_lamports }, vec![])
+}
+
+pub fn close_payment(accounts: gotsol::accounts::ClosePayment) -> Instruction {
+    build(accounts, gotsol::instruction::ClosePayment {}, vec![])
+}
+
+pub fn initialize_global(accounts: gotsol::accounts::InitializeGlobal, swap_router: Pubkey) -> Instruction {
+    build(accounts, gotsol::instruction::InitializeGlobal { swap_router }, vec![])
+}
+
+pub fn set_swap_router(accounts: gotsol::accounts::SetSwapRouter, swap_router: Pubkey) -> Instruction {
+    build(accounts, gotsol::instruction::SetSwapRouter { swap_router }, vec![])
+}
+
+pub fn withdraw_sol_as_spl(accounts: gotsol::accounts::WithdrawSolAsSpl, amount: u64, minimum_out: u64, swap_data: Vec<u8>, remaining_accounts: Vec<AccountMeta>) -> Instruction {
+    build(accounts, gotsol::instruction::WithdrawSolAsSpl { amount, minimum_out, swap_data }, remaining_accounts)
+}
+
+pub fn withdraw_many(accounts: gotsol::accounts::WithdrawMany, amounts: Vec<Option<u64>>, remaining_accounts: Vec<AccountMeta>) -> Instruction {
+    build(accounts, gotsol::instruction::WithdrawMany { amounts }, remaining_accounts)
+}
+
+pub fn set_payout_schedule(accounts: gotsol::accounts::SetPayoutSchedule, interval: i64, minimum_threshold: u64, destination: Pubkey, bounty_basis_points: u16) -> Instruction {
+    build(accounts, gotsol::instruction::SetPayoutSchedule { interval, minimum_threshold, destination, bounty_basis_points }, vec![])
+}
+
+pub fn cancel_payout_schedule(accounts: gotsol::accounts::CancelPayoutSchedule) -> Instruction {
+    build(accounts, gotsol::instruction::CancelPayoutSchedule {}, vec![])
+}
+
+pub fn crank_payout(accounts: gotsol::accounts::CrankPayout) -> Instruction {
+    build(accounts, gotsol::instruction::CrankPayout {}, vec![])
+}
+
+pub fn set_sol_withdrawal_limit(accounts: gotsol::accounts::SetSolWithdrawalLimit, max_per_withdrawal: u64, max_per_day: u64) -> Instruction {
+    build(accounts, gotsol::instruction::SetSolWithdrawalLimit { max_per_withdrawal, max_per_day }, vec![])
+}
+
+pub fn set_spl_withdrawal_limit(accounts: gotsol::accounts::SetSplWithdrawalLimit, max_per_withdrawal: u64, max_per_day: u64) -> Instruction {
+    build(accounts, gotsol::instruction::SetSplWithdrawalLimit { max_per_withdrawal, max_per_day }, vec![])
+}
+
+pub fn set_rent_recoup_rate(accounts: gotsol::accounts::SetRentRecoupRate, rent_recoup_rate: u64) -> Instruction {
+    build(accounts, gotsol::instruction::SetRentRecoupRate { rent_recoup_rate }, vec![])
+}
+
+pub fn set_sponsorship_budget(accounts: gotsol::accounts::SetSponsorshipBudget, allowance_per_period: u64, period: i64) -> Instruction {
+    build(accounts, gotsol::instruction::SetSponsorshipBudget { allowance_per_period, period }, vec![])
+}
+
+pub fn top_up_sponsorship_budget(accounts: gotsol::accounts::TopUpSponsorshipBudget, lamports: u64) -> Instruction {
+    build(accounts, gotsol::instruction::TopUpSponsorshipBudget { lamports }, vec![])
+}
+
+pub fn set_fee_payer_approval(accounts: gotsol::accounts::SetFeePayerApproval, fee_payer: Pubkey, approved: bool) -> Instruction {
+    build(accounts, gotsol::instruction::SetFeePayerApproval { fee_payer, approved }, vec![])
+}
@@ -0,0 +1,17 @@
+//! Typed helpers for building GotSOL transactions and reading its accounts and
+//! events off-chain: PDA derivation, one instruction builder per program entry
+//! point, account deserializers and event decoders.
+
+pub mod accounts;
+pub mod events;
+pub mod instructions;
+pub mod pda;
+#[cfg(feature = "rpc")]
+pub mod rpc;
+
+use anchor_lang::prelude::{pubkey, Pubkey};
+
+pub use gotsol::ID as PROGRAM_ID;
+
+/// Receives the house share of withdrawals; mirrors `HOUSE` in programs/gotsol/src/constants.rs
+pub const HOUSE: Pubkey = pubkey!("Hth4EBxLWJSoRWj7raCKoniuzcvXt8MUFgGKty3B66ih");
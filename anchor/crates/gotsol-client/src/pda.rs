@@ -0,0 +1,111 @@
+use anchor_lang::prelude::Pubkey;
+
+use crate::PROGRAM_ID;
+
+// Seeds mirror the `seeds = [...]` constraints in programs/gotsol/src/instructions
+
+pub fn merchant(name: &str, owner: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"merchant", name.as_bytes(), owner.as_ref()], &PROGRAM_ID)
+}
+
+pub fn vault(merchant: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"vault", merchant.as_ref()], &PROGRAM_ID)
+}
+
+pub fn refund(original_tx_sig: &str) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"refund", original_tx_sig.as_bytes()], &PROGRAM_ID)
+}
+
+pub fn invoice(merchant: &Pubkey, invoice_id: u64) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"invoice", merchant.as_ref(), &invoice_id.to_le_bytes()], &PROGRAM_ID)
+}
+
+pub fn tip_pool(merchant: &Pubkey, stablecoin_mint: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"tip_pool", merchant.as_ref(), stablecoin_mint.as_ref()], &PROGRAM_ID)
+}
+
+pub fn authorization(merchant: &Pubkey, customer: &Pubkey, authorization_id: u64) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[b"authorization", merchant.as_ref(), customer.as_ref(), &authorization_id.to_le_bytes()],
+        &PROGRAM_ID,
+    )
+}
+
+pub fn escrow(authorization: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"escrow", authorization.as_ref()], &PROGRAM_ID)
+}
+
+pub fn subscription(merchant: &Pubkey, customer: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"subscription", merchant.as_ref(), customer.as_ref()], &PROGRAM_ID)
+}
+
+// Delegate approved on customers' token accounts for all of their subscriptions
+pub fn subscription_delegate() -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"subscription_delegate"], &PROGRAM_ID)
+}
+
+pub fn gift_card(merchant: &Pubkey, card_id: u64) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"gift_card", merchant.as_ref(), &card_id.to_le_bytes()], &PROGRAM_ID)
+}
+
+pub fn loyalty(merchant: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"loyalty", merchant.as_ref()], &PROGRAM_ID)
+}
+
+pub fn points_mint(merchant: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"points_mint", merchant.as_ref()], &PROGRAM_ID)
+}
+
+pub fn coupon(merchant: &Pubkey, code_hash: &[u8; 32]) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"coupon", merchant.as_ref(), code_hash.as_ref()], &PROGRAM_ID)
+}
+
+pub fn manager(merchant: &Pubkey, manager: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"manager", merchant.as_ref(), manager.as_ref()], &PROGRAM_ID)
+}
+
+pub fn product(merchant: &Pubkey, sku: &str) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"product", merchant.as_ref(), sku.as_bytes()], &PROGRAM_ID)
+}
+
+pub fn order(merchant: &Pubkey, customer: &Pubkey, order_id: u64) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"order", merchant.as_ref(), customer.as_ref(), &order_id.to_le_bytes()], &PROGRAM_ID)
+}
+
+pub fn payment(merchant: &Pubkey, payer: &Pubkey, payment_id: u64) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"payment", merchant.as_ref(), payer.as_ref(), &payment_id.to_le_bytes()], &PROGRAM_ID)
+}
+
+pub fn global() -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"global"], &PROGRAM_ID)
+}
+
+pub fn payout_schedule(merchant: &Pubkey, stablecoin_mint: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"payout_schedule", merchant.as_ref(), stablecoin_mint.as_ref()], &PROGRAM_ID)
+}
+
+pub fn sol_withdrawal_limit(merchant: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"withdrawal_limit", merchant.as_ref()], &PROGRAM_ID)
+}
+
+pub fn spl_withdrawal_limit(merchant: &Pubkey, stablecoin_mint: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"withdrawal_limit", merchant.as_ref(), stablecoin_mint.as_ref()], &PROGRAM_ID)
+}
+
+// withdraw_sol_as_spl routes must spend from `swap_wsol`, signed for by `swap_authority`
+pub fn swap_authority(merchant: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"swap_authority", merchant.as_ref()], &PROGRAM_ID)
+}
+
+pub fn swap_wsol(merchant: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"swap_wsol", merchant.as_ref()], &PROGRAM_ID)
+}
+
+pub fn sponsorship_budget(merchant: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"sponsorship_budget", merchant.as_ref()], &PROGRAM_ID)
+}
+
+// `#[event_cpi]` instructions take this PDA as `event_authority`
+pub fn event_authority() -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"__event_authority"], &PROGRAM_ID)
+}
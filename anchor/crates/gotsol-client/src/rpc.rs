@@ -0,0 +1,372 @@
+use std::str::FromStr;
+use std::thread::sleep;
+use std::time::Duration;
+
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::solana_program::hash::Hash;
+use anyhow::{anyhow, bail, Context, Result};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use serde_json::{json, Value};
+
+use crate::events::{decode_cpi_event, parse_logged_events, LoggedEvent};
+
+const CONFIRM_POLL_INTERVAL: Duration = Duration::from_millis(500);
+const CONFIRM_POLL_ATTEMPTS: usize = 60;
+
+/// The most signatures `getSignaturesForAddress` returns in one call
+pub const SIGNATURE_PAGE_SIZE: usize = 1000;
+
+pub struct RpcAccount {
+    pub owner: Pubkey,
+    pub data: Vec<u8>,
+}
+
+/// Minimal blocking JSON-RPC client covering the handful of calls the CLI needs
+pub struct RpcClient {
+    url: String,
+    commitment: String,
+}
+
+impl RpcClient {
+    pub fn new(url: String, commitment: String) -> Self {
+        Self { url, commitment }
+    }
+
+    fn call(&self, method: &str, params: Value) -> Result<Value> {
+        let request = json!({ "jsonrpc": "2.0", "id": 1, "method": method, "params": params });
+        let response: Value = ureq::post(&self.url)
+            .send_json(request)
+            .with_context(|| format!("{method} request to {} failed", self.url))?
+            .into_json()?;
+
+        if let Some(error) = response.get("error") {
+            bail!("{method} failed: {error}");
+        }
+        Ok(response["result"].clone())
+    }
+
+    pub fn latest_blockhash(&self) -> Result<Hash> {
+        let result = self.call("getLatestBlockhash", json!([{ "commitment": self.commitment }]))?;
+        let blockhash = result["value"]["blockhash"].as_str().ok_or_else(|| anyhow!("missing blockhash"))?;
+        Hash::from_str(blockhash).map_err(|e| anyhow!("invalid blockhash {blockhash}: {e}"))
+    }
+
+    pub fn account(&self, pubkey: &Pubkey) -> Result<Option<RpcAccount>> {
+        let result = self.call(
+            "getAccountInfo",
+            json!([pubkey.to_string(), { "encoding": "base64", "commitment": self.commitment }]),
+        )?;
+        match &result["value"] {
+            Value::Null => Ok(None),
+            account => parse_account(account).map(Some),
+        }
+    }
+
+    pub fn program_accounts(&self, program_id: &Pubkey, filters: Value) -> Result<Vec<(Pubkey, RpcAccount)>> {
+        let result = self.call(
+            "getProgramAccounts",
+            json!([program_id.to_string(), { "encoding": "base64", "commitment": self.commitment, "filters": filters }]),
+        )?;
+        let entries = result.as_array().ok_or_else(|| anyhow!("getProgramAccounts returned no list"))?;
+
+        entries
+            .iter()
+            .map(|entry| {
+                let pubkey = parse_pubkey(&entry["pubkey"])?;
+                Ok((pubkey, parse_account(&entry["account"])?))
+            })
+            .collect()
+    }
+
+    /// Simulates without signature checks so a dry run never needs every signer online
+    pub fn simulate(&self, transaction: &[u8]) -> Result<Value> {
+        let result = self.call(
+            "simulateTransaction",
+            json!([STANDARD.encode(transaction), {
+                "encoding": "base64",
+                "commitment": self.commitment,
+                "sigVerify": false,
+                "replaceRecentBlockhash": true,
+                "innerInstructions": true,
+            }]),
+        )?;
+        Ok(result["value"].clone())
+    }
+
+    pub fn send_and_confirm(&self, transaction: &[u8]) -> Result<String> {
+        let result = self.call(
+            "sendTransaction",
+            json!([STANDARD.encode(transaction), { "encoding": "base64", "preflightCommitment": self.commitment }]),
+        )?;
+        let signature = result.as_str().ok_or_else(|| anyhow!("sendTransaction returned no signature"))?.to_string();
+
+        for _ in 0..CONFIRM_POLL_ATTEMPTS {
+            let statuses = self.call("getSignatureStatuses", json!([[signature]]))?;
+            let status = &statuses["value"][0];
+            if !status.is_null() {
+                if !status["err"].is_null() {
+                    bail!("transaction {signature} failed: {}", status["err"]);
+                }
+                if matches!(status["confirmationStatus"].as_str(), Some("confirmed") | Some("finalized")) {
+                    return Ok(signature);
+                }
+            }
+            sleep(CONFIRM_POLL_INTERVAL);
+        }
+
+        bail!("transaction {signature} was not confirmed in time")
+    }
+
+    /// Fetches a transaction in the `getTransaction` JSON shape, or `None` if the node doesn't have it
+    pub fn transaction(&self, signature: &str) -> Result<Option<Value>> {
+        let result = self.call(
+            "getTransaction",
+            json!([signature, { "encoding": "json", "commitment": self.commitment, "maxSupportedTransactionVersion": 0 }]),
+        )?;
+        Ok((!result.is_null()).then_some(result))
+    }
+
+    /// Signatures touching `address`, newest first, strictly between `until` and `before`
+    pub fn signatures_for_address(
+        &self,
+        address: &Pubkey,
+        before: Option<&str>,
+        until: Option<&str>,
+        limit: usize,
+    ) -> Result<Vec<String>> {
+        let result = self.call(
+            "getSignaturesForAddress",
+            json!([address.to_string(), { "commitment": self.commitment, "before": before, "until": until, "limit": limit }]),
+        )?;
+        let entries = result.as_array().ok_or_else(|| anyhow!("getSignaturesForAddress returned no list"))?;
+
+        entries
+            .iter()
+            .map(|entry| entry["signature"].as_str().map(str::to_string).ok_or_else(|| anyhow!("missing signature")))
+            .collect()
+    }
+
+    /// Every signature touching `address` newer than `until`, oldest first
+    pub fn signatures_since(&self, address: &Pubkey, until: Option<&str>) -> Result<Vec<String>> {
+        let mut pending: Vec<String> = vec![];
+        loop {
+            let page = self.signatures_for_address(address, pending.last().map(String::as_str), until, SIGNATURE_PAGE_SIZE)?;
+            let exhausted = page.len() < SIGNATURE_PAGE_SIZE;
+            pending.extend(page);
+            if exhausted {
+                break;
+            }
+        }
+
+        pending.reverse();
+        Ok(pending)
+    }
+}
+
+/// A successful transaction reduced to the GotSOL events it emitted
+pub struct ProgramTransaction {
+    pub signature: String,
+    pub slot: u64,
+    pub block_time: Option<i64>,
+    pub events: Vec<LoggedEvent>,
+}
+
+impl ProgramTransaction {
+    /// Parses a transaction in the `getTransaction` JSON shape, bare or wrapped in a
+    /// JSON-RPC response. Failed transactions yield `None`: their logs can still carry
+    /// events that were rolled back.
+    pub fn from_json(value: &Value) -> Result<Option<Self>> {
+        let value = value.get("result").unwrap_or(value);
+        if !value["meta"]["err"].is_null() {
+            return Ok(None);
+        }
+
+        let signature = value["transaction"]["signatures"][0]
+            .as_str()
+            .ok_or_else(|| anyhow!("transaction has no signature"))?
+            .to_string();
+        let slot = value["slot"].as_u64().ok_or_else(|| anyhow!("transaction {signature} has no slot"))?;
+
+        Ok(Some(Self {
+            events: transaction_events(value)?,
+            signature,
+            slot,
+            block_time: value["blockTime"].as_i64(),
+        }))
+    }
+}
+
+pub fn string_list(value: &Value) -> Vec<String> {
+    value
+        .as_array()
+        .map(|items| items.iter().filter_map(|item| item.as_str().map(str::to_string)).collect())
+        .unwrap_or_default()
+}
+
+fn parse_pubkey(value: &Value) -> Result<Pubkey> {
+    let text = value.as_str().ok_or_else(|| anyhow!("expected a base58 pubkey, got {value}"))?;
+    Pubkey::from_str(text).map_err(|e| anyhow!("invalid pubkey {text}: {e}"))
+}
+
+fn parse_account(value: &Value) -> Result<RpcAccount> {
+    let encoded = value["data"][0].as_str().ok_or_else(|| anyhow!("account data is not base64"))?;
+    Ok(RpcAccount {
+        owner: parse_pubkey(&value["owner"])?,
+        data: STANDARD.decode(encoded)?,
+    })
+}
+
+/// Static keys followed by any addresses loaded from lookup tables, matching the
+/// indexes used by compiled instructions in a `getTransaction` JSON result
+pub fn account_keys(transaction: &Value) -> Result<Vec<Pubkey>> {
+    let loaded = &transaction["meta"]["loadedAddresses"];
+
+    [&transaction["transaction"]["message"]["accountKeys"], &loaded["writable"], &loaded["readonly"]]
+        .into_iter()
+        .flat_map(string_list)
+        .map(|key| Pubkey::from_str(&key).map_err(|e| anyhow!("invalid account key {key}: {e}")))
+        .collect()
+}
+
+/// Events emitted with `emit_cpi!`, read from the inner instructions GotSOL invoked on
+/// itself. Only the program's event authority PDA can sign those, so they can't be
+/// forged by other programs the way log lines can.
+pub fn inner_instruction_events(inner_instructions: &Value, account_keys: &[Pubkey]) -> Vec<LoggedEvent> {
+    let mut events = vec![];
+
+    for group in inner_instructions.as_array().into_iter().flatten() {
+        let Some(instruction_index) = group["index"].as_u64() else {
+            continue;
+        };
+
+        for instruction in group["instructions"].as_array().into_iter().flatten() {
+            let program_id = instruction["programIdIndex"].as_u64().and_then(|index| account_keys.get(index as usize));
+            if program_id != Some(&crate::PROGRAM_ID) {
+                continue;
+            }
+
+            let event = instruction["data"]
+                .as_str()
+                .and_then(|data| bs58::decode(data).into_vec().ok())
+                .and_then(|data| decode_cpi_event(&data));
+            if let Some(event) = event {
+                events.push(LoggedEvent {
+                    instruction_index: instruction_index as usize,
+                    event,
+                });
+            }
+        }
+    }
+
+    events
+}
+
+/// Every GotSOL event in a `getTransaction` JSON result, ordered by the top-level
+/// instruction that emitted it: `emit!` events from the logs, then `emit_cpi!` events
+/// from inner instructions
+pub fn transaction_events(transaction: &Value) -> Result<Vec<LoggedEvent>> {
+    let account_keys = account_keys(transaction)?;
+
+    let mut events = parse_logged_events(&string_list(&transaction["meta"]["logMessages"]));
+    events.extend(inner_instruction_events(&transaction["meta"]["innerInstructions"], &account_keys));
+    events.sort_by_key(|logged| logged.instruction_index);
+
+    Ok(events)
+}
+
+#[cfg(test)]
+mod tests {
+    use anchor_lang::event::EVENT_IX_TAG_LE;
+    use anchor_lang::Event;
+    use gotsol::events::{EventHeader, MerchantClosed, RefundProcessed, EVENT_SCHEMA_VERSION};
+
+    use super::*;
+
+    fn merchant_closed() -> MerchantClosed {
+        MerchantClosed {
+            header: EventHeader {
+                version: EVENT_SCHEMA_VERSION,
+                merchant: Some(Pubkey::new_from_array([1; 32])),
+                actor: Pubkey::new_from_array([2; 32]),
+                mint: None,
+                timestamp: 1_700_000_000,
+                slot: 42,
+                balance: None,
+            },
+            entity_name: "Coffee Shop".to_string(),
+        }
+    }
+
+    fn inner(program_id_index: usize, data: &[u8]) -> Value {
+        json!({ "programIdIndex": program_id_index, "accounts": [], "data": bs58::encode(data).into_string() })
+    }
+
+    fn names(events: &[LoggedEvent]) -> Vec<(usize, &'static str)> {
+        events.iter().map(|logged| (logged.instruction_index, logged.event.name())).collect()
+    }
+
+    #[test]
+    fn decodes_cpi_tagged_self_invocations_only() {
+        let other_program = Pubkey::new_unique();
+        let account_keys = [Pubkey::new_unique(), other_program, crate::PROGRAM_ID];
+        let tagged = [EVENT_IX_TAG_LE, &merchant_closed().data()].concat();
+
+        let inner_instructions = json!([
+            { "index": 0, "instructions": [
+                // the same bytes sent to another program aren't ours
+                inner(1, &tagged),
+                // an untagged self-CPI is an ordinary instruction, not an event
+                inner(2, &merchant_closed().data()),
+            ] },
+            { "index": 3, "instructions": [inner(2, &tagged), inner(7, &tagged)] },
+        ]);
+
+        let events = inner_instruction_events(&inner_instructions, &account_keys);
+
+        assert_eq!(names(&events), vec![(3, "MerchantClosed")]);
+        assert_eq!(events[0].event.merchant(), Some(Pubkey::new_from_array([1; 32])));
+    }
+
+    #[test]
+    fn orders_logged_and_cpi_events_by_instruction() {
+        let gotsol = crate::PROGRAM_ID.to_string();
+        let refund = RefundProcessed {
+            header: merchant_closed().header,
+            original_tx_sig: "5VERv8NM".to_string(),
+            amount: 1_000,
+            recipient: Pubkey::new_unique(),
+            sponsor: None,
+        };
+        let tagged = [EVENT_IX_TAG_LE, &refund.data()].concat();
+
+        let transaction = json!({
+            "transaction": { "message": { "accountKeys": [Pubkey::new_unique().to_string(), gotsol] } },
+            "meta": {
+                "err": null,
+                "logMessages": [
+                    format!("Program {gotsol} invoke [1]"),
+                    format!("Program {gotsol} success"),
+                    format!("Program {gotsol} invoke [1]"),
+                    format!("Program data: {}", STANDARD.encode(merchant_closed().data())),
+                    format!("Program {gotsol} success"),
+                ],
+                "innerInstructions": [{ "index": 0, "instructions": [inner(1, &tagged)] }],
+            },
+        });
+
+        let events = transaction_events(&transaction).unwrap();
+
+        assert_eq!(names(&events), vec![(0, "RefundProcessed"), (1, "MerchantClosed")]);
+    }
+
+    #[test]
+    fn skips_failed_transactions() {
+        let transaction = json!({
+            "slot": 1,
+            "transaction": { "signatures": ["sig"], "message": { "accountKeys": [] } },
+            "meta": { "err": { "InstructionError": [0, { "Custom": 6000 }] }, "logMessages": [] },
+        });
+
+        assert!(ProgramTransaction::from_json(&transaction).unwrap().is_none());
+    }
+}
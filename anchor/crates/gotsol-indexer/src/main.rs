@@ -0,0 +1,167 @@
+mod store;
+
+use std::fs::File;
+use std::io::{self, BufRead, BufReader};
+use std::path::PathBuf;
+use std::thread::sleep;
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use clap::{Parser, Subcommand};
+use gotsol_client::rpc::{ProgramTransaction, RpcClient, SIGNATURE_PAGE_SIZE};
+use gotsol_client::PROGRAM_ID;
+use serde_json::Value;
+
+use crate::store::Store;
+
+const FOLLOW_CURSOR: &str = "follow";
+const BACKFILL_CURSOR: &str = "backfill";
+
+#[derive(Parser)]
+#[command(name = "gotsol-indexer", version, about = "Materializes GotSOL program events into SQLite")]
+struct Cli {
+    /// SQLite database, created if missing
+    #[arg(long, env = "GOTSOL_INDEX_DB", default_value = "gotsol-index.sqlite", global = true)]
+    db: PathBuf,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Ingest a dump of transactions, one `getTransaction` JSON object per line
+    File { path: PathBuf },
+    /// Ingest transactions from stdin as they arrive, in the same line format (e.g. piped from a Geyser plugin)
+    Stream,
+    /// Poll an RPC node for new program transactions
+    Follow {
+        #[command(flatten)]
+        rpc: RpcArgs,
+        #[arg(long, default_value_t = 5)]
+        interval_secs: u64,
+    },
+    /// Walk the program's history backwards, resuming where the last backfill stopped
+    Backfill {
+        #[command(flatten)]
+        rpc: RpcArgs,
+        /// Start from this signature instead of the saved backfill position
+        #[arg(long)]
+        before: Option<String>,
+        /// Stop once this signature is reached
+        #[arg(long)]
+        until: Option<String>,
+    },
+}
+
+#[derive(clap::Args)]
+struct RpcArgs {
+    #[arg(long, short = 'u', env = "GOTSOL_RPC_URL", default_value = "http://127.0.0.1:8899")]
+    url: String,
+
+    #[arg(long, default_value = "confirmed")]
+    commitment: String,
+}
+
+impl RpcArgs {
+    fn client(self) -> RpcClient {
+        RpcClient::new(self.url, self.commitment)
+    }
+}
+
+fn main() -> Result<()> {
+    let cli = Cli::parse();
+    let mut store = Store::open(&cli.db).with_context(|| format!("failed to open {}", cli.db.display()))?;
+
+    match cli.command {
+        Command::File { path } => {
+            let file = File::open(&path).with_context(|| format!("failed to open {}", path.display()))?;
+            ingest_lines(&mut store, BufReader::new(file))
+        }
+        Command::Stream => ingest_lines(&mut store, io::stdin().lock()),
+        Command::Follow { rpc, interval_secs } => follow(&mut store, &rpc.client(), Duration::from_secs(interval_secs)),
+        Command::Backfill { rpc, before, until } => backfill(&mut store, &rpc.client(), before, until),
+    }
+}
+
+fn ingest_lines(store: &mut Store, reader: impl BufRead) -> Result<()> {
+    let (mut transactions, mut events) = (0, 0);
+
+    for (number, line) in reader.lines().enumerate() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let value: Value = serde_json::from_str(&line).with_context(|| format!("line {} is not JSON", number + 1))?;
+        if let Some(transaction) = ProgramTransaction::from_json(&value)? {
+            events += ingest(store, &transaction)?;
+            transactions += 1;
+        }
+    }
+
+    println!("{transactions} transactions read, {events} new events");
+    Ok(())
+}
+
+fn ingest(store: &mut Store, transaction: &ProgramTransaction) -> Result<usize> {
+    let written = store.ingest(transaction)?;
+    if written > 0 {
+        println!("{} (slot {}): {written} events", transaction.signature, transaction.slot);
+    }
+    Ok(written)
+}
+
+fn fetch_and_ingest(store: &mut Store, rpc: &RpcClient, signature: &str) -> Result<()> {
+    let value = rpc.transaction(signature)?.with_context(|| format!("transaction {signature} not found"))?;
+    if let Some(transaction) = ProgramTransaction::from_json(&value)? {
+        ingest(store, &transaction)?;
+    }
+    Ok(())
+}
+
+/// Ingests everything newer than the follow cursor, oldest first, then polls for more.
+/// A fresh database starts at the current tip; use `backfill` for earlier history.
+fn follow(store: &mut Store, rpc: &RpcClient, interval: Duration) -> Result<()> {
+    if store.cursor(FOLLOW_CURSOR)?.is_none() {
+        if let Some(newest) = rpc.signatures_for_address(&PROGRAM_ID, None, None, 1)?.first() {
+            fetch_and_ingest(store, rpc, newest)?;
+            store.set_cursor(FOLLOW_CURSOR, newest)?;
+        }
+    }
+
+    loop {
+        let until = store.cursor(FOLLOW_CURSOR)?;
+        for signature in &rpc.signatures_since(&PROGRAM_ID, until.as_deref())? {
+            fetch_and_ingest(store, rpc, signature)?;
+            store.set_cursor(FOLLOW_CURSOR, signature)?;
+        }
+
+        sleep(interval);
+    }
+}
+
+/// Walks history newest to oldest, saving the oldest signature handled so an
+/// interrupted backfill picks up where it stopped
+fn backfill(store: &mut Store, rpc: &RpcClient, before: Option<String>, until: Option<String>) -> Result<()> {
+    let mut before = match before {
+        Some(before) => Some(before),
+        None => store.cursor(BACKFILL_CURSOR)?,
+    };
+
+    loop {
+        let page = rpc.signatures_for_address(&PROGRAM_ID, before.as_deref(), until.as_deref(), SIGNATURE_PAGE_SIZE)?;
+        for signature in &page {
+            fetch_and_ingest(store, rpc, signature)?;
+            store.set_cursor(BACKFILL_CURSOR, signature)?;
+        }
+
+        if page.len() < SIGNATURE_PAGE_SIZE {
+            println!("backfill complete");
+            return Ok(());
+        }
+        before = page.last().cloned();
+    }
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,622 @@
+use std::path::Path;
+
+use anchor_lang::prelude::Pubkey;
+use anyhow::{anyhow, bail, Result};
+use gotsol_client::events::{GotsolEvent, LoggedEvent};
+use gotsol_client::rpc::ProgramTransaction;
+use rusqlite::{params, Connection, OptionalExtension, Transaction};
+
+// Bumped with the table layout; older databases have to be rebuilt with a fresh backfill
+const STORE_VERSION: i64 = 4;
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS transactions (
+    signature TEXT PRIMARY KEY,
+    slot INTEGER NOT NULL,
+    block_time INTEGER
+);
+
+-- every decoded event, including kinds without a dedicated table; the columns between
+-- name and data come from the event header, timestamp is the program's Clock time and
+-- data holds the event's own fields as JSON
+CREATE TABLE IF NOT EXISTS events (
+    signature TEXT NOT NULL REFERENCES transactions (signature),
+    event_index INTEGER NOT NULL,
+    instruction_index INTEGER NOT NULL,
+    name TEXT NOT NULL,
+    version INTEGER NOT NULL,
+    merchant TEXT,
+    actor TEXT NOT NULL,
+    mint TEXT,
+    balance INTEGER,
+    timestamp INTEGER NOT NULL,
+    slot INTEGER NOT NULL,
+    block_time INTEGER,
+    data TEXT NOT NULL,
+    PRIMARY KEY (signature, event_index)
+);
+CREATE INDEX IF NOT EXISTS events_by_merchant ON events (merchant, slot);
+
+-- kind is 'spl' (pay_spl), 'invoice', 'order', 'sol' (pay_sol_usd), 'capture' (capture_payment)
+-- or 'subscription' (charge_subscription). amount is what the payer was charged after
+-- discounts, tip excluded, in base units of mint (lamports for SOL); reference is the
+-- invoice, order, payment, authorization or subscription account and NULL for plain payments
+CREATE TABLE IF NOT EXISTS payments (
+    signature TEXT NOT NULL,
+    event_index INTEGER NOT NULL,
+    merchant TEXT,
+    kind TEXT NOT NULL,
+    mint TEXT,
+    payer TEXT NOT NULL,
+    reference TEXT,
+    amount INTEGER NOT NULL,
+    tip INTEGER NOT NULL,
+    discount INTEGER NOT NULL,
+    gift_card_amount INTEGER NOT NULL,
+    usd_amount INTEGER,
+    balance INTEGER,
+    slot INTEGER NOT NULL,
+    block_time INTEGER,
+    PRIMARY KEY (signature, event_index)
+);
+CREATE INDEX IF NOT EXISTS payments_by_merchant ON payments (merchant, slot);
+
+-- kind is 'payment' (refund_sol / refund_spl), keyed by original_tx_sig, or 'order_items'
+-- (refund_order_items), where reference is the order and original_tx_sig is NULL
+CREATE TABLE IF NOT EXISTS refunds (
+    signature TEXT NOT NULL,
+    event_index INTEGER NOT NULL,
+    merchant TEXT,
+    kind TEXT NOT NULL,
+    mint TEXT NOT NULL,
+    original_tx_sig TEXT,
+    reference TEXT,
+    amount INTEGER NOT NULL,
+    recipient TEXT NOT NULL,
+    sponsor TEXT,
+    balance INTEGER,
+    slot INTEGER NOT NULL,
+    block_time INTEGER,
+    PRIMARY KEY (signature, event_index)
+);
+CREATE INDEX IF NOT EXISTS refunds_by_merchant ON refunds (merchant, slot);
+
+-- kind is 'sol', 'spl' or 'sol_as_spl'; mint is NULL for SOL and the output mint for swaps.
+-- balance is what the merchant has left: vault lamports for SOL and swaps, token units for SPL
+CREATE TABLE IF NOT EXISTS withdrawals (
+    signature TEXT NOT NULL,
+    event_index INTEGER NOT NULL,
+    merchant TEXT,
+    kind TEXT NOT NULL,
+    mint TEXT,
+    amount INTEGER NOT NULL,
+    amount_out INTEGER,
+    owner_amount INTEGER NOT NULL,
+    house_amount INTEGER NOT NULL,
+    balance INTEGER,
+    slot INTEGER NOT NULL,
+    block_time INTEGER,
+    PRIMARY KEY (signature, event_index)
+);
+CREATE INDEX IF NOT EXISTS withdrawals_by_merchant ON withdrawals (merchant, slot);
+
+CREATE TABLE IF NOT EXISTS merchant_closures (
+    signature TEXT NOT NULL,
+    event_index INTEGER NOT NULL,
+    merchant TEXT NOT NULL,
+    entity_name TEXT NOT NULL,
+    slot INTEGER NOT NULL,
+    block_time INTEGER,
+    PRIMARY KEY (signature, event_index)
+);
+
+CREATE TABLE IF NOT EXISTS cursors (
+    name TEXT PRIMARY KEY,
+    signature TEXT NOT NULL
+);
+";
+
+pub struct Store {
+    conn: Connection,
+}
+
+impl Store {
+    pub fn open(path: &Path) -> Result<Self> {
+        let conn = Connection::open(path)?;
+
+        let version: i64 = conn.query_row("PRAGMA user_version", [], |row| row.get(0))?;
+        let empty: bool = conn.query_row("SELECT count(*) = 0 FROM sqlite_master", [], |row| row.get(0))?;
+        if !empty && version != STORE_VERSION {
+            bail!("database was written by an older indexer (layout {version}, expected {STORE_VERSION}); index into a new file");
+        }
+
+        conn.execute_batch(SCHEMA)?;
+        conn.pragma_update(None, "user_version", STORE_VERSION)?;
+        Ok(Self { conn })
+    }
+
+    /// Writes a transaction and its events atomically. Re-ingesting a transaction
+    /// that's already stored is a no-op, so sources can overlap freely.
+    /// Returns the number of events written.
+    pub fn ingest(&mut self, transaction: &ProgramTransaction) -> Result<usize> {
+        let db = self.conn.transaction()?;
+
+        let inserted = db.execute(
+            "INSERT OR IGNORE INTO transactions (signature, slot, block_time) VALUES (?1, ?2, ?3)",
+            params![transaction.signature, transaction.slot as i64, transaction.block_time],
+        )?;
+        if inserted == 0 {
+            return Ok(0);
+        }
+
+        for (event_index, event) in transaction.events.iter().enumerate() {
+            insert_event(&db, transaction, event_index, event)?;
+        }
+
+        db.commit()?;
+        Ok(transaction.events.len())
+    }
+
+    pub fn cursor(&self, name: &str) -> Result<Option<String>> {
+        let signature = self
+            .conn
+            .query_row("SELECT signature FROM cursors WHERE name = ?1", [name], |row| row.get(0))
+            .optional()?;
+        Ok(signature)
+    }
+
+    pub fn set_cursor(&self, name: &str, signature: &str) -> Result<()> {
+        self.conn.execute(
+            "INSERT INTO cursors (name, signature) VALUES (?1, ?2)
+             ON CONFLICT (name) DO UPDATE SET signature = excluded.signature",
+            params![name, signature],
+        )?;
+        Ok(())
+    }
+}
+
+fn insert_event(db: &Transaction, transaction: &ProgramTransaction, event_index: usize, event: &LoggedEvent) -> Result<()> {
+    let header = event.event.header();
+    let merchant = header.merchant.map(|merchant| merchant.to_string());
+    let mint = header.mint.map(|mint| mint.to_string());
+    let balance = header.balance.map(i64::try_from).transpose()?;
+    let signature = &transaction.signature;
+    let slot = transaction.slot as i64;
+    let block_time = transaction.block_time;
+
+    db.execute(
+        "INSERT INTO events
+         (signature, event_index, instruction_index, name, version, merchant, actor, mint, balance, timestamp, slot, block_time, data)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13)",
+        params![
+            signature,
+            event_index,
+            event.instruction_index,
+            event.event.name(),
+            header.version,
+            merchant,
+            header.actor.to_string(),
+            mint,
+            balance,
+            header.timestamp,
+            slot,
+            block_time,
+            event.event.data().to_string(),
+        ],
+    )?;
+
+    match &event.event {
+        GotsolEvent::PaymentProcessed(payment) => {
+            let row = PaymentRow {
+                kind: "spl",
+                payer: payment.payer,
+                reference: None,
+                amount: payment.amount,
+                tip: payment.tip,
+                discount: payment.discount,
+                gift_card_amount: payment.gift_card_amount,
+                usd_amount: None,
+            };
+            insert_payment(db, transaction, event_index, &merchant, mint, &row, balance)?;
+        }
+        GotsolEvent::InvoicePaid(payment) => {
+            let row = PaymentRow {
+                kind: "invoice",
+                payer: payment.payer,
+                reference: Some(payment.invoice),
+                amount: payment.amount,
+                tip: 0,
+                discount: payment.discount,
+                gift_card_amount: 0,
+                usd_amount: None,
+            };
+            insert_payment(db, transaction, event_index, &merchant, mint, &row, balance)?;
+        }
+        GotsolEvent::OrderPaid(payment) => {
+            let charged = payment
+                .subtotal
+                .checked_add(payment.tax)
+                .and_then(|total| total.checked_sub(payment.discount))
+                .ok_or_else(|| anyhow!("order {} has inconsistent totals", payment.order))?;
+            let row = PaymentRow {
+                kind: "order",
+                payer: payment.customer,
+                reference: Some(payment.order),
+                amount: charged,
+                tip: payment.tip,
+                discount: payment.discount,
+                gift_card_amount: payment.gift_card_amount,
+                usd_amount: None,
+            };
+            insert_payment(db, transaction, event_index, &merchant, mint, &row, balance)?;
+        }
+        GotsolEvent::SolPaymentProcessed(payment) => {
+            let row = PaymentRow {
+                kind: "sol",
+                payer: payment.payer,
+                reference: Some(payment.payment),
+                amount: payment.lamports,
+                tip: 0,
+                discount: 0,
+                gift_card_amount: 0,
+                usd_amount: Some(payment.usd_amount),
+            };
+            insert_payment(db, transaction, event_index, &merchant, mint, &row, balance)?;
+        }
+        GotsolEvent::PaymentCaptured(capture) => {
+            let row = PaymentRow {
+                kind: "capture",
+                payer: capture.customer,
+                reference: Some(capture.authorization),
+                amount: capture.captured,
+                tip: 0,
+                discount: 0,
+                gift_card_amount: 0,
+                usd_amount: None,
+            };
+            insert_payment(db, transaction, event_index, &merchant, mint, &row, balance)?;
+        }
+        GotsolEvent::SubscriptionCharged(charge) => {
+            let row = PaymentRow {
+                kind: "subscription",
+                payer: charge.customer,
+                reference: Some(charge.subscription),
+                amount: charge.amount,
+                tip: 0,
+                discount: 0,
+                gift_card_amount: 0,
+                usd_amount: None,
+            };
+            insert_payment(db, transaction, event_index, &merchant, mint, &row, balance)?;
+        }
+        GotsolEvent::RefundProcessed(refund) => {
+            let row = RefundRow {
+                kind: "payment",
+                original_tx_sig: Some(&refund.original_tx_sig),
+                reference: None,
+                amount: refund.amount,
+                recipient: refund.recipient,
+                sponsor: refund.sponsor,
+            };
+            insert_refund(db, transaction, event_index, &merchant, mint, &row, balance)?;
+        }
+        GotsolEvent::OrderItemsRefunded(refund) => {
+            let row = RefundRow {
+                kind: "order_items",
+                original_tx_sig: None,
+                reference: Some(refund.order),
+                amount: refund.amount,
+                recipient: refund.customer,
+                sponsor: None,
+            };
+            insert_refund(db, transaction, event_index, &merchant, mint, &row, balance)?;
+        }
+        GotsolEvent::WithdrawSolProcessed(withdrawal) => {
+            insert_withdrawal(db, transaction, event_index, &merchant, "sol", None, withdrawal.amount, None, withdrawal.owner_amount, withdrawal.house_amount, balance)?;
+        }
+        GotsolEvent::WithdrawSplProcessed(withdrawal) => {
+            insert_withdrawal(db, transaction, event_index, &merchant, "spl", mint, withdrawal.amount, None, withdrawal.owner_amount, withdrawal.house_amount, balance)?;
+        }
+        GotsolEvent::WithdrawSolAsSplProcessed(withdrawal) => {
+            let mint = Some(withdrawal.output_mint.to_string());
+            insert_withdrawal(
+                db,
+                transaction,
+                event_index,
+                &merchant,
+                "sol_as_spl",
+                mint,
+                withdrawal.amount,
+                Some(withdrawal.amount_out),
+                withdrawal.owner_amount,
+                withdrawal.house_amount,
+                balance,
+            )?;
+        }
+        GotsolEvent::MerchantClosed(closed) => {
+            db.execute(
+                "INSERT INTO merchant_closures (signature, event_index, merchant, entity_name, slot, block_time)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+                params![signature, event_index, merchant, closed.entity_name, slot, block_time],
+            )?;
+        }
+        _ => {}
+    }
+
+    Ok(())
+}
+
+/// The columns that differ between the payment event kinds
+struct PaymentRow {
+    kind: &'static str,
+    payer: Pubkey,
+    reference: Option<Pubkey>,
+    amount: u64,
+    tip: u64,
+    discount: u64,
+    gift_card_amount: u64,
+    usd_amount: Option<u64>,
+}
+
+fn insert_payment(
+    db: &Transaction,
+    transaction: &ProgramTransaction,
+    event_index: usize,
+    merchant: &Option<String>,
+    mint: Option<String>,
+    row: &PaymentRow,
+    balance: Option<i64>,
+) -> Result<()> {
+    db.execute(
+        "INSERT INTO payments
+         (signature, event_index, merchant, kind, mint, payer, reference, amount, tip, discount, gift_card_amount, usd_amount, balance, slot, block_time)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15)",
+        params![
+            transaction.signature,
+            event_index,
+            merchant,
+            row.kind,
+            mint,
+            row.payer.to_string(),
+            row.reference.map(|reference| reference.to_string()),
+            i64::try_from(row.amount)?,
+            i64::try_from(row.tip)?,
+            i64::try_from(row.discount)?,
+            i64::try_from(row.gift_card_amount)?,
+            row.usd_amount.map(i64::try_from).transpose()?,
+            balance,
+            transaction.slot as i64,
+            transaction.block_time,
+        ],
+    )?;
+    Ok(())
+}
+
+/// The columns that differ between the refund event kinds
+struct RefundRow<'a> {
+    kind: &'static str,
+    original_tx_sig: Option<&'a str>,
+    reference: Option<Pubkey>,
+    amount: u64,
+    recipient: Pubkey,
+    sponsor: Option<Pubkey>,
+}
+
+fn insert_refund(
+    db: &Transaction,
+    transaction: &ProgramTransaction,
+    event_index: usize,
+    merchant: &Option<String>,
+    mint: Option<String>,
+    row: &RefundRow,
+    balance: Option<i64>,
+) -> Result<()> {
+    db.execute(
+        "INSERT INTO refunds
+         (signature, event_index, merchant, kind, mint, original_tx_sig, reference, amount, recipient, sponsor, balance, slot, block_time)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13)",
+        params![
+            transaction.signature,
+            event_index,
+            merchant,
+            row.kind,
+            mint,
+            row.original_tx_sig,
+            row.reference.map(|reference| reference.to_string()),
+            i64::try_from(row.amount)?,
+            row.recipient.to_string(),
+            row.sponsor.map(|sponsor| sponsor.to_string()),
+            balance,
+            transaction.slot as i64,
+            transaction.block_time,
+        ],
+    )?;
+    Ok(())
+}
+
+/// Helper function to reduce code duplication across the withdrawal event kinds
+#[allow(clippy::too_many_arguments)]
+fn insert_withdrawal(
+    db: &Transaction,
+    transaction: &ProgramTransaction,
+    event_index: usize,
+    merchant: &Option<String>,
+    kind: &str,
+    mint: Option<String>,
+    amount: u64,
+    amount_out: Option<u64>,
+    owner_amount: u64,
+    house_amount: u64,
+    balance: Option<i64>,
+) -> Result<()> {
+    db.execute(
+        "INSERT INTO withdrawals
+         (signature, event_index, merchant, kind, mint, amount, amount_out, owner_amount, house_amount, balance, slot, block_time)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12)",
+        params![
+            transaction.signature,
+            event_index,
+            merchant,
+            kind,
+            mint,
+            i64::try_from(amount)?,
+            amount_out.map(i64::try_from).transpose()?,
+            i64::try_from(owner_amount)?,
+            i64::try_from(house_amount)?,
+            balance,
+            transaction.slot as i64,
+            transaction.block_time,
+        ],
+    )?;
+    Ok(())
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use anchor_lang::event::EVENT_IX_TAG_LE;
+    use anchor_lang::Event;
+    use gotsol::events::{
+        EventHeader, OrderItemsRefunded, PaymentCaptured, RefundProcessed, SubscriptionCharged, EVENT_SCHEMA_VERSION,
+    };
+    use serde_json::{json, Value};
+
+    use super::*;
+
+    // kind, original_tx_sig, reference, amount, recipient
+    type RefundColumns = (String, Option<String>, Option<String>, i64, String);
+
+    const MERCHANT: Pubkey = Pubkey::new_from_array([1; 32]);
+    const CUSTOMER: Pubkey = Pubkey::new_from_array([2; 32]);
+    const MINT: Pubkey = Pubkey::new_from_array([3; 32]);
+
+    pub fn header() -> EventHeader {
+        EventHeader {
+            version: EVENT_SCHEMA_VERSION,
+            merchant: Some(MERCHANT),
+            actor: CUSTOMER,
+            mint: Some(MINT),
+            timestamp: 1_700_000_000,
+            slot: 42,
+            balance: Some(5_000),
+        }
+    }
+
+    /// A `getTransaction` result whose first instruction emitted `events` with `emit_cpi!`
+    pub fn transaction(signature: &str, err: Value, events: &[Vec<u8>]) -> Value {
+        let instructions: Vec<Value> = events
+            .iter()
+            .map(|event| {
+                let data = [EVENT_IX_TAG_LE, event].concat();
+                json!({ "programIdIndex": 1, "accounts": [], "data": bs58::encode(data).into_string() })
+            })
+            .collect();
+
+        json!({
+            "slot": 7,
+            "blockTime": 1_700_000_000,
+            "transaction": {
+                "signatures": [signature],
+                "message": { "accountKeys": [CUSTOMER.to_string(), gotsol_client::PROGRAM_ID.to_string()] },
+            },
+            "meta": {
+                "err": err,
+                "logMessages": [],
+                "innerInstructions": [{ "index": 0, "instructions": instructions }],
+            },
+        })
+    }
+
+    pub fn refund(original_tx_sig: &str) -> Vec<u8> {
+        RefundProcessed {
+            header: header(),
+            original_tx_sig: original_tx_sig.to_string(),
+            amount: 1_000,
+            recipient: CUSTOMER,
+            sponsor: None,
+        }
+        .data()
+    }
+
+    fn in_memory() -> Store {
+        Store::open(Path::new(":memory:")).unwrap()
+    }
+
+    fn ingest(store: &mut Store, value: &Value) -> usize {
+        store.ingest(&ProgramTransaction::from_json(value).unwrap().unwrap()).unwrap()
+    }
+
+    fn count(store: &Store, table: &str) -> i64 {
+        store.conn.query_row(&format!("SELECT count(*) FROM {table}"), [], |row| row.get(0)).unwrap()
+    }
+
+    #[test]
+    fn ingesting_a_transaction_twice_writes_it_once() {
+        let mut store = in_memory();
+        let value = transaction("sig", Value::Null, &[refund("5VERv8NM")]);
+
+        assert_eq!(ingest(&mut store, &value), 1);
+        assert_eq!(ingest(&mut store, &value), 0);
+
+        assert_eq!(count(&store, "transactions"), 1);
+        assert_eq!(count(&store, "events"), 1);
+        assert_eq!(count(&store, "refunds"), 1);
+    }
+
+    #[test]
+    fn records_captures_and_subscription_charges_as_payments() {
+        let mut store = in_memory();
+        let authorization = Pubkey::new_unique();
+        let subscription = Pubkey::new_unique();
+        let capture = PaymentCaptured { header: header(), customer: CUSTOMER, authorization, captured: 200, released: 100 };
+        let charge = SubscriptionCharged {
+            header: header(),
+            customer: CUSTOMER,
+            subscription,
+            amount: 300,
+            next_charge_at: 1_700_086_400,
+            cranker: Pubkey::new_unique(),
+        };
+
+        ingest(&mut store, &transaction("sig", Value::Null, &[capture.data(), charge.data()]));
+
+        let mut query = store.conn.prepare("SELECT kind, payer, reference, amount FROM payments ORDER BY event_index").unwrap();
+        let rows: Vec<(String, String, String, i64)> = query
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?)))
+            .unwrap()
+            .collect::<rusqlite::Result<_>>()
+            .unwrap();
+        assert_eq!(
+            rows,
+            vec![
+                ("capture".to_string(), CUSTOMER.to_string(), authorization.to_string(), 200),
+                ("subscription".to_string(), CUSTOMER.to_string(), subscription.to_string(), 300),
+            ]
+        );
+    }
+
+    #[test]
+    fn records_order_item_refunds_against_the_order() {
+        let mut store = in_memory();
+        let order = Pubkey::new_unique();
+        let refunded = OrderItemsRefunded { header: header(), order, customer: CUSTOMER, amount: 400, refunded_amount: 400 };
+
+        ingest(&mut store, &transaction("sig", Value::Null, &[refund("5VERv8NM"), refunded.data()]));
+
+        let mut query = store
+            .conn
+            .prepare("SELECT kind, original_tx_sig, reference, amount, recipient FROM refunds ORDER BY event_index")
+            .unwrap();
+        let rows: Vec<RefundColumns> = query
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?, row.get(4)?)))
+            .unwrap()
+            .collect::<rusqlite::Result<_>>()
+            .unwrap();
+        assert_eq!(
+            rows,
+            vec![
+                ("payment".to_string(), Some("5VERv8NM".to_string()), None, 1_000, CUSTOMER.to_string()),
+                ("order_items".to_string(), None, Some(order.to_string()), 400, CUSTOMER.to_string()),
+            ]
+        );
+    }
+}
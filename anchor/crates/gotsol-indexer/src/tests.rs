@@ -0,0 +1,131 @@
+use std::io::{BufRead, BufReader, Cursor, Read, Write};
+use std::net::TcpListener;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+use serde_json::json;
+
+use super::*;
+use crate::store::tests::{refund, transaction};
+
+/// Stand-in JSON-RPC node: answers every call with `respond(method, params)` and keeps
+/// the requests so tests can see what the indexer asked for
+struct StubRpc {
+    url: String,
+    requests: Arc<Mutex<Vec<Value>>>,
+}
+
+impl StubRpc {
+    fn start(respond: impl Fn(&str, &Value) -> Value + Send + 'static) -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let requests = Arc::new(Mutex::new(vec![]));
+        let seen = requests.clone();
+
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                let mut stream = stream.unwrap();
+                let mut reader = BufReader::new(stream.try_clone().unwrap());
+                let mut content_length = 0;
+                loop {
+                    let mut line = String::new();
+                    reader.read_line(&mut line).unwrap();
+                    if line.trim().is_empty() {
+                        break;
+                    }
+                    if let Some((name, value)) = line.split_once(':') {
+                        if name.eq_ignore_ascii_case("content-length") {
+                            content_length = value.trim().parse().unwrap();
+                        }
+                    }
+                }
+                let mut body = vec![0; content_length];
+                reader.read_exact(&mut body).unwrap();
+
+                let request: Value = serde_json::from_slice(&body).unwrap();
+                let result = respond(request["method"].as_str().unwrap(), &request["params"]);
+                seen.lock().unwrap().push(request);
+
+                let response = json!({ "jsonrpc": "2.0", "id": 1, "result": result }).to_string();
+                write!(
+                    stream,
+                    "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{response}",
+                    response.len()
+                )
+                .unwrap();
+            }
+        });
+
+        Self { url, requests }
+    }
+
+    fn client(&self) -> RpcClient {
+        RpcClient::new(self.url.clone(), "confirmed".to_string())
+    }
+
+    /// The signatures passed to `getTransaction`, in order
+    fn fetched(&self) -> Vec<String> {
+        self.requests
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|request| request["method"] == "getTransaction")
+            .map(|request| request["params"][0].as_str().unwrap().to_string())
+            .collect()
+    }
+}
+
+fn in_memory() -> Store {
+    Store::open(Path::new(":memory:")).unwrap()
+}
+
+fn succeeded(signature: &str) -> Value {
+    transaction(signature, Value::Null, &[refund(signature)])
+}
+
+#[test]
+fn skips_failed_transactions() {
+    let mut store = in_memory();
+    let failed = transaction("failed", json!({ "InstructionError": [0, { "Custom": 6000 }] }), &[refund("failed")]);
+    let lines = format!("{failed}\n{}\n", succeeded("ok"));
+
+    ingest_lines(&mut store, Cursor::new(lines)).unwrap();
+
+    // Only the successful transaction was stored, so the failed signature is still new
+    let stored = |value: &Value| ProgramTransaction::from_json(value).unwrap().unwrap();
+    assert_eq!(store.ingest(&stored(&succeeded("ok"))).unwrap(), 0);
+    assert_eq!(store.ingest(&stored(&succeeded("failed"))).unwrap(), 1);
+}
+
+#[test]
+fn backfill_resumes_from_the_saved_cursor() {
+    // Program history, newest first
+    const HISTORY: [&str; 5] = ["sig4", "sig3", "sig2", "sig1", "sig0"];
+    let interrupted = Arc::new(AtomicBool::new(false));
+    let interrupt = interrupted.clone();
+
+    let rpc = StubRpc::start(move |method, params| match method {
+        "getSignaturesForAddress" => {
+            let before = params[1]["before"].as_str();
+            let start = before.map_or(0, |before| HISTORY.iter().position(|signature| *signature == before).unwrap() + 1);
+            json!(HISTORY[start..].iter().map(|signature| json!({ "signature": signature })).collect::<Vec<_>>())
+        }
+        // The node loses track of sig1 once, as if the backfill was cut off there
+        "getTransaction" => match params[0].as_str().unwrap() {
+            "sig1" if !interrupt.swap(true, Ordering::SeqCst) => Value::Null,
+            signature => succeeded(signature),
+        },
+        other => panic!("unexpected {other} call"),
+    });
+    let mut store = in_memory();
+
+    assert!(backfill(&mut store, &rpc.client(), None, None).is_err());
+    assert_eq!(store.cursor(BACKFILL_CURSOR).unwrap().as_deref(), Some("sig2"));
+
+    backfill(&mut store, &rpc.client(), None, None).unwrap();
+
+    assert_eq!(store.cursor(BACKFILL_CURSOR).unwrap().as_deref(), Some("sig0"));
+    assert_eq!(rpc.fetched(), vec!["sig4", "sig3", "sig2", "sig1", "sig1", "sig0"]);
+}
@@ -0,0 +1,235 @@
+use std::time::Duration;
+
+use anyhow::Result;
+
+use crate::signature::{sign, SIGNATURE_HEADER};
+use crate::now;
+use crate::store::{Delivery, Store};
+
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Sends queued webhooks, retrying failures with exponential backoff until they
+/// succeed or run out of attempts and land in the dead-letter queue
+pub struct Deliverer {
+    agent: ureq::Agent,
+    max_attempts: u32,
+    retry_base_secs: i64,
+    retry_max_secs: i64,
+}
+
+impl Deliverer {
+    pub fn new(max_attempts: u32, retry_base_secs: i64, retry_max_secs: i64) -> Self {
+        Self {
+            agent: ureq::AgentBuilder::new().timeout(REQUEST_TIMEOUT).build(),
+            max_attempts,
+            retry_base_secs,
+            retry_max_secs,
+        }
+    }
+
+    /// Attempts every delivery that's due. Returns how many succeeded.
+    pub fn deliver_due(&self, store: &Store, now: i64) -> Result<usize> {
+        let mut delivered = 0;
+
+        for delivery in store.due(now)? {
+            let attempts = delivery.attempts + 1;
+            match self.send(&delivery) {
+                Ok(()) => {
+                    store.mark_delivered(delivery.id, attempts)?;
+                    println!("delivered {} {} to {}", delivery.event_type, delivery.event_id, delivery.url);
+                    delivered += 1;
+                }
+                Err(error) => {
+                    let retry_at = (attempts < self.max_attempts).then(|| now + self.backoff(attempts));
+                    store.mark_failed(delivery.id, attempts, &error, retry_at)?;
+                    match retry_at {
+                        Some(retry_at) => eprintln!(
+                            "{} {} to {} failed (attempt {attempts}): {error}; retrying at {retry_at}",
+                            delivery.event_type, delivery.event_id, delivery.url
+                        ),
+                        None => eprintln!(
+                            "{} {} to {} failed after {attempts} attempts: {error}; moved to dead letters",
+                            delivery.event_type, delivery.event_id, delivery.url
+                        ),
+                    }
+                }
+            }
+        }
+
+        Ok(delivered)
+    }
+
+    /// Seconds to wait after the given number of failed attempts: base, 2x base, 4x base, ... up to the cap
+    fn backoff(&self, attempts: u32) -> i64 {
+        let factor = 2i64.checked_pow(attempts.saturating_sub(1)).unwrap_or(i64::MAX);
+        self.retry_base_secs.saturating_mul(factor).min(self.retry_max_secs)
+    }
+
+    /// Posts one delivery, signed at the moment it's sent so a slow batch doesn't push later
+    /// requests past the receiver's tolerance; any 2xx response counts as success
+    fn send(&self, delivery: &Delivery) -> Result<(), String> {
+        let response = self
+            .agent
+            .post(&delivery.url)
+            .set("Content-Type", "application/json")
+            .set("Gotsol-Event", &delivery.event_type)
+            .set("Gotsol-Delivery", &delivery.event_id)
+            .set(SIGNATURE_HEADER, &sign(&delivery.secret, now(), &delivery.payload))
+            .send_string(&delivery.payload);
+
+        match response {
+            Ok(_) => Ok(()),
+            Err(ureq::Error::Status(status, _)) => Err(format!("HTTP {status}")),
+            Err(error) => Err(error.to_string()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::TcpListener;
+    use std::path::Path;
+    use std::sync::atomic::{AtomicU16, AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use std::thread;
+
+    use super::*;
+    use crate::listen::handle;
+
+    const SECRET: &str = "whsec_test";
+
+    /// The `listen` stand-in on a free port, answering verified requests with whatever
+    /// `status` holds at the time and counting the requests it sees
+    struct StandIn {
+        url: String,
+        status: Arc<AtomicU16>,
+        requests: Arc<AtomicUsize>,
+    }
+
+    impl StandIn {
+        fn start(status: u16) -> Self {
+            let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+            let url = format!("http://{}/hooks", listener.local_addr().unwrap());
+            let status = Arc::new(AtomicU16::new(status));
+            let requests = Arc::new(AtomicUsize::new(0));
+
+            let (answer, seen) = (status.clone(), requests.clone());
+            thread::spawn(move || {
+                for stream in listener.incoming() {
+                    seen.fetch_add(1, Ordering::SeqCst);
+                    handle(stream.unwrap(), SECRET, answer.load(Ordering::SeqCst)).unwrap();
+                }
+            });
+
+            Self { url, status, requests }
+        }
+
+        fn requests(&self) -> usize {
+            self.requests.load(Ordering::SeqCst)
+        }
+    }
+
+    fn queue(url: &str, secret: &str) -> Store {
+        let store = Store::open(Path::new(":memory:")).unwrap();
+        let endpoint = store.add_endpoint("merchant", url, secret, None).unwrap();
+        store.enqueue(endpoint, "sig:0", "PaymentProcessed", r#"{"id":"sig:0"}"#, 0).unwrap();
+        store
+    }
+
+    #[test]
+    fn backoff_doubles_up_to_the_cap() {
+        let deliverer = Deliverer::new(8, 30, 3600);
+
+        let delays: Vec<i64> = (1..=9).map(|attempts| deliverer.backoff(attempts)).collect();
+        assert_eq!(delays, vec![30, 60, 120, 240, 480, 960, 1920, 3600, 3600]);
+        assert_eq!(deliverer.backoff(64), 3600);
+        assert_eq!(deliverer.backoff(u32::MAX), 3600);
+    }
+
+    #[test]
+    fn delivers_signed_requests() {
+        let stand_in = StandIn::start(200);
+        let store = queue(&stand_in.url, SECRET);
+
+        // the stand-in answers 401 unless the signature verifies
+        assert_eq!(Deliverer::new(3, 1, 2).deliver_due(&store, now()).unwrap(), 1);
+        assert_eq!(stand_in.requests(), 1);
+        assert!(store.due(i64::MAX).unwrap().is_empty());
+        assert!(store.dead_letters().unwrap().is_empty());
+    }
+
+    #[test]
+    fn signs_at_send_time_rather_than_when_the_batch_started() {
+        let stand_in = StandIn::start(200);
+        let store = queue(&stand_in.url, SECRET);
+
+        // a batch started long before the send would otherwise sign outside the tolerance
+        assert_eq!(Deliverer::new(3, 1, 2).deliver_due(&store, 0).unwrap(), 1);
+    }
+
+    #[test]
+    fn wrongly_signed_requests_are_rejected_and_retried() {
+        let stand_in = StandIn::start(200);
+        let store = queue(&stand_in.url, "not the endpoint's secret");
+
+        assert_eq!(Deliverer::new(3, 1, 2).deliver_due(&store, now()).unwrap(), 0);
+        assert_eq!(store.due(i64::MAX).unwrap()[0].last_error.as_deref(), Some("HTTP 401"));
+    }
+
+    #[test]
+    fn server_errors_retry_with_backoff_then_become_dead_letters() {
+        let stand_in = StandIn::start(500);
+        let store = queue(&stand_in.url, SECRET);
+        let deliverer = Deliverer::new(3, 1, 2);
+        let start = now();
+
+        assert_eq!(deliverer.deliver_due(&store, start).unwrap(), 0);
+        // not due again until the first backoff has passed
+        assert!(store.due(start).unwrap().is_empty());
+        let retry = store.due(start + 1).unwrap();
+        assert_eq!(retry[0].attempts, 1);
+        assert_eq!(retry[0].last_error.as_deref(), Some("HTTP 500"));
+
+        deliverer.deliver_due(&store, start + 1).unwrap();
+        assert!(store.due(start + 2).unwrap().is_empty());
+        assert_eq!(store.due(start + 3).unwrap()[0].attempts, 2);
+
+        deliverer.deliver_due(&store, start + 3).unwrap();
+        assert!(store.due(i64::MAX).unwrap().is_empty());
+        let dead = store.dead_letters().unwrap();
+        assert_eq!(dead.len(), 1);
+        assert_eq!(dead[0].attempts, 3);
+        assert_eq!(dead[0].last_error.as_deref(), Some("HTTP 500"));
+        assert_eq!(stand_in.requests(), 3);
+    }
+
+    #[test]
+    fn requeued_dead_letters_are_sent_again() {
+        let stand_in = StandIn::start(500);
+        let store = queue(&stand_in.url, SECRET);
+        let deliverer = Deliverer::new(1, 1, 2);
+
+        deliverer.deliver_due(&store, now()).unwrap();
+        assert_eq!(store.dead_letters().unwrap().len(), 1);
+
+        stand_in.status.store(200, Ordering::SeqCst);
+        let requeued_at = now();
+        assert_eq!(store.requeue(None, requeued_at).unwrap(), 1);
+        assert_eq!(deliverer.deliver_due(&store, requeued_at).unwrap(), 1);
+
+        assert_eq!(stand_in.requests(), 2);
+        assert!(store.dead_letters().unwrap().is_empty());
+        assert!(store.due(i64::MAX).unwrap().is_empty());
+    }
+
+    #[test]
+    fn unreachable_endpoints_count_as_failures() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/hooks", listener.local_addr().unwrap());
+        drop(listener);
+        let store = queue(&url, SECRET);
+
+        assert_eq!(Deliverer::new(1, 1, 2).deliver_due(&store, now()).unwrap(), 0);
+        assert_eq!(store.dead_letters().unwrap().len(), 1);
+    }
+}
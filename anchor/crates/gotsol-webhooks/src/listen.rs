@@ -0,0 +1,66 @@
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream};
+
+use anyhow::{Context, Result};
+
+use crate::now;
+use crate::signature::{verify, SIGNATURE_HEADER};
+
+/// A local stand-in for a merchant's webhook endpoint. Prints each request it receives,
+/// checks its signature against `secret` and answers `status` (401 if the signature is bad).
+pub fn listen(port: u16, secret: &str, status: u16) -> Result<()> {
+    let listener = TcpListener::bind(("127.0.0.1", port)).with_context(|| format!("failed to listen on port {port}"))?;
+    println!("listening on http://127.0.0.1:{port}");
+
+    for stream in listener.incoming() {
+        if let Err(error) = handle(stream?, secret, status) {
+            eprintln!("bad request: {error:#}");
+        }
+    }
+    Ok(())
+}
+
+/// Reads one request off `stream`, prints it and answers it
+pub fn handle(stream: TcpStream, secret: &str, status: u16) -> Result<()> {
+    let mut reader = BufReader::new(stream.try_clone()?);
+
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line)?;
+
+    let mut content_length = 0;
+    let mut signature = None;
+    let mut event_type = None;
+    loop {
+        let mut line = String::new();
+        reader.read_line(&mut line)?;
+        let line = line.trim_end();
+        if line.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = line.split_once(':') {
+            let value = value.trim().to_string();
+            match name.trim().to_ascii_lowercase().as_str() {
+                "content-length" => content_length = value.parse().context("invalid Content-Length")?,
+                "gotsol-event" => event_type = Some(value),
+                name if name == SIGNATURE_HEADER.to_ascii_lowercase() => signature = Some(value),
+                _ => {}
+            }
+        }
+    }
+
+    let mut body = vec![0; content_length];
+    reader.read_exact(&mut body)?;
+    let body = String::from_utf8(body).context("body is not UTF-8")?;
+
+    let verified = signature.is_some_and(|signature| verify(secret, &signature, &body, now()));
+    let status = if verified { status } else { 401 };
+    println!(
+        "{} {} signature {}, answering {status}\n{body}",
+        request_line.trim_end(),
+        event_type.as_deref().unwrap_or("-"),
+        if verified { "ok" } else { "INVALID" },
+    );
+
+    write!(&stream, "HTTP/1.1 {status} Stand-in\r\nContent-Length: 0\r\nConnection: close\r\n\r\n")?;
+    Ok(())
+}
@@ -0,0 +1,312 @@
+mod delivery;
+mod listen;
+mod payload;
+mod signature;
+mod store;
+
+use std::io::{self, BufRead};
+use std::path::PathBuf;
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::thread::{self, sleep};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use anchor_lang::prelude::Pubkey;
+use anyhow::{Context, Result};
+use clap::{Parser, Subcommand};
+use gotsol_client::rpc::{ProgramTransaction, RpcClient};
+use gotsol_client::PROGRAM_ID;
+use serde_json::Value;
+
+use crate::delivery::Deliverer;
+use crate::payload::event_payload;
+use crate::store::Store;
+
+const FOLLOW_CURSOR: &str = "follow";
+
+#[derive(Parser)]
+#[command(name = "gotsol-webhooks", version, about = "Delivers signed webhooks for GotSOL merchant events")]
+struct Cli {
+    /// SQLite database holding endpoints and the delivery queue, created if missing
+    #[arg(long, env = "GOTSOL_WEBHOOKS_DB", default_value = "gotsol-webhooks.sqlite", global = true)]
+    db: PathBuf,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Manage merchant webhook endpoints
+    #[command(subcommand)]
+    Endpoints(EndpointCommand),
+    /// Poll an RPC node for new program transactions and deliver their events
+    Follow {
+        #[command(flatten)]
+        rpc: RpcArgs,
+        #[command(flatten)]
+        retry: RetryArgs,
+        #[arg(long, default_value_t = 5)]
+        interval_secs: u64,
+    },
+    /// Read transactions from stdin, one `getTransaction` JSON object per line, and deliver their events
+    Stream {
+        #[command(flatten)]
+        retry: RetryArgs,
+        /// How often queued retries are attempted while waiting for input
+        #[arg(long, default_value_t = 5)]
+        interval_secs: u64,
+    },
+    /// Attempt every delivery that's due once, then exit
+    Deliver {
+        #[command(flatten)]
+        retry: RetryArgs,
+    },
+    /// Inspect or requeue deliveries that ran out of attempts
+    #[command(subcommand)]
+    DeadLetters(DeadLetterCommand),
+    /// Run a local stand-in endpoint that prints and verifies the webhooks it receives
+    Listen {
+        #[arg(long, default_value_t = 8787)]
+        port: u16,
+        #[arg(long, env = "GOTSOL_WEBHOOK_SECRET")]
+        secret: String,
+        /// Status code to answer verified requests with, e.g. 500 to exercise retries
+        #[arg(long, default_value_t = 200)]
+        status: u16,
+    },
+}
+
+#[derive(Subcommand)]
+enum EndpointCommand {
+    /// Send a merchant's events to a URL
+    Add {
+        #[arg(long)]
+        merchant: Pubkey,
+        #[arg(long)]
+        url: String,
+        /// Shared secret the payloads are signed with
+        #[arg(long, env = "GOTSOL_WEBHOOK_SECRET")]
+        secret: String,
+        /// Only deliver these events (e.g. PaymentProcessed,RefundProcessed); every event when omitted
+        #[arg(long, value_delimiter = ',')]
+        events: Vec<String>,
+    },
+    /// Stop delivering to an endpoint and drop its queue
+    Remove { id: i64 },
+    /// List endpoints, optionally for one merchant
+    List {
+        #[arg(long)]
+        merchant: Option<Pubkey>,
+    },
+}
+
+#[derive(Subcommand)]
+enum DeadLetterCommand {
+    /// List deliveries that ran out of attempts
+    List,
+    /// Requeue one dead letter, or all of them when no id is given
+    Retry { id: Option<i64> },
+}
+
+#[derive(clap::Args)]
+struct RpcArgs {
+    #[arg(long, short = 'u', env = "GOTSOL_RPC_URL", default_value = "http://127.0.0.1:8899")]
+    url: String,
+
+    #[arg(long, default_value = "confirmed")]
+    commitment: String,
+}
+
+impl RpcArgs {
+    fn client(self) -> RpcClient {
+        RpcClient::new(self.url, self.commitment)
+    }
+}
+
+#[derive(clap::Args)]
+struct RetryArgs {
+    /// Attempts before a delivery is moved to the dead letters
+    #[arg(long, default_value_t = 8)]
+    max_attempts: u32,
+    /// Delay before the first retry; each later retry waits twice as long
+    #[arg(long, default_value_t = 30)]
+    retry_base_secs: i64,
+    /// Longest delay between retries
+    #[arg(long, default_value_t = 3600)]
+    retry_max_secs: i64,
+}
+
+impl RetryArgs {
+    fn deliverer(self) -> Deliverer {
+        Deliverer::new(self.max_attempts, self.retry_base_secs, self.retry_max_secs)
+    }
+}
+
+fn main() -> Result<()> {
+    let cli = Cli::parse();
+    if let Command::Listen { port, secret, status } = &cli.command {
+        return listen::listen(*port, secret, *status);
+    }
+
+    let store = Store::open(&cli.db).with_context(|| format!("failed to open {}", cli.db.display()))?;
+
+    match cli.command {
+        Command::Endpoints(command) => endpoints(&store, command),
+        Command::Follow { rpc, retry, interval_secs } => {
+            follow(&store, &rpc.client(), &retry.deliverer(), Duration::from_secs(interval_secs))
+        }
+        Command::Stream { retry, interval_secs } => stream(&store, &retry.deliverer(), Duration::from_secs(interval_secs)),
+        Command::Deliver { retry } => {
+            let delivered = retry.deliverer().deliver_due(&store, now())?;
+            println!("{delivered} delivered");
+            Ok(())
+        }
+        Command::DeadLetters(command) => dead_letters(&store, command),
+        Command::Listen { .. } => unreachable!(),
+    }
+}
+
+fn now() -> i64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |elapsed| elapsed.as_secs() as i64)
+}
+
+fn endpoints(store: &Store, command: EndpointCommand) -> Result<()> {
+    match command {
+        EndpointCommand::Add { merchant, url, secret, events } => {
+            let events = (!events.is_empty()).then(|| events.join(","));
+            let id = store.add_endpoint(&merchant.to_string(), &url, &secret, events.as_deref())?;
+            println!("endpoint {id} added");
+        }
+        EndpointCommand::Remove { id } => {
+            if store.remove_endpoint(id)? {
+                println!("endpoint {id} removed");
+            } else {
+                println!("no endpoint {id}");
+            }
+        }
+        EndpointCommand::List { merchant } => {
+            for endpoint in store.endpoints(merchant.map(|merchant| merchant.to_string()).as_deref())? {
+                let events = endpoint.events.as_deref().unwrap_or("all events");
+                println!("{}  {}  {}  {events}", endpoint.id, endpoint.merchant, endpoint.url);
+            }
+        }
+    }
+    Ok(())
+}
+
+fn dead_letters(store: &Store, command: DeadLetterCommand) -> Result<()> {
+    match command {
+        DeadLetterCommand::List => {
+            for delivery in store.dead_letters()? {
+                println!(
+                    "{}  {} {}  {}  {} attempts, last error: {}",
+                    delivery.id,
+                    delivery.event_type,
+                    delivery.event_id,
+                    delivery.url,
+                    delivery.attempts,
+                    delivery.last_error.as_deref().unwrap_or("-"),
+                );
+            }
+        }
+        DeadLetterCommand::Retry { id } => {
+            let requeued = store.requeue(id, now())?;
+            println!("{requeued} requeued");
+        }
+    }
+    Ok(())
+}
+
+/// Queues a webhook for every endpoint subscribed to each merchant event in the
+/// transaction. Program-wide admin events have no merchant and are never delivered.
+fn enqueue(store: &Store, transaction: &ProgramTransaction) -> Result<usize> {
+    let now = now();
+    let mut queued = 0;
+
+    for (event_index, logged) in transaction.events.iter().enumerate() {
+        let Some(merchant) = logged.event.merchant() else {
+            continue;
+        };
+        let event_type = logged.event.name();
+        let event_id = format!("{}:{event_index}", transaction.signature);
+
+        for endpoint in store.endpoints(Some(&merchant.to_string()))? {
+            if !endpoint.accepts(event_type) {
+                continue;
+            }
+            let payload = event_payload(&event_id, &transaction.signature, &logged.event).to_string();
+            if store.enqueue(endpoint.id, &event_id, event_type, &payload, now)? {
+                queued += 1;
+            }
+        }
+    }
+
+    if queued > 0 {
+        println!("{} (slot {}): {queued} webhooks queued", transaction.signature, transaction.slot);
+    }
+    Ok(queued)
+}
+
+/// Queues events from transactions newer than the follow cursor, then delivers and polls
+/// again. A fresh database starts at the current tip: merchants hear about new activity,
+/// not the program's history.
+fn follow(store: &Store, rpc: &RpcClient, deliverer: &Deliverer, interval: Duration) -> Result<()> {
+    if store.cursor(FOLLOW_CURSOR)?.is_none() {
+        if let Some(newest) = rpc.signatures_for_address(&PROGRAM_ID, None, None, 1)?.first() {
+            store.set_cursor(FOLLOW_CURSOR, newest)?;
+        }
+    }
+
+    loop {
+        let until = store.cursor(FOLLOW_CURSOR)?;
+        for signature in &rpc.signatures_since(&PROGRAM_ID, until.as_deref())? {
+            let value = rpc.transaction(signature)?.with_context(|| format!("transaction {signature} not found"))?;
+            if let Some(transaction) = ProgramTransaction::from_json(&value)? {
+                enqueue(store, &transaction)?;
+            }
+            store.set_cursor(FOLLOW_CURSOR, signature)?;
+        }
+
+        deliverer.deliver_due(store, now())?;
+        sleep(interval);
+    }
+}
+
+/// Queues events from stdin as they arrive, attempting due deliveries after each line and
+/// at least every `interval`. Retries still pending when input ends stay queued for the
+/// next run or `deliver`.
+fn stream(store: &Store, deliverer: &Deliverer, interval: Duration) -> Result<()> {
+    let (sender, receiver) = mpsc::channel();
+    thread::spawn(move || {
+        for (number, line) in io::stdin().lock().lines().enumerate() {
+            let transaction = line.map_err(anyhow::Error::from).and_then(|line| parse_line(number, &line));
+            if sender.send(transaction).is_err() {
+                break;
+            }
+        }
+    });
+
+    loop {
+        match receiver.recv_timeout(interval) {
+            Ok(transaction) => {
+                if let Some(transaction) = transaction? {
+                    enqueue(store, &transaction)?;
+                }
+            }
+            Err(RecvTimeoutError::Timeout) => {}
+            Err(RecvTimeoutError::Disconnected) => break,
+        }
+        deliverer.deliver_due(store, now())?;
+    }
+
+    deliverer.deliver_due(store, now())?;
+    Ok(())
+}
+
+fn parse_line(number: usize, line: &str) -> Result<Option<ProgramTransaction>> {
+    if line.trim().is_empty() {
+        return Ok(None);
+    }
+    let value: Value = serde_json::from_str(line).with_context(|| format!("line {} is not JSON", number + 1))?;
+    ProgramTransaction::from_json(&value)
+}
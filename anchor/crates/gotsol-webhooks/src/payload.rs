@@ -0,0 +1,11 @@
+use gotsol_client::events::GotsolEvent;
+use serde_json::{json, Value};
+
+/// The webhook body for one event: the header fields at the top level, the event's
+/// own fields under `data`. Amounts are base units of `mint` (lamports for SOL).
+pub fn event_payload(id: &str, signature: &str, event: &GotsolEvent) -> Value {
+    let mut payload = event.to_json();
+    payload["id"] = json!(id);
+    payload["signature"] = json!(signature);
+    payload
+}
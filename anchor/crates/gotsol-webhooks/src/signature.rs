@@ -0,0 +1,113 @@
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+
+/// Header carrying `t=<unix seconds>,v1=<hex HMAC-SHA256 of "<t>.<body>">`
+pub const SIGNATURE_HEADER: &str = "Gotsol-Signature";
+
+// How far a signed timestamp may drift from the receiver's clock before it's treated as a replay
+pub const TOLERANCE_SECS: i64 = 300;
+
+fn mac(secret: &str, timestamp: i64, body: &str) -> Hmac<Sha256> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC accepts keys of any length");
+    mac.update(timestamp.to_string().as_bytes());
+    mac.update(b".");
+    mac.update(body.as_bytes());
+    mac
+}
+
+/// Signs a body for delivery at `timestamp`, returning the signature header value
+pub fn sign(secret: &str, timestamp: i64, body: &str) -> String {
+    let digest = mac(secret, timestamp, body).finalize().into_bytes();
+    let hex: String = digest.iter().map(|byte| format!("{byte:02x}")).collect();
+    format!("t={timestamp},v1={hex}")
+}
+
+/// Checks a signature header against the body, in constant time, rejecting stale timestamps
+pub fn verify(secret: &str, header: &str, body: &str, now: i64) -> bool {
+    let mut timestamp = None;
+    let mut signature = None;
+    for part in header.split(',') {
+        match part.trim().split_once('=') {
+            Some(("t", value)) => timestamp = value.parse::<i64>().ok(),
+            Some(("v1", value)) => signature = decode_hex(value),
+            _ => {}
+        }
+    }
+
+    let (Some(timestamp), Some(signature)) = (timestamp, signature) else {
+        return false;
+    };
+    if now.abs_diff(timestamp) > TOLERANCE_SECS as u64 {
+        return false;
+    }
+    mac(secret, timestamp, body).verify_slice(&signature).is_ok()
+}
+
+fn decode_hex(hex: &str) -> Option<Vec<u8>> {
+    if !hex.len().is_multiple_of(2) {
+        return None;
+    }
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SECRET: &str = "whsec_test";
+    const BODY: &str = r#"{"id":"sig:0","type":"PaymentProcessed"}"#;
+    const NOW: i64 = 1_700_000_000;
+
+    #[test]
+    fn verifies_its_own_signatures() {
+        let header = sign(SECRET, NOW, BODY);
+
+        assert!(header.starts_with(&format!("t={NOW},v1=")));
+        assert!(verify(SECRET, &header, BODY, NOW));
+        assert!(verify(SECRET, &header, BODY, NOW + TOLERANCE_SECS));
+        assert!(verify(SECRET, &header, BODY, NOW - TOLERANCE_SECS));
+    }
+
+    #[test]
+    fn rejects_tampered_requests() {
+        let header = sign(SECRET, NOW, BODY);
+
+        assert!(!verify(SECRET, &header, &BODY.replace("PaymentProcessed", "RefundProcessed"), NOW));
+        assert!(!verify("another secret", &header, BODY, NOW));
+
+        // a signature made at one time can't be replayed under another
+        let retimed = header.replace(&format!("t={NOW}"), &format!("t={}", NOW + 1));
+        assert!(!verify(SECRET, &retimed, BODY, NOW));
+
+        let flipped = format!("{}{}", &header[..header.len() - 1], if header.ends_with('0') { '1' } else { '0' });
+        assert!(!verify(SECRET, &flipped, BODY, NOW));
+    }
+
+    #[test]
+    fn rejects_stale_and_future_timestamps() {
+        let header = sign(SECRET, NOW, BODY);
+
+        assert!(!verify(SECRET, &header, BODY, NOW + TOLERANCE_SECS + 1));
+        assert!(!verify(SECRET, &header, BODY, NOW - TOLERANCE_SECS - 1));
+
+        // timestamps at the ends of the range are rejected rather than overflowing
+        for timestamp in [i64::MIN, i64::MAX] {
+            assert!(!verify(SECRET, &sign(SECRET, timestamp, BODY), BODY, NOW));
+        }
+    }
+
+    #[test]
+    fn rejects_malformed_headers() {
+        let header = sign(SECRET, NOW, BODY);
+        let (timestamp, signature) = header.split_once(',').unwrap();
+
+        assert!(!verify(SECRET, timestamp, BODY, NOW));
+        assert!(!verify(SECRET, signature, BODY, NOW));
+        assert!(!verify(SECRET, &format!("{timestamp},v1=abc"), BODY, NOW));
+        assert!(!verify(SECRET, &format!("{timestamp},v1=zz"), BODY, NOW));
+        assert!(!verify(SECRET, "", BODY, NOW));
+    }
+}
@@ -0,0 +1,285 @@
+use std::path::Path;
+
+use anyhow::Result;
+use rusqlite::{params, Connection, OptionalExtension, Row};
+
+const SCHEMA: &str = "
+-- events is a comma-separated list of event names, NULL for every event
+CREATE TABLE IF NOT EXISTS endpoints (
+    id INTEGER PRIMARY KEY,
+    merchant TEXT NOT NULL,
+    url TEXT NOT NULL,
+    secret TEXT NOT NULL,
+    events TEXT,
+    UNIQUE (merchant, url)
+);
+
+-- status is 'pending', 'delivered' or 'dead'; dead rows are the dead-letter queue.
+-- event_id is '<signature>:<event index>' and doubles as the delivery id receivers dedupe on
+CREATE TABLE IF NOT EXISTS deliveries (
+    id INTEGER PRIMARY KEY,
+    endpoint_id INTEGER NOT NULL,
+    event_id TEXT NOT NULL,
+    event_type TEXT NOT NULL,
+    payload TEXT NOT NULL,
+    status TEXT NOT NULL DEFAULT 'pending',
+    attempts INTEGER NOT NULL DEFAULT 0,
+    next_attempt_at INTEGER NOT NULL,
+    last_error TEXT,
+    UNIQUE (endpoint_id, event_id)
+);
+CREATE INDEX IF NOT EXISTS deliveries_due ON deliveries (status, next_attempt_at);
+
+CREATE TABLE IF NOT EXISTS cursors (
+    name TEXT PRIMARY KEY,
+    signature TEXT NOT NULL
+);
+";
+
+pub struct Endpoint {
+    pub id: i64,
+    pub merchant: String,
+    pub url: String,
+    pub events: Option<String>,
+}
+
+impl Endpoint {
+    fn from_row(row: &Row) -> rusqlite::Result<Self> {
+        Ok(Self {
+            id: row.get(0)?,
+            merchant: row.get(1)?,
+            url: row.get(2)?,
+            events: row.get(3)?,
+        })
+    }
+
+    /// Whether the endpoint subscribed to this event name
+    pub fn accepts(&self, event_type: &str) -> bool {
+        match &self.events {
+            Some(events) => events.split(',').any(|name| name == event_type),
+            None => true,
+        }
+    }
+}
+
+pub struct Delivery {
+    pub id: i64,
+    pub url: String,
+    pub secret: String,
+    pub event_id: String,
+    pub event_type: String,
+    pub payload: String,
+    pub attempts: u32,
+    pub last_error: Option<String>,
+}
+
+impl Delivery {
+    fn from_row(row: &Row) -> rusqlite::Result<Self> {
+        Ok(Self {
+            id: row.get(0)?,
+            url: row.get(1)?,
+            secret: row.get(2)?,
+            event_id: row.get(3)?,
+            event_type: row.get(4)?,
+            payload: row.get(5)?,
+            attempts: row.get(6)?,
+            last_error: row.get(7)?,
+        })
+    }
+}
+
+const DELIVERY_COLUMNS: &str = "d.id, e.url, e.secret, d.event_id, d.event_type, d.payload, d.attempts, d.last_error";
+
+pub struct Store {
+    conn: Connection,
+}
+
+impl Store {
+    pub fn open(path: &Path) -> Result<Self> {
+        let conn = Connection::open(path)?;
+        conn.execute_batch(SCHEMA)?;
+        Ok(Self { conn })
+    }
+
+    pub fn add_endpoint(&self, merchant: &str, url: &str, secret: &str, events: Option<&str>) -> Result<i64> {
+        self.conn.execute(
+            "INSERT INTO endpoints (merchant, url, secret, events) VALUES (?1, ?2, ?3, ?4)",
+            params![merchant, url, secret, events],
+        )?;
+        Ok(self.conn.last_insert_rowid())
+    }
+
+    /// Removes an endpoint along with its queue and delivery history. Returns false if it didn't exist.
+    pub fn remove_endpoint(&self, id: i64) -> Result<bool> {
+        self.conn.execute("DELETE FROM deliveries WHERE endpoint_id = ?1", [id])?;
+        Ok(self.conn.execute("DELETE FROM endpoints WHERE id = ?1", [id])? > 0)
+    }
+
+    pub fn endpoints(&self, merchant: Option<&str>) -> Result<Vec<Endpoint>> {
+        let mut statement = self.conn.prepare(
+            "SELECT id, merchant, url, events FROM endpoints
+             WHERE ?1 IS NULL OR merchant = ?1 ORDER BY id",
+        )?;
+        let endpoints = statement.query_map([merchant], Endpoint::from_row)?.collect::<rusqlite::Result<_>>()?;
+        Ok(endpoints)
+    }
+
+    /// Queues a payload for an endpoint. Queuing the same event twice is a no-op, so a
+    /// transaction seen again never produces a second webhook. Returns whether it was queued.
+    pub fn enqueue(&self, endpoint_id: i64, event_id: &str, event_type: &str, payload: &str, now: i64) -> Result<bool> {
+        let inserted = self.conn.execute(
+            "INSERT OR IGNORE INTO deliveries (endpoint_id, event_id, event_type, payload, next_attempt_at)
+             VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![endpoint_id, event_id, event_type, payload, now],
+        )?;
+        Ok(inserted > 0)
+    }
+
+    /// Pending deliveries whose next attempt is due, oldest first
+    pub fn due(&self, now: i64) -> Result<Vec<Delivery>> {
+        let mut statement = self.conn.prepare(&format!(
+            "SELECT {DELIVERY_COLUMNS} FROM deliveries d JOIN endpoints e ON e.id = d.endpoint_id
+             WHERE d.status = 'pending' AND d.next_attempt_at <= ?1 ORDER BY d.next_attempt_at, d.id"
+        ))?;
+        let deliveries = statement.query_map([now], Delivery::from_row)?.collect::<rusqlite::Result<_>>()?;
+        Ok(deliveries)
+    }
+
+    pub fn mark_delivered(&self, id: i64, attempts: u32) -> Result<()> {
+        self.conn.execute(
+            "UPDATE deliveries SET status = 'delivered', attempts = ?2, last_error = NULL WHERE id = ?1",
+            params![id, attempts],
+        )?;
+        Ok(())
+    }
+
+    /// Records a failed attempt, scheduling another at `retry_at` or, when `None`, moving
+    /// the delivery to the dead-letter queue
+    pub fn mark_failed(&self, id: i64, attempts: u32, error: &str, retry_at: Option<i64>) -> Result<()> {
+        match retry_at {
+            Some(retry_at) => self.conn.execute(
+                "UPDATE deliveries SET attempts = ?2, last_error = ?3, next_attempt_at = ?4 WHERE id = ?1",
+                params![id, attempts, error, retry_at],
+            )?,
+            None => self.conn.execute(
+                "UPDATE deliveries SET status = 'dead', attempts = ?2, last_error = ?3 WHERE id = ?1",
+                params![id, attempts, error],
+            )?,
+        };
+        Ok(())
+    }
+
+    pub fn dead_letters(&self) -> Result<Vec<Delivery>> {
+        let mut statement = self.conn.prepare(&format!(
+            "SELECT {DELIVERY_COLUMNS} FROM deliveries d JOIN endpoints e ON e.id = d.endpoint_id
+             WHERE d.status = 'dead' ORDER BY d.id"
+        ))?;
+        let deliveries = statement.query_map([], Delivery::from_row)?.collect::<rusqlite::Result<_>>()?;
+        Ok(deliveries)
+    }
+
+    /// Puts dead letters back in the queue with a fresh attempt budget, all of them when
+    /// `id` is `None`. Returns how many were requeued.
+    pub fn requeue(&self, id: Option<i64>, now: i64) -> Result<usize> {
+        let requeued = self.conn.execute(
+            "UPDATE deliveries SET status = 'pending', attempts = 0, next_attempt_at = ?2
+             WHERE status = 'dead' AND (?1 IS NULL OR id = ?1)",
+            params![id, now],
+        )?;
+        Ok(requeued)
+    }
+
+    pub fn cursor(&self, name: &str) -> Result<Option<String>> {
+        let signature = self
+            .conn
+            .query_row("SELECT signature FROM cursors WHERE name = ?1", [name], |row| row.get(0))
+            .optional()?;
+        Ok(signature)
+    }
+
+    pub fn set_cursor(&self, name: &str, signature: &str) -> Result<()> {
+        self.conn.execute(
+            "INSERT INTO cursors (name, signature) VALUES (?1, ?2)
+             ON CONFLICT (name) DO UPDATE SET signature = excluded.signature",
+            params![name, signature],
+        )?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NOW: i64 = 1_700_000_000;
+
+    fn store_with_endpoint() -> (Store, i64) {
+        let store = Store::open(Path::new(":memory:")).unwrap();
+        let endpoint = store.add_endpoint("merchant", "http://127.0.0.1:1/hooks", "secret", None).unwrap();
+        (store, endpoint)
+    }
+
+    #[test]
+    fn enqueuing_the_same_event_twice_is_a_no_op() {
+        let (store, endpoint) = store_with_endpoint();
+
+        assert!(store.enqueue(endpoint, "sig:0", "PaymentProcessed", "{}", NOW).unwrap());
+        assert!(!store.enqueue(endpoint, "sig:0", "PaymentProcessed", r#"{"again":true}"#, NOW + 60).unwrap());
+
+        let due = store.due(NOW + 60).unwrap();
+        assert_eq!(due.len(), 1);
+        assert_eq!(due[0].payload, "{}");
+
+        // the same event still goes to every other endpoint once
+        let other = store.add_endpoint("merchant", "http://127.0.0.1:2/hooks", "secret", None).unwrap();
+        assert!(store.enqueue(other, "sig:0", "PaymentProcessed", "{}", NOW).unwrap());
+    }
+
+    #[test]
+    fn delivered_events_are_not_queued_again() {
+        let (store, endpoint) = store_with_endpoint();
+        store.enqueue(endpoint, "sig:0", "PaymentProcessed", "{}", NOW).unwrap();
+        let delivery = store.due(NOW).unwrap().remove(0);
+        store.mark_delivered(delivery.id, 1).unwrap();
+
+        assert!(!store.enqueue(endpoint, "sig:0", "PaymentProcessed", "{}", NOW).unwrap());
+        assert!(store.due(NOW).unwrap().is_empty());
+    }
+
+    #[test]
+    fn requeues_one_dead_letter_or_all() {
+        let (store, endpoint) = store_with_endpoint();
+        for event_id in ["sig:0", "sig:1"] {
+            store.enqueue(endpoint, event_id, "PaymentProcessed", "{}", NOW).unwrap();
+        }
+        for delivery in store.due(NOW).unwrap() {
+            store.mark_failed(delivery.id, 8, "HTTP 500", None).unwrap();
+        }
+        let dead = store.dead_letters().unwrap();
+        assert_eq!(dead.len(), 2);
+        assert_eq!(dead[0].last_error.as_deref(), Some("HTTP 500"));
+
+        assert_eq!(store.requeue(Some(dead[0].id), NOW).unwrap(), 1);
+        let due = store.due(NOW).unwrap();
+        assert_eq!(due.len(), 1);
+        assert_eq!(due[0].attempts, 0);
+
+        assert_eq!(store.requeue(None, NOW).unwrap(), 1);
+        assert!(store.dead_letters().unwrap().is_empty());
+    }
+
+    #[test]
+    fn endpoints_filter_by_event_name() {
+        let store = Store::open(Path::new(":memory:")).unwrap();
+        store.add_endpoint("merchant", "http://127.0.0.1:1/all", "secret", None).unwrap();
+        store
+            .add_endpoint("merchant", "http://127.0.0.1:1/refunds", "secret", Some("RefundProcessed,OrderItemsRefunded"))
+            .unwrap();
+
+        let endpoints = store.endpoints(Some("merchant")).unwrap();
+        assert!(endpoints[0].accepts("PaymentProcessed"));
+        assert!(!endpoints[1].accepts("PaymentProcessed"));
+        assert!(endpoints[1].accepts("OrderItemsRefunded"));
+        assert!(store.endpoints(Some("someone else")).unwrap().is_empty());
+    }
+}
@@ -3,4 +3,12 @@
 pub const HOUSE: &str = "Hth4EBxLWJSoRWj7raCKoniuzcvXt8MUFgGKty3B66ih";
 pub const AUTH_0: &str = "Hth4EBxLWJSoRWj7raCKoniuzcvXt8MUFgGKty3B66ih";
 pub const AUTH_2: &str = "Hth4EBxLWJSoRWj7raCKoniuzcvXt8MUFgGKty3B66ih";
-pub const AUTH_3: &str = "Hth4EBxLWJSoRWj7raCKoniuzcvXt8MUFgGKty3B66ih";
\ No newline at end of file
+pub const AUTH_3: &str = "Hth4EBxLWJSoRWj7raCKoniuzcvXt8MUFgGKty3B66ih";
+// Pyth pull-oracle receiver program that owns PriceUpdateV2 accounts
+pub const PYTH_RECEIVER_PROGRAM: &str = "rec5EKMGg6MxZYaMdyBfgwp4d5rB9T1VQH5pJv5LtFJ";
+
+// Pyth SOL/USD price feed id
+pub const SOL_USD_FEED_ID: [u8; 32] = [
+    0xef, 0x0d, 0x8b, 0x6f, 0xda, 0x2c, 0xeb, 0xa4, 0x1d, 0xa1, 0x5d, 0x40, 0x95, 0xd1, 0xda, 0x39,
+    0x2a, 0x0d, 0x2f, 0x8e, 0xd0, 0xc6, 0xc7, 0xbc, 0x0f, 0x4c, 0xfa, 0xc8, 0xc2, 0x80, 0xb5, 0x6d,
+];
\ No newline at end of file
@@ -25,4 +25,199 @@ pub enum CustomError {
 
     #[msg("Insufficient balance: withdrawal would leave vault below rent-exempt threshold!")]
     InsufficientRentBalance,
-}
\ No newline at end of file
+
+    #[msg("Invoice amount must be greater than 0!")]
+    InvalidInvoiceAmount,
+
+    #[msg("Invalid invoice due date: must be in the future!")]
+    InvalidDueDate,
+
+    #[msg("Invoice is not open; it has already been paid or cancelled!")]
+    InvoiceNotOpen,
+
+    #[msg("Payment amount does not match the invoice amount!")]
+    InvoiceAmountMismatch,
+
+    #[msg("Invoice has expired and can no longer be paid!")]
+    InvoiceExpired,
+
+    #[msg("Payment mint does not match the invoice mint!")]
+    InvalidInvoiceMint,
+
+    #[msg("Payment amount must be greater than 0!")]
+    ZeroAmountPayment,
+
+    #[msg("A tip pool account is required when a tip is included!")]
+    TipPoolRequired,
+
+    #[msg("Tip distribution amounts do not match the provided employee accounts!")]
+    TipDistributionMismatch,
+
+    #[msg("Tip recipient is not a token account for the tip pool mint!")]
+    InvalidTipRecipient,
+
+    #[msg("Invalid expiry: must be in the future!")]
+    InvalidExpiry,
+
+    #[msg("Authorization has expired and can no longer be captured!")]
+    AuthorizationExpired,
+
+    #[msg("Capture amount is greater than the authorized amount!")]
+    CaptureExceedsAuthorization,
+
+    #[msg("Only the merchant owner or customer can void an authorization before it expires!")]
+    AuthorizationNotExpired,
+
+    #[msg("Token mint does not match the mint recorded on the account!")]
+    MintMismatch,
+
+    #[msg("Invalid subscription terms: interval and charge count must be greater than 0!")]
+    InvalidSubscriptionTerms,
+
+    #[msg("Subscription is not yet due for its next charge!")]
+    SubscriptionNotDue,
+
+    #[msg("Only the customer or merchant owner can cancel a subscription!")]
+    UnauthorizedCancellation,
+
+    #[msg("Gift card must have a holder or a redemption code!")]
+    InvalidGiftCardTerms,
+
+    #[msg("Gift card has expired!")]
+    GiftCardExpired,
+
+    #[msg("Only the gift card holder or someone with its redemption code can redeem it!")]
+    UnauthorizedGiftCardRedemption,
+
+    #[msg("Gift card balance is lower than the redemption amount!")]
+    InsufficientGiftCardBalance,
+
+    #[msg("Gift card still has an unexpired balance and cannot be closed!")]
+    GiftCardStillActive,
+
+    #[msg("Loyalty earn and redemption rates must be greater than 0!")]
+    InvalidLoyaltyRate,
+
+    #[msg("Loyalty program accounts are required to earn or redeem points!")]
+    LoyaltyProgramRequired,
+
+    #[msg("Loyalty points account does not belong to the paying customer!")]
+    InvalidLoyaltyAccount,
+
+    #[msg("Redeemed points are worth more than the payment amount!")]
+    LoyaltyDiscountExceedsAmount,
+
+    #[msg("Invalid coupon: discount must be 1-10000 basis points or a positive amount, with at least one redemption!")]
+    InvalidCouponTerms,
+
+    #[msg("Coupon has expired!")]
+    CouponExpired,
+
+    #[msg("Coupon has reached its maximum number of redemptions!")]
+    CouponExhausted,
+
+    #[msg("Only the merchant owner or one of its managers can manage products!")]
+    UnauthorizedProductManager,
+
+    #[msg("Invalid SKU: cannot be empty or longer than 32 bytes!")]
+    InvalidSku,
+
+    #[msg("Product price must be greater than 0!")]
+    InvalidProductPrice,
+
+    #[msg("Invalid order: 1 to 16 writable line items, each with a positive quantity!")]
+    InvalidOrder,
+
+    #[msg("Product does not belong to this merchant!")]
+    InvalidProduct,
+
+    #[msg("Product is not currently for sale!")]
+    ProductInactive,
+
+    #[msg("Not enough stock to fill the order!")]
+    OutOfStock,
+
+    #[msg("Order total does not match catalog prices!")]
+    OrderTotalMismatch,
+
+    #[msg("Tax rate cannot exceed 10000 basis points!")]
+    InvalidTaxRate,
+
+    #[msg("Invalid refund: line item does not exist or quantity exceeds what remains unrefunded!")]
+    InvalidLineItemRefund,
+
+    #[msg("Price account is not a verified Pyth price update for the expected feed!")]
+    InvalidPriceAccount,
+
+    #[msg("Oracle price is too old!")]
+    StalePrice,
+
+    #[msg("Oracle price confidence interval is too wide!")]
+    PriceConfidenceTooWide,
+
+    #[msg("Converted amount exceeds the maximum the payer allowed!")]
+    PriceSlippageExceeded,
+
+    #[msg("Unauthorized configuration change!")]
+    UnauthorizedConfigChange,
+
+    #[msg("Swap program does not match the configured router or spent more than it was given!")]
+    InvalidSwapProgram,
+
+    #[msg("Swap output is below the minimum amount!")]
+    SwapSlippageExceeded,
+
+    #[msg("Invalid withdrawal group: expected up to 6 (mint, merchant ATA, owner ATA, house ATA, withdrawal limit) groups matching the amounts!")]
+    InvalidWithdrawGroup,
+
+    #[msg("Invalid payout schedule: interval must be positive and the bounty at most 1%!")]
+    InvalidPayoutSchedule,
+
+    #[msg("Payout is not due yet!")]
+    PayoutNotDue,
+
+    #[msg("Withdrawal exceeds the merchant's velocity limit!")]
+    WithdrawalLimitExceeded,
+
+    #[msg("Rent must be returned to the account that paid it!")]
+    InvalidRentPayer,
+
+    #[msg("Sponsorship budget period must be positive!")]
+    InvalidSponsorshipBudget,
+
+    #[msg("Fee payer sponsorship requires the merchant's sponsorship budget!")]
+    SponsorshipBudgetRequired,
+
+    #[msg("Merchant's sponsorship budget for this period is exhausted!")]
+    SponsorshipBudgetExceeded,
+
+    #[msg("Fee payer is not an approved sponsor!")]
+    UnapprovedFeePayer,
+
+    #[msg("The approved fee payer list is full!")]
+    TooManyFeePayers,
+
+    #[msg("Invoice is still open; cancel it before closing!")]
+    InvoiceStillOpen,
+
+    #[msg("Subscription charge failed recently; wait before retrying!")]
+    SubscriptionRetryTooSoon,
+
+    #[msg("Coupon code is missing or doesn't match the coupon!")]
+    InvalidCouponCode,
+
+    #[msg("Order is past its refund window!")]
+    OrderRefundWindowClosed,
+
+    #[msg("Order can still be refunded; it can be closed once fully refunded or past its refund window!")]
+    OrderStillRefundable,
+
+    #[msg("Oracle price is dated in the future!")]
+    InvalidPriceTimestamp,
+
+    #[msg("Payment receipt is still within its retention period!")]
+    PaymentStillRetained,
+
+    #[msg("Refund record is not in the legacy layout!")]
+    InvalidLegacyRefundRecord,
+}
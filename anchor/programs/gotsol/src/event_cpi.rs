@@ -0,0 +1,29 @@
+use anchor_lang::prelude::*;
+
+// `emit_cpi!` expects a `ctx` in scope and reads `ctx.accounts.event_authority` and
+// `ctx.bumps.event_authority`. Our handlers live on the accounts structs rather than
+// taking a `Context`, so they build one of these from `self` and their bumps instead:
+//
+//     let ctx = EventCpiContext::new(&self.event_authority, bumps.event_authority);
+//     emit_cpi!(SomeEvent { .. });
+pub struct EventCpiContext<'a, 'info> {
+    pub accounts: EventAuthorityAccount<'a, 'info>,
+    pub bumps: EventAuthorityBump,
+}
+
+pub struct EventAuthorityAccount<'a, 'info> {
+    pub event_authority: &'a AccountInfo<'info>,
+}
+
+pub struct EventAuthorityBump {
+    pub event_authority: u8,
+}
+
+impl<'a, 'info> EventCpiContext<'a, 'info> {
+    pub fn new(event_authority: &'a AccountInfo<'info>, bump: u8) -> Self {
+        Self {
+            accounts: EventAuthorityAccount { event_authority },
+            bumps: EventAuthorityBump { event_authority: bump },
+        }
+    }
+}
\ No newline at end of file
@@ -1,29 +1,430 @@
 use anchor_lang::prelude::*;
+use anchor_spl::token::spl_token::native_mint;
+use crate::state::CouponDiscount;
+
+// Bumped whenever an event's layout changes so consumers can tell old records apart
+pub const EVENT_SCHEMA_VERSION: u8 = 1;
+
+// Common fields carried first in every event, so indexers and webhook consumers can
+// attribute activity without looking up the transaction's accounts
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq)]
+pub struct EventHeader {
+    pub version: u8,
+    pub merchant: Option<Pubkey>, // None for program-wide admin events
+    pub actor: Pubkey,            // signer that triggered the event
+    pub mint: Option<Pubkey>,     // native mint for SOL, None when no funds are involved
+    pub timestamp: i64,
+    pub slot: u64,
+    pub balance: Option<u64>, // merchant's vault, token account or tip pool balance of `mint` after the instruction
+}
+
+impl EventHeader {
+    pub fn new(merchant: Option<Pubkey>, actor: Pubkey, mint: Option<Pubkey>, balance: Option<u64>) -> Result<Self> {
+        let clock = Clock::get()?;
+        Ok(Self {
+            version: EVENT_SCHEMA_VERSION,
+            merchant,
+            actor,
+            mint,
+            timestamp: clock.unix_timestamp,
+            slot: clock.slot,
+            balance,
+        })
+    }
+
+    // Header for a merchant event that moved no funds
+    pub fn merchant(merchant: Pubkey, actor: Pubkey) -> Result<Self> {
+        Self::new(Some(merchant), actor, None, None)
+    }
+
+    // Header for SOL held in the merchant's vault
+    pub fn sol(merchant: Pubkey, actor: Pubkey, vault_lamports: u64) -> Result<Self> {
+        Self::new(Some(merchant), actor, Some(native_mint::ID), Some(vault_lamports))
+    }
+
+    // Header for tokens held in one of the merchant's token accounts
+    pub fn token(merchant: Pubkey, actor: Pubkey, mint: Pubkey, balance: u64) -> Result<Self> {
+        Self::new(Some(merchant), actor, Some(mint), Some(balance))
+    }
+
+    // Header for a program-wide admin event
+    pub fn global(actor: Pubkey) -> Result<Self> {
+        Self::new(None, actor, None, None)
+    }
+}
 
 #[event]
 pub struct RefundProcessed {
+    pub header: EventHeader,
     pub original_tx_sig: String,
     pub amount: u64,
     pub recipient: Pubkey,
+    pub sponsor: Option<Pubkey>, // approved fee payer that funded the refund record, if any
 }
 
 #[event]
 pub struct MerchantClosed {
-    pub merchant: Pubkey,
+    pub header: EventHeader,
     pub entity_name: String,
 }
 
 #[event]
 pub struct WithdrawSplProcessed {
+    pub header: EventHeader,
     pub amount: u64,
     pub owner_amount: u64,
     pub house_amount: u64,
-    pub mint: Pubkey,
 }
 
 #[event]
 pub struct WithdrawSolProcessed {
+    pub header: EventHeader,
     pub amount: u64,
     pub owner_amount: u64,
     pub house_amount: u64,
 }
+
+#[event]
+pub struct InvoiceCreated {
+    pub header: EventHeader,
+    pub invoice: Pubkey,
+    pub invoice_id: u64,
+    pub amount: u64,
+    pub due_date: i64,
+}
+
+#[event]
+pub struct InvoicePaid {
+    pub header: EventHeader,
+    pub invoice: Pubkey,
+    pub invoice_id: u64,
+    pub amount: u64, // what the payer transferred, after any discount
+    pub payer: Pubkey,
+    pub discount: u64,
+}
+
+#[event]
+pub struct InvoiceCancelled {
+    pub header: EventHeader,
+    pub invoice: Pubkey,
+    pub invoice_id: u64,
+}
+
+#[event]
+pub struct PaymentProcessed {
+    pub header: EventHeader,
+    pub payer: Pubkey,
+    pub amount: u64,
+    pub tip: u64,
+    pub employee: Option<Pubkey>,
+    pub discount: u64,
+    pub gift_card_amount: u64, // part of `amount` settled from a gift card rather than transferred
+}
+
+#[event]
+pub struct TipsDistributed {
+    pub header: EventHeader,
+    pub employee: Pubkey,
+    pub amount: u64,
+}
+
+#[event]
+pub struct PaymentAuthorized {
+    pub header: EventHeader,
+    pub customer: Pubkey,
+    pub authorization: Pubkey,
+    pub amount: u64,
+    pub expires_at: i64,
+}
+
+#[event]
+pub struct PaymentCaptured {
+    pub header: EventHeader,
+    pub customer: Pubkey,
+    pub authorization: Pubkey,
+    pub captured: u64,
+    pub released: u64,
+}
+
+#[event]
+pub struct AuthorizationVoided {
+    pub header: EventHeader,
+    pub customer: Pubkey,
+    pub authorization: Pubkey,
+    pub amount: u64,
+    pub expired: bool,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub enum ChargeFailureReason {
+    DelegateRevoked,
+    AllowanceExhausted,
+    InsufficientBalance,
+}
+
+#[event]
+pub struct SubscriptionCreated {
+    pub header: EventHeader,
+    pub customer: Pubkey,
+    pub subscription: Pubkey,
+    pub amount: u64,
+    pub interval: i64,
+    pub allowance: u64,
+}
+
+#[event]
+pub struct SubscriptionCharged {
+    pub header: EventHeader,
+    pub customer: Pubkey,
+    pub subscription: Pubkey,
+    pub amount: u64,
+    pub next_charge_at: i64,
+    pub cranker: Pubkey,
+}
+
+#[event]
+pub struct SubscriptionChargeFailed {
+    pub header: EventHeader,
+    pub customer: Pubkey,
+    pub subscription: Pubkey,
+    pub amount: u64,
+    pub reason: ChargeFailureReason,
+    pub retry_after: i64,
+}
+
+#[event]
+pub struct SubscriptionCancelled {
+    pub header: EventHeader,
+    pub customer: Pubkey,
+    pub subscription: Pubkey,
+    pub cancelled_by: Pubkey,
+}
+
+#[event]
+pub struct GiftCardPurchased {
+    pub header: EventHeader,
+    pub gift_card: Pubkey,
+    pub purchaser: Pubkey,
+    pub amount: u64,
+    pub expires_at: i64,
+}
+
+#[event]
+pub struct GiftCardRedeemed {
+    pub header: EventHeader,
+    pub gift_card: Pubkey,
+    pub redeemer: Pubkey,
+    pub amount: u64,
+    pub remaining_balance: u64,
+}
+
+#[event]
+pub struct GiftCardClosed {
+    pub header: EventHeader,
+    pub gift_card: Pubkey,
+    pub unredeemed_balance: u64,
+}
+
+#[event]
+pub struct LoyaltyProgramCreated {
+    pub header: EventHeader,
+    pub points_mint: Pubkey,
+    pub earn_basis_points: u64,
+    pub redemption_basis_points: u64,
+    pub non_transferable: bool,
+}
+
+#[event]
+pub struct LoyaltyProgramUpdated {
+    pub header: EventHeader,
+    pub previous_earn_basis_points: u64,
+    pub previous_redemption_basis_points: u64,
+    pub earn_basis_points: u64,
+    pub redemption_basis_points: u64,
+}
+
+#[event]
+pub struct LoyaltyPointsEarned {
+    pub header: EventHeader,
+    pub customer: Pubkey,
+    pub points: u64,
+}
+
+#[event]
+pub struct LoyaltyPointsRedeemed {
+    pub header: EventHeader,
+    pub customer: Pubkey,
+    pub points: u64,
+    pub discount: u64,
+}
+
+#[event]
+pub struct CouponCreated {
+    pub header: EventHeader,
+    pub coupon: Pubkey,
+    pub discount: CouponDiscount,
+    pub max_redemptions: u32,
+    pub expires_at: i64,
+}
+
+#[event]
+pub struct CouponRedeemed {
+    pub header: EventHeader,
+    pub coupon: Pubkey,
+    pub customer: Pubkey,
+    pub discount: u64,
+    pub redemption_count: u32,
+}
+
+#[event]
+pub struct ManagerAdded {
+    pub header: EventHeader,
+    pub manager: Pubkey,
+}
+
+#[event]
+pub struct ManagerRemoved {
+    pub header: EventHeader,
+    pub manager: Pubkey,
+}
+
+#[event]
+pub struct ProductUpdated {
+    pub header: EventHeader,
+    pub product: Pubkey,
+    pub sku: String,
+    pub price: u64,
+    pub stock: u64,
+    pub tax_basis_points: u16,
+    pub active: bool,
+}
+
+#[event]
+pub struct ProductDeleted {
+    pub header: EventHeader,
+    pub product: Pubkey,
+    pub sku: String,
+}
+
+#[event]
+pub struct OrderPaid {
+    pub header: EventHeader,
+    pub order: Pubkey,
+    pub customer: Pubkey,
+    pub subtotal: u64,
+    pub tax: u64,
+    pub tip: u64,
+    pub line_items: u32,
+    pub gift_card_amount: u64, // part of the total settled from a gift card rather than transferred
+    pub discount: u64,
+}
+
+#[event]
+pub struct OrderItemsRefunded {
+    pub header: EventHeader,
+    pub order: Pubkey,
+    pub customer: Pubkey,
+    pub amount: u64,
+    pub refunded_amount: u64,
+}
+
+#[event]
+pub struct SolPaymentProcessed {
+    pub header: EventHeader,
+    pub payment: Pubkey,
+    pub payer: Pubkey,
+    pub usd_amount: u64,
+    pub lamports: u64,
+    pub price: i64,
+    pub price_expo: i32,
+}
+
+#[event]
+pub struct SwapRouterUpdated {
+    pub header: EventHeader,
+    pub swap_router: Pubkey,
+}
+
+#[event]
+pub struct WithdrawSolAsSplProcessed {
+    pub header: EventHeader,
+    pub amount: u64,
+    pub amount_out: u64,
+    pub owner_amount: u64,
+    pub house_amount: u64,
+    pub output_mint: Pubkey,
+}
+
+#[event]
+pub struct PayoutScheduleSet {
+    pub header: EventHeader,
+    pub destination: Pubkey,
+    pub interval: i64,
+    pub minimum_threshold: u64,
+    pub bounty_basis_points: u16,
+    pub next_payout_at: i64,
+}
+
+#[event]
+pub struct PayoutScheduleCancelled {
+    pub header: EventHeader,
+}
+
+#[event]
+pub struct PayoutCranked {
+    pub header: EventHeader,
+    pub destination: Pubkey,
+    pub cranker: Pubkey,
+    pub amount: u64,
+    pub owner_amount: u64,
+    pub house_amount: u64,
+    pub bounty: u64,
+    pub next_payout_at: i64,
+}
+
+#[event]
+pub struct WithdrawalLimitUpdated {
+    pub header: EventHeader,
+    pub max_per_withdrawal: u64,
+    pub max_per_day: u64,
+    pub pending_max_per_withdrawal: u64,
+    pub pending_max_per_day: u64,
+    pub pending_effective_at: i64,
+}
+
+#[event]
+pub struct RentRecoupRateUpdated {
+    pub header: EventHeader,
+    pub rent_recoup_rate: u64,
+}
+
+#[event]
+pub struct AtaRentSponsored {
+    pub header: EventHeader,
+    pub fee_payer: Pubkey,
+    pub lamports: u64,
+    pub recouped: u64,
+}
+
+#[event]
+pub struct SponsorshipBudgetUpdated {
+    pub header: EventHeader,
+    pub allowance_per_period: u64,
+    pub period: i64,
+    pub consumed: u64,
+}
+
+#[event]
+pub struct SponsorshipCharged {
+    pub header: EventHeader,
+    pub sponsor: Pubkey,
+    pub lamports: u64,
+    pub consumed: u64,
+}
+
+#[event]
+pub struct FeePayerApprovalChanged {
+    pub header: EventHeader,
+    pub fee_payer: Pubkey,
+    pub approved: bool,
+}
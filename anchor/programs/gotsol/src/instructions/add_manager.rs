@@ -0,0 +1,70 @@
+use anchor_lang::prelude::*;
+use crate::state::{Global, Merchant, MerchantManager, SponsorshipBudget};
+use crate::event_cpi::EventCpiContext;
+use crate::events::*;
+
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(manager: Pubkey)]
+pub struct AddManager<'info> {
+
+    // our node's fee payer
+    #[account(mut)]
+    pub fee_payer: Option<Signer<'info>>,
+
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    #[account(
+        seeds = [b"merchant", merchant.entity_name.as_bytes(), owner.key().as_ref()], 
+        bump = merchant.merchant_bump)]
+    pub merchant: Box<Account<'info, Merchant>>,
+
+    // caps what our fee payer fronts for this merchant; required whenever fee_payer signs
+    #[account(mut,
+        seeds = [b"sponsorship_budget", merchant.key().as_ref()],
+        bump = sponsorship_budget.bump)]
+    pub sponsorship_budget: Option<Box<Account<'info, SponsorshipBudget>>>,
+
+    // required whenever fee_payer signs, so it can be checked against the approved fee payers
+    #[account(seeds = [b"global"], bump = global.bump)]
+    pub global: Option<Box<Account<'info, Global>>>,
+
+    #[account(
+        init,
+        payer = fee_payer.as_ref().unwrap_or(&owner),
+        seeds = [b"manager", merchant.key().as_ref(), manager.as_ref()],
+        space = MerchantManager::LEN,
+        bump
+    )]
+    pub merchant_manager: Box<Account<'info, MerchantManager>>,
+
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> AddManager<'info> {
+    pub fn add_manager(&mut self, manager: Pubkey, bumps: &AddManagerBumps) -> Result<()> {
+        // Only fee payers approved in the global config may sponsor
+        Global::require_approved_fee_payer(&self.global, self.fee_payer.as_ref().map(|fee_payer| fee_payer.key()))?;
+
+        self.merchant_manager.set_inner(MerchantManager {
+            merchant: self.merchant.key(),
+            manager,
+            rent_payer: self.fee_payer.as_ref().map_or(self.owner.key(), |fee_payer| fee_payer.key()),
+            bump: bumps.merchant_manager,
+        });
+
+        // Emit event
+        emit!(ManagerAdded {
+            header: EventHeader::merchant(self.merchant.key(), self.owner.key())?,
+            manager,
+        });
+
+        // Count what our fee payer fronted against the merchant's budget
+        let rent = self.merchant_manager.to_account_info().lamports();
+        let ctx = EventCpiContext::new(&self.event_authority, bumps.event_authority);
+        SponsorshipBudget::charge(&mut self.sponsorship_budget, self.fee_payer.as_ref().map(|fee_payer| fee_payer.key()), rent, &ctx)?;
+
+        Ok(())
+    }
+}
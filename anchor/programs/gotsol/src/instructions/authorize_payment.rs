@@ -0,0 +1,98 @@
+use anchor_lang::prelude::*;
+use crate::state::{Authorization, Merchant};
+use crate::errors::*;
+use crate::event_cpi::EventCpiContext;
+use crate::events::*;
+
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token_interface::{Mint, TokenAccount, TokenInterface},
+};
+
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(authorization_id: u64, amount: u64)]
+pub struct AuthorizePayment<'info> {
+    #[account(mut,
+        constraint = amount > 0 @ CustomError::ZeroAmountPayment)]
+    pub customer: Signer<'info>,
+
+    pub merchant: Box<Account<'info, Merchant>>,
+
+    pub stablecoin_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(mut,
+        associated_token::mint = stablecoin_mint,
+        associated_token::authority = customer,
+        constraint = customer_stablecoin_ata.amount >= amount @ CustomError::InsufficientFunds)]
+    pub customer_stablecoin_ata: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        init,
+        payer = customer,
+        seeds = [b"authorization", merchant.key().as_ref(), customer.key().as_ref(), authorization_id.to_le_bytes().as_ref()],
+        space = Authorization::LEN,
+        bump
+    )]
+    pub authorization: Box<Account<'info, Authorization>>,
+
+    // held funds sit in a token account owned by the authorization PDA until captured or released
+    #[account(
+        init,
+        payer = customer,
+        seeds = [b"escrow", authorization.key().as_ref()],
+        bump,
+        token::mint = stablecoin_mint,
+        token::authority = authorization,
+        token::token_program = token_program)]
+    pub escrow: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> AuthorizePayment<'info> {
+    pub fn authorize_payment(&mut self, authorization_id: u64, amount: u64, expires_at: i64, bumps: &AuthorizePaymentBumps) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        require!(expires_at > now, CustomError::InvalidExpiry);
+
+        self.authorization.set_inner(Authorization {
+            merchant: self.merchant.key(),
+            customer: self.customer.key(),
+            mint: self.stablecoin_mint.key(),
+            authorization_id,
+            amount,
+            expires_at,
+            bump: bumps.authorization,
+            escrow_bump: bumps.escrow,
+        });
+
+        // Move the held amount from the customer into escrow
+        anchor_spl::token_interface::transfer_checked(
+            CpiContext::new(
+                self.token_program.to_account_info(),
+                anchor_spl::token_interface::TransferChecked {
+                    from: self.customer_stablecoin_ata.to_account_info(),
+                    mint: self.stablecoin_mint.to_account_info(),
+                    to: self.escrow.to_account_info(),
+                    authority: self.customer.to_account_info(),
+                },
+            ),
+            amount,
+            self.stablecoin_mint.decimals,
+        )?;
+
+        // Emit event
+        let ctx = EventCpiContext::new(&self.event_authority, bumps.event_authority);
+        emit_cpi!(PaymentAuthorized {
+            header: EventHeader::new(Some(self.merchant.key()), self.customer.key(), Some(self.stablecoin_mint.key()), None)?,
+            customer: self.customer.key(),
+            authorization: self.authorization.key(),
+            amount,
+            expires_at,
+        });
+
+        Ok(())
+    }
+}
@@ -0,0 +1,38 @@
+use anchor_lang::prelude::*;
+use crate::state::{Invoice, InvoiceStatus, Merchant};
+use crate::errors::*;
+use crate::events::*;
+
+#[derive(Accounts)]
+pub struct CancelInvoice<'info> {
+    pub owner: Signer<'info>,
+
+    #[account(
+        seeds = [b"merchant", merchant.entity_name.as_bytes(), owner.key().as_ref()], 
+        bump = merchant.merchant_bump)]
+    pub merchant: Box<Account<'info, Merchant>>,
+
+    #[account(mut,
+        seeds = [b"invoice", merchant.key().as_ref(), invoice.invoice_id.to_le_bytes().as_ref()],
+        bump = invoice.bump,
+        has_one = merchant)]
+    pub invoice: Box<Account<'info, Invoice>>,
+}
+
+impl<'info> CancelInvoice<'info> {
+    pub fn cancel_invoice(&mut self) -> Result<()> {
+        // Paid invoices must be settled through a refund instead
+        require!(self.invoice.status == InvoiceStatus::Open, CustomError::InvoiceNotOpen);
+
+        self.invoice.status = InvoiceStatus::Cancelled;
+
+        // Emit event
+        emit!(InvoiceCancelled {
+            header: EventHeader::merchant(self.merchant.key(), self.owner.key())?,
+            invoice: self.invoice.key(),
+            invoice_id: self.invoice.invoice_id,
+        });
+
+        Ok(())
+    }
+}
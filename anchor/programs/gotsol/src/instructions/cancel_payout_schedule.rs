@@ -0,0 +1,32 @@
+use anchor_lang::prelude::*;
+use crate::state::{Merchant, PayoutSchedule};
+use crate::events::*;
+
+#[derive(Accounts)]
+pub struct CancelPayoutSchedule<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    #[account(
+        seeds = [b"merchant", merchant.entity_name.as_bytes(), owner.key().as_ref()], 
+        bump = merchant.merchant_bump)]
+    pub merchant: Box<Account<'info, Merchant>>,
+
+    #[account(mut,
+        seeds = [b"payout_schedule", merchant.key().as_ref(), payout_schedule.mint.as_ref()],
+        bump = payout_schedule.bump,
+        has_one = merchant,
+        close = owner)]
+    pub payout_schedule: Box<Account<'info, PayoutSchedule>>,
+}
+
+impl<'info> CancelPayoutSchedule<'info> {
+    pub fn cancel_payout_schedule(&mut self) -> Result<()> {
+        // Emit event
+        emit!(PayoutScheduleCancelled {
+            header: EventHeader::new(Some(self.merchant.key()), self.owner.key(), Some(self.payout_schedule.mint), None)?,
+        });
+
+        Ok(())
+    }
+}
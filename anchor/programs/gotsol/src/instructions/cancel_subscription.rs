@@ -0,0 +1,88 @@
+use anchor_lang::prelude::*;
+use crate::state::{Merchant, Subscription};
+use crate::errors::*;
+use crate::events::*;
+
+use anchor_spl::token_interface::{approve, revoke, Approve, Revoke, Mint, TokenAccount, TokenInterface};
+
+#[derive(Accounts)]
+pub struct CancelSubscription<'info> {
+    // either the customer or the merchant owner
+    pub authority: Signer<'info>,
+
+    pub merchant: Box<Account<'info, Merchant>>,
+
+    /// CHECK: the subscribed customer; receives the reclaimed rent
+    #[account(mut)]
+    pub customer: AccountInfo<'info>,
+
+    #[account(mut,
+        seeds = [b"subscription", merchant.key().as_ref(), customer.key().as_ref()],
+        bump = subscription.bump,
+        has_one = merchant,
+        has_one = customer,
+        constraint = subscription.mint == stablecoin_mint.key() @ CustomError::MintMismatch,
+        close = customer)]
+    pub subscription: Box<Account<'info, Subscription>>,
+
+    pub stablecoin_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(mut,
+        associated_token::mint = stablecoin_mint,
+        associated_token::authority = customer)]
+    pub customer_stablecoin_ata: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// CHECK: program-owned delegate shared by all of the customer's subscriptions
+    #[account(seeds = [b"subscription_delegate"], bump)]
+    pub subscription_delegate: AccountInfo<'info>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+impl<'info> CancelSubscription<'info> {
+    pub fn cancel_subscription(&mut self) -> Result<()> {
+        let authority = self.authority.key();
+        let by_customer = authority == self.customer.key();
+        require!(by_customer || authority == self.merchant.owner, CustomError::UnauthorizedCancellation);
+
+        // Only the token account owner can change the approval. The customer's other
+        // subscriptions keep their share; on a merchant-side cancel the leftover approval
+        // stays with the shared delegate, but no subscription can charge against it anymore
+        if by_customer && self.customer_stablecoin_ata.delegate == Some(self.subscription_delegate.key()).into() {
+            let remaining = self.customer_stablecoin_ata.delegated_amount
+                .saturating_sub(self.subscription.remaining_allowance);
+
+            if remaining > 0 {
+                approve(
+                    CpiContext::new(
+                        self.token_program.to_account_info(),
+                        Approve {
+                            to: self.customer_stablecoin_ata.to_account_info(),
+                            delegate: self.subscription_delegate.to_account_info(),
+                            authority: self.authority.to_account_info(),
+                        },
+                    ),
+                    remaining,
+                )?;
+            } else {
+                revoke(CpiContext::new(
+                    self.token_program.to_account_info(),
+                    Revoke {
+                        source: self.customer_stablecoin_ata.to_account_info(),
+                        authority: self.authority.to_account_info(),
+                    },
+                ))?;
+            }
+        }
+
+        // Emit event
+        emit!(SubscriptionCancelled {
+            header: EventHeader::new(Some(self.merchant.key()), self.authority.key(), Some(self.stablecoin_mint.key()), None)?,
+            customer: self.customer.key(),
+            subscription: self.subscription.key(),
+            cancelled_by: authority,
+        });
+
+        Ok(())
+    }
+}
@@ -0,0 +1,133 @@
+use anchor_lang::prelude::*;
+use crate::state::{Authorization, Merchant};
+use crate::errors::*;
+use crate::event_cpi::EventCpiContext;
+use crate::events::*;
+
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token_interface::{close_account, CloseAccount, Mint, TokenAccount, TokenInterface},
+};
+
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(amount: u64)]
+pub struct CapturePayment<'info> {
+    #[account(mut,
+        constraint = amount > 0 @ CustomError::ZeroAmountPayment)]
+    pub owner: Signer<'info>,
+
+    #[account(
+        seeds = [b"merchant", merchant.entity_name.as_bytes(), owner.key().as_ref()], 
+        bump = merchant.merchant_bump)]
+    pub merchant: Box<Account<'info, Merchant>>,
+
+    /// CHECK: the customer who placed the hold; receives any released remainder and the reclaimed rent
+    #[account(mut)]
+    pub customer: AccountInfo<'info>,
+
+    #[account(mut,
+        seeds = [b"authorization", merchant.key().as_ref(), customer.key().as_ref(), authorization.authorization_id.to_le_bytes().as_ref()],
+        bump = authorization.bump,
+        has_one = merchant,
+        has_one = customer,
+        constraint = authorization.mint == stablecoin_mint.key() @ CustomError::MintMismatch,
+        constraint = amount <= authorization.amount @ CustomError::CaptureExceedsAuthorization,
+        close = customer)]
+    pub authorization: Box<Account<'info, Authorization>>,
+
+    #[account(mut,
+        seeds = [b"escrow", authorization.key().as_ref()],
+        bump = authorization.escrow_bump)]
+    pub escrow: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    pub stablecoin_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(init_if_needed,
+        payer = owner,
+        associated_token::mint = stablecoin_mint,
+        associated_token::authority = merchant)]
+    pub merchant_stablecoin_ata: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(mut,
+        associated_token::mint = stablecoin_mint,
+        associated_token::authority = customer)]
+    pub customer_stablecoin_ata: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> CapturePayment<'info> {
+    pub fn capture_payment(&mut self, amount: u64, bumps: &CapturePaymentBumps) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        require!(now <= self.authorization.expires_at, CustomError::AuthorizationExpired);
+
+        // A capture is final: any uncaptured remainder goes back to the customer
+        let released = self.authorization.amount
+            .checked_sub(amount)
+            .ok_or(CustomError::ArithmeticOverflow)?;
+
+        let merchant_key = self.merchant.key();
+        let customer_key = self.customer.key();
+        let authorization_id = self.authorization.authorization_id.to_le_bytes();
+        let seeds = &[
+            b"authorization".as_ref(),
+            merchant_key.as_ref(),
+            customer_key.as_ref(),
+            authorization_id.as_ref(),
+            &[self.authorization.bump],
+        ];
+
+        // Transfer the captured amount to the merchant
+        self.transfer_from_escrow(&self.merchant_stablecoin_ata.to_account_info(), amount, seeds)?;
+
+        // Release the remainder back to the customer
+        if released > 0 {
+            self.transfer_from_escrow(&self.customer_stablecoin_ata.to_account_info(), released, seeds)?;
+        }
+
+        // Close the empty escrow, returning its rent to the customer
+        close_account(CpiContext::new_with_signer(
+            self.token_program.to_account_info(),
+            CloseAccount {
+                account: self.escrow.to_account_info(),
+                destination: self.customer.to_account_info(),
+                authority: self.authorization.to_account_info(),
+            },
+            &[seeds],
+        ))?;
+
+        // Emit event
+        self.merchant_stablecoin_ata.reload()?;
+        let ctx = EventCpiContext::new(&self.event_authority, bumps.event_authority);
+        emit_cpi!(PaymentCaptured {
+            header: EventHeader::token(merchant_key, self.owner.key(), self.stablecoin_mint.key(), self.merchant_stablecoin_ata.amount)?,
+            customer: customer_key,
+            authorization: self.authorization.key(),
+            captured: amount,
+            released,
+        });
+
+        Ok(())
+    }
+
+    /// Helper function to reduce code duplication for escrow transfers
+    fn transfer_from_escrow(&self, to: &AccountInfo<'info>, amount: u64, seeds: &[&[u8]]) -> Result<()> {
+        anchor_spl::token_interface::transfer_checked(
+            CpiContext::new_with_signer(
+                self.token_program.to_account_info(),
+                anchor_spl::token_interface::TransferChecked {
+                    from: self.escrow.to_account_info(),
+                    mint: self.stablecoin_mint.to_account_info(),
+                    to: to.clone(),
+                    authority: self.authorization.to_account_info(),
+                },
+                &[seeds],
+            ),
+            amount,
+            self.stablecoin_mint.decimals,
+        )
+    }
+}
@@ -0,0 +1,145 @@
+use anchor_lang::prelude::*;
+use crate::state::{Merchant, Subscription};
+use crate::errors::*;
+use crate::event_cpi::EventCpiContext;
+use crate::events::*;
+
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+
+// How long the crank must wait after a failed charge before trying again
+const CHARGE_RETRY_DELAY_SECONDS: i64 = 3600;
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct ChargeSubscription<'info> {
+    // permissionless crank; anyone may submit a due charge
+    pub cranker: Signer<'info>,
+
+    pub merchant: Box<Account<'info, Merchant>>,
+
+    /// CHECK: the subscribed customer, used only to derive the subscription and their ATA
+    pub customer: AccountInfo<'info>,
+
+    #[account(mut,
+        seeds = [b"subscription", merchant.key().as_ref(), customer.key().as_ref()],
+        bump = subscription.bump,
+        has_one = merchant,
+        has_one = customer,
+        constraint = subscription.mint == stablecoin_mint.key() @ CustomError::MintMismatch)]
+    pub subscription: Box<Account<'info, Subscription>>,
+
+    pub stablecoin_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(mut,
+        associated_token::mint = stablecoin_mint,
+        associated_token::authority = customer)]
+    pub customer_stablecoin_ata: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(mut,
+        associated_token::mint = stablecoin_mint,
+        associated_token::authority = merchant)]
+    pub merchant_stablecoin_ata: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// CHECK: program-owned delegate shared by all of the customer's subscriptions
+    #[account(seeds = [b"subscription_delegate"], bump)]
+    pub subscription_delegate: AccountInfo<'info>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+impl<'info> ChargeSubscription<'info> {
+    pub fn charge_subscription(&mut self, bumps: &ChargeSubscriptionBumps) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        require!(now >= self.subscription.next_charge_at, CustomError::SubscriptionNotDue);
+        require!(now >= self.subscription.retry_after, CustomError::SubscriptionRetryTooSoon);
+
+        let amount = self.subscription.amount;
+
+        // A charge the customer's account can't cover is reported rather than reverted, so the
+        // failure is recorded on-chain; the next attempt waits out a retry delay so a crank
+        // can't replay failures for free
+        let failure = if self.customer_stablecoin_ata.delegate != Some(self.subscription_delegate.key()).into() {
+            Some(ChargeFailureReason::DelegateRevoked)
+        } else if self.subscription.remaining_allowance < amount
+            || self.customer_stablecoin_ata.delegated_amount < amount
+        {
+            Some(ChargeFailureReason::AllowanceExhausted)
+        } else if self.customer_stablecoin_ata.amount < amount {
+            Some(ChargeFailureReason::InsufficientBalance)
+        } else {
+            None
+        };
+
+        if let Some(reason) = failure {
+            self.subscription.retry_after = now
+                .checked_add(CHARGE_RETRY_DELAY_SECONDS.min(self.subscription.interval))
+                .ok_or(CustomError::ArithmeticOverflow)?;
+
+            let ctx = EventCpiContext::new(&self.event_authority, bumps.event_authority);
+            emit_cpi!(SubscriptionChargeFailed {
+                header: EventHeader::token(
+                    self.merchant.key(),
+                    self.cranker.key(),
+                    self.stablecoin_mint.key(),
+                    self.merchant_stablecoin_ata.amount,
+                )?,
+                customer: self.customer.key(),
+                subscription: self.subscription.key(),
+                amount,
+                reason,
+                retry_after: self.subscription.retry_after,
+            });
+            return Ok(());
+        }
+
+        let merchant_key = self.merchant.key();
+        let customer_key = self.customer.key();
+        let seeds = &[b"subscription_delegate".as_ref(), &[bumps.subscription_delegate]];
+
+        // Pull the period's amount using the shared delegate's authority
+        anchor_spl::token_interface::transfer_checked(
+            CpiContext::new_with_signer(
+                self.token_program.to_account_info(),
+                anchor_spl::token_interface::TransferChecked {
+                    from: self.customer_stablecoin_ata.to_account_info(),
+                    mint: self.stablecoin_mint.to_account_info(),
+                    to: self.merchant_stablecoin_ata.to_account_info(),
+                    authority: self.subscription_delegate.to_account_info(),
+                },
+                &[seeds],
+            ),
+            amount,
+            self.stablecoin_mint.decimals,
+        )?;
+        self.subscription.remaining_allowance = self.subscription.remaining_allowance
+            .checked_sub(amount)
+            .ok_or(CustomError::ArithmeticOverflow)?;
+
+        // Advance the schedule; missed periods are skipped rather than back-charged
+        let next_charge_at = self.subscription.next_charge_at
+            .checked_add(self.subscription.interval)
+            .ok_or(CustomError::ArithmeticOverflow)?;
+        self.subscription.next_charge_at = if next_charge_at > now {
+            next_charge_at
+        } else {
+            now.checked_add(self.subscription.interval).ok_or(CustomError::ArithmeticOverflow)?
+        };
+        self.subscription.charges_made = self.subscription.charges_made
+            .checked_add(1)
+            .ok_or(CustomError::ArithmeticOverflow)?;
+
+        // Emit event
+        self.merchant_stablecoin_ata.reload()?;
+        let ctx = EventCpiContext::new(&self.event_authority, bumps.event_authority);
+        emit_cpi!(SubscriptionCharged {
+            header: EventHeader::token(merchant_key, self.cranker.key(), self.stablecoin_mint.key(), self.merchant_stablecoin_ata.amount)?,
+            customer: customer_key,
+            subscription: self.subscription.key(),
+            amount,
+            next_charge_at: self.subscription.next_charge_at,
+            cranker: self.cranker.key(),
+        });
+
+        Ok(())
+    }
+}
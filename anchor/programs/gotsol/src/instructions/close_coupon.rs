@@ -0,0 +1,33 @@
+use anchor_lang::prelude::*;
+use crate::state::{Coupon, Merchant};
+use crate::errors::*;
+
+#[derive(Accounts)]
+pub struct CloseCoupon<'info> {
+    pub owner: Signer<'info>,
+
+    #[account(
+        seeds = [b"merchant", merchant.entity_name.as_bytes(), owner.key().as_ref()], 
+        bump = merchant.merchant_bump)]
+    pub merchant: Box<Account<'info, Merchant>>,
+
+    /// CHECK: whoever funded the coupon (our fee payer or the merchant owner) gets the rent back
+    #[account(mut)]
+    pub rent_payer: AccountInfo<'info>,
+
+    #[account(mut,
+        seeds = [b"coupon", merchant.key().as_ref(), coupon.code_hash.as_ref()],
+        bump = coupon.bump,
+        has_one = merchant,
+        has_one = rent_payer @ CustomError::InvalidRentPayer,
+        close = rent_payer)]
+    pub coupon: Box<Account<'info, Coupon>>,
+
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> CloseCoupon<'info> {
+    pub fn close_coupon(&mut self) -> Result<()> {
+        Ok(())
+    }
+}
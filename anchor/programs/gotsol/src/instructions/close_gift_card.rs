@@ -0,0 +1,49 @@
+use anchor_lang::prelude::*;
+use crate::state::{GiftCard, Merchant};
+use crate::errors::*;
+use crate::event_cpi::EventCpiContext;
+use crate::events::*;
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct CloseGiftCard<'info> {
+    pub owner: Signer<'info>,
+
+    #[account(
+        seeds = [b"merchant", merchant.entity_name.as_bytes(), owner.key().as_ref()], 
+        bump = merchant.merchant_bump)]
+    pub merchant: Box<Account<'info, Merchant>>,
+
+    /// CHECK: the original purchaser, who paid the card's rent
+    #[account(mut)]
+    pub purchaser: AccountInfo<'info>,
+
+    #[account(mut,
+        seeds = [b"gift_card", merchant.key().as_ref(), gift_card.card_id.to_le_bytes().as_ref()],
+        bump = gift_card.bump,
+        has_one = merchant,
+        has_one = purchaser,
+        close = purchaser)]
+    pub gift_card: Box<Account<'info, GiftCard>>,
+}
+
+impl<'info> CloseGiftCard<'info> {
+    pub fn close_gift_card(&mut self, bumps: &CloseGiftCardBumps) -> Result<()> {
+        // Only spent or expired cards can be closed; any expired balance is reported as breakage
+        let now = Clock::get()?.unix_timestamp;
+        require!(
+            self.gift_card.balance == 0 || self.gift_card.is_expired(now),
+            CustomError::GiftCardStillActive
+        );
+
+        // Emit event
+        let ctx = EventCpiContext::new(&self.event_authority, bumps.event_authority);
+        emit_cpi!(GiftCardClosed {
+            header: EventHeader::new(Some(self.merchant.key()), self.owner.key(), Some(self.gift_card.mint), None)?,
+            gift_card: self.gift_card.key(),
+            unredeemed_balance: self.gift_card.balance,
+        });
+
+        Ok(())
+    }
+}
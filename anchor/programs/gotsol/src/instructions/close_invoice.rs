@@ -0,0 +1,34 @@
+use anchor_lang::prelude::*;
+use crate::state::{Invoice, InvoiceStatus, Merchant};
+use crate::errors::*;
+
+#[derive(Accounts)]
+pub struct CloseInvoice<'info> {
+    pub owner: Signer<'info>,
+
+    #[account(
+        seeds = [b"merchant", merchant.entity_name.as_bytes(), owner.key().as_ref()],
+        bump = merchant.merchant_bump)]
+    pub merchant: Box<Account<'info, Merchant>>,
+
+    /// CHECK: whoever funded the invoice (our fee payer or the merchant owner) gets the rent back
+    #[account(mut)]
+    pub rent_payer: AccountInfo<'info>,
+
+    #[account(mut,
+        seeds = [b"invoice", merchant.key().as_ref(), invoice.invoice_id.to_le_bytes().as_ref()],
+        bump = invoice.bump,
+        has_one = merchant,
+        has_one = rent_payer @ CustomError::InvalidRentPayer,
+        close = rent_payer)]
+    pub invoice: Box<Account<'info, Invoice>>,
+}
+
+impl<'info> CloseInvoice<'info> {
+    pub fn close_invoice(&mut self) -> Result<()> {
+        // Open invoices can still be paid, so they must be cancelled first
+        require!(self.invoice.status != InvoiceStatus::Open, CustomError::InvoiceStillOpen);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,61 @@
+use anchor_lang::prelude::*;
+use crate::state::RefundRecord;
+use crate::constants::*;
+use crate::errors::*;
+
+use std::str::FromStr;
+
+#[derive(Accounts)]
+#[instruction(original_tx_sig: String)]
+pub struct CloseLegacyRefund<'info> {
+    #[account(mut, constraint = auth.key() == Pubkey::from_str(AUTH_2).unwrap() || auth.key() == Pubkey::from_str(AUTH_3).unwrap())]
+    pub auth: Signer<'info>,
+
+    /// CHECK: a refund record from before `rent_payer` was stored, which no longer deserializes; its layout is checked in the handler
+    #[account(mut,
+        seeds = [b"refund", original_tx_sig.as_bytes()],
+        bump,
+        owner = crate::ID)]
+    pub refund_record: AccountInfo<'info>,
+
+    /// CHECK: whoever funded the record (our fee payer or the merchant owner), as resolved off-chain
+    #[account(mut)]
+    pub rent_payer: AccountInfo<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> CloseLegacyRefund<'info> {
+    /// Closes an old-layout record to `rent_payer`. These records don't say who funded them, so
+    /// the program has to trust auth to name the funder; the CLI and the close-refund action take
+    /// it from the fee payer of the transaction that created the record. Only records that still
+    /// have the legacy layout can be closed this way; current ones go through `close_refund`.
+    pub fn close_legacy_refund(&mut self, original_tx_sig: String) -> Result<()> {
+        {
+            // discriminator, then the Borsh string holding the signature
+            let data = self.refund_record.try_borrow_data()?;
+            let sig_start = RefundRecord::DISCRIMINATOR.len() + 4;
+            let sig_end = sig_start + original_tx_sig.len();
+            require!(
+                data.len() == RefundRecord::LEGACY_LEN
+                    && data.starts_with(RefundRecord::DISCRIMINATOR)
+                    && data[sig_start - 4..sig_start] == (original_tx_sig.len() as u32).to_le_bytes()
+                    && data.get(sig_start..sig_end) == Some(original_tx_sig.as_bytes()),
+                CustomError::InvalidLegacyRefundRecord
+            );
+        }
+
+        // Same as Anchor's `close`: move the lamports, then hand the emptied account back to the system program
+        let record = self.refund_record.to_account_info();
+        let rent_payer = self.rent_payer.to_account_info();
+        **rent_payer.try_borrow_mut_lamports()? = rent_payer
+            .lamports()
+            .checked_add(record.lamports())
+            .ok_or(CustomError::ArithmeticOverflow)?;
+        **record.try_borrow_mut_lamports()? = 0;
+        record.assign(&System::id());
+        record.resize(0)?;
+
+        Ok(())
+    }
+}
\ No newline at end of file
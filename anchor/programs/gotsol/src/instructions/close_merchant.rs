@@ -1,14 +1,16 @@
 use anchor_lang::prelude::*;
 use crate::state::Merchant;
+use crate::event_cpi::EventCpiContext;
 use crate::events::*;
 
+#[event_cpi]
 #[derive(Accounts)]
 pub struct CloseMerchant<'info> {
     #[account(mut)]
     pub owner: Signer<'info>,
 
     #[account(mut, 
-        seeds = [b"merchant", merchant.entity_name.as_str().as_bytes(), owner.key().as_ref()], 
+        seeds = [b"merchant", merchant.entity_name.as_bytes(), owner.key().as_ref()], 
         bump = merchant.merchant_bump,
         close = owner)]
     pub merchant: Box<Account<'info, Merchant>>,
@@ -17,11 +19,12 @@ pub struct CloseMerchant<'info> {
 }
 
 impl<'info> CloseMerchant<'info> {
-    pub fn close_merchant(&mut self) -> Result<()> {
+    pub fn close_merchant(&mut self, bumps: &CloseMerchantBumps) -> Result<()> {
 
         // Emit event for Merchant closure
-        emit!(MerchantClosed {
-            merchant: self.merchant.key(),
+        let ctx = EventCpiContext::new(&self.event_authority, bumps.event_authority);
+        emit_cpi!(MerchantClosed {
+            header: EventHeader::merchant(self.merchant.key(), self.owner.key())?,
             entity_name: self.merchant.entity_name.clone()
         });
 
@@ -0,0 +1,33 @@
+use anchor_lang::prelude::*;
+use crate::state::{Merchant, Order};
+use crate::errors::*;
+
+#[derive(Accounts)]
+pub struct CloseOrder<'info> {
+    // paid the order's rent when paying for it, so gets it back
+    #[account(mut)]
+    pub customer: Signer<'info>,
+
+    pub merchant: Box<Account<'info, Merchant>>,
+
+    #[account(mut,
+        seeds = [b"order", merchant.key().as_ref(), customer.key().as_ref(), order.order_id.to_le_bytes().as_ref()],
+        bump = order.bump,
+        has_one = merchant,
+        has_one = customer,
+        close = customer)]
+    pub order: Box<Account<'info, Order>>,
+}
+
+impl<'info> CloseOrder<'info> {
+    pub fn close_order(&mut self) -> Result<()> {
+        // The merchant refunds against the order, so it has to stay until nothing is left to refund
+        let now = Clock::get()?.unix_timestamp;
+        require!(
+            self.order.is_fully_refunded() || now > self.order.refund_deadline(),
+            CustomError::OrderStillRefundable
+        );
+
+        Ok(())
+    }
+}
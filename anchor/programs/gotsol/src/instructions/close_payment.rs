@@ -0,0 +1,33 @@
+use anchor_lang::prelude::*;
+use crate::state::{Merchant, Payment, PAYMENT_RETENTION_SECONDS};
+use crate::errors::*;
+
+#[derive(Accounts)]
+pub struct ClosePayment<'info> {
+    // paid the receipt's rent when paying, so gets it back
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub merchant: Box<Account<'info, Merchant>>,
+
+    #[account(mut,
+        seeds = [b"payment", merchant.key().as_ref(), payer.key().as_ref(), payment.payment_id.to_le_bytes().as_ref()],
+        bump = payment.bump,
+        has_one = merchant,
+        has_one = payer,
+        close = payer)]
+    pub payment: Box<Account<'info, Payment>>,
+}
+
+impl<'info> ClosePayment<'info> {
+    pub fn close_payment(&mut self) -> Result<()> {
+        // The receipt backs the merchant's books, so it stays for the retention period
+        let now = Clock::get()?.unix_timestamp;
+        require!(
+            now > self.payment.created_at.saturating_add(PAYMENT_RETENTION_SECONDS),
+            CustomError::PaymentStillRetained
+        );
+
+        Ok(())
+    }
+}
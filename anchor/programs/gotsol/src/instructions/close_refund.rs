@@ -1,6 +1,7 @@
 use anchor_lang::prelude::*;
 use crate::state::RefundRecord;
 use crate::constants::*;
+use crate::errors::*;
 
 use std::str::FromStr;
 
@@ -10,9 +11,13 @@ pub struct CloseRefund<'info> {
     #[account(mut, constraint = auth.key() == Pubkey::from_str(AUTH_2).unwrap() || auth.key() == Pubkey::from_str(AUTH_3).unwrap())]
     pub auth: Signer<'info>,
 
-    #[account(mut, close = auth)]
+    #[account(mut, has_one = rent_payer @ CustomError::InvalidRentPayer, close = rent_payer)]
     pub refund_record: Box<Account<'info, RefundRecord>>,
 
+    /// CHECK: whoever funded the record (our fee payer or the merchant owner) gets the rent back
+    #[account(mut)]
+    pub rent_payer: AccountInfo<'info>,
+
     pub system_program: Program<'info, System>,
 }
 
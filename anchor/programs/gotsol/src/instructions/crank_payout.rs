@@ -0,0 +1,192 @@
+use anchor_lang::prelude::*;
+
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token_interface::{Mint, TokenAccount, TokenInterface},
+};
+
+use std::str::FromStr;
+
+use crate::state::{Merchant, PayoutSchedule, WithdrawalLimit};
+use crate::constants::*;
+use crate::errors::*;
+use crate::event_cpi::EventCpiContext;
+use crate::events::*;
+
+const OWNER_SHARE_BASIS_POINTS: u64 = 9900; // 99%
+const HOUSE_SHARE_BASIS_POINTS: u64 = 100;  // 1%
+const BASIS_POINTS_DIVISOR: u64 = 10000;
+const MINIMUM_WITHDRAWAL_SPL_UNITS: u64 = 100; 
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct CrankPayout<'info> {
+    // permissionless crank; pays for any destination/house ATA that doesn't exist yet
+    #[account(mut)]
+    pub cranker: Signer<'info>,
+
+    #[account(
+        seeds = [b"merchant", merchant.entity_name.as_bytes(), merchant.owner.as_ref()], 
+        bump = merchant.merchant_bump)]
+    pub merchant: Box<Account<'info, Merchant>>,
+
+    #[account(mut,
+        seeds = [b"payout_schedule", merchant.key().as_ref(), stablecoin_mint.key().as_ref()],
+        bump = payout_schedule.bump,
+        has_one = merchant,
+        has_one = destination)]
+    pub payout_schedule: Box<Account<'info, PayoutSchedule>>,
+
+    pub stablecoin_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(mut,
+        associated_token::mint = stablecoin_mint,
+        associated_token::authority = merchant)]
+    pub merchant_stablecoin_ata: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// CHECK: the merchant's withdrawal limit for this mint; enforced only once the owner has created it
+    #[account(mut, seeds = [b"withdrawal_limit", merchant.key().as_ref(), stablecoin_mint.key().as_ref()], bump)]
+    pub withdrawal_limit: AccountInfo<'info>,
+
+    /// CHECK: the payout wallet stored on the schedule
+    pub destination: AccountInfo<'info>,
+
+    #[account(init_if_needed,
+        payer = cranker,
+        associated_token::mint = stablecoin_mint,
+        associated_token::authority = destination)]
+    pub destination_stablecoin_ata: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// CHECK: This is the HOUSE Squads multi-sig
+    #[account(constraint = house.key() == Pubkey::from_str(HOUSE).unwrap())]
+    pub house: AccountInfo<'info>,
+
+    #[account(init_if_needed,
+        payer = cranker,
+        associated_token::mint = stablecoin_mint,
+        associated_token::authority = house)]
+    pub house_stablecoin_ata: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    // receives the bounty; without it the bounty stays with the owner
+    #[account(mut, token::mint = stablecoin_mint)]
+    pub cranker_stablecoin_ata: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> CrankPayout<'info> {
+    /// Sweeps the merchant ATA's balance to the schedule's destination once due, up to what the
+    /// withdrawal limits still allow, applying the standard house split and the optional cranker bounty
+    pub fn crank_payout(&mut self, bumps: &CrankPayoutBumps) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        require!(now >= self.payout_schedule.next_payout_at, CustomError::PayoutNotDue);
+
+        // Anything over the owner's velocity limits waits for a later payout
+        let amount = self
+            .merchant_stablecoin_ata
+            .amount
+            .min(WithdrawalLimit::remaining_at(&self.withdrawal_limit)?);
+        require!(
+            amount >= self.payout_schedule.minimum_threshold.max(MINIMUM_WITHDRAWAL_SPL_UNITS),
+            CustomError::BelowMinimumWithdrawal
+        );
+
+        WithdrawalLimit::enforce(&self.withdrawal_limit, amount)?;
+
+        // Calculate shares using basis points for better precision
+        let owner_amount = amount
+            .checked_mul(OWNER_SHARE_BASIS_POINTS)
+            .ok_or(CustomError::ArithmeticOverflow)?
+            .checked_div(BASIS_POINTS_DIVISOR)
+            .ok_or(CustomError::ArithmeticOverflow)?;
+            
+        let house_amount = amount
+            .checked_mul(HOUSE_SHARE_BASIS_POINTS)
+            .ok_or(CustomError::ArithmeticOverflow)?
+            .checked_div(BASIS_POINTS_DIVISOR)
+            .ok_or(CustomError::ArithmeticOverflow)?;
+
+        // The bounty comes out of the owner's share, never the house's
+        let bounty = if self.cranker_stablecoin_ata.is_some() {
+            amount
+                .checked_mul(self.payout_schedule.bounty_basis_points as u64)
+                .ok_or(CustomError::ArithmeticOverflow)?
+                .checked_div(BASIS_POINTS_DIVISOR)
+                .ok_or(CustomError::ArithmeticOverflow)?
+        } else {
+            0
+        };
+        let owner_amount = owner_amount
+            .checked_sub(bounty)
+            .ok_or(CustomError::ArithmeticOverflow)?;
+
+        // Validate amounts
+        require!(owner_amount > 0, CustomError::InvalidWithdrawalAmount);
+        require!(house_amount > 0, CustomError::InvalidWithdrawalAmount);
+
+        let seeds = &[
+            b"merchant".as_ref(),
+            self.merchant.entity_name.as_bytes(),
+            self.merchant.owner.as_ref(),
+            &[self.merchant.merchant_bump],
+        ];
+
+        // Transfer the owner's share to the payout destination
+        self.transfer_spl_tokens(&self.destination_stablecoin_ata.to_account_info(), owner_amount, seeds)?;
+
+        // Transfer house's share
+        self.transfer_spl_tokens(&self.house_stablecoin_ata.to_account_info(), house_amount, seeds)?;
+
+        // Pay the cranker
+        if let Some(cranker_stablecoin_ata) = &self.cranker_stablecoin_ata {
+            if bounty > 0 {
+                self.transfer_spl_tokens(&cranker_stablecoin_ata.to_account_info(), bounty, seeds)?;
+            }
+        }
+
+        self.payout_schedule.next_payout_at = now
+            .checked_add(self.payout_schedule.interval)
+            .ok_or(CustomError::ArithmeticOverflow)?;
+
+        // Emit event
+        self.merchant_stablecoin_ata.reload()?;
+        let ctx = EventCpiContext::new(&self.event_authority, bumps.event_authority);
+        emit_cpi!(PayoutCranked {
+            header: EventHeader::token(
+                self.merchant.key(),
+                self.cranker.key(),
+                self.stablecoin_mint.key(),
+                self.merchant_stablecoin_ata.amount,
+            )?,
+            destination: self.payout_schedule.destination,
+            cranker: self.cranker.key(),
+            amount,
+            owner_amount,
+            house_amount,
+            bounty,
+            next_payout_at: self.payout_schedule.next_payout_at,
+        });
+
+        Ok(())
+    }
+
+    /// Helper function to reduce code duplication for SPL token transfers
+    fn transfer_spl_tokens(&self, to: &AccountInfo<'info>, amount: u64, seeds: &[&[u8]]) -> Result<()> {
+        anchor_spl::token_interface::transfer_checked(
+            CpiContext::new_with_signer(
+                self.token_program.to_account_info(),
+                anchor_spl::token_interface::TransferChecked {
+                    from: self.merchant_stablecoin_ata.to_account_info(),
+                    mint: self.stablecoin_mint.to_account_info(),
+                    to: to.clone(),
+                    authority: self.merchant.to_account_info(),
+                },
+                &[seeds],
+            ),
+            amount,
+            self.stablecoin_mint.decimals,
+        )
+    }
+}
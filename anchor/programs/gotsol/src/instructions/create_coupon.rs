@@ -0,0 +1,94 @@
+use anchor_lang::prelude::*;
+use crate::state::{Coupon, CouponDiscount, Global, Merchant, SponsorshipBudget};
+use crate::errors::*;
+use crate::event_cpi::EventCpiContext;
+use crate::events::*;
+
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(code_hash: [u8; 32])]
+pub struct CreateCoupon<'info> {
+
+    // our node's fee payer
+    #[account(mut)]
+    pub fee_payer: Option<Signer<'info>>,
+
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    #[account(
+        seeds = [b"merchant", merchant.entity_name.as_bytes(), owner.key().as_ref()], 
+        bump = merchant.merchant_bump)]
+    pub merchant: Box<Account<'info, Merchant>>,
+
+    // caps what our fee payer fronts for this merchant; required whenever fee_payer signs
+    #[account(mut,
+        seeds = [b"sponsorship_budget", merchant.key().as_ref()],
+        bump = sponsorship_budget.bump)]
+    pub sponsorship_budget: Option<Box<Account<'info, SponsorshipBudget>>>,
+
+    // required whenever fee_payer signs, so it can be checked against the approved fee payers
+    #[account(seeds = [b"global"], bump = global.bump)]
+    pub global: Option<Box<Account<'info, Global>>>,
+
+    #[account(
+        init,
+        payer = fee_payer.as_ref().unwrap_or(&owner),
+        seeds = [b"coupon", merchant.key().as_ref(), code_hash.as_ref()],
+        space = Coupon::LEN,
+        bump
+    )]
+    pub coupon: Box<Account<'info, Coupon>>,
+
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> CreateCoupon<'info> {
+    pub fn create_coupon(
+        &mut self,
+        code_hash: [u8; 32],
+        discount: CouponDiscount,
+        max_redemptions: u32,
+        expires_at: i64,
+        bumps: &CreateCouponBumps,
+    ) -> Result<()> {
+        // Only fee payers approved in the global config may sponsor
+        Global::require_approved_fee_payer(&self.global, self.fee_payer.as_ref().map(|fee_payer| fee_payer.key()))?;
+
+        let valid_discount = match discount {
+            CouponDiscount::Percent { basis_points } => basis_points > 0 && basis_points <= 10000,
+            CouponDiscount::Fixed { amount } => amount > 0,
+        };
+        require!(valid_discount && max_redemptions > 0, CustomError::InvalidCouponTerms);
+
+        let now = Clock::get()?.unix_timestamp;
+        require!(expires_at == 0 || expires_at > now, CustomError::InvalidExpiry);
+
+        self.coupon.set_inner(Coupon {
+            merchant: self.merchant.key(),
+            code_hash,
+            discount,
+            max_redemptions,
+            redemption_count: 0,
+            expires_at,
+            rent_payer: self.fee_payer.as_ref().map_or(self.owner.key(), |fee_payer| fee_payer.key()),
+            bump: bumps.coupon,
+        });
+
+        // Emit event
+        emit!(CouponCreated {
+            header: EventHeader::merchant(self.merchant.key(), self.owner.key())?,
+            coupon: self.coupon.key(),
+            discount,
+            max_redemptions,
+            expires_at,
+        });
+
+        // Count what our fee payer fronted against the merchant's budget
+        let rent = self.coupon.to_account_info().lamports();
+        let ctx = EventCpiContext::new(&self.event_authority, bumps.event_authority);
+        SponsorshipBudget::charge(&mut self.sponsorship_budget, self.fee_payer.as_ref().map(|fee_payer| fee_payer.key()), rent, &ctx)?;
+
+        Ok(())
+    }
+}
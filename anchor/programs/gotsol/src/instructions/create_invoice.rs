@@ -0,0 +1,90 @@
+use anchor_lang::prelude::*;
+use crate::state::{Global, Invoice, InvoiceStatus, Merchant, SponsorshipBudget};
+use crate::errors::*;
+use crate::event_cpi::EventCpiContext;
+use crate::events::*;
+
+use anchor_spl::token_interface::Mint;
+
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(invoice_id: u64, amount: u64)]
+pub struct CreateInvoice<'info> {
+
+    // our node's fee payer
+    #[account(mut)]
+    pub fee_payer: Option<Signer<'info>>,
+
+    #[account(mut,
+        constraint = amount > 0 @ CustomError::InvalidInvoiceAmount)]
+    pub owner: Signer<'info>,
+
+    #[account(
+        seeds = [b"merchant", merchant.entity_name.as_bytes(), owner.key().as_ref()], 
+        bump = merchant.merchant_bump)]
+    pub merchant: Box<Account<'info, Merchant>>,
+
+    // caps what our fee payer fronts for this merchant; required whenever fee_payer signs
+    #[account(mut,
+        seeds = [b"sponsorship_budget", merchant.key().as_ref()],
+        bump = sponsorship_budget.bump)]
+    pub sponsorship_budget: Option<Box<Account<'info, SponsorshipBudget>>>,
+
+    // required whenever fee_payer signs, so it can be checked against the approved fee payers
+    #[account(seeds = [b"global"], bump = global.bump)]
+    pub global: Option<Box<Account<'info, Global>>>,
+
+    pub stablecoin_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        init,
+        payer = fee_payer.as_ref().unwrap_or(&owner),
+        seeds = [b"invoice", merchant.key().as_ref(), invoice_id.to_le_bytes().as_ref()],
+        space = Invoice::LEN,
+        bump
+    )]
+    pub invoice: Box<Account<'info, Invoice>>,
+
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> CreateInvoice<'info> {
+    pub fn create_invoice(&mut self, invoice_id: u64, amount: u64, due_date: i64, memo_hash: [u8; 32], bumps: &CreateInvoiceBumps) -> Result<()> {
+        // Only fee payers approved in the global config may sponsor
+        Global::require_approved_fee_payer(&self.global, self.fee_payer.as_ref().map(|fee_payer| fee_payer.key()))?;
+
+        // An invoice that is already past due could never be paid
+        let now = Clock::get()?.unix_timestamp;
+        require!(due_date > now, CustomError::InvalidDueDate);
+
+        self.invoice.set_inner(Invoice {
+            merchant: self.merchant.key(),
+            invoice_id,
+            amount,
+            mint: self.stablecoin_mint.key(),
+            due_date,
+            memo_hash,
+            status: InvoiceStatus::Open,
+            payer: None,
+            paid_at: 0,
+            rent_payer: self.fee_payer.as_ref().map_or(self.owner.key(), |fee_payer| fee_payer.key()),
+            bump: bumps.invoice,
+        });
+
+        // Emit event
+        emit!(InvoiceCreated {
+            header: EventHeader::new(Some(self.merchant.key()), self.owner.key(), Some(self.stablecoin_mint.key()), None)?,
+            invoice: self.invoice.key(),
+            invoice_id,
+            amount,
+            due_date,
+        });
+
+        // Count what our fee payer fronted against the merchant's budget
+        let rent = self.invoice.to_account_info().lamports();
+        let ctx = EventCpiContext::new(&self.event_authority, bumps.event_authority);
+        SponsorshipBudget::charge(&mut self.sponsorship_budget, self.fee_payer.as_ref().map(|fee_payer| fee_payer.key()), rent, &ctx)?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,183 @@
+use anchor_lang::{
+    prelude::*,
+    system_program::{allocate, assign, transfer, Allocate, Assign, Transfer},
+};
+use crate::state::{Global, LoyaltyProgram, Merchant, SponsorshipBudget};
+use crate::errors::*;
+use crate::event_cpi::EventCpiContext;
+use crate::events::*;
+
+use anchor_spl::{
+    token_2022::{
+        initialize_mint2,
+        spl_token_2022::{extension::ExtensionType, state::Mint as Token2022Mint},
+        InitializeMint2, Token2022,
+    },
+    token_2022_extensions::{non_transferable_mint_initialize, NonTransferableMintInitialize},
+};
+
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(earn_basis_points: u64, redemption_basis_points: u64)]
+pub struct CreateLoyaltyProgram<'info> {
+
+    // our node's fee payer
+    #[account(mut)]
+    pub fee_payer: Option<Signer<'info>>,
+
+    #[account(mut,
+        constraint = earn_basis_points > 0 && redemption_basis_points > 0 @ CustomError::InvalidLoyaltyRate)]
+    pub owner: Signer<'info>,
+
+    #[account(
+        seeds = [b"merchant", merchant.entity_name.as_bytes(), owner.key().as_ref()], 
+        bump = merchant.merchant_bump)]
+    pub merchant: Box<Account<'info, Merchant>>,
+
+    // caps what our fee payer fronts for this merchant; required whenever fee_payer signs
+    #[account(mut,
+        seeds = [b"sponsorship_budget", merchant.key().as_ref()],
+        bump = sponsorship_budget.bump)]
+    pub sponsorship_budget: Option<Box<Account<'info, SponsorshipBudget>>>,
+
+    // required whenever fee_payer signs, so it can be checked against the approved fee payers
+    #[account(seeds = [b"global"], bump = global.bump)]
+    pub global: Option<Box<Account<'info, Global>>>,
+
+    #[account(
+        init,
+        payer = fee_payer.as_ref().unwrap_or(&owner),
+        seeds = [b"loyalty", merchant.key().as_ref()],
+        space = LoyaltyProgram::LEN,
+        bump
+    )]
+    pub loyalty_program: Box<Account<'info, LoyaltyProgram>>,
+
+    /// CHECK: created and initialized below as a Token-2022 mint; its size depends on the requested extensions
+    #[account(mut, seeds = [b"points_mint", merchant.key().as_ref()], bump)]
+    pub points_mint: AccountInfo<'info>,
+
+    pub points_token_program: Program<'info, Token2022>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> CreateLoyaltyProgram<'info> {
+    pub fn create_loyalty_program(
+        &mut self,
+        earn_basis_points: u64,
+        redemption_basis_points: u64,
+        decimals: u8,
+        non_transferable: bool,
+        bumps: &CreateLoyaltyProgramBumps,
+    ) -> Result<()> {
+        // Only fee payers approved in the global config may sponsor
+        Global::require_approved_fee_payer(&self.global, self.fee_payer.as_ref().map(|fee_payer| fee_payer.key()))?;
+
+        self.loyalty_program.set_inner(LoyaltyProgram {
+            merchant: self.merchant.key(),
+            points_mint: self.points_mint.key(),
+            earn_basis_points,
+            redemption_basis_points,
+            non_transferable,
+            bump: bumps.loyalty_program,
+            points_mint_bump: bumps.points_mint,
+        });
+
+        // Allocate the points mint, leaving room for the non-transferable extension if requested
+        let extensions: &[ExtensionType] = if non_transferable {
+            &[ExtensionType::NonTransferable]
+        } else {
+            &[]
+        };
+        let space = ExtensionType::try_calculate_account_len::<Token2022Mint>(extensions)?;
+        let lamports = Rent::get()?.minimum_balance(space);
+
+        let merchant_key = self.merchant.key();
+        let mint_seeds = &[
+            b"points_mint".as_ref(),
+            merchant_key.as_ref(),
+            &[bumps.points_mint],
+        ];
+        let payer = self.fee_payer.as_ref().unwrap_or(&self.owner);
+
+        // The address is predictable, so anyone may have sent it lamports already; create_account
+        // would fail on a funded account, so top it up to rent exemption and allocate/assign instead
+        let top_up = lamports.saturating_sub(self.points_mint.lamports());
+        if top_up > 0 {
+            transfer(
+                CpiContext::new(
+                    self.system_program.to_account_info(),
+                    Transfer {
+                        from: payer.to_account_info(),
+                        to: self.points_mint.to_account_info(),
+                    },
+                ),
+                top_up,
+            )?;
+        }
+
+        allocate(
+            CpiContext::new_with_signer(
+                self.system_program.to_account_info(),
+                Allocate {
+                    account_to_allocate: self.points_mint.to_account_info(),
+                },
+                &[mint_seeds],
+            ),
+            space as u64,
+        )?;
+
+        assign(
+            CpiContext::new_with_signer(
+                self.system_program.to_account_info(),
+                Assign {
+                    account_to_assign: self.points_mint.to_account_info(),
+                },
+                &[mint_seeds],
+            ),
+            &self.points_token_program.key(),
+        )?;
+
+        // Extensions must be initialized before the mint itself
+        if non_transferable {
+            non_transferable_mint_initialize(CpiContext::new(
+                self.points_token_program.to_account_info(),
+                NonTransferableMintInitialize {
+                    token_program_id: self.points_token_program.to_account_info(),
+                    mint: self.points_mint.to_account_info(),
+                },
+            ))?;
+        }
+
+        // The merchant PDA is the sole mint authority, so points can only be issued through payments
+        initialize_mint2(
+            CpiContext::new(
+                self.points_token_program.to_account_info(),
+                InitializeMint2 {
+                    mint: self.points_mint.to_account_info(),
+                },
+            ),
+            decimals,
+            &merchant_key,
+            None,
+        )?;
+
+        // Emit event
+        emit!(LoyaltyProgramCreated {
+            header: EventHeader::merchant(merchant_key, self.owner.key())?,
+            points_mint: self.points_mint.key(),
+            earn_basis_points,
+            redemption_basis_points,
+            non_transferable,
+        });
+
+        // Count what our fee payer fronted against the merchant's budget
+        let rent = self.loyalty_program.to_account_info().lamports()
+            .checked_add(top_up)
+            .ok_or(CustomError::ArithmeticOverflow)?;
+        let ctx = EventCpiContext::new(&self.event_authority, bumps.event_authority);
+        SponsorshipBudget::charge(&mut self.sponsorship_budget, self.fee_payer.as_ref().map(|fee_payer| fee_payer.key()), rent, &ctx)?;
+
+        Ok(())
+    }
+}
@@ -13,7 +13,7 @@ pub struct CreateMerchant<'info> {
     #[account(mut, constraint = !name.trim().is_empty() && name.len() <= 32 @ CustomError::InvalidMerchantName)]
     pub owner: Signer<'info>,
 
-    #[account(init, payer = owner, seeds = [b"merchant", name.as_str().as_bytes(), owner.key().as_ref()], space = Merchant::LEN, bump)]
+    #[account(init, payer = owner, seeds = [b"merchant", name.as_bytes(), owner.key().as_ref()], space = Merchant::LEN, bump)]
     pub merchant: Box<Account<'info, Merchant>>,
 
     #[account(mut, seeds = [b"vault", merchant.key().as_ref()], bump)]
@@ -0,0 +1,94 @@
+use anchor_lang::prelude::*;
+use crate::state::{Global, MAX_SKU_LEN, Merchant, MerchantManager, Product, SponsorshipBudget};
+use crate::errors::*;
+use crate::event_cpi::EventCpiContext;
+use crate::events::*;
+
+use anchor_spl::token_interface::Mint;
+
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(sku: String)]
+pub struct CreateProduct<'info> {
+
+    // our node's fee payer
+    #[account(mut)]
+    pub fee_payer: Option<Signer<'info>>,
+
+    // the merchant owner, or a manager they have added
+    #[account(mut,
+        constraint = authority.key() == merchant.owner || manager.is_some() @ CustomError::UnauthorizedProductManager,
+        constraint = !sku.trim().is_empty() && sku.len() <= MAX_SKU_LEN @ CustomError::InvalidSku)]
+    pub authority: Signer<'info>,
+
+    pub merchant: Box<Account<'info, Merchant>>,
+
+    // caps what our fee payer fronts for this merchant; required whenever fee_payer signs
+    #[account(mut,
+        seeds = [b"sponsorship_budget", merchant.key().as_ref()],
+        bump = sponsorship_budget.bump)]
+    pub sponsorship_budget: Option<Box<Account<'info, SponsorshipBudget>>>,
+
+    // required whenever fee_payer signs, so it can be checked against the approved fee payers
+    #[account(seeds = [b"global"], bump = global.bump)]
+    pub global: Option<Box<Account<'info, Global>>>,
+
+    #[account(
+        seeds = [b"manager", merchant.key().as_ref(), authority.key().as_ref()],
+        bump = manager.bump,
+        has_one = merchant)]
+    pub manager: Option<Box<Account<'info, MerchantManager>>>,
+
+    pub stablecoin_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        init,
+        payer = fee_payer.as_ref().unwrap_or(&authority),
+        seeds = [b"product", merchant.key().as_ref(), sku.as_bytes()],
+        space = Product::LEN,
+        bump
+    )]
+    pub product: Box<Account<'info, Product>>,
+
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> CreateProduct<'info> {
+    pub fn create_product(&mut self, sku: String, price: u64, stock: u64, tax_basis_points: u16, bumps: &CreateProductBumps) -> Result<()> {
+        // Only fee payers approved in the global config may sponsor
+        Global::require_approved_fee_payer(&self.global, self.fee_payer.as_ref().map(|fee_payer| fee_payer.key()))?;
+
+        require!(price > 0, CustomError::InvalidProductPrice);
+        require!(tax_basis_points <= 10000, CustomError::InvalidTaxRate);
+
+        self.product.set_inner(Product {
+            merchant: self.merchant.key(),
+            sku: sku.clone(),
+            price,
+            mint: self.stablecoin_mint.key(),
+            stock,
+            tax_basis_points,
+            active: true,
+            rent_payer: self.fee_payer.as_ref().map_or(self.authority.key(), |fee_payer| fee_payer.key()),
+            bump: bumps.product,
+        });
+
+        // Emit event
+        emit!(ProductUpdated {
+            header: EventHeader::merchant(self.merchant.key(), self.authority.key())?,
+            product: self.product.key(),
+            sku,
+            price,
+            stock,
+            tax_basis_points,
+            active: true,
+        });
+
+        // Count what our fee payer fronted against the merchant's budget
+        let rent = self.product.to_account_info().lamports();
+        let ctx = EventCpiContext::new(&self.event_authority, bumps.event_authority);
+        SponsorshipBudget::charge(&mut self.sponsorship_budget, self.fee_payer.as_ref().map(|fee_payer| fee_payer.key()), rent, &ctx)?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,104 @@
+use anchor_lang::prelude::*;
+use crate::state::{Merchant, Subscription};
+use crate::errors::*;
+use crate::events::*;
+
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token_interface::{approve, Approve, Mint, TokenAccount, TokenInterface},
+};
+
+#[derive(Accounts)]
+#[instruction(amount: u64, interval: i64, max_charges: u64)]
+pub struct CreateSubscription<'info> {
+    #[account(mut,
+        constraint = amount > 0 @ CustomError::ZeroAmountPayment,
+        constraint = interval > 0 && max_charges > 0 @ CustomError::InvalidSubscriptionTerms)]
+    pub customer: Signer<'info>,
+
+    pub merchant: Box<Account<'info, Merchant>>,
+
+    pub stablecoin_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(mut,
+        associated_token::mint = stablecoin_mint,
+        associated_token::authority = customer)]
+    pub customer_stablecoin_ata: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        init,
+        payer = customer,
+        seeds = [b"subscription", merchant.key().as_ref(), customer.key().as_ref()],
+        space = Subscription::LEN,
+        bump
+    )]
+    pub subscription: Box<Account<'info, Subscription>>,
+
+    /// CHECK: program-owned delegate shared by all of a customer's subscriptions, so a new
+    /// subscription adds to the approval instead of replacing another merchant's
+    #[account(seeds = [b"subscription_delegate"], bump)]
+    pub subscription_delegate: AccountInfo<'info>,
+
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> CreateSubscription<'info> {
+    pub fn create_subscription(&mut self, amount: u64, interval: i64, max_charges: u64, bumps: &CreateSubscriptionBumps) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+
+        // Cap what this subscription can pull at `max_charges` periods
+        let allowance = amount
+            .checked_mul(max_charges)
+            .ok_or(CustomError::ArithmeticOverflow)?;
+
+        self.subscription.set_inner(Subscription {
+            merchant: self.merchant.key(),
+            customer: self.customer.key(),
+            mint: self.stablecoin_mint.key(),
+            amount,
+            interval,
+            next_charge_at: now,
+            charges_made: 0,
+            remaining_allowance: allowance,
+            retry_after: 0,
+            bump: bumps.subscription,
+        });
+
+        // Add the allowance to whatever the shared delegate can already pull for the
+        // customer's other subscriptions; each charge is still bounded by its own subscription
+        let delegated = if self.customer_stablecoin_ata.delegate == Some(self.subscription_delegate.key()).into() {
+            self.customer_stablecoin_ata.delegated_amount
+        } else {
+            0
+        };
+        let approval = delegated
+            .checked_add(allowance)
+            .ok_or(CustomError::ArithmeticOverflow)?;
+
+        approve(
+            CpiContext::new(
+                self.token_program.to_account_info(),
+                Approve {
+                    to: self.customer_stablecoin_ata.to_account_info(),
+                    delegate: self.subscription_delegate.to_account_info(),
+                    authority: self.customer.to_account_info(),
+                },
+            ),
+            approval,
+        )?;
+
+        // Emit event
+        emit!(SubscriptionCreated {
+            header: EventHeader::new(Some(self.merchant.key()), self.customer.key(), Some(self.stablecoin_mint.key()), None)?,
+            customer: self.customer.key(),
+            subscription: self.subscription.key(),
+            amount,
+            interval,
+            allowance,
+        });
+
+        Ok(())
+    }
+}
@@ -0,0 +1,62 @@
+use anchor_lang::prelude::*;
+use crate::state::{Global, Merchant, SponsorshipBudget};
+use crate::event_cpi::EventCpiContext;
+
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct CreateTipPool<'info> {
+
+    // our node's fee payer
+    #[account(mut)]
+    pub fee_payer: Option<Signer<'info>>,
+
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    #[account(
+        seeds = [b"merchant", merchant.entity_name.as_bytes(), owner.key().as_ref()], 
+        bump = merchant.merchant_bump)]
+    pub merchant: Box<Account<'info, Merchant>>,
+
+    // caps what our fee payer fronts for this merchant; required whenever fee_payer signs
+    #[account(mut,
+        seeds = [b"sponsorship_budget", merchant.key().as_ref()],
+        bump = sponsorship_budget.bump)]
+    pub sponsorship_budget: Option<Box<Account<'info, SponsorshipBudget>>>,
+
+    // required whenever fee_payer signs, so it can be checked against the approved fee payers
+    #[account(seeds = [b"global"], bump = global.bump)]
+    pub global: Option<Box<Account<'info, Global>>>,
+
+    pub stablecoin_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    // Tips are held apart from the merchant's ATA so they never enter the withdraw_spl fee split
+    #[account(
+        init,
+        payer = fee_payer.as_ref().unwrap_or(&owner),
+        seeds = [b"tip_pool", merchant.key().as_ref(), stablecoin_mint.key().as_ref()],
+        bump,
+        token::mint = stablecoin_mint,
+        token::authority = merchant,
+        token::token_program = token_program)]
+    pub tip_pool: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> CreateTipPool<'info> {
+    pub fn create_tip_pool(&mut self, bumps: &CreateTipPoolBumps) -> Result<()> {
+        // Only fee payers approved in the global config may sponsor
+        Global::require_approved_fee_payer(&self.global, self.fee_payer.as_ref().map(|fee_payer| fee_payer.key()))?;
+
+        // Count what our fee payer fronted against the merchant's budget
+        let rent = self.tip_pool.to_account_info().lamports();
+        let ctx = EventCpiContext::new(&self.event_authority, bumps.event_authority);
+        SponsorshipBudget::charge(&mut self.sponsorship_budget, self.fee_payer.as_ref().map(|fee_payer| fee_payer.key()), rent, &ctx)?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,47 @@
+use anchor_lang::prelude::*;
+use crate::state::{Merchant, MerchantManager, Product};
+use crate::errors::*;
+use crate::events::*;
+
+#[derive(Accounts)]
+pub struct DeleteProduct<'info> {
+    // the merchant owner, or a manager they have added
+    #[account(
+        constraint = authority.key() == merchant.owner || manager.is_some() @ CustomError::UnauthorizedProductManager)]
+    pub authority: Signer<'info>,
+
+    pub merchant: Box<Account<'info, Merchant>>,
+
+    #[account(
+        seeds = [b"manager", merchant.key().as_ref(), authority.key().as_ref()],
+        bump = manager.bump,
+        has_one = merchant)]
+    pub manager: Option<Box<Account<'info, MerchantManager>>>,
+
+    /// CHECK: whoever funded the product (our fee payer, the owner or the manager who created it) gets the rent back
+    #[account(mut)]
+    pub rent_payer: AccountInfo<'info>,
+
+    #[account(mut,
+        seeds = [b"product", merchant.key().as_ref(), product.sku.as_bytes()],
+        bump = product.bump,
+        has_one = merchant,
+        has_one = rent_payer @ CustomError::InvalidRentPayer,
+        close = rent_payer)]
+    pub product: Box<Account<'info, Product>>,
+
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> DeleteProduct<'info> {
+    pub fn delete_product(&mut self) -> Result<()> {
+        // Emit event
+        emit!(ProductDeleted {
+            header: EventHeader::merchant(self.merchant.key(), self.authority.key())?,
+            product: self.product.key(),
+            sku: self.product.sku.clone(),
+        });
+
+        Ok(())
+    }
+}
@@ -0,0 +1,91 @@
+use anchor_lang::prelude::*;
+use crate::state::{Merchant, WithdrawalLimit};
+use crate::errors::*;
+use crate::event_cpi::EventCpiContext;
+use crate::events::*;
+
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct DistributeTips<'info> {
+    pub owner: Signer<'info>,
+
+    #[account(
+        seeds = [b"merchant", merchant.entity_name.as_bytes(), owner.key().as_ref()], 
+        bump = merchant.merchant_bump)]
+    pub merchant: Box<Account<'info, Merchant>>,
+
+    pub stablecoin_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(mut,
+        seeds = [b"tip_pool", merchant.key().as_ref(), stablecoin_mint.key().as_ref()],
+        bump,
+        token::mint = stablecoin_mint,
+        token::authority = merchant)]
+    pub tip_pool: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// CHECK: the merchant's withdrawal limit for this mint; enforced only once the owner has created it
+    #[account(mut, seeds = [b"withdrawal_limit", merchant.key().as_ref(), stablecoin_mint.key().as_ref()], bump)]
+    pub withdrawal_limit: AccountInfo<'info>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+impl<'info> DistributeTips<'info> {
+    /// Pays out `amounts[i]` from the tip pool to the employee token account at `remaining_accounts[i]`
+    pub fn distribute_tips(&mut self, amounts: Vec<u64>, employee_atas: &'info [AccountInfo<'info>], bumps: &DistributeTipsBumps) -> Result<()> {
+        require!(!amounts.is_empty() && amounts.len() == employee_atas.len(), CustomError::TipDistributionMismatch);
+
+        let total = amounts
+            .iter()
+            .try_fold(0u64, |acc, amount| acc.checked_add(*amount))
+            .ok_or(CustomError::ArithmeticOverflow)?;
+        require!(self.tip_pool.amount >= total, CustomError::InsufficientFunds);
+
+        // The owner picks the recipients, so a stolen owner key could drain the pool through here
+        WithdrawalLimit::enforce(&self.withdrawal_limit, total)?;
+
+        let owner_key = self.owner.key();
+        let seeds = &[
+            b"merchant".as_ref(),
+            self.merchant.entity_name.as_bytes(),
+            owner_key.as_ref(),
+            &[self.merchant.merchant_bump],
+        ];
+
+        for (employee_ata, amount) in employee_atas.iter().zip(amounts) {
+            require!(amount > 0, CustomError::InvalidWithdrawalAmount);
+
+            // Each recipient must be a token account for the pool's mint
+            let employee_token_account = InterfaceAccount::<TokenAccount>::try_from(employee_ata)?;
+            require_keys_eq!(employee_token_account.mint, self.stablecoin_mint.key(), CustomError::InvalidTipRecipient);
+
+            anchor_spl::token_interface::transfer_checked(
+                CpiContext::new_with_signer(
+                    self.token_program.to_account_info(),
+                    anchor_spl::token_interface::TransferChecked {
+                        from: self.tip_pool.to_account_info(),
+                        mint: self.stablecoin_mint.to_account_info(),
+                        to: employee_ata.clone(),
+                        authority: self.merchant.to_account_info(),
+                    },
+                    &[seeds],
+                ),
+                amount,
+                self.stablecoin_mint.decimals,
+            )?;
+
+            // Emit event
+            self.tip_pool.reload()?;
+            let ctx = EventCpiContext::new(&self.event_authority, bumps.event_authority);
+            emit_cpi!(TipsDistributed {
+                header: EventHeader::token(self.merchant.key(), owner_key, self.stablecoin_mint.key(), self.tip_pool.amount)?,
+                employee: employee_token_account.owner,
+                amount,
+            });
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,43 @@
+use anchor_lang::prelude::*;
+use crate::state::Global;
+use crate::constants::*;
+use crate::errors::*;
+use crate::events::*;
+
+use std::str::FromStr;
+
+#[derive(Accounts)]
+pub struct InitializeGlobal<'info> {
+    #[account(mut, constraint = auth.key() == Pubkey::from_str(AUTH_2).unwrap() || auth.key() == Pubkey::from_str(AUTH_3).unwrap() @ CustomError::UnauthorizedConfigChange)]
+    pub auth: Signer<'info>,
+
+    #[account(
+        init,
+        payer = auth,
+        seeds = [b"global"],
+        space = Global::LEN,
+        bump
+    )]
+    pub global: Box<Account<'info, Global>>,
+
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> InitializeGlobal<'info> {
+    pub fn initialize_global(&mut self, swap_router: Pubkey, bumps: &InitializeGlobalBumps) -> Result<()> {
+        self.global.set_inner(Global {
+            swap_router,
+            rent_recoup_rate: 0,
+            fee_payers: Vec::new(),
+            bump: bumps.global,
+        });
+
+        // Emit event
+        emit!(SwapRouterUpdated {
+            header: EventHeader::global(self.auth.key())?,
+            swap_router,
+        });
+
+        Ok(())
+    }
+}
@@ -6,6 +6,49 @@ pub mod refund_sol;
 pub mod set_merchant_status;
 pub mod close_merchant;
 pub mod close_refund;
+pub mod close_legacy_refund;
+pub mod create_invoice;
+pub mod pay_invoice;
+pub mod cancel_invoice;
+pub mod close_invoice;
+pub mod create_tip_pool;
+pub mod pay_spl;
+pub mod distribute_tips;
+pub mod authorize_payment;
+pub mod capture_payment;
+pub mod void_authorization;
+pub mod create_subscription;
+pub mod charge_subscription;
+pub mod cancel_subscription;
+pub mod purchase_gift_card;
+pub mod close_gift_card;
+pub mod create_loyalty_program;
+pub mod update_loyalty_program;
+pub mod create_coupon;
+pub mod close_coupon;
+pub mod add_manager;
+pub mod remove_manager;
+pub mod create_product;
+pub mod update_product;
+pub mod delete_product;
+pub mod pay_order;
+pub mod refund_order_items;
+pub mod close_order;
+pub mod pay_sol_usd;
+pub mod close_payment;
+pub mod initialize_global;
+pub mod set_swap_router;
+pub mod withdraw_sol_as_spl;
+pub mod withdraw_many;
+pub mod set_payout_schedule;
+pub mod cancel_payout_schedule;
+pub mod crank_payout;
+pub mod set_sol_withdrawal_limit;
+pub mod set_spl_withdrawal_limit;
+pub mod set_rent_recoup_rate;
+pub mod set_sponsorship_budget;
+pub mod top_up_sponsorship_budget;
+pub mod set_fee_payer_approval;
 
 pub use create_merchant::*;
 pub use withdraw_spl::*;
@@ -15,4 +58,47 @@ pub use refund_sol::*;
 pub use set_merchant_status::*;
 pub use close_merchant::*;
 pub use close_refund::*;
+pub use close_legacy_refund::*;
+pub use create_invoice::*;
+pub use pay_invoice::*;
+pub use cancel_invoice::*;
+pub use close_invoice::*;
+pub use create_tip_pool::*;
+pub use pay_spl::*;
+pub use distribute_tips::*;
+pub use authorize_payment::*;
+pub use capture_payment::*;
+pub use void_authorization::*;
+pub use create_subscription::*;
+pub use charge_subscription::*;
+pub use cancel_subscription::*;
+pub use purchase_gift_card::*;
+pub use close_gift_card::*;
+pub use create_loyalty_program::*;
+pub use update_loyalty_program::*;
+pub use create_coupon::*;
+pub use close_coupon::*;
+pub use add_manager::*;
+pub use remove_manager::*;
+pub use create_product::*;
+pub use update_product::*;
+pub use delete_product::*;
+pub use pay_order::*;
+pub use refund_order_items::*;
+pub use close_order::*;
+pub use pay_sol_usd::*;
+pub use close_payment::*;
+pub use initialize_global::*;
+pub use set_swap_router::*;
+pub use withdraw_sol_as_spl::*;
+pub use withdraw_many::*;
+pub use set_payout_schedule::*;
+pub use cancel_payout_schedule::*;
+pub use crank_payout::*;
+pub use set_sol_withdrawal_limit::*;
+pub use set_spl_withdrawal_limit::*;
+pub use set_rent_recoup_rate::*;
+pub use set_sponsorship_budget::*;
+pub use top_up_sponsorship_budget::*;
+pub use set_fee_payer_approval::*;
 
@@ -0,0 +1,137 @@
+use anchor_lang::prelude::*;
+use crate::state::{Coupon, Invoice, InvoiceStatus, LoyaltyProgram, Merchant};
+use crate::errors::*;
+use crate::event_cpi::EventCpiContext;
+use crate::events::*;
+use crate::loyalty::{award_points, optional_loyalty_accounts};
+use crate::redemptions::apply_coupon;
+
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token_2022::Token2022,
+    token_interface::{Mint, TokenAccount, TokenInterface},
+};
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct PayInvoice<'info> {
+    #[account(mut)]
+    pub customer: Signer<'info>,
+
+    pub merchant: Box<Account<'info, Merchant>>,
+
+    #[account(mut,
+        seeds = [b"invoice", merchant.key().as_ref(), invoice.invoice_id.to_le_bytes().as_ref()],
+        bump = invoice.bump,
+        has_one = merchant,
+        constraint = invoice.mint == stablecoin_mint.key() @ CustomError::InvalidInvoiceMint)]
+    pub invoice: Box<Account<'info, Invoice>>,
+
+    pub stablecoin_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(mut,
+        associated_token::mint = stablecoin_mint,
+        associated_token::authority = customer)]
+    pub customer_stablecoin_ata: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(init_if_needed,
+        payer = customer,
+        associated_token::mint = stablecoin_mint,
+        associated_token::authority = merchant)]
+    pub merchant_stablecoin_ata: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    // only required when a coupon is applied
+    #[account(mut,
+        seeds = [b"coupon", merchant.key().as_ref(), coupon.code_hash.as_ref()],
+        bump = coupon.bump,
+        has_one = merchant)]
+    pub coupon: Option<Box<Account<'info, Coupon>>>,
+
+    // only required when the merchant runs a loyalty program
+    #[account(
+        seeds = [b"loyalty", merchant.key().as_ref()],
+        bump = loyalty_program.bump,
+        has_one = merchant)]
+    pub loyalty_program: Option<Box<Account<'info, LoyaltyProgram>>>,
+
+    #[account(mut)]
+    pub points_mint: Option<Box<InterfaceAccount<'info, Mint>>>,
+
+    #[account(mut)]
+    pub customer_points_ata: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+
+    pub points_token_program: Option<Program<'info, Token2022>>,
+
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> PayInvoice<'info> {
+    pub fn pay_invoice(&mut self, amount: u64, coupon_code: Option<Vec<u8>>, bumps: &PayInvoiceBumps) -> Result<()> {
+        // An invoice can only ever be settled once, for exactly its face amount, before it expires
+        require!(self.invoice.status == InvoiceStatus::Open, CustomError::InvoiceNotOpen);
+        require!(amount == self.invoice.amount, CustomError::InvoiceAmountMismatch);
+
+        let now = Clock::get()?.unix_timestamp;
+        require!(now <= self.invoice.due_date, CustomError::InvoiceExpired);
+
+        // A coupon still settles the whole invoice, the customer just pays less of it
+        let merchant = self.merchant.key();
+        let customer = self.customer.key();
+        let ctx = EventCpiContext::new(&self.event_authority, bumps.event_authority);
+        let discount = apply_coupon(&mut self.coupon, coupon_code.as_deref(), amount, merchant, customer, &ctx)?;
+        let charged = amount
+            .checked_sub(discount)
+            .ok_or(CustomError::ArithmeticOverflow)?;
+        require!(self.customer_stablecoin_ata.amount >= charged, CustomError::InsufficientFunds);
+
+        // Transfer the invoice amount from the customer to the merchant
+        if charged > 0 {
+            anchor_spl::token_interface::transfer_checked(
+                CpiContext::new(
+                    self.token_program.to_account_info(),
+                    anchor_spl::token_interface::TransferChecked {
+                        from: self.customer_stablecoin_ata.to_account_info(),
+                        mint: self.stablecoin_mint.to_account_info(),
+                        to: self.merchant_stablecoin_ata.to_account_info(),
+                        authority: self.customer.to_account_info(),
+                    },
+                ),
+                charged,
+                self.stablecoin_mint.decimals,
+            )?;
+        }
+
+        self.invoice.status = InvoiceStatus::Paid;
+        self.invoice.payer = Some(self.customer.key());
+        self.invoice.paid_at = now;
+
+        let accounts = optional_loyalty_accounts(
+            &self.loyalty_program,
+            &self.points_mint,
+            &self.customer_points_ata,
+            &self.points_token_program,
+            customer,
+        )?;
+        award_points(accounts, &self.merchant, customer, charged, &ctx)?;
+
+        // Emit event
+        self.merchant_stablecoin_ata.reload()?;
+        emit_cpi!(InvoicePaid {
+            header: EventHeader::token(
+                self.merchant.key(),
+                self.customer.key(),
+                self.stablecoin_mint.key(),
+                self.merchant_stablecoin_ata.amount,
+            )?,
+            invoice: self.invoice.key(),
+            invoice_id: self.invoice.invoice_id,
+            amount: charged,
+            payer: self.customer.key(),
+            discount,
+        });
+
+        Ok(())
+    }
+}
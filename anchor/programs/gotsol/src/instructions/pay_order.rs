@@ -0,0 +1,256 @@
+use anchor_lang::prelude::*;
+use crate::state::{Coupon, GiftCard, LoyaltyProgram, Merchant, Order, OrderLineItem, Product, MAX_ORDER_LINE_ITEMS};
+use crate::errors::*;
+use crate::event_cpi::EventCpiContext;
+use crate::events::*;
+use crate::loyalty::{award_points, optional_loyalty_accounts};
+use crate::redemptions::{apply_coupon, redeem_gift_card};
+
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token_2022::Token2022,
+    token_interface::{Mint, TokenAccount, TokenInterface},
+};
+
+const BASIS_POINTS_DIVISOR: u64 = 10000;
+
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(order_id: u64)]
+pub struct PayOrder<'info> {
+    #[account(mut)]
+    pub customer: Signer<'info>,
+
+    pub merchant: Box<Account<'info, Merchant>>,
+
+    pub stablecoin_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(mut,
+        associated_token::mint = stablecoin_mint,
+        associated_token::authority = customer)]
+    pub customer_stablecoin_ata: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(init_if_needed,
+        payer = customer,
+        associated_token::mint = stablecoin_mint,
+        associated_token::authority = merchant)]
+    pub merchant_stablecoin_ata: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    // only required when the order carries a tip
+    #[account(mut,
+        seeds = [b"tip_pool", merchant.key().as_ref(), stablecoin_mint.key().as_ref()],
+        bump,
+        token::mint = stablecoin_mint,
+        token::authority = merchant)]
+    pub tip_pool: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+
+    // only required when a coupon is applied
+    #[account(mut,
+        seeds = [b"coupon", merchant.key().as_ref(), coupon.code_hash.as_ref()],
+        bump = coupon.bump,
+        has_one = merchant)]
+    pub coupon: Option<Box<Account<'info, Coupon>>>,
+
+    // only required when paying with a gift card
+    #[account(mut,
+        seeds = [b"gift_card", merchant.key().as_ref(), gift_card.card_id.to_le_bytes().as_ref()],
+        bump = gift_card.bump,
+        has_one = merchant,
+        constraint = gift_card.mint == stablecoin_mint.key() @ CustomError::MintMismatch)]
+    pub gift_card: Option<Box<Account<'info, GiftCard>>>,
+
+    // only required when redeeming a gift card by its code; the code is this keypair's secret
+    pub gift_card_code: Option<Signer<'info>>,
+
+    // only required when the merchant runs a loyalty program
+    #[account(
+        seeds = [b"loyalty", merchant.key().as_ref()],
+        bump = loyalty_program.bump,
+        has_one = merchant)]
+    pub loyalty_program: Option<Box<Account<'info, LoyaltyProgram>>>,
+
+    #[account(mut)]
+    pub points_mint: Option<Box<InterfaceAccount<'info, Mint>>>,
+
+    #[account(mut)]
+    pub customer_points_ata: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+
+    pub points_token_program: Option<Program<'info, Token2022>>,
+
+    #[account(
+        init,
+        payer = customer,
+        seeds = [b"order", merchant.key().as_ref(), customer.key().as_ref(), order_id.to_le_bytes().as_ref()],
+        space = Order::LEN,
+        bump
+    )]
+    pub order: Box<Account<'info, Order>>,
+
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> PayOrder<'info> {
+    /// Charges for `quantities[i]` units of the product at `remaining_accounts[i]`, decrementing its stock
+    /// and recording each line on the order receipt
+    #[allow(clippy::too_many_arguments)]
+    pub fn pay_order(
+        &mut self,
+        order_id: u64,
+        quantities: Vec<u64>,
+        expected_total: u64,
+        tip: u64,
+        coupon_code: Option<Vec<u8>>,
+        products: &'info [AccountInfo<'info>],
+        bumps: &PayOrderBumps,
+    ) -> Result<()> {
+        require!(
+            !quantities.is_empty() && quantities.len() <= MAX_ORDER_LINE_ITEMS && quantities.len() == products.len(),
+            CustomError::InvalidOrder
+        );
+
+        let mut line_items = Vec::with_capacity(quantities.len());
+        let mut subtotal: u64 = 0;
+        let mut tax: u64 = 0;
+        for (product_info, quantity) in products.iter().zip(quantities) {
+            require!(quantity > 0, CustomError::InvalidOrder);
+            require!(product_info.is_writable, CustomError::InvalidOrder);
+
+            let mut product = Account::<Product>::try_from(product_info)?;
+            require_keys_eq!(product.merchant, self.merchant.key(), CustomError::InvalidProduct);
+            require_keys_eq!(product.mint, self.stablecoin_mint.key(), CustomError::MintMismatch);
+            require!(product.active, CustomError::ProductInactive);
+
+            product.stock = product.stock
+                .checked_sub(quantity)
+                .ok_or(CustomError::OutOfStock)?;
+
+            let line_subtotal = product.price
+                .checked_mul(quantity)
+                .ok_or(CustomError::ArithmeticOverflow)?;
+            let line_tax = line_subtotal
+                .checked_mul(product.tax_basis_points as u64)
+                .ok_or(CustomError::ArithmeticOverflow)?
+                .checked_div(BASIS_POINTS_DIVISOR)
+                .ok_or(CustomError::ArithmeticOverflow)?;
+
+            subtotal = subtotal
+                .checked_add(line_subtotal)
+                .ok_or(CustomError::ArithmeticOverflow)?;
+            tax = tax
+                .checked_add(line_tax)
+                .ok_or(CustomError::ArithmeticOverflow)?;
+
+            line_items.push(OrderLineItem {
+                product: product.key(),
+                quantity,
+                unit_price: product.price,
+                tax: line_tax,
+                refunded_quantity: 0,
+            });
+
+            // Persist now so a product listed twice is checked against its already-reduced stock
+            product.exit(&crate::ID)?;
+        }
+
+        // The client's total must match what the catalog says the order costs
+        let total = subtotal.checked_add(tax).ok_or(CustomError::ArithmeticOverflow)?;
+        require!(total == expected_total, CustomError::OrderTotalMismatch);
+
+        // Apply the coupon to the order total; a gift card then covers what it can of the rest,
+        // the merchant having been paid for it at purchase
+        let merchant = self.merchant.key();
+        let customer = self.customer.key();
+        let ctx = EventCpiContext::new(&self.event_authority, bumps.event_authority);
+        let discount = apply_coupon(&mut self.coupon, coupon_code.as_deref(), total, merchant, customer, &ctx)?;
+        let charged = total
+            .checked_sub(discount)
+            .ok_or(CustomError::ArithmeticOverflow)?;
+
+        let code_signer = self.gift_card_code.as_ref().map(|code| code.key());
+        let gift_card_amount = redeem_gift_card(&mut self.gift_card, code_signer, charged, merchant, customer, &ctx)?;
+        let from_wallet = charged
+            .checked_sub(gift_card_amount)
+            .ok_or(CustomError::ArithmeticOverflow)?;
+        require!(
+            self.customer_stablecoin_ata.amount >= from_wallet.checked_add(tip).ok_or(CustomError::ArithmeticOverflow)?,
+            CustomError::InsufficientFunds
+        );
+
+        // Transfer the rest of the order total to the merchant
+        if from_wallet > 0 {
+            self.transfer_from_customer(&self.merchant_stablecoin_ata.to_account_info(), from_wallet)?;
+        }
+
+        // Transfer the tip into the merchant's tip pool, kept out of sales revenue
+        if tip > 0 {
+            let tip_pool = self.tip_pool.as_ref().ok_or(CustomError::TipPoolRequired)?;
+            self.transfer_from_customer(&tip_pool.to_account_info(), tip)?;
+        }
+
+        // Points are earned on what left the wallet, not on the tip or the gift card's share
+        let accounts = optional_loyalty_accounts(
+            &self.loyalty_program,
+            &self.points_mint,
+            &self.customer_points_ata,
+            &self.points_token_program,
+            customer,
+        )?;
+        award_points(accounts, &self.merchant, customer, from_wallet, &ctx)?;
+
+        let line_count = line_items.len() as u32;
+        self.order.set_inner(Order {
+            merchant: self.merchant.key(),
+            customer: self.customer.key(),
+            mint: self.stablecoin_mint.key(),
+            order_id,
+            line_items,
+            subtotal,
+            tax,
+            discount,
+            tip,
+            refunded_amount: 0,
+            created_at: Clock::get()?.unix_timestamp,
+            bump: bumps.order,
+        });
+
+        // Emit event
+        self.merchant_stablecoin_ata.reload()?;
+        emit_cpi!(OrderPaid {
+            header: EventHeader::token(
+                self.merchant.key(),
+                self.customer.key(),
+                self.stablecoin_mint.key(),
+                self.merchant_stablecoin_ata.amount,
+            )?,
+            order: self.order.key(),
+            customer: self.customer.key(),
+            subtotal,
+            tax,
+            tip,
+            line_items: line_count,
+            gift_card_amount,
+            discount,
+        });
+
+        Ok(())
+    }
+
+    /// Helper function to reduce code duplication for customer transfers
+    fn transfer_from_customer(&self, to: &AccountInfo<'info>, amount: u64) -> Result<()> {
+        anchor_spl::token_interface::transfer_checked(
+            CpiContext::new(
+                self.token_program.to_account_info(),
+                anchor_spl::token_interface::TransferChecked {
+                    from: self.customer_stablecoin_ata.to_account_info(),
+                    mint: self.stablecoin_mint.to_account_info(),
+                    to: to.clone(),
+                    authority: self.customer.to_account_info(),
+                },
+            ),
+            amount,
+            self.stablecoin_mint.decimals,
+        )
+    }
+}
@@ -0,0 +1,132 @@
+use anchor_lang::{
+    prelude::*,
+    system_program::{transfer, Transfer},
+};
+use crate::state::{LoyaltyProgram, Merchant, Payment};
+use crate::constants::*;
+use crate::errors::*;
+use crate::event_cpi::EventCpiContext;
+use crate::events::*;
+use crate::loyalty::{award_points, optional_loyalty_accounts};
+use crate::oracle::{load_price, usd_to_token_amount};
+
+use anchor_spl::{
+    token_2022::Token2022,
+    token_interface::{Mint, TokenAccount},
+};
+
+const MAX_PRICE_AGE_SECONDS: i64 = 60;
+const MAX_PRICE_CONF_BASIS_POINTS: u64 = 100; // 1%
+const SOL_DECIMALS: u8 = 9;
+
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(payment_id: u64, usd_amount: u64)]
+pub struct PaySolUsd<'info> {
+    #[account(mut,
+        constraint = usd_amount > 0 @ CustomError::ZeroAmountPayment)]
+    pub customer: Signer<'info>,
+
+    pub merchant: Box<Account<'info, Merchant>>,
+
+    #[account(mut, 
+        seeds = [b"vault", merchant.key().as_ref()], 
+        bump = merchant.vault_bump)]
+    pub vault: SystemAccount<'info>,
+
+    /// CHECK: Pyth SOL/USD PriceUpdateV2 account; owner, layout and feed id are verified in oracle::load_price
+    pub price_update: AccountInfo<'info>,
+
+    #[account(
+        init,
+        payer = customer,
+        seeds = [b"payment", merchant.key().as_ref(), customer.key().as_ref(), payment_id.to_le_bytes().as_ref()],
+        space = Payment::LEN,
+        bump
+    )]
+    pub payment: Box<Account<'info, Payment>>,
+
+    // only required when the merchant runs a loyalty program
+    #[account(
+        seeds = [b"loyalty", merchant.key().as_ref()],
+        bump = loyalty_program.bump,
+        has_one = merchant)]
+    pub loyalty_program: Option<Box<Account<'info, LoyaltyProgram>>>,
+
+    #[account(mut)]
+    pub points_mint: Option<Box<InterfaceAccount<'info, Mint>>>,
+
+    #[account(mut)]
+    pub customer_points_ata: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+
+    pub points_token_program: Option<Program<'info, Token2022>>,
+
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> PaySolUsd<'info> {
+    pub fn pay_sol_usd(&mut self, payment_id: u64, usd_amount: u64, max_lamports: u64, bumps: &PaySolUsdBumps) -> Result<()> {
+        let price = load_price(
+            &self.price_update,
+            &SOL_USD_FEED_ID,
+            MAX_PRICE_AGE_SECONDS,
+            MAX_PRICE_CONF_BASIS_POINTS,
+        )?;
+
+        let lamports = usd_to_token_amount(usd_amount, &price, SOL_DECIMALS)?;
+        require!(lamports > 0, CustomError::ZeroAmountPayment);
+
+        // Protects the customer from paying more than the price they were quoted
+        require!(lamports <= max_lamports, CustomError::PriceSlippageExceeded);
+
+        // Transfer the converted amount into the merchant's vault
+        transfer(
+            CpiContext::new(
+                self.system_program.to_account_info(),
+                Transfer {
+                    from: self.customer.to_account_info(),
+                    to: self.vault.to_account_info(),
+                },
+            ),
+            lamports,
+        )?;
+
+        self.payment.set_inner(Payment {
+            merchant: self.merchant.key(),
+            payer: self.customer.key(),
+            payment_id,
+            usd_amount,
+            lamports,
+            price: price.price,
+            price_expo: price.expo,
+            price_conf: price.conf,
+            price_publish_time: price.publish_time,
+            created_at: Clock::get()?.unix_timestamp,
+            bump: bumps.payment,
+        });
+
+        // Points are earned on the USD amount, in the same 6-decimal units as stablecoin payments
+        let ctx = EventCpiContext::new(&self.event_authority, bumps.event_authority);
+        let accounts = optional_loyalty_accounts(
+            &self.loyalty_program,
+            &self.points_mint,
+            &self.customer_points_ata,
+            &self.points_token_program,
+            self.customer.key(),
+        )?;
+        award_points(accounts, &self.merchant, self.customer.key(), usd_amount, &ctx)?;
+
+        // Emit event
+        emit_cpi!(SolPaymentProcessed {
+            header: EventHeader::sol(self.merchant.key(), self.customer.key(), self.vault.lamports())?,
+            payment: self.payment.key(),
+            payer: self.customer.key(),
+            usd_amount,
+            lamports,
+            price: price.price,
+            price_expo: price.expo,
+        });
+
+        Ok(())
+    }
+}
@@ -0,0 +1,192 @@
+use anchor_lang::prelude::*;
+use crate::state::{Coupon, GiftCard, LoyaltyProgram, Merchant};
+use crate::errors::*;
+use crate::event_cpi::EventCpiContext;
+use crate::events::*;
+use crate::loyalty::{award_points, loyalty_accounts, optional_loyalty_accounts, redeem_points};
+use crate::redemptions::{apply_coupon, redeem_gift_card};
+
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token_2022::Token2022,
+    token_interface::{Mint, TokenAccount, TokenInterface},
+};
+
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(amount: u64, tip: u64)]
+pub struct PaySpl<'info> {
+    #[account(mut,
+        constraint = amount > 0 @ CustomError::ZeroAmountPayment)]
+    pub customer: Signer<'info>,
+
+    pub merchant: Box<Account<'info, Merchant>>,
+
+    pub stablecoin_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(mut,
+        associated_token::mint = stablecoin_mint,
+        associated_token::authority = customer)]
+    pub customer_stablecoin_ata: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(init_if_needed,
+        payer = customer,
+        associated_token::mint = stablecoin_mint,
+        associated_token::authority = merchant)]
+    pub merchant_stablecoin_ata: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    // only required when the payment carries a tip
+    #[account(mut,
+        seeds = [b"tip_pool", merchant.key().as_ref(), stablecoin_mint.key().as_ref()],
+        bump,
+        token::mint = stablecoin_mint,
+        token::authority = merchant)]
+    pub tip_pool: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+
+    // only required when a coupon is applied
+    #[account(mut,
+        seeds = [b"coupon", merchant.key().as_ref(), coupon.code_hash.as_ref()],
+        bump = coupon.bump,
+        has_one = merchant)]
+    pub coupon: Option<Box<Account<'info, Coupon>>>,
+
+    // only required when paying with a gift card
+    #[account(mut,
+        seeds = [b"gift_card", merchant.key().as_ref(), gift_card.card_id.to_le_bytes().as_ref()],
+        bump = gift_card.bump,
+        has_one = merchant,
+        constraint = gift_card.mint == stablecoin_mint.key() @ CustomError::MintMismatch)]
+    pub gift_card: Option<Box<Account<'info, GiftCard>>>,
+
+    // only required when redeeming a gift card by its code; the code is this keypair's secret
+    pub gift_card_code: Option<Signer<'info>>,
+
+    // only required when the merchant runs a loyalty program
+    #[account(
+        seeds = [b"loyalty", merchant.key().as_ref()],
+        bump = loyalty_program.bump,
+        has_one = merchant)]
+    pub loyalty_program: Option<Box<Account<'info, LoyaltyProgram>>>,
+
+    #[account(mut)]
+    pub points_mint: Option<Box<InterfaceAccount<'info, Mint>>>,
+
+    #[account(mut)]
+    pub customer_points_ata: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+
+    pub points_token_program: Option<Program<'info, Token2022>>,
+
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> PaySpl<'info> {
+    #[allow(clippy::too_many_arguments)]
+    pub fn pay_spl(
+        &mut self,
+        amount: u64,
+        tip: u64,
+        employee: Option<Pubkey>,
+        points_to_redeem: u64,
+        coupon_code: Option<Vec<u8>>,
+        bumps: &PaySplBumps,
+    ) -> Result<()> {
+        let merchant = self.merchant.key();
+        let customer = self.customer.key();
+        let ctx = EventCpiContext::new(&self.event_authority, bumps.event_authority);
+
+        // Apply the coupon to the list amount, then burn any redeemed points against the remainder
+        let coupon_discount = apply_coupon(&mut self.coupon, coupon_code.as_deref(), amount, merchant, customer, &ctx)?;
+        let discounted = amount
+            .checked_sub(coupon_discount)
+            .ok_or(CustomError::ArithmeticOverflow)?;
+
+        let points_discount = if points_to_redeem > 0 {
+            let accounts = loyalty_accounts(
+                &self.loyalty_program,
+                &self.points_mint,
+                &self.customer_points_ata,
+                &self.points_token_program,
+                customer,
+            )?;
+            redeem_points(accounts, &self.customer, merchant, points_to_redeem, &ctx)?
+        } else {
+            0
+        };
+        let charged = discounted
+            .checked_sub(points_discount)
+            .ok_or(CustomError::LoyaltyDiscountExceedsAmount)?;
+
+        let discount = coupon_discount
+            .checked_add(points_discount)
+            .ok_or(CustomError::ArithmeticOverflow)?;
+
+        // A gift card covers what it can; the merchant was already paid for it at purchase
+        let code_signer = self.gift_card_code.as_ref().map(|code| code.key());
+        let gift_card_amount = redeem_gift_card(&mut self.gift_card, code_signer, charged, merchant, customer, &ctx)?;
+        let from_wallet = charged
+            .checked_sub(gift_card_amount)
+            .ok_or(CustomError::ArithmeticOverflow)?;
+
+        let total = from_wallet.checked_add(tip).ok_or(CustomError::ArithmeticOverflow)?;
+        require!(self.customer_stablecoin_ata.amount >= total, CustomError::InsufficientFunds);
+
+        // Transfer the rest of the sale amount to the merchant
+        if from_wallet > 0 {
+            self.transfer_from_customer(&self.merchant_stablecoin_ata.to_account_info(), from_wallet)?;
+        }
+
+        // Transfer the tip into the merchant's tip pool, kept out of sales revenue
+        if tip > 0 {
+            let tip_pool = self.tip_pool.as_ref().ok_or(CustomError::TipPoolRequired)?;
+            self.transfer_from_customer(&tip_pool.to_account_info(), tip)?;
+        }
+
+        // Points are earned on what left the wallet, not on tips, discounts or gift card value
+        let accounts = optional_loyalty_accounts(
+            &self.loyalty_program,
+            &self.points_mint,
+            &self.customer_points_ata,
+            &self.points_token_program,
+            customer,
+        )?;
+        award_points(accounts, &self.merchant, customer, from_wallet, &ctx)?;
+
+        // Emit event
+        self.merchant_stablecoin_ata.reload()?;
+        emit_cpi!(PaymentProcessed {
+            header: EventHeader::token(
+                self.merchant.key(),
+                self.customer.key(),
+                self.stablecoin_mint.key(),
+                self.merchant_stablecoin_ata.amount,
+            )?,
+            payer: self.customer.key(),
+            amount: charged,
+            tip,
+            employee,
+            discount,
+            gift_card_amount,
+        });
+
+        Ok(())
+    }
+
+    /// Helper function to reduce code duplication for customer transfers
+    fn transfer_from_customer(&self, to: &AccountInfo<'info>, amount: u64) -> Result<()> {
+        anchor_spl::token_interface::transfer_checked(
+            CpiContext::new(
+                self.token_program.to_account_info(),
+                anchor_spl::token_interface::TransferChecked {
+                    from: self.customer_stablecoin_ata.to_account_info(),
+                    mint: self.stablecoin_mint.to_account_info(),
+                    to: to.clone(),
+                    authority: self.customer.to_account_info(),
+                },
+            ),
+            amount,
+            self.stablecoin_mint.decimals,
+        )
+    }
+}
@@ -0,0 +1,111 @@
+use anchor_lang::prelude::*;
+use crate::state::{GiftCard, Merchant};
+use crate::errors::*;
+use crate::event_cpi::EventCpiContext;
+use crate::events::*;
+
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token_interface::{Mint, TokenAccount, TokenInterface},
+};
+
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(card_id: u64, amount: u64)]
+pub struct PurchaseGiftCard<'info> {
+    #[account(mut,
+        constraint = amount > 0 @ CustomError::ZeroAmountPayment)]
+    pub buyer: Signer<'info>,
+
+    pub merchant: Box<Account<'info, Merchant>>,
+
+    pub stablecoin_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(mut,
+        associated_token::mint = stablecoin_mint,
+        associated_token::authority = buyer,
+        constraint = buyer_stablecoin_ata.amount >= amount @ CustomError::InsufficientFunds)]
+    pub buyer_stablecoin_ata: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(init_if_needed,
+        payer = buyer,
+        associated_token::mint = stablecoin_mint,
+        associated_token::authority = merchant)]
+    pub merchant_stablecoin_ata: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        init,
+        payer = buyer,
+        seeds = [b"gift_card", merchant.key().as_ref(), card_id.to_le_bytes().as_ref()],
+        space = GiftCard::LEN,
+        bump
+    )]
+    pub gift_card: Box<Account<'info, GiftCard>>,
+
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> PurchaseGiftCard<'info> {
+    pub fn purchase_gift_card(
+        &mut self,
+        card_id: u64,
+        amount: u64,
+        holder: Option<Pubkey>,
+        code_key: Option<Pubkey>,
+        expires_at: i64,
+        bumps: &PurchaseGiftCardBumps,
+    ) -> Result<()> {
+        // A card with neither a holder nor a code could be redeemed by anyone
+        require!(holder.is_some() || code_key.is_some(), CustomError::InvalidGiftCardTerms);
+
+        let now = Clock::get()?.unix_timestamp;
+        require!(expires_at == 0 || expires_at > now, CustomError::InvalidExpiry);
+
+        // The purchase is paid to the merchant up front; redemptions only draw down the card
+        anchor_spl::token_interface::transfer_checked(
+            CpiContext::new(
+                self.token_program.to_account_info(),
+                anchor_spl::token_interface::TransferChecked {
+                    from: self.buyer_stablecoin_ata.to_account_info(),
+                    mint: self.stablecoin_mint.to_account_info(),
+                    to: self.merchant_stablecoin_ata.to_account_info(),
+                    authority: self.buyer.to_account_info(),
+                },
+            ),
+            amount,
+            self.stablecoin_mint.decimals,
+        )?;
+
+        self.gift_card.set_inner(GiftCard {
+            merchant: self.merchant.key(),
+            card_id,
+            mint: self.stablecoin_mint.key(),
+            purchaser: self.buyer.key(),
+            balance: amount,
+            holder,
+            code_key,
+            expires_at,
+            bump: bumps.gift_card,
+        });
+
+        // Emit event
+        self.merchant_stablecoin_ata.reload()?;
+        let ctx = EventCpiContext::new(&self.event_authority, bumps.event_authority);
+        emit_cpi!(GiftCardPurchased {
+            header: EventHeader::token(
+                self.merchant.key(),
+                self.buyer.key(),
+                self.stablecoin_mint.key(),
+                self.merchant_stablecoin_ata.amount,
+            )?,
+            gift_card: self.gift_card.key(),
+            purchaser: self.buyer.key(),
+            amount,
+            expires_at,
+        });
+
+        Ok(())
+    }
+}
@@ -0,0 +1,157 @@
+use anchor_lang::prelude::*;
+use crate::state::{Merchant, Order, OrderItemRefund, WithdrawalLimit};
+use crate::errors::*;
+use crate::event_cpi::EventCpiContext;
+use crate::events::*;
+
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token_interface::{Mint, TokenAccount, TokenInterface},
+};
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct RefundOrderItems<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    #[account(
+        seeds = [b"merchant", merchant.entity_name.as_bytes(), owner.key().as_ref()], 
+        bump = merchant.merchant_bump)]
+    pub merchant: Box<Account<'info, Merchant>>,
+
+    #[account(mut,
+        seeds = [b"order", merchant.key().as_ref(), customer.key().as_ref(), order.order_id.to_le_bytes().as_ref()],
+        bump = order.bump,
+        has_one = merchant,
+        has_one = customer,
+        constraint = order.mint == stablecoin_mint.key() @ CustomError::MintMismatch)]
+    pub order: Box<Account<'info, Order>>,
+
+    /// CHECK: the customer recorded on the order, to derive their stablecoin ata
+    pub customer: AccountInfo<'info>,
+
+    pub stablecoin_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(mut,
+        associated_token::mint = stablecoin_mint,
+        associated_token::authority = merchant)]
+    pub merchant_stablecoin_ata: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// CHECK: the merchant's withdrawal limit for this mint; item refunds count against it like any other outflow
+    #[account(mut, seeds = [b"withdrawal_limit", merchant.key().as_ref(), stablecoin_mint.key().as_ref()], bump)]
+    pub withdrawal_limit: AccountInfo<'info>,
+
+    #[account(init_if_needed,
+        payer = owner,
+        associated_token::mint = stablecoin_mint,
+        associated_token::authority = customer)]
+    pub customer_stablecoin_ata: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> RefundOrderItems<'info> {
+    pub fn refund_order_items(&mut self, items: Vec<OrderItemRefund>, bumps: &RefundOrderItemsBumps) -> Result<()> {
+        require!(!items.is_empty(), CustomError::InvalidLineItemRefund);
+        require!(Clock::get()?.unix_timestamp <= self.order.refund_deadline(), CustomError::OrderRefundWindowClosed);
+
+        // Each line refunds its unit price plus its share of the line's tax
+        let mut amount: u64 = 0;
+        for item in items {
+            require!(item.quantity > 0, CustomError::InvalidLineItemRefund);
+            let line = self.order.line_items
+                .get_mut(item.line_index as usize)
+                .ok_or(CustomError::InvalidLineItemRefund)?;
+
+            let refunded_quantity = line.refunded_quantity
+                .checked_add(item.quantity)
+                .ok_or(CustomError::ArithmeticOverflow)?;
+            require!(refunded_quantity <= line.quantity, CustomError::InvalidLineItemRefund);
+            line.refunded_quantity = refunded_quantity;
+
+            let line_amount = line.unit_price
+                .checked_mul(item.quantity)
+                .ok_or(CustomError::ArithmeticOverflow)?;
+            let line_tax = line.tax
+                .checked_mul(item.quantity)
+                .ok_or(CustomError::ArithmeticOverflow)?
+                .checked_div(line.quantity)
+                .ok_or(CustomError::ArithmeticOverflow)?;
+
+            amount = amount
+                .checked_add(line_amount)
+                .ok_or(CustomError::ArithmeticOverflow)?
+                .checked_add(line_tax)
+                .ok_or(CustomError::ArithmeticOverflow)?;
+        }
+
+        // A coupon lowered what the customer paid, so each refunded line shrinks in proportion
+        if self.order.discount > 0 {
+            let total = self.order.subtotal
+                .checked_add(self.order.tax)
+                .ok_or(CustomError::ArithmeticOverflow)?;
+            let paid = total
+                .checked_sub(self.order.discount)
+                .ok_or(CustomError::ArithmeticOverflow)?;
+            amount = (amount as u128)
+                .checked_mul(paid as u128)
+                .ok_or(CustomError::ArithmeticOverflow)?
+                .checked_div(total as u128)
+                .ok_or(CustomError::ArithmeticOverflow)? as u64;
+        }
+
+        require!(self.merchant_stablecoin_ata.amount >= amount, CustomError::InsufficientFunds);
+
+        // Velocity limits, if the owner has configured any
+        WithdrawalLimit::enforce(&self.withdrawal_limit, amount)?;
+
+        self.order.refunded_amount = self.order.refunded_amount
+            .checked_add(amount)
+            .ok_or(CustomError::ArithmeticOverflow)?;
+
+        let owner_key = self.owner.key();
+        let seeds = &[
+            b"merchant".as_ref(),
+            self.merchant.entity_name.as_bytes(),
+            owner_key.as_ref(),
+            &[self.merchant.merchant_bump],
+        ];
+
+        // Transfer the refund amount back to the customer
+        anchor_spl::token_interface::transfer_checked(
+            CpiContext::new_with_signer(
+                self.token_program.to_account_info(),
+                anchor_spl::token_interface::TransferChecked {
+                    from: self.merchant_stablecoin_ata.to_account_info(),
+                    mint: self.stablecoin_mint.to_account_info(),
+                    to: self.customer_stablecoin_ata.to_account_info(),
+                    authority: self.merchant.to_account_info(),
+                },
+                &[seeds],
+            ),
+            amount,
+            self.stablecoin_mint.decimals,
+        )?;
+
+        // Emit event
+        self.merchant_stablecoin_ata.reload()?;
+        let ctx = EventCpiContext::new(&self.event_authority, bumps.event_authority);
+        emit_cpi!(OrderItemsRefunded {
+            header: EventHeader::token(
+                self.merchant.key(),
+                self.owner.key(),
+                self.stablecoin_mint.key(),
+                self.merchant_stablecoin_ata.amount,
+            )?,
+            order: self.order.key(),
+            customer: self.customer.key(),
+            amount,
+            refunded_amount: self.order.refunded_amount,
+        });
+
+        Ok(())
+    }
+}
@@ -2,11 +2,13 @@ use anchor_lang::{
     prelude::*,
     system_program::{transfer, Transfer},
 };
-use crate::state::Merchant;
+use crate::state::{Global, Merchant, SponsorshipBudget, WithdrawalLimit};
 use crate::state::RefundRecord;
 use crate::errors::*;
+use crate::event_cpi::EventCpiContext;
 use crate::events::*;
 
+#[event_cpi]
 #[derive(Accounts)]
 #[instruction(original_tx_sig: String, amount: u64)]
 pub struct RefundSol<'info> {
@@ -20,16 +22,30 @@ pub struct RefundSol<'info> {
     pub owner: Signer<'info>,
 
     #[account(
-        seeds = [b"merchant", merchant.entity_name.as_str().as_bytes(), owner.key().as_ref()], 
+        seeds = [b"merchant", merchant.entity_name.as_bytes(), owner.key().as_ref()], 
         bump = merchant.merchant_bump)]
     pub merchant: Box<Account<'info, Merchant>>,
 
+    // caps what our fee payer fronts for this merchant; required whenever fee_payer signs
+    #[account(mut,
+        seeds = [b"sponsorship_budget", merchant.key().as_ref()],
+        bump = sponsorship_budget.bump)]
+    pub sponsorship_budget: Option<Box<Account<'info, SponsorshipBudget>>>,
+
+    // required whenever fee_payer signs, so it can be checked against the approved fee payers
+    #[account(seeds = [b"global"], bump = global.bump)]
+    pub global: Option<Box<Account<'info, Global>>>,
+
     #[account(mut, 
         seeds = [b"vault", merchant.key().as_ref()], 
         bump = merchant.vault_bump,
         constraint = vault.lamports() >= amount @ CustomError::InsufficientFunds)]
     pub vault: SystemAccount<'info>,
 
+    /// CHECK: the merchant's SOL withdrawal limit; refunds can pay any recipient, so they count against it too
+    #[account(mut, seeds = [b"withdrawal_limit", merchant.key().as_ref()], bump)]
+    pub withdrawal_limit: AccountInfo<'info>,
+
     #[account(
         init,
         payer = fee_payer.as_ref().unwrap_or(&owner),
@@ -48,10 +64,16 @@ pub struct RefundSol<'info> {
 
 impl<'info> RefundSol<'info> {
     pub fn refund_sol(&mut self, original_tx_sig: String, amount: u64, bumps: &RefundSolBumps) -> Result<()> {
-        
+        // Only fee payers approved in the global config may sponsor
+        Global::require_approved_fee_payer(&self.global, self.fee_payer.as_ref().map(|fee_payer| fee_payer.key()))?;
+
+        // Velocity limits, if the owner has configured any
+        WithdrawalLimit::enforce(&self.withdrawal_limit, amount)?;
+
         // Initialize refund record
         self.refund_record.set_inner(RefundRecord {
             original_tx_sig: original_tx_sig.clone(),
+            rent_payer: self.fee_payer.as_ref().map_or(self.owner.key(), |fee_payer| fee_payer.key()),
             bump: bumps.refund_record
         });
 
@@ -77,12 +99,20 @@ impl<'info> RefundSol<'info> {
         transfer(cpi_ctx, amount)?;
 
         // Emit event
-        emit!(RefundProcessed {
+        let ctx = EventCpiContext::new(&self.event_authority, bumps.event_authority);
+        emit_cpi!(RefundProcessed {
+            header: EventHeader::sol(self.merchant.key(), self.owner.key(), self.vault.lamports())?,
             original_tx_sig,
             amount,
-            recipient: self.recipient.key()
+            recipient: self.recipient.key(),
+            sponsor: self.fee_payer.as_ref().map(|fee_payer| fee_payer.key())
         });
 
+        // Count what our fee payer fronted against the merchant's budget
+        let rent = self.refund_record.to_account_info().lamports();
+        let ctx = EventCpiContext::new(&self.event_authority, bumps.event_authority);
+        SponsorshipBudget::charge(&mut self.sponsorship_budget, self.fee_payer.as_ref().map(|fee_payer| fee_payer.key()), rent, &ctx)?;
+
         Ok(())
     }
 }
\ No newline at end of file
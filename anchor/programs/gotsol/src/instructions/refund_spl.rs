@@ -1,14 +1,18 @@
 use anchor_lang::prelude::*;
-use crate::state::Merchant;
+use crate::state::Global;
+use crate::state::{Merchant, SponsorshipBudget, WithdrawalLimit};
 use crate::state::RefundRecord;
+use crate::event_cpi::EventCpiContext;
 use crate::events::*;
 use crate::errors::*;
+use crate::sponsorship::{create_ata_if_missing, rent_recoup_amount};
 
 use anchor_spl::{
-    associated_token::AssociatedToken,
+    associated_token::{get_associated_token_address_with_program_id, AssociatedToken},
     token_interface::{Mint, TokenAccount, TokenInterface},
 };
 
+#[event_cpi]
 #[derive(Accounts)]
 #[instruction(original_tx_sig: String, amount: u64)]
 pub struct RefundSpl<'info> {
@@ -22,10 +26,16 @@ pub struct RefundSpl<'info> {
     pub owner: Signer<'info>,
 
     #[account(
-        seeds = [b"merchant", merchant.entity_name.as_str().as_bytes(), owner.key().as_ref()], 
+        seeds = [b"merchant", merchant.entity_name.as_bytes(), owner.key().as_ref()], 
         bump = merchant.merchant_bump)]
     pub merchant: Box<Account<'info, Merchant>>,
 
+    // caps what our fee payer fronts for this merchant; required whenever fee_payer signs
+    #[account(mut,
+        seeds = [b"sponsorship_budget", merchant.key().as_ref()],
+        bump = sponsorship_budget.bump)]
+    pub sponsorship_budget: Option<Box<Account<'info, SponsorshipBudget>>>,
+
     pub stablecoin_mint: Box<InterfaceAccount<'info, Mint>>,
 
     #[account(mut,
@@ -34,11 +44,14 @@ pub struct RefundSpl<'info> {
         constraint = merchant_stablecoin_ata.amount >= amount @ CustomError::InsufficientFunds)]
     pub merchant_stablecoin_ata: Box<InterfaceAccount<'info, TokenAccount>>,
 
-    #[account(init_if_needed, 
-        payer = owner,
-        associated_token::mint = stablecoin_mint,
-        associated_token::authority = recipient)]
-    pub recipient_stablecoin_ata: Box<InterfaceAccount<'info, TokenAccount>>,
+    /// CHECK: the merchant's withdrawal limit for this mint; refunds can pay any recipient, so they count against it too
+    #[account(mut, seeds = [b"withdrawal_limit", merchant.key().as_ref(), stablecoin_mint.key().as_ref()], bump)]
+    pub withdrawal_limit: AccountInfo<'info>,
+
+    /// CHECK: the recipient's ATA for the mint, created in the handler if missing
+    #[account(mut,
+        address = get_associated_token_address_with_program_id(&recipient.key(), &stablecoin_mint.key(), &token_program.key()))]
+    pub recipient_stablecoin_ata: AccountInfo<'info>,
 
     #[account(
         init,
@@ -51,6 +64,13 @@ pub struct RefundSpl<'info> {
 
     /// CHECK: this is the public key of address you are refunding, to derive their stablecoin ata
     pub recipient: AccountInfo<'info>,
+
+    // global is required whenever fee_payer signs; with fee_payer_stablecoin_ata, sponsored ATA rent is recouped at its rate
+    #[account(seeds = [b"global"], bump = global.bump)]
+    pub global: Option<Box<Account<'info, Global>>>,
+
+    #[account(mut, token::mint = stablecoin_mint)]
+    pub fee_payer_stablecoin_ata: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
     
     pub associated_token_program: Program<'info, AssociatedToken>,
     pub token_program: Interface<'info, TokenInterface>,
@@ -59,13 +79,26 @@ pub struct RefundSpl<'info> {
 
 impl<'info> RefundSpl<'info> {
     pub fn refund_spl(&mut self, original_tx_sig: String, amount: u64, bumps: &RefundSplBumps) -> Result<()> {
-        
+        // Only fee payers approved in the global config may sponsor
+        Global::require_approved_fee_payer(&self.global, self.fee_payer.as_ref().map(|fee_payer| fee_payer.key()))?;
+
+        // Velocity limits, if the owner has configured any
+        WithdrawalLimit::enforce(&self.withdrawal_limit, amount)?;
+
         // Initialize refund record
         self.refund_record.set_inner(RefundRecord {
             original_tx_sig: original_tx_sig.clone(),
+            rent_payer: self.fee_payer.as_ref().map_or(self.owner.key(), |fee_payer| fee_payer.key()),
             bump: bumps.refund_record
         });
 
+        // The customer never needs SOL for their ATA; the fee payer (or owner) fronts the rent
+        let recouped = self.create_recipient_ata(bumps)?;
+        require!(
+            self.merchant_stablecoin_ata.amount >= amount.checked_add(recouped).ok_or(CustomError::ArithmeticOverflow)?,
+            CustomError::InsufficientFunds
+        );
+
         let owner_key = self.owner.key();
         let seeds = &[
             b"merchant".as_ref(),
@@ -90,13 +123,95 @@ impl<'info> RefundSpl<'info> {
             self.stablecoin_mint.decimals,
         )?;
 
+        // Pay back the fee payer for the ATA rent it fronted
+        if let Some(fee_payer_stablecoin_ata) = &self.fee_payer_stablecoin_ata {
+            if recouped > 0 {
+                anchor_spl::token_interface::transfer_checked(
+                    CpiContext::new_with_signer(
+                        self.token_program.to_account_info(),
+                        anchor_spl::token_interface::TransferChecked {
+                            from: self.merchant_stablecoin_ata.to_account_info(),
+                            mint: self.stablecoin_mint.to_account_info(),
+                            to: fee_payer_stablecoin_ata.to_account_info(),
+                            authority: self.merchant.to_account_info(),
+                        },
+                        &[seeds],
+                    ),
+                    recouped,
+                    self.stablecoin_mint.decimals,
+                )?;
+            }
+        }
+
         // Emit event
-        emit!(RefundProcessed {
+        self.merchant_stablecoin_ata.reload()?;
+        let ctx = EventCpiContext::new(&self.event_authority, bumps.event_authority);
+        emit_cpi!(RefundProcessed {
+            header: EventHeader::token(
+                self.merchant.key(),
+                self.owner.key(),
+                self.stablecoin_mint.key(),
+                self.merchant_stablecoin_ata.amount,
+            )?,
             original_tx_sig,
             amount,
-            recipient: self.recipient.key()
+            recipient: self.recipient.key(),
+            sponsor: self.fee_payer.as_ref().map(|fee_payer| fee_payer.key())
         });
 
         Ok(())
     }
+
+    /// Creates the recipient's ATA if needed and returns the stablecoin amount owed to the
+    /// fee payer for its rent, which is 0 unless the fee payer asked to recoup it
+    fn create_recipient_ata(&mut self, bumps: &RefundSplBumps) -> Result<u64> {
+        let payer = match &self.fee_payer {
+            Some(fee_payer) => fee_payer.to_account_info(),
+            None => self.owner.to_account_info(),
+        };
+
+        let lamports = create_ata_if_missing(
+            &payer,
+            &self.recipient_stablecoin_ata,
+            &self.recipient,
+            &self.stablecoin_mint.to_account_info(),
+            &self.associated_token_program.to_account_info(),
+            &self.token_program.to_account_info(),
+            &self.system_program.to_account_info(),
+        )?;
+
+        let Some(fee_payer) = &self.fee_payer else {
+            return Ok(0);
+        };
+
+        // Count what our fee payer fronted, including the refund record, against the merchant's budget
+        let rent = lamports
+            .checked_add(self.refund_record.to_account_info().lamports())
+            .ok_or(CustomError::ArithmeticOverflow)?;
+        let ctx = EventCpiContext::new(&self.event_authority, bumps.event_authority);
+        SponsorshipBudget::charge(&mut self.sponsorship_budget, Some(fee_payer.key()), rent, &ctx)?;
+
+        if lamports == 0 {
+            return Ok(0);
+        }
+
+        let recouped = match (&self.global, &self.fee_payer_stablecoin_ata) {
+            (Some(global), Some(fee_payer_stablecoin_ata)) => {
+                require_keys_eq!(fee_payer_stablecoin_ata.owner, fee_payer.key(), CustomError::InvalidRentPayer);
+                rent_recoup_amount(lamports, global.rent_recoup_rate, self.stablecoin_mint.decimals)?
+            }
+            _ => 0,
+        };
+
+        // Emit event
+        let ctx = EventCpiContext::new(&self.event_authority, bumps.event_authority);
+        emit_cpi!(AtaRentSponsored {
+            header: EventHeader::new(Some(self.merchant.key()), self.owner.key(), Some(self.stablecoin_mint.key()), None)?,
+            fee_payer: fee_payer.key(),
+            lamports,
+            recouped,
+        });
+
+        Ok(recouped)
+    }
 }
\ No newline at end of file
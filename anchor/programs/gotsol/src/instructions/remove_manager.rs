@@ -0,0 +1,40 @@
+use anchor_lang::prelude::*;
+use crate::state::{Merchant, MerchantManager};
+use crate::errors::*;
+use crate::events::*;
+
+#[derive(Accounts)]
+pub struct RemoveManager<'info> {
+    pub owner: Signer<'info>,
+
+    #[account(
+        seeds = [b"merchant", merchant.entity_name.as_bytes(), owner.key().as_ref()], 
+        bump = merchant.merchant_bump)]
+    pub merchant: Box<Account<'info, Merchant>>,
+
+    /// CHECK: whoever funded the manager account (our fee payer or the merchant owner) gets the rent back
+    #[account(mut)]
+    pub rent_payer: AccountInfo<'info>,
+
+    #[account(mut,
+        seeds = [b"manager", merchant.key().as_ref(), merchant_manager.manager.as_ref()],
+        bump = merchant_manager.bump,
+        has_one = merchant,
+        has_one = rent_payer @ CustomError::InvalidRentPayer,
+        close = rent_payer)]
+    pub merchant_manager: Box<Account<'info, MerchantManager>>,
+
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> RemoveManager<'info> {
+    pub fn remove_manager(&mut self) -> Result<()> {
+        // Emit event
+        emit!(ManagerRemoved {
+            header: EventHeader::merchant(self.merchant.key(), self.owner.key())?,
+            manager: self.merchant_manager.manager,
+        });
+
+        Ok(())
+    }
+}
@@ -0,0 +1,40 @@
+use anchor_lang::prelude::*;
+use crate::state::{Global, MAX_FEE_PAYERS};
+use crate::constants::*;
+use crate::errors::*;
+use crate::events::*;
+
+use std::str::FromStr;
+
+#[derive(Accounts)]
+pub struct SetFeePayerApproval<'info> {
+    #[account(constraint = auth.key() == Pubkey::from_str(AUTH_2).unwrap() || auth.key() == Pubkey::from_str(AUTH_3).unwrap() @ CustomError::UnauthorizedConfigChange)]
+    pub auth: Signer<'info>,
+
+    #[account(mut, seeds = [b"global"], bump = global.bump)]
+    pub global: Box<Account<'info, Global>>,
+}
+
+impl<'info> SetFeePayerApproval<'info> {
+    /// Adds `fee_payer` to, or removes it from, the approved sponsor list
+    pub fn set_fee_payer_approval(&mut self, fee_payer: Pubkey, approved: bool) -> Result<()> {
+        let fee_payers = &mut self.global.fee_payers;
+        let listed = fee_payers.contains(&fee_payer);
+
+        if approved && !listed {
+            require!(fee_payers.len() < MAX_FEE_PAYERS, CustomError::TooManyFeePayers);
+            fee_payers.push(fee_payer);
+        } else if !approved {
+            fee_payers.retain(|key| *key != fee_payer);
+        }
+
+        // Emit event
+        emit!(FeePayerApprovalChanged {
+            header: EventHeader::global(self.auth.key())?,
+            fee_payer,
+            approved,
+        });
+
+        Ok(())
+    }
+}
@@ -0,0 +1,72 @@
+use anchor_lang::prelude::*;
+use crate::state::{Merchant, PayoutSchedule, MAX_PAYOUT_BOUNTY_BASIS_POINTS};
+use crate::errors::*;
+use crate::events::*;
+
+use anchor_spl::token_interface::Mint;
+
+#[derive(Accounts)]
+#[instruction(interval: i64, minimum_threshold: u64, destination: Pubkey, bounty_basis_points: u16)]
+pub struct SetPayoutSchedule<'info> {
+    #[account(mut,
+        constraint = interval > 0 && bounty_basis_points <= MAX_PAYOUT_BOUNTY_BASIS_POINTS @ CustomError::InvalidPayoutSchedule)]
+    pub owner: Signer<'info>,
+
+    #[account(
+        seeds = [b"merchant", merchant.entity_name.as_bytes(), owner.key().as_ref()], 
+        bump = merchant.merchant_bump)]
+    pub merchant: Box<Account<'info, Merchant>>,
+
+    pub stablecoin_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        init_if_needed,
+        payer = owner,
+        seeds = [b"payout_schedule", merchant.key().as_ref(), stablecoin_mint.key().as_ref()],
+        space = PayoutSchedule::LEN,
+        bump
+    )]
+    pub payout_schedule: Box<Account<'info, PayoutSchedule>>,
+
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> SetPayoutSchedule<'info> {
+    /// Creates or replaces the schedule; the first payout becomes due one interval from now
+    pub fn set_payout_schedule(
+        &mut self,
+        interval: i64,
+        minimum_threshold: u64,
+        destination: Pubkey,
+        bounty_basis_points: u16,
+        bumps: &SetPayoutScheduleBumps,
+    ) -> Result<()> {
+        let next_payout_at = Clock::get()?
+            .unix_timestamp
+            .checked_add(interval)
+            .ok_or(CustomError::ArithmeticOverflow)?;
+
+        self.payout_schedule.set_inner(PayoutSchedule {
+            merchant: self.merchant.key(),
+            mint: self.stablecoin_mint.key(),
+            destination,
+            interval,
+            minimum_threshold,
+            bounty_basis_points,
+            next_payout_at,
+            bump: bumps.payout_schedule,
+        });
+
+        // Emit event
+        emit!(PayoutScheduleSet {
+            header: EventHeader::new(Some(self.merchant.key()), self.owner.key(), Some(self.stablecoin_mint.key()), None)?,
+            destination,
+            interval,
+            minimum_threshold,
+            bounty_basis_points,
+            next_payout_at,
+        });
+
+        Ok(())
+    }
+}
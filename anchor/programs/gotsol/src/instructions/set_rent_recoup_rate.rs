@@ -0,0 +1,30 @@
+use anchor_lang::prelude::*;
+use crate::state::Global;
+use crate::constants::*;
+use crate::errors::*;
+use crate::events::*;
+
+use std::str::FromStr;
+
+#[derive(Accounts)]
+pub struct SetRentRecoupRate<'info> {
+    #[account(constraint = auth.key() == Pubkey::from_str(AUTH_2).unwrap() || auth.key() == Pubkey::from_str(AUTH_3).unwrap() @ CustomError::UnauthorizedConfigChange)]
+    pub auth: Signer<'info>,
+
+    #[account(mut, seeds = [b"global"], bump = global.bump)]
+    pub global: Box<Account<'info, Global>>,
+}
+
+impl<'info> SetRentRecoupRate<'info> {
+    pub fn set_rent_recoup_rate(&mut self, rent_recoup_rate: u64) -> Result<()> {
+        self.global.rent_recoup_rate = rent_recoup_rate;
+
+        // Emit event
+        emit!(RentRecoupRateUpdated {
+            header: EventHeader::global(self.auth.key())?,
+            rent_recoup_rate,
+        });
+
+        Ok(())
+    }
+}
@@ -0,0 +1,52 @@
+use anchor_lang::prelude::*;
+use crate::state::{Merchant, WithdrawalLimit};
+use crate::events::*;
+
+use anchor_spl::token_interface::Mint;
+
+#[derive(Accounts)]
+pub struct SetSplWithdrawalLimit<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    #[account(
+        seeds = [b"merchant", merchant.entity_name.as_bytes(), owner.key().as_ref()], 
+        bump = merchant.merchant_bump)]
+    pub merchant: Box<Account<'info, Merchant>>,
+
+    pub stablecoin_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        init_if_needed,
+        payer = owner,
+        seeds = [b"withdrawal_limit", merchant.key().as_ref(), stablecoin_mint.key().as_ref()],
+        space = WithdrawalLimit::LEN,
+        bump
+    )]
+    pub withdrawal_limit: Box<Account<'info, WithdrawalLimit>>,
+
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> SetSplWithdrawalLimit<'info> {
+    pub fn set_spl_withdrawal_limit(&mut self, max_per_withdrawal: u64, max_per_day: u64, bumps: &SetSplWithdrawalLimitBumps) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+
+        self.withdrawal_limit.merchant = self.merchant.key();
+        self.withdrawal_limit.mint = self.stablecoin_mint.key();
+        self.withdrawal_limit.bump = bumps.withdrawal_limit;
+        self.withdrawal_limit.update(max_per_withdrawal, max_per_day, now)?;
+
+        // Emit event
+        emit!(WithdrawalLimitUpdated {
+            header: EventHeader::new(Some(self.merchant.key()), self.owner.key(), Some(self.stablecoin_mint.key()), None)?,
+            max_per_withdrawal: self.withdrawal_limit.max_per_withdrawal,
+            max_per_day: self.withdrawal_limit.max_per_day,
+            pending_max_per_withdrawal: self.withdrawal_limit.pending_max_per_withdrawal,
+            pending_max_per_day: self.withdrawal_limit.pending_max_per_day,
+            pending_effective_at: self.withdrawal_limit.pending_effective_at,
+        });
+
+        Ok(())
+    }
+}
@@ -0,0 +1,53 @@
+use anchor_lang::prelude::*;
+use crate::state::{Merchant, SponsorshipBudget};
+use crate::constants::*;
+use crate::errors::*;
+use crate::events::*;
+
+use std::str::FromStr;
+
+#[derive(Accounts)]
+#[instruction(allowance_per_period: u64, period: i64)]
+pub struct SetSponsorshipBudget<'info> {
+    #[account(mut,
+        constraint = auth.key() == Pubkey::from_str(AUTH_2).unwrap() || auth.key() == Pubkey::from_str(AUTH_3).unwrap() @ CustomError::UnauthorizedConfigChange,
+        constraint = period > 0 @ CustomError::InvalidSponsorshipBudget)]
+    pub auth: Signer<'info>,
+
+    pub merchant: Box<Account<'info, Merchant>>,
+
+    #[account(
+        init_if_needed,
+        payer = auth,
+        seeds = [b"sponsorship_budget", merchant.key().as_ref()],
+        space = SponsorshipBudget::LEN,
+        bump
+    )]
+    pub sponsorship_budget: Box<Account<'info, SponsorshipBudget>>,
+
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> SetSponsorshipBudget<'info> {
+    /// Sets the budget's terms and starts a fresh period, which also resets what was consumed
+    pub fn set_sponsorship_budget(&mut self, allowance_per_period: u64, period: i64, bumps: &SetSponsorshipBudgetBumps) -> Result<()> {
+        self.sponsorship_budget.set_inner(SponsorshipBudget {
+            merchant: self.merchant.key(),
+            allowance_per_period,
+            period,
+            period_start: Clock::get()?.unix_timestamp,
+            consumed: 0,
+            bump: bumps.sponsorship_budget,
+        });
+
+        // Emit event
+        emit!(SponsorshipBudgetUpdated {
+            header: EventHeader::merchant(self.merchant.key(), self.auth.key())?,
+            allowance_per_period,
+            period,
+            consumed: 0,
+        });
+
+        Ok(())
+    }
+}
@@ -0,0 +1,30 @@
+use anchor_lang::prelude::*;
+use crate::state::Global;
+use crate::constants::*;
+use crate::errors::*;
+use crate::events::*;
+
+use std::str::FromStr;
+
+#[derive(Accounts)]
+pub struct SetSwapRouter<'info> {
+    #[account(constraint = auth.key() == Pubkey::from_str(AUTH_2).unwrap() || auth.key() == Pubkey::from_str(AUTH_3).unwrap() @ CustomError::UnauthorizedConfigChange)]
+    pub auth: Signer<'info>,
+
+    #[account(mut, seeds = [b"global"], bump = global.bump)]
+    pub global: Box<Account<'info, Global>>,
+}
+
+impl<'info> SetSwapRouter<'info> {
+    pub fn set_swap_router(&mut self, swap_router: Pubkey) -> Result<()> {
+        self.global.swap_router = swap_router;
+
+        // Emit event
+        emit!(SwapRouterUpdated {
+            header: EventHeader::global(self.auth.key())?,
+            swap_router,
+        });
+
+        Ok(())
+    }
+}
@@ -0,0 +1,38 @@
+use anchor_lang::prelude::*;
+use crate::state::{Merchant, SponsorshipBudget};
+use crate::constants::*;
+use crate::errors::*;
+use crate::events::*;
+
+use std::str::FromStr;
+
+#[derive(Accounts)]
+pub struct TopUpSponsorshipBudget<'info> {
+    #[account(constraint = auth.key() == Pubkey::from_str(AUTH_2).unwrap() || auth.key() == Pubkey::from_str(AUTH_3).unwrap() @ CustomError::UnauthorizedConfigChange)]
+    pub auth: Signer<'info>,
+
+    pub merchant: Box<Account<'info, Merchant>>,
+
+    #[account(mut,
+        seeds = [b"sponsorship_budget", merchant.key().as_ref()],
+        bump = sponsorship_budget.bump,
+        has_one = merchant)]
+    pub sponsorship_budget: Box<Account<'info, SponsorshipBudget>>,
+}
+
+impl<'info> TopUpSponsorshipBudget<'info> {
+    /// Gives `lamports` of extra headroom for the current period only
+    pub fn top_up_sponsorship_budget(&mut self, lamports: u64) -> Result<()> {
+        self.sponsorship_budget.consumed = self.sponsorship_budget.consumed.saturating_sub(lamports);
+
+        // Emit event
+        emit!(SponsorshipBudgetUpdated {
+            header: EventHeader::merchant(self.merchant.key(), self.auth.key())?,
+            allowance_per_period: self.sponsorship_budget.allowance_per_period,
+            period: self.sponsorship_budget.period,
+            consumed: self.sponsorship_budget.consumed,
+        });
+
+        Ok(())
+    }
+}
@@ -0,0 +1,52 @@
+use anchor_lang::prelude::*;
+use crate::state::{LoyaltyProgram, Merchant};
+use crate::errors::*;
+use crate::event_cpi::EventCpiContext;
+use crate::events::*;
+
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(earn_basis_points: u64, redemption_basis_points: u64)]
+pub struct UpdateLoyaltyProgram<'info> {
+    #[account(
+        constraint = earn_basis_points > 0 && redemption_basis_points > 0 @ CustomError::InvalidLoyaltyRate)]
+    pub owner: Signer<'info>,
+
+    #[account(
+        seeds = [b"merchant", merchant.entity_name.as_bytes(), owner.key().as_ref()], 
+        bump = merchant.merchant_bump)]
+    pub merchant: Box<Account<'info, Merchant>>,
+
+    #[account(mut,
+        seeds = [b"loyalty", merchant.key().as_ref()],
+        bump = loyalty_program.bump,
+        has_one = merchant)]
+    pub loyalty_program: Box<Account<'info, LoyaltyProgram>>,
+}
+
+impl<'info> UpdateLoyaltyProgram<'info> {
+    pub fn update_loyalty_program(
+        &mut self,
+        earn_basis_points: u64,
+        redemption_basis_points: u64,
+        bumps: &UpdateLoyaltyProgramBumps,
+    ) -> Result<()> {
+        let previous_earn_basis_points = self.loyalty_program.earn_basis_points;
+        let previous_redemption_basis_points = self.loyalty_program.redemption_basis_points;
+
+        self.loyalty_program.earn_basis_points = earn_basis_points;
+        self.loyalty_program.redemption_basis_points = redemption_basis_points;
+
+        // Emit event
+        let ctx = EventCpiContext::new(&self.event_authority, bumps.event_authority);
+        emit_cpi!(LoyaltyProgramUpdated {
+            header: EventHeader::merchant(self.merchant.key(), self.owner.key())?,
+            previous_earn_basis_points,
+            previous_redemption_basis_points,
+            earn_basis_points,
+            redemption_basis_points,
+        });
+
+        Ok(())
+    }
+}
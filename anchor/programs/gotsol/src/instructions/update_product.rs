@@ -0,0 +1,58 @@
+use anchor_lang::prelude::*;
+use crate::state::{Merchant, MerchantManager, Product};
+use crate::errors::*;
+use crate::events::*;
+
+#[derive(Accounts)]
+pub struct UpdateProduct<'info> {
+    // the merchant owner, or a manager they have added
+    #[account(
+        constraint = authority.key() == merchant.owner || manager.is_some() @ CustomError::UnauthorizedProductManager)]
+    pub authority: Signer<'info>,
+
+    pub merchant: Box<Account<'info, Merchant>>,
+
+    #[account(
+        seeds = [b"manager", merchant.key().as_ref(), authority.key().as_ref()],
+        bump = manager.bump,
+        has_one = merchant)]
+    pub manager: Option<Box<Account<'info, MerchantManager>>>,
+
+    #[account(mut,
+        seeds = [b"product", merchant.key().as_ref(), product.sku.as_bytes()],
+        bump = product.bump,
+        has_one = merchant)]
+    pub product: Box<Account<'info, Product>>,
+}
+
+impl<'info> UpdateProduct<'info> {
+    pub fn update_product(&mut self, price: Option<u64>, stock: Option<u64>, tax_basis_points: Option<u16>, active: Option<bool>) -> Result<()> {
+        if let Some(price) = price {
+            require!(price > 0, CustomError::InvalidProductPrice);
+            self.product.price = price;
+        }
+        if let Some(stock) = stock {
+            self.product.stock = stock;
+        }
+        if let Some(tax_basis_points) = tax_basis_points {
+            require!(tax_basis_points <= 10000, CustomError::InvalidTaxRate);
+            self.product.tax_basis_points = tax_basis_points;
+        }
+        if let Some(active) = active {
+            self.product.active = active;
+        }
+
+        // Emit event
+        emit!(ProductUpdated {
+            header: EventHeader::merchant(self.merchant.key(), self.authority.key())?,
+            product: self.product.key(),
+            sku: self.product.sku.clone(),
+            price: self.product.price,
+            stock: self.product.stock,
+            tax_basis_points: self.product.tax_basis_points,
+            active: self.product.active,
+        });
+
+        Ok(())
+    }
+}
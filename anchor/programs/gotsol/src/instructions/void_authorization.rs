@@ -0,0 +1,111 @@
+use anchor_lang::prelude::*;
+use crate::state::{Authorization, Merchant};
+use crate::errors::*;
+use crate::event_cpi::EventCpiContext;
+use crate::events::*;
+
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token_interface::{close_account, CloseAccount, Mint, TokenAccount, TokenInterface},
+};
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct VoidAuthorization<'info> {
+    // the merchant owner or customer may void at any time; anyone may release an expired hold
+    pub authority: Signer<'info>,
+
+    pub merchant: Box<Account<'info, Merchant>>,
+
+    /// CHECK: the customer who placed the hold; receives the released funds and the reclaimed rent
+    #[account(mut)]
+    pub customer: AccountInfo<'info>,
+
+    #[account(mut,
+        seeds = [b"authorization", merchant.key().as_ref(), customer.key().as_ref(), authorization.authorization_id.to_le_bytes().as_ref()],
+        bump = authorization.bump,
+        has_one = merchant,
+        has_one = customer,
+        constraint = authorization.mint == stablecoin_mint.key() @ CustomError::MintMismatch,
+        close = customer)]
+    pub authorization: Box<Account<'info, Authorization>>,
+
+    #[account(mut,
+        seeds = [b"escrow", authorization.key().as_ref()],
+        bump = authorization.escrow_bump)]
+    pub escrow: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    pub stablecoin_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(mut,
+        associated_token::mint = stablecoin_mint,
+        associated_token::authority = customer)]
+    pub customer_stablecoin_ata: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> VoidAuthorization<'info> {
+    pub fn void_authorization(&mut self, bumps: &VoidAuthorizationBumps) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        let expired = now > self.authorization.expires_at;
+        let authority = self.authority.key();
+        require!(
+            expired || authority == self.merchant.owner || authority == self.customer.key(),
+            CustomError::AuthorizationNotExpired
+        );
+
+        let merchant_key = self.merchant.key();
+        let customer_key = self.customer.key();
+        let authorization_id = self.authorization.authorization_id.to_le_bytes();
+        let seeds = &[
+            b"authorization".as_ref(),
+            merchant_key.as_ref(),
+            customer_key.as_ref(),
+            authorization_id.as_ref(),
+            &[self.authorization.bump],
+        ];
+
+        // Release the full hold back to the customer
+        let amount = self.escrow.amount;
+        anchor_spl::token_interface::transfer_checked(
+            CpiContext::new_with_signer(
+                self.token_program.to_account_info(),
+                anchor_spl::token_interface::TransferChecked {
+                    from: self.escrow.to_account_info(),
+                    mint: self.stablecoin_mint.to_account_info(),
+                    to: self.customer_stablecoin_ata.to_account_info(),
+                    authority: self.authorization.to_account_info(),
+                },
+                &[seeds],
+            ),
+            amount,
+            self.stablecoin_mint.decimals,
+        )?;
+
+        // Close the empty escrow, returning its rent to the customer
+        close_account(CpiContext::new_with_signer(
+            self.token_program.to_account_info(),
+            CloseAccount {
+                account: self.escrow.to_account_info(),
+                destination: self.customer.to_account_info(),
+                authority: self.authorization.to_account_info(),
+            },
+            &[seeds],
+        ))?;
+
+        // Emit event
+        let ctx = EventCpiContext::new(&self.event_authority, bumps.event_authority);
+        emit_cpi!(AuthorizationVoided {
+            header: EventHeader::new(Some(merchant_key), self.authority.key(), Some(self.stablecoin_mint.key()), None)?,
+            customer: customer_key,
+            authorization: self.authorization.key(),
+            amount,
+            expired,
+        });
+
+        Ok(())
+    }
+}
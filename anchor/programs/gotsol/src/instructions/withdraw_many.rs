@@ -0,0 +1,173 @@
+use anchor_lang::prelude::*;
+
+use anchor_spl::{
+    associated_token::get_associated_token_address_with_program_id,
+    token::Token,
+    token_2022::Token2022,
+    token_interface::{Mint, TokenAccount},
+};
+
+use std::str::FromStr;
+
+use crate::state::{Merchant, WithdrawalLimit};
+use crate::constants::*;
+use crate::errors::*;
+use crate::event_cpi::EventCpiContext;
+use crate::events::*;
+
+const OWNER_SHARE_BASIS_POINTS: u64 = 9900; // 99%
+const HOUSE_SHARE_BASIS_POINTS: u64 = 100;  // 1%
+const BASIS_POINTS_DIVISOR: u64 = 10000;
+const MINIMUM_WITHDRAWAL_SPL_UNITS: u64 = 100; 
+
+// (mint, merchant ATA, owner ATA, house ATA, withdrawal limit)
+const ACCOUNTS_PER_MINT: usize = 5;
+const MAX_WITHDRAW_MINTS: usize = 6;
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct WithdrawMany<'info> {
+    pub owner: Signer<'info>,
+
+    #[account(
+        seeds = [b"merchant", merchant.entity_name.as_bytes(), owner.key().as_ref()], 
+        bump = merchant.merchant_bump,
+    )]
+    pub merchant: Box<Account<'info, Merchant>>,
+
+    /// CHECK: This is the HOUSE Squads multi-sig
+    #[account(constraint = house.key() == Pubkey::from_str(HOUSE).unwrap())]
+    pub house: AccountInfo<'info>,
+
+    pub token_program: Program<'info, Token>,
+    pub token_2022_program: Program<'info, Token2022>,
+}
+
+impl<'info> WithdrawMany<'info> {
+    /// Withdraws from one merchant ATA per `(mint, merchant ATA, owner ATA, house ATA, withdrawal limit)` group in
+    /// `remaining_accounts`; `amounts[i]` of `None` withdraws the whole balance of group `i`
+    pub fn withdraw_many(&mut self, amounts: Vec<Option<u64>>, groups: &'info [AccountInfo<'info>], bumps: &WithdrawManyBumps) -> Result<()> {
+        require!(
+            !amounts.is_empty()
+                && amounts.len() <= MAX_WITHDRAW_MINTS
+                && groups.len() == amounts.len() * ACCOUNTS_PER_MINT,
+            CustomError::InvalidWithdrawGroup
+        );
+
+        let owner_key = self.owner.key();
+        let seeds = &[
+            b"merchant".as_ref(),
+            self.merchant.entity_name.as_bytes(),
+            owner_key.as_ref(),
+            &[self.merchant.merchant_bump],
+        ];
+
+        for (group, amount) in groups.chunks(ACCOUNTS_PER_MINT).zip(amounts) {
+            let [mint_info, merchant_ata_info, owner_ata_info, house_ata_info, withdrawal_limit_info] = group else {
+                return err!(CustomError::InvalidWithdrawGroup);
+            };
+
+            // Each group may use either token program, so pick the one that owns the mint
+            let token_program = if *mint_info.owner == self.token_program.key() {
+                self.token_program.to_account_info()
+            } else if *mint_info.owner == self.token_2022_program.key() {
+                self.token_2022_program.to_account_info()
+            } else {
+                return err!(CustomError::InvalidWithdrawGroup);
+            };
+            let mint = InterfaceAccount::<Mint>::try_from(mint_info)?;
+
+            // Same account checks as WithdrawSpl, but the ATAs must already exist
+            self.check_ata(merchant_ata_info, &self.merchant.key(), mint_info.key, token_program.key)?;
+            self.check_ata(owner_ata_info, &owner_key, mint_info.key, token_program.key)?;
+            self.check_ata(house_ata_info, &self.house.key(), mint_info.key, token_program.key)?;
+
+            let mut merchant_ata = InterfaceAccount::<TokenAccount>::try_from(merchant_ata_info)?;
+            let amount = amount.unwrap_or(merchant_ata.amount);
+            require!(amount >= MINIMUM_WITHDRAWAL_SPL_UNITS, CustomError::BelowMinimumWithdrawal);
+            require!(merchant_ata.amount >= amount, CustomError::InsufficientFunds);
+
+            // Velocity limits, if the owner has configured any for this mint
+            let (withdrawal_limit, _) = Pubkey::find_program_address(
+                &[b"withdrawal_limit", self.merchant.key().as_ref(), mint_info.key.as_ref()],
+                &crate::ID,
+            );
+            require_keys_eq!(withdrawal_limit_info.key(), withdrawal_limit, CustomError::InvalidWithdrawGroup);
+            WithdrawalLimit::enforce(withdrawal_limit_info, amount)?;
+
+            // Calculate shares using basis points for better precision
+            let owner_amount = amount
+                .checked_mul(OWNER_SHARE_BASIS_POINTS)
+                .ok_or(CustomError::ArithmeticOverflow)?
+                .checked_div(BASIS_POINTS_DIVISOR)
+                .ok_or(CustomError::ArithmeticOverflow)?;
+
+            let house_amount = amount
+                .checked_mul(HOUSE_SHARE_BASIS_POINTS)
+                .ok_or(CustomError::ArithmeticOverflow)?
+                .checked_div(BASIS_POINTS_DIVISOR)
+                .ok_or(CustomError::ArithmeticOverflow)?;
+
+            // Validate amounts
+            require!(owner_amount > 0, CustomError::InvalidWithdrawalAmount);
+            require!(house_amount > 0, CustomError::InvalidWithdrawalAmount);
+            require!(owner_amount.checked_add(house_amount).unwrap() <= amount, CustomError::ArithmeticOverflow);
+
+            // Transfer the owner's share
+            self.transfer_spl_tokens(&token_program, mint_info, &mint, merchant_ata_info, owner_ata_info, owner_amount, seeds)?;
+
+            // Transfer house's share
+            self.transfer_spl_tokens(&token_program, mint_info, &mint, merchant_ata_info, house_ata_info, house_amount, seeds)?;
+
+            // Emit event
+            merchant_ata.reload()?;
+            let ctx = EventCpiContext::new(&self.event_authority, bumps.event_authority);
+            emit_cpi!(WithdrawSplProcessed {
+                header: EventHeader::token(self.merchant.key(), owner_key, mint.key(), merchant_ata.amount)?,
+                amount,
+                owner_amount,
+                house_amount,
+            });
+        }
+
+        Ok(())
+    }
+
+    fn check_ata(&self, ata: &AccountInfo<'info>, authority: &Pubkey, mint: &Pubkey, token_program: &Pubkey) -> Result<()> {
+        require_keys_eq!(
+            ata.key(),
+            get_associated_token_address_with_program_id(authority, mint, token_program),
+            CustomError::InvalidWithdrawGroup
+        );
+        require!(ata.is_writable, CustomError::InvalidWithdrawGroup);
+        Ok(())
+    }
+
+    /// Helper function to reduce code duplication for SPL token transfers
+    #[allow(clippy::too_many_arguments)]
+    fn transfer_spl_tokens(
+        &self,
+        token_program: &AccountInfo<'info>,
+        mint_info: &AccountInfo<'info>,
+        mint: &InterfaceAccount<'info, Mint>,
+        from: &AccountInfo<'info>,
+        to: &AccountInfo<'info>,
+        amount: u64,
+        seeds: &[&[u8]],
+    ) -> Result<()> {
+        anchor_spl::token_interface::transfer_checked(
+            CpiContext::new_with_signer(
+                token_program.clone(),
+                anchor_spl::token_interface::TransferChecked {
+                    from: from.clone(),
+                    mint: mint_info.clone(),
+                    to: to.clone(),
+                    authority: self.merchant.to_account_info(),
+                },
+                &[seeds],
+            ),
+            amount,
+            mint.decimals,
+        )
+    }
+}
@@ -2,9 +2,10 @@ use anchor_lang::{
     prelude::*,
     system_program::{transfer, Transfer},
 };
-use crate::state::Merchant;
+use crate::state::{Merchant, WithdrawalLimit};
 use crate::constants::*;
 use crate::errors::*;
+use crate::event_cpi::EventCpiContext;
 use crate::events::*;
 
 use std::str::FromStr;
@@ -15,26 +16,27 @@ const BASIS_POINTS_DIVISOR: u64 = 10000;
 const MINIMUM_WITHDRAWAL_SOL_LAMPORTS: u64 = 1000; // 1000 lamports = 0.000001 SOL
 // const MINIMUM_WITHDRAWAL_SPL_UNITS: u64 = 100; 
 
+#[event_cpi]
 #[derive(Accounts)]
-#[instruction(amount: u64)]
 pub struct WithdrawSol<'info> {
-    #[account(mut,
-        constraint = amount >= MINIMUM_WITHDRAWAL_SOL_LAMPORTS @ CustomError::BelowMinimumWithdrawal)]
+    #[account(mut)]
     pub owner: Signer<'info>,
 
     #[account(
-        seeds = [b"merchant", merchant.entity_name.as_str().as_bytes(), owner.key().as_ref()], 
+        seeds = [b"merchant", merchant.entity_name.as_bytes(), owner.key().as_ref()], 
         bump = merchant.merchant_bump,
     )]
     pub merchant: Box<Account<'info, Merchant>>,
 
     #[account(mut, 
         seeds = [b"vault", merchant.key().as_ref()], 
-        bump = merchant.vault_bump,
-        constraint = vault.lamports() >= amount @ CustomError::InsufficientFunds,
-        constraint = vault.lamports().checked_sub(amount).unwrap() >= Rent::get()?.minimum_balance(0) @ CustomError::InsufficientRentBalance)]
+        bump = merchant.vault_bump)]
     pub vault: SystemAccount<'info>,
 
+    /// CHECK: the merchant's SOL withdrawal limit; enforced only once the owner has created it
+    #[account(mut, seeds = [b"withdrawal_limit", merchant.key().as_ref()], bump)]
+    pub withdrawal_limit: AccountInfo<'info>,
+
     /// CHECK: This is the HOUSE Squads multi-sig
     #[account(mut, constraint = house.key() == Pubkey::from_str(HOUSE).unwrap())]
     pub house: AccountInfo<'info>,
@@ -43,7 +45,18 @@ pub struct WithdrawSol<'info> {
 }
 
 impl<'info> WithdrawSol<'info> {
-    pub fn withdraw_sol(&mut self, amount: u64) -> Result<()> {
+    /// Withdraws `amount` lamports, or everything above the vault's rent-exempt minimum when `None` (`withdraw_sol_all`)
+    pub fn withdraw_sol(&mut self, amount: Option<u64>, bumps: &WithdrawSolBumps) -> Result<()> {
+        let available = self.vault.lamports().saturating_sub(Rent::get()?.minimum_balance(0));
+        let amount = amount.unwrap_or(available);
+
+        require!(amount >= MINIMUM_WITHDRAWAL_SOL_LAMPORTS, CustomError::BelowMinimumWithdrawal);
+        require!(self.vault.lamports() >= amount, CustomError::InsufficientFunds);
+        require!(amount <= available, CustomError::InsufficientRentBalance);
+
+        // Velocity limits, if the owner has configured any
+        WithdrawalLimit::enforce(&self.withdrawal_limit, amount)?;
+
         // Calculate shares using basis points for better precision
         let owner_amount = amount
             .checked_mul(OWNER_SHARE_BASIS_POINTS)
@@ -69,7 +82,9 @@ impl<'info> WithdrawSol<'info> {
         self.transfer_from_vault(&self.house.to_account_info(), house_amount)?;
 
         // Emit event
-        emit!(WithdrawSolProcessed {
+        let ctx = EventCpiContext::new(&self.event_authority, bumps.event_authority);
+        emit_cpi!(WithdrawSolProcessed {
+            header: EventHeader::sol(self.merchant.key(), self.owner.key(), self.vault.lamports())?,
             amount,
             owner_amount,
             house_amount,
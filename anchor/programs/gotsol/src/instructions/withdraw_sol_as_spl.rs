@@ -0,0 +1,272 @@
+use anchor_lang::{
+    prelude::*,
+    solana_program::{instruction::{AccountMeta, Instruction}, program::invoke_signed},
+    system_program::{transfer, Transfer},
+};
+
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token::spl_token::native_mint,
+    token_interface::{close_account, sync_native, CloseAccount, Mint, SyncNative, TokenAccount, TokenInterface},
+};
+
+use std::str::FromStr;
+
+use crate::state::{Global, Merchant, WithdrawalLimit};
+use crate::constants::*;
+use crate::errors::*;
+use crate::event_cpi::EventCpiContext;
+use crate::events::*;
+
+const OWNER_SHARE_BASIS_POINTS: u64 = 9900; // 99%
+const HOUSE_SHARE_BASIS_POINTS: u64 = 100;  // 1%
+const BASIS_POINTS_DIVISOR: u64 = 10000;
+const MINIMUM_WITHDRAWAL_SOL_LAMPORTS: u64 = 1000; // 1000 lamports = 0.000001 SOL
+
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(amount: u64)]
+pub struct WithdrawSolAsSpl<'info> {
+    #[account(mut,
+        constraint = amount >= MINIMUM_WITHDRAWAL_SOL_LAMPORTS @ CustomError::BelowMinimumWithdrawal)]
+    pub owner: Signer<'info>,
+
+    #[account(
+        seeds = [b"merchant", merchant.entity_name.as_bytes(), owner.key().as_ref()], 
+        bump = merchant.merchant_bump,
+    )]
+    pub merchant: Box<Account<'info, Merchant>>,
+
+    #[account(mut, 
+        seeds = [b"vault", merchant.key().as_ref()], 
+        bump = merchant.vault_bump,
+        constraint = vault.lamports() >= amount @ CustomError::InsufficientFunds,
+        constraint = vault.lamports().checked_sub(amount).unwrap() >= Rent::get()?.minimum_balance(0) @ CustomError::InsufficientRentBalance)]
+    pub vault: SystemAccount<'info>,
+
+    /// CHECK: the merchant's SOL withdrawal limit; enforced only once the owner has created it
+    #[account(mut, seeds = [b"withdrawal_limit", merchant.key().as_ref()], bump)]
+    pub withdrawal_limit: AccountInfo<'info>,
+
+    #[account(seeds = [b"global"], bump = global.bump)]
+    pub global: Box<Account<'info, Global>>,
+
+    /// CHECK: The swap router configured in `global`; invoked with only the swap authority as signer
+    #[account(executable, constraint = swap_program.key() == global.swap_router @ CustomError::InvalidSwapProgram)]
+    pub swap_program: AccountInfo<'info>,
+
+    /// CHECK: signs the swap in the merchant's place and owns nothing but `swap_wsol`
+    #[account(seeds = [b"swap_authority", merchant.key().as_ref()], bump)]
+    pub swap_authority: AccountInfo<'info>,
+
+    #[account(address = native_mint::ID)]
+    pub wsol_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    // temporary account holding just the wrapped lamports handed to the swap router; closed before returning
+    #[account(init,
+        payer = owner,
+        seeds = [b"swap_wsol", merchant.key().as_ref()],
+        bump,
+        token::mint = wsol_mint,
+        token::authority = swap_authority,
+        token::token_program = token_program)]
+    pub swap_wsol: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    pub stablecoin_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    // receives the swap output before it is split
+    #[account(init_if_needed,
+        payer = owner,
+        associated_token::mint = stablecoin_mint,
+        associated_token::authority = merchant
+    )]
+    pub merchant_stablecoin_ata: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(init_if_needed, 
+        payer = owner,
+        associated_token::mint = stablecoin_mint,
+        associated_token::authority = owner
+    )]
+    pub owner_stablecoin_ata: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// CHECK: This is the HOUSE Squads multi-sig
+    #[account(constraint = house.key() == Pubkey::from_str(HOUSE).unwrap())]
+    pub house: AccountInfo<'info>,
+
+    #[account(init_if_needed, payer = owner,
+        associated_token::mint = stablecoin_mint,
+        associated_token::authority = house
+    )]
+    pub house_stablecoin_ata: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> WithdrawSolAsSpl<'info> {
+    /// Wraps `amount` lamports from the vault, swaps them through the configured router using
+    /// the client-built `swap_data` and `swap_accounts`, then splits the output like `withdraw_spl`
+    pub fn withdraw_sol_as_spl(
+        &mut self,
+        amount: u64,
+        minimum_out: u64,
+        swap_data: Vec<u8>,
+        swap_accounts: &'info [AccountInfo<'info>],
+        bumps: &WithdrawSolAsSplBumps,
+    ) -> Result<()> {
+        require!(minimum_out > 0, CustomError::InvalidWithdrawalAmount);
+
+        // Velocity limits, if the owner has configured any
+        WithdrawalLimit::enforce(&self.withdrawal_limit, amount)?;
+
+        let stablecoin_before = self.merchant_stablecoin_ata.amount;
+
+        // Wrap the vault's lamports into the temporary wSOL account
+        let merchant_key = self.merchant.key();
+        let vault_seeds = &[
+            b"vault",
+            merchant_key.as_ref(),
+            &[self.merchant.vault_bump],
+        ];
+        transfer(
+            CpiContext::new_with_signer(
+                self.system_program.to_account_info(),
+                Transfer {
+                    from: self.vault.to_account_info(),
+                    to: self.swap_wsol.to_account_info(),
+                },
+                &[&vault_seeds[..]],
+            ),
+            amount,
+        )?;
+        sync_native(CpiContext::new(
+            self.token_program.to_account_info(),
+            SyncNative {
+                account: self.swap_wsol.to_account_info(),
+            },
+        ))?;
+
+        // Swap through the router. Only the swap authority signs for the program, and all it
+        // controls is the wrapped `amount`, so the client-built accounts can't reach anything else.
+        let swap_authority_key = self.swap_authority.key();
+        let swap_authority_seeds = &[
+            b"swap_authority".as_ref(),
+            merchant_key.as_ref(),
+            &[bumps.swap_authority],
+        ];
+        let swap_ix = Instruction {
+            program_id: self.swap_program.key(),
+            accounts: swap_accounts
+                .iter()
+                .map(|account| AccountMeta {
+                    pubkey: account.key(),
+                    is_signer: account.is_signer || account.key() == swap_authority_key,
+                    is_writable: account.is_writable,
+                })
+                .collect(),
+            data: swap_data,
+        };
+        let mut swap_infos = swap_accounts.to_vec();
+        swap_infos.push(self.swap_authority.to_account_info());
+        swap_infos.push(self.swap_program.to_account_info());
+        invoke_signed(&swap_ix, &swap_infos, &[swap_authority_seeds])?;
+
+        // The router must deliver at least `minimum_out` to the merchant
+        self.merchant_stablecoin_ata.reload()?;
+        let amount_out = self.merchant_stablecoin_ata.amount
+            .checked_sub(stablecoin_before)
+            .ok_or(CustomError::SwapSlippageExceeded)?;
+        require!(amount_out >= minimum_out, CustomError::SwapSlippageExceeded);
+
+        // Close the temporary account into the vault, which takes back any wSOL the router left,
+        // then return its rent to the owner who paid it
+        self.swap_wsol.reload()?;
+        let swap_wsol_rent = self.swap_wsol.to_account_info().lamports()
+            .checked_sub(self.swap_wsol.amount)
+            .ok_or(CustomError::ArithmeticOverflow)?;
+        close_account(CpiContext::new_with_signer(
+            self.token_program.to_account_info(),
+            CloseAccount {
+                account: self.swap_wsol.to_account_info(),
+                destination: self.vault.to_account_info(),
+                authority: self.swap_authority.to_account_info(),
+            },
+            &[swap_authority_seeds],
+        ))?;
+        transfer(
+            CpiContext::new_with_signer(
+                self.system_program.to_account_info(),
+                Transfer {
+                    from: self.vault.to_account_info(),
+                    to: self.owner.to_account_info(),
+                },
+                &[&vault_seeds[..]],
+            ),
+            swap_wsol_rent,
+        )?;
+
+        let owner_key = self.owner.key();
+        let seeds = &[
+            b"merchant".as_ref(),
+            self.merchant.entity_name.as_bytes(),
+            owner_key.as_ref(),
+            &[self.merchant.merchant_bump],
+        ];
+
+        // Calculate shares of the swap output using basis points for better precision
+        let owner_amount = amount_out
+            .checked_mul(OWNER_SHARE_BASIS_POINTS)
+            .ok_or(CustomError::ArithmeticOverflow)?
+            .checked_div(BASIS_POINTS_DIVISOR)
+            .ok_or(CustomError::ArithmeticOverflow)?;
+            
+        let house_amount = amount_out
+            .checked_mul(HOUSE_SHARE_BASIS_POINTS)
+            .ok_or(CustomError::ArithmeticOverflow)?
+            .checked_div(BASIS_POINTS_DIVISOR)
+            .ok_or(CustomError::ArithmeticOverflow)?;
+
+        // Validate amounts
+        require!(owner_amount > 0, CustomError::InvalidWithdrawalAmount);
+        require!(house_amount > 0, CustomError::InvalidWithdrawalAmount);
+        require!(owner_amount.checked_add(house_amount).unwrap() <= amount_out, CustomError::ArithmeticOverflow);
+
+        // Transfer the owner's share
+        self.transfer_spl_tokens(&self.owner_stablecoin_ata.to_account_info(), owner_amount, seeds)?;
+
+        // Transfer house's share
+        self.transfer_spl_tokens(&self.house_stablecoin_ata.to_account_info(), house_amount, seeds)?;
+
+        // Emit event
+        let ctx = EventCpiContext::new(&self.event_authority, bumps.event_authority);
+        emit_cpi!(WithdrawSolAsSplProcessed {
+            header: EventHeader::sol(self.merchant.key(), self.owner.key(), self.vault.lamports())?,
+            amount,
+            amount_out,
+            owner_amount,
+            house_amount,
+            output_mint: self.stablecoin_mint.key(),
+        });
+
+        Ok(())
+    }
+
+    /// Helper function to reduce code duplication for SPL token transfers
+    fn transfer_spl_tokens(&self, to: &AccountInfo<'info>, amount: u64, seeds: &[&[u8]]) -> Result<()> {
+        anchor_spl::token_interface::transfer_checked(
+            CpiContext::new_with_signer(
+                self.token_program.to_account_info(),
+                anchor_spl::token_interface::TransferChecked {
+                    from: self.merchant_stablecoin_ata.to_account_info(),
+                    mint: self.stablecoin_mint.to_account_info(),
+                    to: to.clone(),
+                    authority: self.merchant.to_account_info(),
+                },
+                &[seeds],
+            ),
+            amount,
+            self.stablecoin_mint.decimals,
+        )
+    }
+}
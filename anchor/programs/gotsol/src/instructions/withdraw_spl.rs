@@ -1,16 +1,18 @@
 use anchor_lang::prelude::*;
 
 use anchor_spl::{
-    associated_token::AssociatedToken,
+    associated_token::{get_associated_token_address_with_program_id, AssociatedToken},
     token_interface::{Mint, TokenAccount, TokenInterface},
 };
 
 use std::str::FromStr;
 
-use crate::state::Merchant;
+use crate::state::{Global, Merchant, SponsorshipBudget, WithdrawalLimit};
 use crate::constants::*;
 use crate::errors::*;
+use crate::event_cpi::EventCpiContext;
 use crate::events::*;
+use crate::sponsorship::{create_ata_if_missing, rent_recoup_amount};
 
 const OWNER_SHARE_BASIS_POINTS: u64 = 9900; // 99%
 const HOUSE_SHARE_BASIS_POINTS: u64 = 100;  // 1%
@@ -18,44 +20,61 @@ const BASIS_POINTS_DIVISOR: u64 = 10000;
 // const MINIMUM_WITHDRAWAL_SOL_LAMPORTS: u64 = 1000; // 1000 lamports = 0.000001 SOL
 const MINIMUM_WITHDRAWAL_SPL_UNITS: u64 = 100; 
 
+#[event_cpi]
 #[derive(Accounts)]
-#[instruction(amount: u64)]
 pub struct WithdrawSpl<'info> {
-    #[account(mut,
-        constraint = amount >= MINIMUM_WITHDRAWAL_SPL_UNITS @ CustomError::BelowMinimumWithdrawal)]
+    // our node's fee payer; sponsors the owner and house ATAs if they don't exist yet
+    #[account(mut)]
+    pub fee_payer: Option<Signer<'info>>,
+
+    #[account(mut)]
     pub owner: Signer<'info>,
 
     #[account(
-        seeds = [b"merchant", merchant.entity_name.as_str().as_bytes(), owner.key().as_ref()], 
+        seeds = [b"merchant", merchant.entity_name.as_bytes(), owner.key().as_ref()], 
         bump = merchant.merchant_bump,
     )]
     pub merchant: Box<Account<'info, Merchant>>,
 
+    // caps what our fee payer fronts for this merchant; required whenever fee_payer signs
+    #[account(mut,
+        seeds = [b"sponsorship_budget", merchant.key().as_ref()],
+        bump = sponsorship_budget.bump)]
+    pub sponsorship_budget: Option<Box<Account<'info, SponsorshipBudget>>>,
+
     pub stablecoin_mint: Box<InterfaceAccount<'info, Mint>>,
 
+    // sales revenue only; tips are held in the merchant's tip_pool and never take the house share
     #[account(mut,
         associated_token::mint = stablecoin_mint,
         associated_token::authority = merchant,
-        constraint = merchant_stablecoin_ata.amount >= amount @ CustomError::InsufficientFunds,
     )]
     pub merchant_stablecoin_ata: Box<InterfaceAccount<'info, TokenAccount>>,
 
-    #[account(init_if_needed, 
-        payer = owner,
-        associated_token::mint = stablecoin_mint,
-        associated_token::authority = owner
-    )]
-    pub owner_stablecoin_ata: Box<InterfaceAccount<'info, TokenAccount>>,
+    /// CHECK: the merchant's withdrawal limit for this mint; enforced only once the owner has created it
+    #[account(mut, seeds = [b"withdrawal_limit", merchant.key().as_ref(), stablecoin_mint.key().as_ref()], bump)]
+    pub withdrawal_limit: AccountInfo<'info>,
+
+    /// CHECK: the owner's ATA for the mint, created in the handler if missing
+    #[account(mut,
+        address = get_associated_token_address_with_program_id(&owner.key(), &stablecoin_mint.key(), &token_program.key()))]
+    pub owner_stablecoin_ata: AccountInfo<'info>,
 
     /// CHECK: This is the HOUSE Squads multi-sig
     #[account(constraint = house.key() == Pubkey::from_str(HOUSE).unwrap())]
     pub house: AccountInfo<'info>,
 
-    #[account(init_if_needed, payer = owner,
-        associated_token::mint = stablecoin_mint,
-        associated_token::authority = house
-    )]
-    pub house_stablecoin_ata: Box<InterfaceAccount<'info, TokenAccount>>,
+    /// CHECK: the house's ATA for the mint, created in the handler if missing
+    #[account(mut,
+        address = get_associated_token_address_with_program_id(&house.key(), &stablecoin_mint.key(), &token_program.key()))]
+    pub house_stablecoin_ata: AccountInfo<'info>,
+
+    // global is required whenever fee_payer signs; with fee_payer_stablecoin_ata, sponsored ATA rent is recouped at its rate
+    #[account(seeds = [b"global"], bump = global.bump)]
+    pub global: Option<Box<Account<'info, Global>>>,
+
+    #[account(mut, token::mint = stablecoin_mint)]
+    pub fee_payer_stablecoin_ata: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
 
     pub associated_token_program: Program<'info, AssociatedToken>,
     pub token_program: Interface<'info, TokenInterface>,
@@ -63,7 +82,23 @@ pub struct WithdrawSpl<'info> {
 }
 
 impl<'info> WithdrawSpl<'info> {
-    pub fn withdraw_spl(&mut self, amount: u64) -> Result<()> {
+    /// Withdraws `amount` units, or the merchant ATA's full balance when `None` (`withdraw_spl_all`)
+    pub fn withdraw_spl(&mut self, amount: Option<u64>, bumps: &WithdrawSplBumps) -> Result<()> {
+        // Only fee payers approved in the global config may sponsor
+        Global::require_approved_fee_payer(&self.global, self.fee_payer.as_ref().map(|fee_payer| fee_payer.key()))?;
+
+        let recouped = self.create_atas(bumps)?;
+        let available = self.merchant_stablecoin_ata.amount
+            .checked_sub(recouped)
+            .ok_or(CustomError::InsufficientFunds)?;
+        let amount = amount.unwrap_or(available);
+
+        require!(amount >= MINIMUM_WITHDRAWAL_SPL_UNITS, CustomError::BelowMinimumWithdrawal);
+        require!(available >= amount, CustomError::InsufficientFunds);
+
+        // Velocity limits, if the owner has configured any
+        WithdrawalLimit::enforce(&self.withdrawal_limit, amount)?;
+
         // Calculate shares using basis points for better precision
         let owner_amount = amount
             .checked_mul(OWNER_SHARE_BASIS_POINTS)
@@ -96,17 +131,88 @@ impl<'info> WithdrawSpl<'info> {
         // Transfer house's share
         self.transfer_spl_tokens(&self.house_stablecoin_ata.to_account_info(), house_amount, seeds)?;
 
+        // Pay back the fee payer for the ATA rent it fronted
+        if let Some(fee_payer_stablecoin_ata) = &self.fee_payer_stablecoin_ata {
+            if recouped > 0 {
+                self.transfer_spl_tokens(&fee_payer_stablecoin_ata.to_account_info(), recouped, seeds)?;
+            }
+        }
+
         // Emit event
-        emit!(WithdrawSplProcessed {
+        self.merchant_stablecoin_ata.reload()?;
+        let ctx = EventCpiContext::new(&self.event_authority, bumps.event_authority);
+        emit_cpi!(WithdrawSplProcessed {
+            header: EventHeader::token(
+                self.merchant.key(),
+                self.owner.key(),
+                self.stablecoin_mint.key(),
+                self.merchant_stablecoin_ata.amount,
+            )?,
             amount,
             owner_amount,
             house_amount,
-            mint: self.stablecoin_mint.key(),
         });
 
         Ok(())
     }
 
+    /// Creates the owner and house ATAs if needed and returns the stablecoin amount owed to
+    /// the fee payer for their rent, which is 0 unless the fee payer asked to recoup it
+    fn create_atas(&mut self, bumps: &WithdrawSplBumps) -> Result<u64> {
+        let payer = match &self.fee_payer {
+            Some(fee_payer) => fee_payer.to_account_info(),
+            None => self.owner.to_account_info(),
+        };
+
+        let mut lamports = 0u64;
+        for (ata, authority) in [
+            (&self.owner_stablecoin_ata, self.owner.to_account_info()),
+            (&self.house_stablecoin_ata, self.house.to_account_info()),
+        ] {
+            let rent = create_ata_if_missing(
+                &payer,
+                ata,
+                &authority,
+                &self.stablecoin_mint.to_account_info(),
+                &self.associated_token_program.to_account_info(),
+                &self.token_program.to_account_info(),
+                &self.system_program.to_account_info(),
+            )?;
+            lamports = lamports.checked_add(rent).ok_or(CustomError::ArithmeticOverflow)?;
+        }
+
+        let Some(fee_payer) = &self.fee_payer else {
+            return Ok(0);
+        };
+
+        // Count what our fee payer fronted against the merchant's budget
+        let ctx = EventCpiContext::new(&self.event_authority, bumps.event_authority);
+        SponsorshipBudget::charge(&mut self.sponsorship_budget, Some(fee_payer.key()), lamports, &ctx)?;
+
+        if lamports == 0 {
+            return Ok(0);
+        }
+
+        let recouped = match (&self.global, &self.fee_payer_stablecoin_ata) {
+            (Some(global), Some(fee_payer_stablecoin_ata)) => {
+                require_keys_eq!(fee_payer_stablecoin_ata.owner, fee_payer.key(), CustomError::InvalidRentPayer);
+                rent_recoup_amount(lamports, global.rent_recoup_rate, self.stablecoin_mint.decimals)?
+            }
+            _ => 0,
+        };
+
+        // Emit event
+        let ctx = EventCpiContext::new(&self.event_authority, bumps.event_authority);
+        emit_cpi!(AtaRentSponsored {
+            header: EventHeader::new(Some(self.merchant.key()), self.owner.key(), Some(self.stablecoin_mint.key()), None)?,
+            fee_payer: fee_payer.key(),
+            lamports,
+            recouped,
+        });
+
+        Ok(recouped)
+    }
+
     /// Helper function to reduce code duplication for SPL token transfers
     fn transfer_spl_tokens(&self, to: &AccountInfo<'info>, amount: u64, seeds: &[&[u8]]) -> Result<()> {
         anchor_spl::token_interface::transfer_checked(
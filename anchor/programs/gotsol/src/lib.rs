@@ -2,12 +2,18 @@
 #![allow(deprecated)]
 
 use anchor_lang::prelude::*;
+#[cfg(not(feature = "no-entrypoint"))]
 use solana_security_txt::security_txt;
 
 mod constants;
 // mod context;
-mod errors;
-mod events;
+pub mod errors;
+mod event_cpi;
+pub mod events;
+mod loyalty;
+mod oracle;
+mod redemptions;
+mod sponsorship;
 // mod state;
 
 // use crate::context::*;
@@ -16,6 +22,7 @@ pub mod instructions;
 pub mod state;
 
 use instructions::*;
+use state::{CouponDiscount, OrderItemRefund};
 
 declare_id!("E6MRtJg483SVLY7EvryXJXPSLybRZyCCTsDY4BhNQYb");
 
@@ -41,12 +48,22 @@ pub mod gotsol {
     }
 
     pub fn withdraw_spl(ctx: Context<WithdrawSpl>, amount: u64) -> Result<()> {
-        ctx.accounts.withdraw_spl(amount)?;
+        ctx.accounts.withdraw_spl(Some(amount), &ctx.bumps)?;
         Ok(())
     }
 
     pub fn withdraw_sol(ctx: Context<WithdrawSol>, amount: u64) -> Result<()> {
-        ctx.accounts.withdraw_sol(amount)?;
+        ctx.accounts.withdraw_sol(Some(amount), &ctx.bumps)?;
+        Ok(())
+    }
+
+    pub fn withdraw_spl_all(ctx: Context<WithdrawSpl>) -> Result<()> {
+        ctx.accounts.withdraw_spl(None, &ctx.bumps)?;
+        Ok(())
+    }
+
+    pub fn withdraw_sol_all(ctx: Context<WithdrawSol>) -> Result<()> {
+        ctx.accounts.withdraw_sol(None, &ctx.bumps)?;
         Ok(())
     }
 
@@ -66,7 +83,7 @@ pub mod gotsol {
     }
 
     pub fn close_merchant(ctx: Context<CloseMerchant>) -> Result<()> {
-        ctx.accounts.close_merchant()?;
+        ctx.accounts.close_merchant(&ctx.bumps)?;
         Ok(())
     }
 
@@ -74,4 +91,219 @@ pub mod gotsol {
         ctx.accounts.close_refund()?;
         Ok(())
     }
+
+    pub fn close_legacy_refund(ctx: Context<CloseLegacyRefund>, original_tx_sig: String) -> Result<()> {
+        ctx.accounts.close_legacy_refund(original_tx_sig)?;
+        Ok(())
+    }
+
+    pub fn create_invoice(ctx: Context<CreateInvoice>, invoice_id: u64, amount: u64, due_date: i64, memo_hash: [u8; 32]) -> Result<()> {
+        ctx.accounts.create_invoice(invoice_id, amount, due_date, memo_hash, &ctx.bumps)?;
+        Ok(())
+    }
+
+    pub fn pay_invoice(ctx: Context<PayInvoice>, amount: u64, coupon_code: Option<Vec<u8>>) -> Result<()> {
+        ctx.accounts.pay_invoice(amount, coupon_code, &ctx.bumps)?;
+        Ok(())
+    }
+
+    pub fn cancel_invoice(ctx: Context<CancelInvoice>) -> Result<()> {
+        ctx.accounts.cancel_invoice()?;
+        Ok(())
+    }
+
+    pub fn close_invoice(ctx: Context<CloseInvoice>) -> Result<()> {
+        ctx.accounts.close_invoice()?;
+        Ok(())
+    }
+
+    pub fn create_tip_pool(ctx: Context<CreateTipPool>) -> Result<()> {
+        ctx.accounts.create_tip_pool(&ctx.bumps)?;
+        Ok(())
+    }
+
+    pub fn pay_spl(ctx: Context<PaySpl>, amount: u64, tip: u64, employee: Option<Pubkey>, points_to_redeem: u64, coupon_code: Option<Vec<u8>>) -> Result<()> {
+        ctx.accounts.pay_spl(amount, tip, employee, points_to_redeem, coupon_code, &ctx.bumps)?;
+        Ok(())
+    }
+
+    pub fn distribute_tips<'info>(ctx: Context<'_, '_, 'info, 'info, DistributeTips<'info>>, amounts: Vec<u64>) -> Result<()> {
+        ctx.accounts.distribute_tips(amounts, ctx.remaining_accounts, &ctx.bumps)?;
+        Ok(())
+    }
+
+    pub fn authorize_payment(ctx: Context<AuthorizePayment>, authorization_id: u64, amount: u64, expires_at: i64) -> Result<()> {
+        ctx.accounts.authorize_payment(authorization_id, amount, expires_at, &ctx.bumps)?;
+        Ok(())
+    }
+
+    pub fn capture_payment(ctx: Context<CapturePayment>, amount: u64) -> Result<()> {
+        ctx.accounts.capture_payment(amount, &ctx.bumps)?;
+        Ok(())
+    }
+
+    pub fn void_authorization(ctx: Context<VoidAuthorization>) -> Result<()> {
+        ctx.accounts.void_authorization(&ctx.bumps)?;
+        Ok(())
+    }
+
+    pub fn create_subscription(ctx: Context<CreateSubscription>, amount: u64, interval: i64, max_charges: u64) -> Result<()> {
+        ctx.accounts.create_subscription(amount, interval, max_charges, &ctx.bumps)?;
+        Ok(())
+    }
+
+    pub fn charge_subscription(ctx: Context<ChargeSubscription>) -> Result<()> {
+        ctx.accounts.charge_subscription(&ctx.bumps)?;
+        Ok(())
+    }
+
+    pub fn cancel_subscription(ctx: Context<CancelSubscription>) -> Result<()> {
+        ctx.accounts.cancel_subscription()?;
+        Ok(())
+    }
+
+    pub fn purchase_gift_card(ctx: Context<PurchaseGiftCard>, card_id: u64, amount: u64, holder: Option<Pubkey>, code_key: Option<Pubkey>, expires_at: i64) -> Result<()> {
+        ctx.accounts.purchase_gift_card(card_id, amount, holder, code_key, expires_at, &ctx.bumps)?;
+        Ok(())
+    }
+
+    pub fn close_gift_card(ctx: Context<CloseGiftCard>) -> Result<()> {
+        ctx.accounts.close_gift_card(&ctx.bumps)?;
+        Ok(())
+    }
+
+    pub fn create_loyalty_program(ctx: Context<CreateLoyaltyProgram>, earn_basis_points: u64, redemption_basis_points: u64, decimals: u8, non_transferable: bool) -> Result<()> {
+        ctx.accounts.create_loyalty_program(earn_basis_points, redemption_basis_points, decimals, non_transferable, &ctx.bumps)?;
+        Ok(())
+    }
+
+    pub fn update_loyalty_program(ctx: Context<UpdateLoyaltyProgram>, earn_basis_points: u64, redemption_basis_points: u64) -> Result<()> {
+        ctx.accounts.update_loyalty_program(earn_basis_points, redemption_basis_points, &ctx.bumps)?;
+        Ok(())
+    }
+
+    pub fn create_coupon(ctx: Context<CreateCoupon>, code_hash: [u8; 32], discount: CouponDiscount, max_redemptions: u32, expires_at: i64) -> Result<()> {
+        ctx.accounts.create_coupon(code_hash, discount, max_redemptions, expires_at, &ctx.bumps)?;
+        Ok(())
+    }
+
+    pub fn close_coupon(ctx: Context<CloseCoupon>) -> Result<()> {
+        ctx.accounts.close_coupon()?;
+        Ok(())
+    }
+
+    pub fn add_manager(ctx: Context<AddManager>, manager: Pubkey) -> Result<()> {
+        ctx.accounts.add_manager(manager, &ctx.bumps)?;
+        Ok(())
+    }
+
+    pub fn remove_manager(ctx: Context<RemoveManager>) -> Result<()> {
+        ctx.accounts.remove_manager()?;
+        Ok(())
+    }
+
+    pub fn create_product(ctx: Context<CreateProduct>, sku: String, price: u64, stock: u64, tax_basis_points: u16) -> Result<()> {
+        ctx.accounts.create_product(sku, price, stock, tax_basis_points, &ctx.bumps)?;
+        Ok(())
+    }
+
+    pub fn update_product(ctx: Context<UpdateProduct>, price: Option<u64>, stock: Option<u64>, tax_basis_points: Option<u16>, active: Option<bool>) -> Result<()> {
+        ctx.accounts.update_product(price, stock, tax_basis_points, active)?;
+        Ok(())
+    }
+
+    pub fn delete_product(ctx: Context<DeleteProduct>) -> Result<()> {
+        ctx.accounts.delete_product()?;
+        Ok(())
+    }
+
+    pub fn pay_order<'info>(ctx: Context<'_, '_, 'info, 'info, PayOrder<'info>>, order_id: u64, quantities: Vec<u64>, expected_total: u64, tip: u64, coupon_code: Option<Vec<u8>>) -> Result<()> {
+        ctx.accounts.pay_order(order_id, quantities, expected_total, tip, coupon_code, ctx.remaining_accounts, &ctx.bumps)?;
+        Ok(())
+    }
+
+    pub fn refund_order_items(ctx: Context<RefundOrderItems>, items: Vec<OrderItemRefund>) -> Result<()> {
+        ctx.accounts.refund_order_items(items, &ctx.bumps)?;
+        Ok(())
+    }
+
+    pub fn close_order(ctx: Context<CloseOrder>) -> Result<()> {
+        ctx.accounts.close_order()?;
+        Ok(())
+    }
+
+    pub fn pay_sol_usd(ctx: Context<PaySolUsd>, payment_id: u64, usd_amount: u64, max_lamports: u64) -> Result<()> {
+        ctx.accounts.pay_sol_usd(payment_id, usd_amount, max_lamports, &ctx.bumps)?;
+        Ok(())
+    }
+
+    pub fn close_payment(ctx: Context<ClosePayment>) -> Result<()> {
+        ctx.accounts.close_payment()?;
+        Ok(())
+    }
+
+    pub fn initialize_global(ctx: Context<InitializeGlobal>, swap_router: Pubkey) -> Result<()> {
+        ctx.accounts.initialize_global(swap_router, &ctx.bumps)?;
+        Ok(())
+    }
+
+    pub fn set_swap_router(ctx: Context<SetSwapRouter>, swap_router: Pubkey) -> Result<()> {
+        ctx.accounts.set_swap_router(swap_router)?;
+        Ok(())
+    }
+
+    pub fn withdraw_sol_as_spl<'info>(ctx: Context<'_, '_, 'info, 'info, WithdrawSolAsSpl<'info>>, amount: u64, minimum_out: u64, swap_data: Vec<u8>) -> Result<()> {
+        ctx.accounts.withdraw_sol_as_spl(amount, minimum_out, swap_data, ctx.remaining_accounts, &ctx.bumps)?;
+        Ok(())
+    }
+
+    pub fn withdraw_many<'info>(ctx: Context<'_, '_, 'info, 'info, WithdrawMany<'info>>, amounts: Vec<Option<u64>>) -> Result<()> {
+        ctx.accounts.withdraw_many(amounts, ctx.remaining_accounts, &ctx.bumps)?;
+        Ok(())
+    }
+
+    pub fn set_payout_schedule(ctx: Context<SetPayoutSchedule>, interval: i64, minimum_threshold: u64, destination: Pubkey, bounty_basis_points: u16) -> Result<()> {
+        ctx.accounts.set_payout_schedule(interval, minimum_threshold, destination, bounty_basis_points, &ctx.bumps)?;
+        Ok(())
+    }
+
+    pub fn cancel_payout_schedule(ctx: Context<CancelPayoutSchedule>) -> Result<()> {
+        ctx.accounts.cancel_payout_schedule()?;
+        Ok(())
+    }
+
+    pub fn crank_payout(ctx: Context<CrankPayout>) -> Result<()> {
+        ctx.accounts.crank_payout(&ctx.bumps)?;
+        Ok(())
+    }
+
+    pub fn set_sol_withdrawal_limit(ctx: Context<SetSolWithdrawalLimit>, max_per_withdrawal: u64, max_per_day: u64) -> Result<()> {
+        ctx.accounts.set_sol_withdrawal_limit(max_per_withdrawal, max_per_day, &ctx.bumps)?;
+        Ok(())
+    }
+
+    pub fn set_spl_withdrawal_limit(ctx: Context<SetSplWithdrawalLimit>, max_per_withdrawal: u64, max_per_day: u64) -> Result<()> {
+        ctx.accounts.set_spl_withdrawal_limit(max_per_withdrawal, max_per_day, &ctx.bumps)?;
+        Ok(())
+    }
+
+    pub fn set_rent_recoup_rate(ctx: Context<SetRentRecoupRate>, rent_recoup_rate: u64) -> Result<()> {
+        ctx.accounts.set_rent_recoup_rate(rent_recoup_rate)?;
+        Ok(())
+    }
+
+    pub fn set_sponsorship_budget(ctx: Context<SetSponsorshipBudget>, allowance_per_period: u64, period: i64) -> Result<()> {
+        ctx.accounts.set_sponsorship_budget(allowance_per_period, period, &ctx.bumps)?;
+        Ok(())
+    }
+
+    pub fn top_up_sponsorship_budget(ctx: Context<TopUpSponsorshipBudget>, lamports: u64) -> Result<()> {
+        ctx.accounts.top_up_sponsorship_budget(lamports)?;
+        Ok(())
+    }
+
+    pub fn set_fee_payer_approval(ctx: Context<SetFeePayerApproval>, fee_payer: Pubkey, approved: bool) -> Result<()> {
+        ctx.accounts.set_fee_payer_approval(fee_payer, approved)?;
+        Ok(())
+    }
 }
\ No newline at end of file
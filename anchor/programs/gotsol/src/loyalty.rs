@@ -0,0 +1,164 @@
+use anchor_lang::prelude::*;
+
+use anchor_spl::{
+    token_2022::Token2022,
+    token_interface::{burn, mint_to, Burn, Mint, MintTo, TokenAccount},
+};
+
+use crate::errors::*;
+use crate::event_cpi::EventCpiContext;
+use crate::events::*;
+use crate::state::{LoyaltyProgram, Merchant};
+
+const BASIS_POINTS_DIVISOR: u64 = 10000;
+
+pub type LoyaltyAccounts<'a, 'info> = (
+    &'a LoyaltyProgram,
+    &'a InterfaceAccount<'info, Mint>,
+    &'a InterfaceAccount<'info, TokenAccount>,
+    &'a Program<'info, Token2022>,
+);
+
+/// Returns a payment's optional loyalty accounts after checking they belong together and to `customer`
+pub fn loyalty_accounts<'a, 'info>(
+    loyalty_program: &'a Option<Box<Account<'info, LoyaltyProgram>>>,
+    points_mint: &'a Option<Box<InterfaceAccount<'info, Mint>>>,
+    customer_points_ata: &'a Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+    points_token_program: &'a Option<Program<'info, Token2022>>,
+    customer: Pubkey,
+) -> Result<LoyaltyAccounts<'a, 'info>> {
+    let (Some(loyalty_program), Some(points_mint), Some(customer_points_ata), Some(points_token_program)) = (
+        loyalty_program.as_deref(),
+        points_mint.as_deref(),
+        customer_points_ata.as_deref(),
+        points_token_program.as_ref(),
+    ) else {
+        return err!(CustomError::LoyaltyProgramRequired);
+    };
+
+    require_keys_eq!(points_mint.key(), loyalty_program.points_mint, CustomError::MintMismatch);
+    require_keys_eq!(customer_points_ata.mint, points_mint.key(), CustomError::MintMismatch);
+    require_keys_eq!(customer_points_ata.owner, customer, CustomError::InvalidLoyaltyAccount);
+
+    Ok((loyalty_program, points_mint, customer_points_ata, points_token_program))
+}
+
+/// Like `loyalty_accounts`, but `None` when the merchant runs no loyalty program
+pub fn optional_loyalty_accounts<'a, 'info>(
+    loyalty_program: &'a Option<Box<Account<'info, LoyaltyProgram>>>,
+    points_mint: &'a Option<Box<InterfaceAccount<'info, Mint>>>,
+    customer_points_ata: &'a Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+    points_token_program: &'a Option<Program<'info, Token2022>>,
+    customer: Pubkey,
+) -> Result<Option<LoyaltyAccounts<'a, 'info>>> {
+    if loyalty_program.is_none() {
+        return Ok(None);
+    }
+    loyalty_accounts(loyalty_program, points_mint, customer_points_ata, points_token_program, customer).map(Some)
+}
+
+/// Burns `points` from the customer and returns the discount they're worth
+pub fn redeem_points<'info>(
+    accounts: LoyaltyAccounts<'_, 'info>,
+    customer: &Signer<'info>,
+    merchant: Pubkey,
+    points: u64,
+    ctx: &EventCpiContext,
+) -> Result<u64> {
+    let (loyalty_program, points_mint, customer_points_ata, points_token_program) = accounts;
+
+    let discount = points
+        .checked_mul(loyalty_program.redemption_basis_points)
+        .ok_or(CustomError::ArithmeticOverflow)?
+        .checked_div(BASIS_POINTS_DIVISOR)
+        .ok_or(CustomError::ArithmeticOverflow)?;
+    require!(discount > 0, CustomError::InvalidWithdrawalAmount);
+
+    // Burn the redeemed points from the customer
+    burn(
+        CpiContext::new(
+            points_token_program.to_account_info(),
+            Burn {
+                mint: points_mint.to_account_info(),
+                from: customer_points_ata.to_account_info(),
+                authority: customer.to_account_info(),
+            },
+        ),
+        points,
+    )?;
+
+    // Emit event
+    emit_cpi!(LoyaltyPointsRedeemed {
+        header: EventHeader::new(Some(merchant), customer.key(), Some(points_mint.key()), None)?,
+        customer: customer.key(),
+        points,
+        discount,
+    });
+
+    Ok(discount)
+}
+
+/// Mints the points earned on `paid` when the merchant runs a loyalty program
+pub fn award_points<'info>(
+    accounts: Option<LoyaltyAccounts<'_, 'info>>,
+    merchant: &Account<'info, Merchant>,
+    customer: Pubkey,
+    paid: u64,
+    ctx: &EventCpiContext,
+) -> Result<()> {
+    let Some(accounts) = accounts else {
+        return Ok(());
+    };
+    let points_mint = accounts.1.key();
+
+    let points = earn_points(accounts, merchant, paid)?;
+    if points == 0 {
+        return Ok(());
+    }
+
+    // Emit event
+    emit_cpi!(LoyaltyPointsEarned {
+        header: EventHeader::new(Some(merchant.key()), customer, Some(points_mint), None)?,
+        customer,
+        points,
+    });
+
+    Ok(())
+}
+
+/// Mints the points earned on `paid` to the customer with the merchant PDA as mint authority.
+/// Returns how many points were minted.
+pub fn earn_points<'info>(accounts: LoyaltyAccounts<'_, 'info>, merchant: &Account<'info, Merchant>, paid: u64) -> Result<u64> {
+    let (loyalty_program, points_mint, customer_points_ata, points_token_program) = accounts;
+
+    let points = paid
+        .checked_mul(loyalty_program.earn_basis_points)
+        .ok_or(CustomError::ArithmeticOverflow)?
+        .checked_div(BASIS_POINTS_DIVISOR)
+        .ok_or(CustomError::ArithmeticOverflow)?;
+    if points == 0 {
+        return Ok(0);
+    }
+
+    let seeds = &[
+        b"merchant".as_ref(),
+        merchant.entity_name.as_bytes(),
+        merchant.owner.as_ref(),
+        &[merchant.merchant_bump],
+    ];
+
+    mint_to(
+        CpiContext::new_with_signer(
+            points_token_program.to_account_info(),
+            MintTo {
+                mint: points_mint.to_account_info(),
+                to: customer_points_ata.to_account_info(),
+                authority: merchant.to_account_info(),
+            },
+            &[seeds],
+        ),
+        points,
+    )?;
+
+    Ok(points)
+}
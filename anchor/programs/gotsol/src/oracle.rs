@@ -0,0 +1,111 @@
+use anchor_lang::prelude::*;
+use std::str::FromStr;
+
+use crate::constants::*;
+use crate::errors::*;
+
+// Anchor discriminator of the Pyth receiver's PriceUpdateV2 account
+const PRICE_UPDATE_V2_DISCRIMINATOR: [u8; 8] = [34, 241, 35, 99, 157, 126, 244, 205];
+
+// How far a publish time may run ahead of the cluster clock, which lags wall time slightly
+const MAX_PUBLISH_TIME_SKEW_SECONDS: i64 = 10;
+
+#[derive(AnchorDeserialize)]
+enum VerificationLevel {
+    Partial { _num_signatures: u8 },
+    Full,
+}
+
+#[derive(AnchorDeserialize)]
+struct PriceFeedMessage {
+    feed_id: [u8; 32],
+    price: i64,
+    conf: u64,
+    exponent: i32,
+    publish_time: i64,
+    _prev_publish_time: i64,
+    _ema_price: i64,
+    _ema_conf: u64,
+}
+
+#[derive(AnchorDeserialize)]
+struct PriceUpdateV2 {
+    _write_authority: Pubkey,
+    verification_level: VerificationLevel,
+    price_message: PriceFeedMessage,
+    _posted_slot: u64,
+}
+
+/// A validated oracle price: `price * 10^expo` USD per whole token
+pub struct OraclePrice {
+    pub price: i64,
+    pub conf: u64,
+    pub expo: i32,
+    pub publish_time: i64,
+}
+
+/// Reads a fully verified Pyth price for `feed_id`, rejecting stale, future-dated or low-confidence prices
+pub fn load_price(
+    price_update: &AccountInfo,
+    feed_id: &[u8; 32],
+    max_age_seconds: i64,
+    max_conf_basis_points: u64,
+) -> Result<OraclePrice> {
+    require_keys_eq!(*price_update.owner, Pubkey::from_str(PYTH_RECEIVER_PROGRAM).unwrap(), CustomError::InvalidPriceAccount);
+
+    let data = price_update.try_borrow_data()?;
+    require!(data.len() > 8 && data[..8] == PRICE_UPDATE_V2_DISCRIMINATOR, CustomError::InvalidPriceAccount);
+    let update = PriceUpdateV2::deserialize(&mut &data[8..])
+        .map_err(|_| error!(CustomError::InvalidPriceAccount))?;
+
+    require!(matches!(update.verification_level, VerificationLevel::Full), CustomError::InvalidPriceAccount);
+
+    let message = update.price_message;
+    require!(message.feed_id == *feed_id, CustomError::InvalidPriceAccount);
+    require!(message.price > 0, CustomError::InvalidPriceAccount);
+
+    let now = Clock::get()?.unix_timestamp;
+    require!(message.publish_time.saturating_add(max_age_seconds) >= now, CustomError::StalePrice);
+    // A price from the future would otherwise never go stale
+    require!(message.publish_time <= now.saturating_add(MAX_PUBLISH_TIME_SKEW_SECONDS), CustomError::InvalidPriceTimestamp);
+
+    // conf / price, in basis points
+    let conf_basis_points = (message.conf as u128)
+        .checked_mul(10000)
+        .ok_or(CustomError::ArithmeticOverflow)?
+        .checked_div(message.price as u128)
+        .ok_or(CustomError::ArithmeticOverflow)?;
+    require!(conf_basis_points <= max_conf_basis_points as u128, CustomError::PriceConfidenceTooWide);
+
+    Ok(OraclePrice {
+        price: message.price,
+        conf: message.conf,
+        expo: message.exponent,
+        publish_time: message.publish_time,
+    })
+}
+
+/// Converts an amount of a 6-decimal USD unit into the smallest unit of a token with `decimals` decimals
+pub fn usd_to_token_amount(usd_amount: u64, price: &OraclePrice, decimals: u8) -> Result<u64> {
+    // amount = usd_amount * 10^(decimals - 6) / (price * 10^expo)
+    //        = usd_amount * 10^(decimals - 6 - expo) / price
+    let scale = decimals as i32 - 6 - price.expo;
+    let pow = 10u128
+        .checked_pow(scale.unsigned_abs())
+        .ok_or(CustomError::ArithmeticOverflow)?;
+
+    let amount = if scale >= 0 {
+        (usd_amount as u128)
+            .checked_mul(pow)
+            .ok_or(CustomError::ArithmeticOverflow)?
+            .checked_div(price.price as u128)
+    } else {
+        (usd_amount as u128)
+            .checked_div(pow)
+            .ok_or(CustomError::ArithmeticOverflow)?
+            .checked_div(price.price as u128)
+    }
+    .ok_or(CustomError::ArithmeticOverflow)?;
+
+    u64::try_from(amount).map_err(|_| error!(CustomError::ArithmeticOverflow))
+}
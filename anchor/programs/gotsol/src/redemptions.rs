@@ -0,0 +1,63 @@
+use anchor_lang::prelude::*;
+
+use crate::event_cpi::EventCpiContext;
+use crate::events::*;
+use crate::state::{Coupon, GiftCard};
+
+/// Redeems `coupon`, when the payment carries one, against `amount` for `customer`.
+/// Returns the discount, 0 without a coupon.
+pub fn apply_coupon(
+    coupon: &mut Option<Box<Account<'_, Coupon>>>,
+    code: Option<&[u8]>,
+    amount: u64,
+    merchant: Pubkey,
+    customer: Pubkey,
+    ctx: &EventCpiContext,
+) -> Result<u64> {
+    let Some(coupon) = coupon.as_mut() else {
+        return Ok(0);
+    };
+
+    let now = Clock::get()?.unix_timestamp;
+    let discount = coupon.redeem(code, amount, now)?;
+
+    // Emit event
+    emit_cpi!(CouponRedeemed {
+        header: EventHeader::merchant(merchant, customer)?,
+        coupon: coupon.key(),
+        customer,
+        discount,
+        redemption_count: coupon.redemption_count,
+    });
+
+    Ok(discount)
+}
+
+/// Draws `gift_card`, when the payment carries one, down towards `amount` for `redeemer`,
+/// `code_signer` being the card's code key when it signed. Returns the part of `amount` the
+/// card covers, 0 without a card.
+pub fn redeem_gift_card(
+    gift_card: &mut Option<Box<Account<'_, GiftCard>>>,
+    code_signer: Option<Pubkey>,
+    amount: u64,
+    merchant: Pubkey,
+    redeemer: Pubkey,
+    ctx: &EventCpiContext,
+) -> Result<u64> {
+    let Some(gift_card) = gift_card.as_mut() else {
+        return Ok(0);
+    };
+
+    let covered = gift_card.redeem(redeemer, code_signer, amount, Clock::get()?.unix_timestamp)?;
+
+    // Emit event
+    emit_cpi!(GiftCardRedeemed {
+        header: EventHeader::new(Some(merchant), redeemer, Some(gift_card.mint), None)?,
+        gift_card: gift_card.key(),
+        redeemer,
+        amount: covered,
+        remaining_balance: gift_card.balance,
+    });
+
+    Ok(covered)
+}
@@ -0,0 +1,60 @@
+use anchor_lang::prelude::*;
+
+use anchor_spl::associated_token::{create_idempotent, Create};
+
+use crate::errors::*;
+
+const LAMPORTS_PER_SOL: u128 = 1_000_000_000;
+const RATE_DECIMALS: u32 = 6;
+
+/// Creates `ata` if it doesn't exist yet and returns the rent lamports `payer` spent on it
+#[allow(clippy::too_many_arguments)]
+pub fn create_ata_if_missing<'info>(
+    payer: &AccountInfo<'info>,
+    ata: &AccountInfo<'info>,
+    authority: &AccountInfo<'info>,
+    mint: &AccountInfo<'info>,
+    associated_token_program: &AccountInfo<'info>,
+    token_program: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+) -> Result<u64> {
+    if !ata.data_is_empty() {
+        return Ok(0);
+    }
+
+    let payer_before = payer.lamports();
+    create_idempotent(CpiContext::new(
+        associated_token_program.clone(),
+        Create {
+            payer: payer.clone(),
+            associated_token: ata.clone(),
+            authority: authority.clone(),
+            mint: mint.clone(),
+            system_program: system_program.clone(),
+            token_program: token_program.clone(),
+        },
+    ))?;
+
+    Ok(payer_before.saturating_sub(payer.lamports()))
+}
+
+/// Converts sponsored rent into stablecoin units of a mint with `decimals` decimals,
+/// at `rate` stablecoin units (6 decimals) per SOL
+pub fn rent_recoup_amount(lamports: u64, rate: u64, decimals: u8) -> Result<u64> {
+    let scaled = (lamports as u128)
+        .checked_mul(rate as u128)
+        .ok_or(CustomError::ArithmeticOverflow)?;
+
+    let amount = if decimals as u32 >= RATE_DECIMALS {
+        scaled
+            .checked_mul(10u128.pow(decimals as u32 - RATE_DECIMALS))
+            .ok_or(CustomError::ArithmeticOverflow)?
+            .checked_div(LAMPORTS_PER_SOL)
+    } else {
+        scaled
+            .checked_div(LAMPORTS_PER_SOL * 10u128.pow(RATE_DECIMALS - decimals as u32))
+    }
+    .ok_or(CustomError::ArithmeticOverflow)?;
+
+    u64::try_from(amount).map_err(|_| error!(CustomError::ArithmeticOverflow))
+}
@@ -0,0 +1,17 @@
+use anchor_lang::prelude::*;
+
+#[account]
+pub struct Authorization {
+    pub merchant: Pubkey,
+    pub customer: Pubkey,
+    pub mint: Pubkey,
+    pub authorization_id: u64,
+    pub amount: u64,
+    pub expires_at: i64,
+    pub bump: u8,
+    pub escrow_bump: u8,
+}
+
+impl Authorization {
+    pub const LEN: usize = 8 + 32 + 32 + 32 + 8 + 8 + 8 + 1 + 1;
+}
@@ -0,0 +1,57 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::hash::hash;
+use crate::errors::*;
+
+const BASIS_POINTS_DIVISOR: u64 = 10000;
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub enum CouponDiscount {
+    Percent { basis_points: u16 },
+    Fixed { amount: u64 },
+}
+
+#[account]
+pub struct Coupon {
+    pub merchant: Pubkey,
+    pub code_hash: [u8; 32], // sha256(code)
+    pub discount: CouponDiscount,
+    pub max_redemptions: u32,
+    pub redemption_count: u32,
+    pub expires_at: i64, // 0 = never expires
+    pub rent_payer: Pubkey, // receives the rent back when the coupon is closed
+    pub bump: u8,
+}
+
+impl Coupon {
+    pub const LEN: usize = 8 + 32 + 32 + (1 + 8) + 4 + 4 + 8 + 32 + 1;
+
+    /// Discount this coupon gives on `amount`; a fixed discount never exceeds the amount itself
+    pub fn discount_for(&self, amount: u64) -> Result<u64> {
+        match self.discount {
+            CouponDiscount::Percent { basis_points } => Ok(amount
+                .checked_mul(basis_points as u64)
+                .ok_or(CustomError::ArithmeticOverflow)?
+                .checked_div(BASIS_POINTS_DIVISOR)
+                .ok_or(CustomError::ArithmeticOverflow)?),
+            CouponDiscount::Fixed { amount: fixed } => Ok(fixed.min(amount)),
+        }
+    }
+
+    /// Redeems the coupon against `amount` for a customer presenting `code`, counting the
+    /// redemption. Returns the discount.
+    pub fn redeem(&mut self, code: Option<&[u8]>, amount: u64, now: i64) -> Result<u64> {
+        // The account alone proves nothing; the customer has to know the code it was created for
+        let code = code.ok_or(CustomError::InvalidCouponCode)?;
+        require!(hash(code).to_bytes() == self.code_hash, CustomError::InvalidCouponCode);
+
+        require!(self.expires_at == 0 || now <= self.expires_at, CustomError::CouponExpired);
+        require!(self.redemption_count < self.max_redemptions, CustomError::CouponExhausted);
+
+        let discount = self.discount_for(amount)?;
+        self.redemption_count = self.redemption_count
+            .checked_add(1)
+            .ok_or(CustomError::ArithmeticOverflow)?;
+
+        Ok(discount)
+    }
+}
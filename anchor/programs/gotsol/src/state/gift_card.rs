@@ -0,0 +1,49 @@
+use anchor_lang::prelude::*;
+use crate::errors::*;
+
+#[account]
+pub struct GiftCard {
+    pub merchant: Pubkey,
+    pub card_id: u64,
+    pub mint: Pubkey,
+    pub purchaser: Pubkey,
+    pub balance: u64,
+    pub holder: Option<Pubkey>,
+    pub code_key: Option<Pubkey>, // public half of the ed25519 keypair whose secret is the card's code
+    pub expires_at: i64, // 0 = never expires
+    pub bump: u8,
+}
+
+impl GiftCard {
+    pub const LEN: usize = 8 + 32 + 8 + 32 + 32 + 8 + (1 + 32) + (1 + 32) + 8 + 1;
+
+    pub fn is_expired(&self, now: i64) -> bool {
+        self.expires_at != 0 && now > self.expires_at
+    }
+
+    /// Draws the card down towards a payment of `amount` by `redeemer`, who must be its holder
+    /// or have `code_signer`, the card's code key, sign the transaction. A signature only covers
+    /// the transaction it's in, so a code seen in a pending one can't be replayed from another
+    /// wallet. Returns the part of `amount` the card covers.
+    pub fn redeem(&mut self, redeemer: Pubkey, code_signer: Option<Pubkey>, amount: u64, now: i64) -> Result<u64> {
+        require!(!self.is_expired(now), CustomError::GiftCardExpired);
+        require!(self.balance > 0, CustomError::InsufficientGiftCardBalance);
+
+        let is_holder = self.holder == Some(redeemer);
+        let has_code = code_signer.is_some() && code_signer == self.code_key;
+        require!(is_holder || has_code, CustomError::UnauthorizedGiftCardRedemption);
+
+        // The code is spent once used; the card stays with whoever redeemed it
+        if !is_holder {
+            self.holder = Some(redeemer);
+            self.code_key = None;
+        }
+
+        let covered = amount.min(self.balance);
+        self.balance = self.balance
+            .checked_sub(covered)
+            .ok_or(CustomError::ArithmeticOverflow)?;
+
+        Ok(covered)
+    }
+}
@@ -0,0 +1,32 @@
+use anchor_lang::prelude::*;
+
+use crate::errors::*;
+
+pub const MAX_FEE_PAYERS: usize = 8;
+
+/// Program-wide settings controlled by the house authorities
+#[account]
+pub struct Global {
+    pub swap_router: Pubkey,
+    pub rent_recoup_rate: u64, // stablecoin units (6 decimals) per SOL of sponsored ATA rent; 0 = never recoup
+    pub fee_payers: Vec<Pubkey>, // sponsor nodes allowed to sign as `fee_payer`
+    pub bump: u8,
+}
+
+impl Global {
+    pub const LEN: usize = 8 + 32 + 8 + (4 + 32 * MAX_FEE_PAYERS) + 1;
+
+    /// Passes when nobody sponsors, otherwise requires `fee_payer` to be on the global allowlist
+    pub fn require_approved_fee_payer(global: &Option<Box<Account<Global>>>, fee_payer: Option<Pubkey>) -> Result<()> {
+        let Some(fee_payer) = fee_payer else {
+            return Ok(());
+        };
+
+        let approved = global
+            .as_ref()
+            .is_some_and(|global| global.fee_payers.contains(&fee_payer));
+        require!(approved, CustomError::UnapprovedFeePayer);
+
+        Ok(())
+    }
+}
\ No newline at end of file
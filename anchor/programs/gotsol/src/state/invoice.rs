@@ -0,0 +1,27 @@
+use anchor_lang::prelude::*;
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub enum InvoiceStatus {
+    Open,
+    Paid,
+    Cancelled,
+}
+
+#[account]
+pub struct Invoice {
+    pub merchant: Pubkey,
+    pub invoice_id: u64,
+    pub amount: u64,
+    pub mint: Pubkey,
+    pub due_date: i64,
+    pub memo_hash: [u8; 32],
+    pub status: InvoiceStatus,
+    pub payer: Option<Pubkey>,
+    pub paid_at: i64,
+    pub rent_payer: Pubkey, // receives the rent back when the invoice is closed
+    pub bump: u8,
+}
+
+impl Invoice {
+    pub const LEN: usize = 8 + 32 + 8 + 8 + 32 + 8 + 32 + 1 + (1 + 32) + 8 + 32 + 1;
+}
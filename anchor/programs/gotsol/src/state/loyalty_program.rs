@@ -0,0 +1,16 @@
+use anchor_lang::prelude::*;
+
+#[account]
+pub struct LoyaltyProgram {
+    pub merchant: Pubkey,
+    pub points_mint: Pubkey,
+    pub earn_basis_points: u64,       // points minted per 10,000 units paid
+    pub redemption_basis_points: u64, // discount units per 10,000 points burned
+    pub non_transferable: bool,
+    pub bump: u8,
+    pub points_mint_bump: u8,
+}
+
+impl LoyaltyProgram {
+    pub const LEN: usize = 8 + 32 + 32 + 8 + 8 + 1 + 1 + 1;
+}
@@ -0,0 +1,13 @@
+use anchor_lang::prelude::*;
+
+#[account]
+pub struct MerchantManager {
+    pub merchant: Pubkey,
+    pub manager: Pubkey,
+    pub rent_payer: Pubkey, // receives the rent back when the manager is removed
+    pub bump: u8,
+}
+
+impl MerchantManager {
+    pub const LEN: usize = 8 + 32 + 32 + 32 + 1;
+}
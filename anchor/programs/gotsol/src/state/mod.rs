@@ -1,5 +1,33 @@
 pub mod merchant;
 pub mod refund_record;
+pub mod invoice;
+pub mod authorization;
+pub mod subscription;
+pub mod gift_card;
+pub mod loyalty_program;
+pub mod coupon;
+pub mod merchant_manager;
+pub mod product;
+pub mod order;
+pub mod payment;
+pub mod global;
+pub mod payout_schedule;
+pub mod withdrawal_limit;
+pub mod sponsorship_budget;
 
 pub use merchant::*;
-pub use refund_record::*;
\ No newline at end of file
+pub use refund_record::*;
+pub use invoice::*;
+pub use authorization::*;
+pub use subscription::*;
+pub use gift_card::*;
+pub use loyalty_program::*;
+pub use coupon::*;
+pub use merchant_manager::*;
+pub use product::*;
+pub use order::*;
+pub use payment::*;
+pub use global::*;
+pub use payout_schedule::*;
+pub use withdrawal_limit::*;
+pub use sponsorship_budget::*;
\ No newline at end of file
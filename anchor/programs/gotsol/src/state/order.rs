@@ -0,0 +1,53 @@
+use anchor_lang::prelude::*;
+
+pub const MAX_ORDER_LINE_ITEMS: usize = 16;
+
+// How long after payment an order's items can still be refunded; afterwards the customer can reclaim its rent
+pub const ORDER_REFUND_WINDOW_SECONDS: i64 = 90 * 24 * 60 * 60;
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub struct OrderLineItem {
+    pub product: Pubkey,
+    pub quantity: u64,
+    pub unit_price: u64,
+    pub tax: u64, // tax on the whole line, not per unit
+    pub refunded_quantity: u64,
+}
+
+impl OrderLineItem {
+    pub const LEN: usize = 32 + 8 + 8 + 8 + 8;
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub struct OrderItemRefund {
+    pub line_index: u8,
+    pub quantity: u64,
+}
+
+#[account]
+pub struct Order {
+    pub merchant: Pubkey,
+    pub customer: Pubkey,
+    pub mint: Pubkey,
+    pub order_id: u64,
+    pub line_items: Vec<OrderLineItem>,
+    pub subtotal: u64,
+    pub tax: u64,
+    pub discount: u64, // coupon discount off subtotal + tax; item refunds are scaled down by it
+    pub tip: u64,
+    pub refunded_amount: u64,
+    pub created_at: i64,
+    pub bump: u8,
+}
+
+impl Order {
+    pub const LEN: usize = 8 + 32 + 32 + 32 + 8 + (4 + MAX_ORDER_LINE_ITEMS * OrderLineItem::LEN) + 8 + 8 + 8 + 8 + 8 + 8 + 1;
+
+    pub fn refund_deadline(&self) -> i64 {
+        self.created_at.saturating_add(ORDER_REFUND_WINDOW_SECONDS)
+    }
+
+    pub fn is_fully_refunded(&self) -> bool {
+        self.line_items.iter().all(|line| line.refunded_quantity == line.quantity)
+    }
+}
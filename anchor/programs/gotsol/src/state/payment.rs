@@ -0,0 +1,23 @@
+use anchor_lang::prelude::*;
+
+// How long a payment receipt is kept for the merchant's reconciliation before its payer can reclaim the rent
+pub const PAYMENT_RETENTION_SECONDS: i64 = 90 * 24 * 60 * 60;
+
+#[account]
+pub struct Payment {
+    pub merchant: Pubkey,
+    pub payer: Pubkey,
+    pub payment_id: u64,
+    pub usd_amount: u64, // 6 decimals, e.g. 1_000_000 = $1.00
+    pub lamports: u64,
+    pub price: i64,
+    pub price_expo: i32,
+    pub price_conf: u64,
+    pub price_publish_time: i64,
+    pub created_at: i64,
+    pub bump: u8,
+}
+
+impl Payment {
+    pub const LEN: usize = 8 + 32 + 32 + 8 + 8 + 8 + 8 + 4 + 8 + 8 + 8 + 1;
+}
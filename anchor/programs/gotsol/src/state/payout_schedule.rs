@@ -0,0 +1,20 @@
+use anchor_lang::prelude::*;
+
+// Upper bound on the cranker bounty, taken out of the owner's share
+pub const MAX_PAYOUT_BOUNTY_BASIS_POINTS: u16 = 100; // 1%
+
+#[account]
+pub struct PayoutSchedule {
+    pub merchant: Pubkey,
+    pub mint: Pubkey,
+    pub destination: Pubkey,
+    pub interval: i64,
+    pub minimum_threshold: u64,
+    pub bounty_basis_points: u16,
+    pub next_payout_at: i64,
+    pub bump: u8,
+}
+
+impl PayoutSchedule {
+    pub const LEN: usize = 8 + 32 + 32 + 32 + 8 + 8 + 2 + 8 + 1;
+}
\ No newline at end of file
@@ -0,0 +1,20 @@
+use anchor_lang::prelude::*;
+
+pub const MAX_SKU_LEN: usize = 32;
+
+#[account]
+pub struct Product {
+    pub merchant: Pubkey,
+    pub sku: String,
+    pub price: u64,
+    pub mint: Pubkey,
+    pub stock: u64,
+    pub tax_basis_points: u16,
+    pub active: bool,
+    pub rent_payer: Pubkey, // receives the rent back when the product is deleted
+    pub bump: u8,
+}
+
+impl Product {
+    pub const LEN: usize = 8 + 32 + (4 + MAX_SKU_LEN) + 8 + 32 + 8 + 2 + 1 + 32 + 1;
+}
@@ -4,10 +4,14 @@ pub const MAX_TX_SIG_LEN: usize = 88; // Base58 signature length
 
 #[account]
 pub struct RefundRecord {
+    pub rent_payer: Pubkey, // receives the rent back when the record is closed
     pub original_tx_sig: String,
     pub bump: u8,
 }
 
 impl RefundRecord {
-    pub const LEN: usize = 8 + (4 + MAX_TX_SIG_LEN) + 1;
+    pub const LEN: usize = 8 + 32 + (4 + MAX_TX_SIG_LEN) + 1;
+
+    // Records created before `rent_payer` was stored hold only the signature and bump
+    pub const LEGACY_LEN: usize = 8 + (4 + MAX_TX_SIG_LEN) + 1;
 }
\ No newline at end of file
@@ -0,0 +1,62 @@
+use anchor_lang::prelude::*;
+
+use crate::errors::*;
+use crate::event_cpi::EventCpiContext;
+use crate::events::*;
+
+// Flat estimate of the signature fee our fee payer covers per sponsored instruction
+pub const SPONSORED_TX_FEE_LAMPORTS: u64 = 5000;
+
+/// Caps the lamports our fee payer fronts for one merchant in each period
+#[account]
+pub struct SponsorshipBudget {
+    pub merchant: Pubkey,
+    pub allowance_per_period: u64,
+    pub period: i64,
+    pub period_start: i64,
+    pub consumed: u64,
+    pub bump: u8,
+}
+
+impl SponsorshipBudget {
+    pub const LEN: usize = 8 + 32 + 8 + 8 + 8 + 8 + 1;
+
+    /// Records `lamports` fronted by the fee payer against the current period's allowance
+    pub fn consume(&mut self, lamports: u64, now: i64) -> Result<()> {
+        if now >= self.period_start.saturating_add(self.period) {
+            self.period_start = now;
+            self.consumed = 0;
+        }
+
+        let consumed = self.consumed
+            .checked_add(lamports)
+            .ok_or(CustomError::ArithmeticOverflow)?;
+        require!(consumed <= self.allowance_per_period, CustomError::SponsorshipBudgetExceeded);
+        self.consumed = consumed;
+
+        Ok(())
+    }
+
+    /// Charges the merchant's budget when `sponsor` funded `rent` lamports of new accounts
+    pub fn charge(budget: &mut Option<Box<Account<SponsorshipBudget>>>, sponsor: Option<Pubkey>, rent: u64, ctx: &EventCpiContext) -> Result<()> {
+        let Some(sponsor) = sponsor else {
+            return Ok(());
+        };
+
+        let budget = budget.as_mut().ok_or(CustomError::SponsorshipBudgetRequired)?;
+        let lamports = rent
+            .checked_add(SPONSORED_TX_FEE_LAMPORTS)
+            .ok_or(CustomError::ArithmeticOverflow)?;
+        budget.consume(lamports, Clock::get()?.unix_timestamp)?;
+
+        // Emit event
+        emit_cpi!(SponsorshipCharged {
+            header: EventHeader::merchant(budget.merchant, sponsor)?,
+            sponsor,
+            lamports,
+            consumed: budget.consumed,
+        });
+
+        Ok(())
+    }
+}
\ No newline at end of file
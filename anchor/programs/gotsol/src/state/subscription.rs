@@ -0,0 +1,19 @@
+use anchor_lang::prelude::*;
+
+#[account]
+pub struct Subscription {
+    pub merchant: Pubkey,
+    pub customer: Pubkey,
+    pub mint: Pubkey,
+    pub amount: u64,
+    pub interval: i64,
+    pub next_charge_at: i64,
+    pub charges_made: u64,
+    pub remaining_allowance: u64, // what this subscription may still pull through the shared delegate
+    pub retry_after: i64,         // earliest retry after a failed charge
+    pub bump: u8,
+}
+
+impl Subscription {
+    pub const LEN: usize = 8 + 32 + 32 + 32 + 8 + 8 + 8 + 8 + 8 + 8 + 1;
+}
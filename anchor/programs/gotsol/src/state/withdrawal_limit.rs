@@ -0,0 +1,157 @@
+use anchor_lang::prelude::*;
+
+use crate::errors::*;
+
+// Raising a limit only takes effect after this delay; lowering is instant
+pub const LIMIT_INCREASE_DELAY_SECONDS: i64 = 24 * 60 * 60;
+
+// The rolling 24h window is tracked in hourly buckets
+const SECONDS_PER_BUCKET: i64 = 60 * 60;
+const WINDOW_BUCKETS: usize = 24;
+
+/// Owner-configured withdrawal velocity limits for one mint (or SOL) of a merchant.
+/// A limit of 0 means unlimited, and a merchant without this account has no limits at all.
+/// Every owner-signed outflow from the vault, the merchant's ATA or its tip pool counts:
+/// withdrawals, swaps, payouts, refunds and tip distributions.
+#[account]
+pub struct WithdrawalLimit {
+    pub merchant: Pubkey,
+    pub mint: Pubkey, // Pubkey::default() for SOL in the vault
+    pub max_per_withdrawal: u64,
+    pub max_per_day: u64,
+    pub pending_max_per_withdrawal: u64,
+    pub pending_max_per_day: u64,
+    pub pending_effective_at: i64, // 0 = nothing pending
+    pub last_bucket: i64,
+    pub withdrawn: [u64; WINDOW_BUCKETS],
+    pub bump: u8,
+}
+
+impl WithdrawalLimit {
+    pub const LEN: usize = 8 + 32 + 32 + 8 + 8 + 8 + 8 + 8 + 8 + (8 * WINDOW_BUCKETS) + 1;
+
+    /// Applies lowered limits immediately and schedules raised ones; a new update replaces any pending raise
+    pub fn update(&mut self, max_per_withdrawal: u64, max_per_day: u64, now: i64) -> Result<()> {
+        self.promote_pending(now);
+
+        let raises_withdrawal = is_raise(self.max_per_withdrawal, max_per_withdrawal);
+        let raises_day = is_raise(self.max_per_day, max_per_day);
+
+        if !raises_withdrawal {
+            self.max_per_withdrawal = max_per_withdrawal;
+        }
+        if !raises_day {
+            self.max_per_day = max_per_day;
+        }
+
+        if raises_withdrawal || raises_day {
+            self.pending_max_per_withdrawal = max_per_withdrawal;
+            self.pending_max_per_day = max_per_day;
+            self.pending_effective_at = now
+                .checked_add(LIMIT_INCREASE_DELAY_SECONDS)
+                .ok_or(CustomError::ArithmeticOverflow)?;
+        } else {
+            self.pending_max_per_withdrawal = 0;
+            self.pending_max_per_day = 0;
+            self.pending_effective_at = 0;
+        }
+
+        Ok(())
+    }
+
+    /// Checks `amount` against the limits in force at `now` and records it in the rolling window
+    pub fn record_withdrawal(&mut self, amount: u64, now: i64) -> Result<()> {
+        self.promote_pending(now);
+
+        if self.max_per_withdrawal != 0 {
+            require!(amount <= self.max_per_withdrawal, CustomError::WithdrawalLimitExceeded);
+        }
+
+        // Clear the buckets that fell out of the window since the last withdrawal
+        let bucket = now / SECONDS_PER_BUCKET;
+        let elapsed = bucket.saturating_sub(self.last_bucket);
+        if elapsed >= WINDOW_BUCKETS as i64 {
+            self.withdrawn = [0; WINDOW_BUCKETS];
+        } else {
+            for b in (self.last_bucket + 1)..=bucket {
+                self.withdrawn[b.rem_euclid(WINDOW_BUCKETS as i64) as usize] = 0;
+            }
+        }
+        self.last_bucket = self.last_bucket.max(bucket);
+
+        let withdrawn_today = self
+            .withdrawn
+            .iter()
+            .try_fold(0u64, |acc, w| acc.checked_add(*w))
+            .and_then(|total| total.checked_add(amount))
+            .ok_or(CustomError::ArithmeticOverflow)?;
+        if self.max_per_day != 0 {
+            require!(withdrawn_today <= self.max_per_day, CustomError::WithdrawalLimitExceeded);
+        }
+
+        let slot = &mut self.withdrawn[self.last_bucket.rem_euclid(WINDOW_BUCKETS as i64) as usize];
+        *slot = slot.checked_add(amount).ok_or(CustomError::ArithmeticOverflow)?;
+
+        Ok(())
+    }
+
+    /// The most that could be withdrawn in one go at `now` without breaching the limits in force
+    pub fn remaining(&self, now: i64) -> u64 {
+        let (max_per_withdrawal, max_per_day) = if self.pending_effective_at != 0 && now >= self.pending_effective_at {
+            (self.pending_max_per_withdrawal, self.pending_max_per_day)
+        } else {
+            (self.max_per_withdrawal, self.max_per_day)
+        };
+
+        // Only the buckets still inside the window count against the daily limit
+        let bucket = now / SECONDS_PER_BUCKET;
+        let oldest = bucket - (WINDOW_BUCKETS as i64 - 1);
+        let withdrawn_today = (oldest.max(self.last_bucket - (WINDOW_BUCKETS as i64 - 1))..=self.last_bucket)
+            .map(|b| self.withdrawn[b.rem_euclid(WINDOW_BUCKETS as i64) as usize])
+            .fold(0u64, u64::saturating_add);
+
+        let per_withdrawal = if max_per_withdrawal == 0 { u64::MAX } else { max_per_withdrawal };
+        let per_day = if max_per_day == 0 { u64::MAX } else { max_per_day.saturating_sub(withdrawn_today) };
+        per_withdrawal.min(per_day)
+    }
+
+    /// What `remaining` allows under the limit stored at `info`; unlimited if the merchant has
+    /// configured none
+    pub fn remaining_at(info: &AccountInfo) -> Result<u64> {
+        if info.data_is_empty() {
+            return Ok(u64::MAX);
+        }
+        require_keys_eq!(*info.owner, crate::ID, ErrorCode::AccountOwnedByWrongProgram);
+
+        let limit = WithdrawalLimit::try_deserialize(&mut &info.try_borrow_data()?[..])?;
+        Ok(limit.remaining(Clock::get()?.unix_timestamp))
+    }
+
+    /// Enforces the limit stored at `info`, if the merchant has configured one
+    pub fn enforce(info: &AccountInfo, amount: u64) -> Result<()> {
+        if info.data_is_empty() {
+            return Ok(());
+        }
+        require_keys_eq!(*info.owner, crate::ID, ErrorCode::AccountOwnedByWrongProgram);
+
+        let mut data = info.try_borrow_mut_data()?;
+        let mut limit = WithdrawalLimit::try_deserialize(&mut &data[..])?;
+        limit.record_withdrawal(amount, Clock::get()?.unix_timestamp)?;
+        limit.try_serialize(&mut &mut data[..])
+    }
+
+    fn promote_pending(&mut self, now: i64) {
+        if self.pending_effective_at != 0 && now >= self.pending_effective_at {
+            self.max_per_withdrawal = self.pending_max_per_withdrawal;
+            self.max_per_day = self.pending_max_per_day;
+            self.pending_max_per_withdrawal = 0;
+            self.pending_max_per_day = 0;
+            self.pending_effective_at = 0;
+        }
+    }
+}
+
+// 0 is unlimited, so moving to 0 or to a higher cap loosens the limit
+fn is_raise(current: u64, requested: u64) -> bool {
+    current != 0 && (requested == 0 || requested > current)
+}
\ No newline at end of file
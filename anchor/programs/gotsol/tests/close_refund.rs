@@ -0,0 +1,147 @@
+mod runtime;
+
+use std::str::FromStr;
+
+use anchor_lang::{
+    prelude::*,
+    solana_program::instruction::Instruction,
+    system_program, Discriminator, InstructionData,
+};
+use gotsol::errors::CustomError;
+use gotsol::state::RefundRecord;
+use runtime::{failure, Bank, TestAccount};
+use solana_sdk::{account::Account, transaction::TransactionError};
+
+const AUTH: &str = "Hth4EBxLWJSoRWj7raCKoniuzcvXt8MUFgGKty3B66ih";
+const ORIGINAL_TX_SIG: &str = "5VERv8NM"; // the frontend keys records by the signature's first 8 characters
+
+fn refund_record(original_tx_sig: &str) -> Pubkey {
+    Pubkey::find_program_address(&[b"refund", original_tx_sig.as_bytes()], &gotsol::ID).0
+}
+
+/// A record as written before `rent_payer` was added: the signature string, then the bump
+fn legacy_record(original_tx_sig: &str) -> Account {
+    let mut data = RefundRecord::DISCRIMINATOR.to_vec();
+    data.extend_from_slice(&(original_tx_sig.len() as u32).to_le_bytes());
+    data.extend_from_slice(original_tx_sig.as_bytes());
+    data.push(255);
+    data.resize(RefundRecord::LEGACY_LEN, 0);
+    Account {
+        lamports: Rent::default().minimum_balance(data.len()),
+        data,
+        owner: gotsol::ID,
+        ..Account::default()
+    }
+}
+
+async fn setup() -> (Bank, Pubkey) {
+    let mut bank = Bank::new().await;
+    let auth = Pubkey::from_str(AUTH).unwrap();
+    bank.set(auth, TestAccount::system(1_000_000_000));
+    (bank, auth)
+}
+
+async fn close_legacy_refund(
+    bank: &mut Bank,
+    auth: Pubkey,
+    original_tx_sig: &str,
+    rent_payer: Pubkey,
+) -> std::result::Result<(), TransactionError> {
+    let ix = Instruction {
+        program_id: gotsol::ID,
+        accounts: gotsol::accounts::CloseLegacyRefund {
+            auth,
+            refund_record: refund_record(original_tx_sig),
+            rent_payer,
+            system_program: system_program::ID,
+        }
+        .to_account_metas(None),
+        data: gotsol::instruction::CloseLegacyRefund { original_tx_sig: original_tx_sig.to_string() }.data(),
+    };
+    bank.process(&ix, &[auth]).await
+}
+
+async fn close_refund(bank: &mut Bank, auth: Pubkey, rent_payer: Pubkey) -> std::result::Result<(), TransactionError> {
+    let ix = Instruction {
+        program_id: gotsol::ID,
+        accounts: gotsol::accounts::CloseRefund {
+            auth,
+            refund_record: refund_record(ORIGINAL_TX_SIG),
+            rent_payer,
+            system_program: system_program::ID,
+        }
+        .to_account_metas(None),
+        data: gotsol::instruction::CloseRefund {}.data(),
+    };
+    bank.process(&ix, &[auth]).await
+}
+
+#[tokio::test]
+async fn closes_legacy_record_to_the_named_rent_payer() {
+    let (mut bank, auth) = setup().await;
+    let record = refund_record(ORIGINAL_TX_SIG);
+    bank.set(record, legacy_record(ORIGINAL_TX_SIG));
+    let rent = bank.lamports(&record).await;
+    let auth_before = bank.lamports(&auth).await;
+    let rent_payer = Pubkey::new_unique();
+
+    close_legacy_refund(&mut bank, auth, ORIGINAL_TX_SIG, rent_payer).await.unwrap();
+
+    assert!(bank.get(&record).await.is_none());
+    assert_eq!(bank.lamports(&rent_payer).await, rent);
+    assert_eq!(bank.lamports(&auth).await, auth_before);
+}
+
+#[tokio::test]
+async fn legacy_close_rejects_current_records() {
+    let (mut bank, auth) = setup().await;
+    let record = refund_record(ORIGINAL_TX_SIG);
+    let rent_payer = Pubkey::new_unique();
+    bank.set(
+        record,
+        TestAccount::anchor(
+            &RefundRecord { rent_payer, original_tx_sig: ORIGINAL_TX_SIG.to_string(), bump: 255 },
+            RefundRecord::LEN,
+        ),
+    );
+
+    // A current record stores its rent payer and must be closed through close_refund, which checks it
+    assert_eq!(
+        close_legacy_refund(&mut bank, auth, ORIGINAL_TX_SIG, rent_payer).await,
+        failure(CustomError::InvalidLegacyRefundRecord)
+    );
+    assert!(bank.get(&record).await.is_some());
+}
+
+#[tokio::test]
+async fn legacy_close_rejects_mismatched_signature() {
+    let (mut bank, auth) = setup().await;
+    let record = refund_record(ORIGINAL_TX_SIG);
+    bank.set(record, legacy_record("someOtherSignature"));
+
+    assert_eq!(
+        close_legacy_refund(&mut bank, auth, ORIGINAL_TX_SIG, Pubkey::new_unique()).await,
+        failure(CustomError::InvalidLegacyRefundRecord)
+    );
+}
+
+#[tokio::test]
+async fn closes_current_record_to_rent_payer() {
+    let (mut bank, auth) = setup().await;
+    let record = refund_record(ORIGINAL_TX_SIG);
+    let rent_payer = Pubkey::new_unique();
+    bank.set(
+        record,
+        TestAccount::anchor(
+            &RefundRecord { rent_payer, original_tx_sig: ORIGINAL_TX_SIG.to_string(), bump: 255 },
+            RefundRecord::LEN,
+        ),
+    );
+    let rent = bank.lamports(&record).await;
+
+    assert_eq!(close_refund(&mut bank, auth, auth).await, failure(CustomError::InvalidRentPayer));
+
+    close_refund(&mut bank, auth, rent_payer).await.unwrap();
+    assert!(bank.get(&record).await.is_none());
+    assert_eq!(bank.lamports(&rent_payer).await, rent);
+}
@@ -0,0 +1,206 @@
+mod runtime;
+
+use std::str::FromStr;
+
+use anchor_lang::{
+    prelude::*,
+    solana_program::instruction::Instruction,
+    system_program,
+    InstructionData,
+};
+use anchor_spl::{associated_token::get_associated_token_address, token::spl_token};
+use gotsol::errors::CustomError;
+use gotsol::state::{Merchant, PayoutSchedule, WithdrawalLimit};
+use runtime::{failure, Bank, TestAccount, START_TIME};
+use solana_sdk::transaction::TransactionError;
+
+const HOUSE: &str = "Hth4EBxLWJSoRWj7raCKoniuzcvXt8MUFgGKty3B66ih";
+const BALANCE: u64 = 1_000_000_000; // 1,000 units of a 6-decimal stablecoin
+const MAX_PER_WITHDRAWAL: u64 = 100_000_000;
+const MAX_PER_DAY: u64 = 250_000_000;
+const INTERVAL: i64 = 60 * 60;
+
+struct Payout {
+    bank: Bank,
+    cranker: Pubkey,
+    merchant: Pubkey,
+    payout_schedule: Pubkey,
+    stablecoin_mint: Pubkey,
+    merchant_stablecoin_ata: Pubkey,
+    withdrawal_limit: Pubkey,
+    destination: Pubkey,
+    destination_stablecoin_ata: Pubkey,
+    house_stablecoin_ata: Pubkey,
+}
+
+fn pda(seeds: &[&[u8]]) -> (Pubkey, u8) {
+    Pubkey::find_program_address(seeds, &gotsol::ID)
+}
+
+impl Payout {
+    async fn new() -> Self {
+        let mut bank = Bank::new().await;
+        let house = Pubkey::from_str(HOUSE).unwrap();
+
+        let owner = Pubkey::new_unique();
+        let entity_name = "Coffee".to_string();
+        let (merchant, merchant_bump) = pda(&[b"merchant", entity_name.as_bytes(), owner.as_ref()]);
+        let (_, vault_bump) = pda(&[b"vault", merchant.as_ref()]);
+        bank.set(
+            merchant,
+            TestAccount::anchor(
+                &Merchant { owner, entity_name, fee_eligible: false, merchant_bump, vault_bump },
+                Merchant::LEN,
+            ),
+        );
+
+        let stablecoin_mint = Pubkey::new_unique();
+        bank.set(stablecoin_mint, TestAccount::mint(6));
+
+        let destination = Pubkey::new_unique();
+        let (payout_schedule, schedule_bump) = pda(&[b"payout_schedule", merchant.as_ref(), stablecoin_mint.as_ref()]);
+        bank.set(
+            payout_schedule,
+            TestAccount::anchor(
+                &PayoutSchedule {
+                    merchant,
+                    mint: stablecoin_mint,
+                    destination,
+                    interval: INTERVAL,
+                    minimum_threshold: 0,
+                    bounty_basis_points: 0,
+                    next_payout_at: START_TIME,
+                    bump: schedule_bump,
+                },
+                PayoutSchedule::LEN,
+            ),
+        );
+
+        let merchant_stablecoin_ata = get_associated_token_address(&merchant, &stablecoin_mint);
+        let destination_stablecoin_ata = get_associated_token_address(&destination, &stablecoin_mint);
+        let house_stablecoin_ata = get_associated_token_address(&house, &stablecoin_mint);
+        bank.set(merchant_stablecoin_ata, TestAccount::token(stablecoin_mint, merchant, BALANCE));
+        bank.set(destination_stablecoin_ata, TestAccount::token(stablecoin_mint, destination, 0));
+        bank.set(house_stablecoin_ata, TestAccount::token(stablecoin_mint, house, 0));
+
+        let cranker = Pubkey::new_unique();
+        bank.set(cranker, TestAccount::system(1_000_000_000));
+
+        Self {
+            bank,
+            cranker,
+            merchant,
+            payout_schedule,
+            stablecoin_mint,
+            merchant_stablecoin_ata,
+            withdrawal_limit: pda(&[b"withdrawal_limit", merchant.as_ref(), stablecoin_mint.as_ref()]).0,
+            destination,
+            destination_stablecoin_ata,
+            house_stablecoin_ata,
+        }
+    }
+
+    fn limit(&mut self, max_per_withdrawal: u64, max_per_day: u64) {
+        let (_, bump) = pda(&[b"withdrawal_limit", self.merchant.as_ref(), self.stablecoin_mint.as_ref()]);
+        let limit = WithdrawalLimit {
+            merchant: self.merchant,
+            mint: self.stablecoin_mint,
+            max_per_withdrawal,
+            max_per_day,
+            pending_max_per_withdrawal: 0,
+            pending_max_per_day: 0,
+            pending_effective_at: 0,
+            last_bucket: 0,
+            withdrawn: [0; 24],
+            bump,
+        };
+        self.bank.set(self.withdrawal_limit, TestAccount::anchor(&limit, WithdrawalLimit::LEN));
+    }
+
+    async fn crank(&mut self) -> std::result::Result<(), TransactionError> {
+        let ix = Instruction {
+            program_id: gotsol::ID,
+            accounts: gotsol::accounts::CrankPayout {
+                cranker: self.cranker,
+                merchant: self.merchant,
+                payout_schedule: self.payout_schedule,
+                stablecoin_mint: self.stablecoin_mint,
+                merchant_stablecoin_ata: self.merchant_stablecoin_ata,
+                withdrawal_limit: self.withdrawal_limit,
+                destination: self.destination,
+                destination_stablecoin_ata: self.destination_stablecoin_ata,
+                house: Pubkey::from_str(HOUSE).unwrap(),
+                house_stablecoin_ata: self.house_stablecoin_ata,
+                cranker_stablecoin_ata: None,
+                associated_token_program: anchor_spl::associated_token::ID,
+                token_program: spl_token::ID,
+                system_program: system_program::ID,
+                event_authority: pda(&[b"__event_authority"]).0,
+                program: gotsol::ID,
+            }
+            .to_account_metas(None),
+            data: gotsol::instruction::CrankPayout {}.data(),
+        };
+        let cranker = self.cranker;
+        self.bank.process(&ix, &[cranker]).await
+    }
+
+    async fn swept(&mut self) -> u64 {
+        BALANCE - self.bank.token_amount(&self.merchant_stablecoin_ata).await
+    }
+}
+
+#[tokio::test]
+async fn sweeps_the_whole_balance_without_limits() {
+    let mut payout = Payout::new().await;
+
+    payout.crank().await.unwrap();
+
+    assert_eq!(payout.swept().await, BALANCE);
+    assert_eq!(payout.bank.token_amount(&payout.destination_stablecoin_ata).await, 990_000_000);
+    assert_eq!(payout.bank.token_amount(&payout.house_stablecoin_ata).await, 10_000_000);
+}
+
+#[tokio::test]
+async fn caps_the_sweep_at_the_per_withdrawal_limit() {
+    let mut payout = Payout::new().await;
+    payout.limit(MAX_PER_WITHDRAWAL, 0);
+
+    // The balance is ten times the limit; the crank sweeps the limit instead of failing
+    payout.crank().await.unwrap();
+
+    assert_eq!(payout.swept().await, MAX_PER_WITHDRAWAL);
+    assert_eq!(payout.bank.token_amount(&payout.destination_stablecoin_ata).await, 99_000_000);
+    assert_eq!(payout.bank.token_amount(&payout.house_stablecoin_ata).await, 1_000_000);
+
+    // Not due again until the interval has passed
+    assert_eq!(payout.crank().await, failure(CustomError::PayoutNotDue));
+
+    payout.bank.warp(INTERVAL).await;
+    payout.crank().await.unwrap();
+    assert_eq!(payout.swept().await, 2 * MAX_PER_WITHDRAWAL);
+}
+
+#[tokio::test]
+async fn leaves_the_rest_for_when_the_daily_limit_frees_up() {
+    let mut payout = Payout::new().await;
+    payout.limit(MAX_PER_WITHDRAWAL, MAX_PER_DAY);
+
+    payout.crank().await.unwrap();
+    payout.bank.warp(INTERVAL).await;
+    payout.crank().await.unwrap();
+
+    // Only half a withdrawal is left in the day's allowance
+    payout.bank.warp(INTERVAL).await;
+    payout.crank().await.unwrap();
+    assert_eq!(payout.swept().await, MAX_PER_DAY);
+
+    // Nothing is left, so the crank is skipped rather than tripping the limit
+    payout.bank.warp(INTERVAL).await;
+    assert_eq!(payout.crank().await, failure(CustomError::BelowMinimumWithdrawal));
+
+    // Once the first payouts fall out of the rolling window the sweeps resume
+    payout.bank.warp(24 * 60 * 60).await;
+    payout.crank().await.unwrap();
+    assert_eq!(payout.swept().await, MAX_PER_DAY + MAX_PER_WITHDRAWAL);
+}
@@ -0,0 +1,146 @@
+//! Runs gotsol and the mock AMM as native programs inside `solana-program-test`, next to the
+//! SPL token programs it ships with.
+//!
+//! Transactions are sent down the path that doesn't verify signatures, so tests can sign as the
+//! hard-coded `AUTH` and `HOUSE` keys without their secrets.
+
+#![allow(dead_code)]
+
+use anchor_lang::{
+    prelude::*,
+    solana_program::{entrypoint::ProgramResult, instruction::Instruction, program_pack::Pack},
+    system_program, AccountSerialize,
+};
+use anchor_spl::token::spl_token;
+use solana_program_test::{processor, ProgramTest, ProgramTestContext};
+use solana_sdk::{
+    account::Account,
+    instruction::InstructionError,
+    message::Message,
+    signature::Signer,
+    transaction::{Transaction, TransactionError},
+};
+
+pub const START_TIME: i64 = 1_700_000_000;
+
+/// What `Bank::process` returns when its instruction fails with `error`
+pub fn failure(error: impl Into<anchor_lang::error::Error>) -> std::result::Result<(), TransactionError> {
+    let code = u64::from(ProgramError::from(error.into()));
+    Err(TransactionError::InstructionError(0, InstructionError::from(code)))
+}
+
+pub struct TestAccount;
+
+impl TestAccount {
+    pub fn system(lamports: u64) -> Account {
+        Account { lamports, owner: system_program::ID, ..Account::default() }
+    }
+
+    /// A rent-exempt account owned by gotsol holding `state`
+    pub fn anchor<T: AccountSerialize>(state: &T, len: usize) -> Account {
+        Self::anchor_owned_by(state, len, gotsol::ID)
+    }
+
+    pub fn anchor_owned_by<T: AccountSerialize>(state: &T, len: usize, owner: Pubkey) -> Account {
+        let mut data = Vec::with_capacity(len);
+        state.try_serialize(&mut data).unwrap();
+        data.resize(len.max(data.len()), 0);
+        Account { lamports: Rent::default().minimum_balance(data.len()), data, owner, ..Account::default() }
+    }
+
+    pub fn mint(decimals: u8) -> Account {
+        let mut data = vec![0; spl_token::state::Mint::LEN];
+        spl_token::state::Mint {
+            decimals,
+            is_initialized: true,
+            ..Default::default()
+        }
+        .pack_into_slice(&mut data);
+        Account { lamports: Rent::default().minimum_balance(data.len()), data, owner: spl_token::ID, ..Account::default() }
+    }
+
+    pub fn token(mint: Pubkey, authority: Pubkey, amount: u64) -> Account {
+        let rent = Rent::default().minimum_balance(spl_token::state::Account::LEN);
+        let native = mint == spl_token::native_mint::ID;
+        let mut data = vec![0; spl_token::state::Account::LEN];
+        spl_token::state::Account {
+            mint,
+            owner: authority,
+            amount,
+            state: spl_token::state::AccountState::Initialized,
+            is_native: if native { Some(rent).into() } else { None.into() },
+            ..Default::default()
+        }
+        .pack_into_slice(&mut data);
+        let lamports = if native { rent + amount } else { rent };
+        Account { lamports, data, owner: spl_token::ID, ..Account::default() }
+    }
+}
+
+// Anchor's entrypoints tie the accounts slice to the accounts' own lifetime, which the native
+// processor signature doesn't; the runtime keeps both alive for the whole call
+fn gotsol_entry(program_id: &Pubkey, accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
+    gotsol::entry(program_id, unsafe { std::mem::transmute::<&[AccountInfo], &[AccountInfo]>(accounts) }, data)
+}
+
+fn mock_amm_entry(program_id: &Pubkey, accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
+    mock_amm::entry(program_id, unsafe { std::mem::transmute::<&[AccountInfo], &[AccountInfo]>(accounts) }, data)
+}
+
+pub struct Bank {
+    context: ProgramTestContext,
+}
+
+impl Bank {
+    pub async fn new() -> Self {
+        let mut program_test = ProgramTest::default();
+        program_test.prefer_bpf(false);
+        program_test.add_program("gotsol", gotsol::ID, processor!(gotsol_entry));
+        program_test.add_program("mock_amm", mock_amm::ID, processor!(mock_amm_entry));
+
+        let context = program_test.start_with_context().await;
+        let clock = Clock { unix_timestamp: START_TIME, ..context.banks_client.get_sysvar::<Clock>().await.unwrap() };
+        context.set_sysvar(&clock);
+        Self { context }
+    }
+
+    pub fn set(&mut self, key: Pubkey, account: Account) {
+        self.context.set_account(&key, &account.into());
+    }
+
+    pub async fn get(&mut self, key: &Pubkey) -> Option<Account> {
+        self.context.banks_client.get_account(*key).await.unwrap()
+    }
+
+    pub async fn lamports(&mut self, key: &Pubkey) -> u64 {
+        self.get(key).await.map_or(0, |account| account.lamports)
+    }
+
+    pub async fn token_amount(&mut self, key: &Pubkey) -> u64 {
+        let account = self.get(key).await.expect("token account");
+        spl_token::state::Account::unpack(&account.data).unwrap().amount
+    }
+
+    pub async fn warp(&mut self, seconds: i64) {
+        let mut clock = self.context.banks_client.get_sysvar::<Clock>().await.unwrap();
+        clock.unix_timestamp += seconds;
+        self.context.set_sysvar(&clock);
+    }
+
+    /// Runs `instruction` as a transaction paid for by the test payer and signed by `signers`,
+    /// committing account changes only if it succeeds
+    pub async fn process(&mut self, instruction: &Instruction, signers: &[Pubkey]) -> std::result::Result<(), TransactionError> {
+        let mut instruction = instruction.clone();
+        for meta in &mut instruction.accounts {
+            meta.is_signer |= signers.contains(&meta.pubkey);
+        }
+
+        // A fresh blockhash each time, so repeating a transaction doesn't dedupe it
+        let blockhash = self.context.get_new_latest_blockhash().await.unwrap();
+        let payer = &self.context.payer;
+        let mut transaction = Transaction::new_unsigned(Message::new(&[instruction], Some(&payer.pubkey())));
+        transaction.partial_sign(&[payer], blockhash);
+
+        self.context.banks_client.process_transaction_with_metadata(transaction).await.unwrap().result
+    }
+}
@@ -0,0 +1,265 @@
+mod runtime;
+
+use std::str::FromStr;
+
+use anchor_lang::{
+    prelude::*,
+    solana_program::instruction::Instruction,
+    system_program,
+    InstructionData,
+};
+use anchor_spl::{associated_token::get_associated_token_address, token::spl_token};
+use gotsol::errors::CustomError;
+use gotsol::state::{Global, Merchant, WithdrawalLimit};
+use runtime::{failure, Bank, TestAccount};
+use solana_sdk::transaction::TransactionError;
+
+const HOUSE: &str = "Hth4EBxLWJSoRWj7raCKoniuzcvXt8MUFgGKty3B66ih";
+const VAULT_BALANCE: u64 = 1_000_000_000;
+const AMOUNT: u64 = 100_000_000; // 0.1 SOL
+const RATE: u64 = 150_000_000; // 150 stablecoin units (6 decimals) per SOL
+const POOL_LIQUIDITY: u64 = 1_000_000_000_000;
+
+struct Swap {
+    bank: Bank,
+    owner: Pubkey,
+    merchant: Pubkey,
+    vault: Pubkey,
+    withdrawal_limit: Pubkey,
+    swap_authority: Pubkey,
+    swap_wsol: Pubkey,
+    stablecoin_mint: Pubkey,
+    merchant_stablecoin_ata: Pubkey,
+    owner_stablecoin_ata: Pubkey,
+    house_stablecoin_ata: Pubkey,
+    merchant_wsol_ata: Pubkey,
+    pool: Pubkey,
+    pool_in: Pubkey,
+    pool_out: Pubkey,
+}
+
+fn pda(seeds: &[&[u8]], program_id: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(seeds, program_id)
+}
+
+impl Swap {
+    async fn new() -> Self {
+        let mut bank = Bank::new().await;
+        let wsol = spl_token::native_mint::ID;
+        let house = Pubkey::from_str(HOUSE).unwrap();
+
+        let owner = Pubkey::new_unique();
+        let entity_name = "Coffee".to_string();
+        let (merchant, merchant_bump) = pda(&[b"merchant", entity_name.as_bytes(), owner.as_ref()], &gotsol::ID);
+        let (vault, vault_bump) = pda(&[b"vault", merchant.as_ref()], &gotsol::ID);
+        let (global, global_bump) = pda(&[b"global"], &gotsol::ID);
+        let (withdrawal_limit, _) = pda(&[b"withdrawal_limit", merchant.as_ref()], &gotsol::ID);
+        let (swap_authority, _) = pda(&[b"swap_authority", merchant.as_ref()], &gotsol::ID);
+        let (swap_wsol, _) = pda(&[b"swap_wsol", merchant.as_ref()], &gotsol::ID);
+
+        bank.set(owner, TestAccount::system(10 * VAULT_BALANCE));
+        bank.set(vault, TestAccount::system(VAULT_BALANCE));
+        bank.set(
+            merchant,
+            TestAccount::anchor(
+                &Merchant { owner, entity_name, fee_eligible: false, merchant_bump, vault_bump },
+                Merchant::LEN,
+            ),
+        );
+        bank.set(
+            global,
+            TestAccount::anchor(
+                &Global { swap_router: mock_amm::ID, rent_recoup_rate: 0, fee_payers: vec![], bump: global_bump },
+                Global::LEN,
+            ),
+        );
+
+        let stablecoin_mint = Pubkey::new_unique();
+        bank.set(wsol, TestAccount::mint(9));
+        bank.set(stablecoin_mint, TestAccount::mint(6));
+
+        let merchant_stablecoin_ata = get_associated_token_address(&merchant, &stablecoin_mint);
+        let owner_stablecoin_ata = get_associated_token_address(&owner, &stablecoin_mint);
+        let house_stablecoin_ata = get_associated_token_address(&house, &stablecoin_mint);
+        bank.set(merchant_stablecoin_ata, TestAccount::token(stablecoin_mint, merchant, 0));
+        bank.set(owner_stablecoin_ata, TestAccount::token(stablecoin_mint, owner, 0));
+        bank.set(house_stablecoin_ata, TestAccount::token(stablecoin_mint, house, 0));
+
+        // wSOL the merchant already holds outside the vault, e.g. from the old swap flow
+        let merchant_wsol_ata = get_associated_token_address(&merchant, &wsol);
+        bank.set(merchant_wsol_ata, TestAccount::token(wsol, merchant, VAULT_BALANCE));
+
+        let (pool, pool_bump) = pda(&[b"pool", wsol.as_ref(), stablecoin_mint.as_ref()], &mock_amm::ID);
+        let pool_in = Pubkey::new_unique();
+        let pool_out = Pubkey::new_unique();
+        bank.set(
+            pool,
+            TestAccount::anchor_owned_by(
+                &mock_amm::Pool { mint_in: wsol, mint_out: stablecoin_mint, rate: RATE, bump: pool_bump },
+                mock_amm::Pool::LEN,
+                mock_amm::ID,
+            ),
+        );
+        bank.set(pool_in, TestAccount::token(wsol, pool, 0));
+        bank.set(pool_out, TestAccount::token(stablecoin_mint, pool, POOL_LIQUIDITY));
+
+        Self {
+            bank,
+            owner,
+            merchant,
+            vault,
+            withdrawal_limit,
+            swap_authority,
+            swap_wsol,
+            stablecoin_mint,
+            merchant_stablecoin_ata,
+            owner_stablecoin_ata,
+            house_stablecoin_ata,
+            merchant_wsol_ata,
+            pool,
+            pool_in,
+            pool_out,
+        }
+    }
+
+    /// The router instruction a client would build: spend `amount_in` of the swap authority's wSOL
+    fn swap_ix(&self, amount_in: u64) -> Instruction {
+        self.swap_ix_from(self.swap_authority, self.swap_wsol, amount_in)
+    }
+
+    fn swap_ix_from(&self, user: Pubkey, user_in: Pubkey, amount_in: u64) -> Instruction {
+        Instruction {
+            program_id: mock_amm::ID,
+            accounts: mock_amm::accounts::Swap {
+                user,
+                pool: self.pool,
+                mint_in: spl_token::native_mint::ID,
+                mint_out: self.stablecoin_mint,
+                user_in,
+                user_out: self.merchant_stablecoin_ata,
+                pool_in: self.pool_in,
+                pool_out: self.pool_out,
+                token_program: spl_token::ID,
+            }
+            .to_account_metas(None),
+            data: mock_amm::instruction::Swap { amount_in, minimum_out: 0 }.data(),
+        }
+    }
+
+    async fn withdraw(&mut self, amount: u64, minimum_out: u64, swap_ix: Instruction) -> std::result::Result<(), TransactionError> {
+        let mut accounts = gotsol::accounts::WithdrawSolAsSpl {
+            owner: self.owner,
+            merchant: self.merchant,
+            vault: self.vault,
+            withdrawal_limit: self.withdrawal_limit,
+            global: pda(&[b"global"], &gotsol::ID).0,
+            swap_program: mock_amm::ID,
+            swap_authority: self.swap_authority,
+            wsol_mint: spl_token::native_mint::ID,
+            swap_wsol: self.swap_wsol,
+            stablecoin_mint: self.stablecoin_mint,
+            merchant_stablecoin_ata: self.merchant_stablecoin_ata,
+            owner_stablecoin_ata: self.owner_stablecoin_ata,
+            house: Pubkey::from_str(HOUSE).unwrap(),
+            house_stablecoin_ata: self.house_stablecoin_ata,
+            associated_token_program: anchor_spl::associated_token::ID,
+            token_program: spl_token::ID,
+            system_program: system_program::ID,
+            event_authority: pda(&[b"__event_authority"], &gotsol::ID).0,
+            program: gotsol::ID,
+        }
+        .to_account_metas(None);
+        accounts.extend(swap_ix.accounts.into_iter().map(|meta| AccountMeta { is_signer: false, ..meta }));
+
+        let ix = Instruction {
+            program_id: gotsol::ID,
+            accounts,
+            data: gotsol::instruction::WithdrawSolAsSpl { amount, minimum_out, swap_data: swap_ix.data }.data(),
+        };
+        let owner = self.owner;
+        self.bank.process(&ix, &[owner]).await
+    }
+}
+
+#[tokio::test]
+async fn swaps_vault_sol_and_splits_the_output() {
+    let mut swap = Swap::new().await;
+    let owner_lamports = swap.bank.lamports(&swap.owner).await;
+
+    swap.withdraw(AMOUNT, 15_000_000, swap.swap_ix(AMOUNT)).await.unwrap();
+
+    // 0.1 SOL at 150 is 15 units: 99% to the owner, 1% to the house
+    assert_eq!(swap.bank.token_amount(&swap.owner_stablecoin_ata).await, 14_850_000);
+    assert_eq!(swap.bank.token_amount(&swap.house_stablecoin_ata).await, 150_000);
+    assert_eq!(swap.bank.token_amount(&swap.merchant_stablecoin_ata).await, 0);
+    assert_eq!(swap.bank.lamports(&swap.vault).await, VAULT_BALANCE - AMOUNT);
+
+    // The temporary wSOL account is closed and its rent returned to the owner
+    assert!(swap.bank.get(&swap.swap_wsol).await.is_none());
+    assert_eq!(swap.bank.lamports(&swap.owner).await, owner_lamports);
+}
+
+#[tokio::test]
+async fn returns_unswapped_wsol_to_the_vault() {
+    let mut swap = Swap::new().await;
+    let owner_lamports = swap.bank.lamports(&swap.owner).await;
+
+    // The router only spends half of what was wrapped
+    swap.withdraw(AMOUNT, 7_500_000, swap.swap_ix(AMOUNT / 2)).await.unwrap();
+
+    assert_eq!(swap.bank.token_amount(&swap.owner_stablecoin_ata).await, 7_425_000);
+    assert_eq!(swap.bank.lamports(&swap.vault).await, VAULT_BALANCE - AMOUNT / 2);
+    assert!(swap.bank.get(&swap.swap_wsol).await.is_none());
+    assert_eq!(swap.bank.lamports(&swap.owner).await, owner_lamports);
+}
+
+#[tokio::test]
+async fn router_cannot_spend_merchant_accounts() {
+    let mut swap = Swap::new().await;
+
+    // A client-built route that spends the merchant's own wSOL instead of the wrapped amount
+    let steal = swap.swap_ix_from(swap.merchant, swap.merchant_wsol_ata, VAULT_BALANCE);
+    assert_eq!(swap.withdraw(AMOUNT, 1, steal).await, failure(ErrorCode::AccountNotSigner));
+
+    assert_eq!(swap.bank.token_amount(&swap.merchant_wsol_ata).await, VAULT_BALANCE);
+    assert_eq!(swap.bank.token_amount(&swap.owner_stablecoin_ata).await, 0);
+    assert_eq!(swap.bank.lamports(&swap.vault).await, VAULT_BALANCE);
+}
+
+#[tokio::test]
+async fn rejects_output_below_the_minimum() {
+    let mut swap = Swap::new().await;
+
+    assert_eq!(
+        swap.withdraw(AMOUNT, 15_000_001, swap.swap_ix(AMOUNT)).await,
+        failure(CustomError::SwapSlippageExceeded)
+    );
+    assert_eq!(swap.bank.lamports(&swap.vault).await, VAULT_BALANCE);
+}
+
+#[tokio::test]
+async fn enforces_the_withdrawal_limit() {
+    let mut swap = Swap::new().await;
+    let (_, bump) = pda(&[b"withdrawal_limit", swap.merchant.as_ref()], &gotsol::ID);
+    let limit = WithdrawalLimit {
+        merchant: swap.merchant,
+        mint: Pubkey::default(),
+        max_per_withdrawal: AMOUNT - 1,
+        max_per_day: 0,
+        pending_max_per_withdrawal: 0,
+        pending_max_per_day: 0,
+        pending_effective_at: 0,
+        last_bucket: 0,
+        withdrawn: [0; 24],
+        bump,
+    };
+    swap.bank.set(swap.withdrawal_limit, TestAccount::anchor(&limit, WithdrawalLimit::LEN));
+
+    assert_eq!(
+        swap.withdraw(AMOUNT, 1, swap.swap_ix(AMOUNT)).await,
+        failure(CustomError::WithdrawalLimitExceeded)
+    );
+
+    swap.withdraw(AMOUNT - 1, 1, swap.swap_ix(AMOUNT - 1)).await.unwrap();
+    assert_eq!(swap.bank.lamports(&swap.vault).await, VAULT_BALANCE - (AMOUNT - 1));
+}
@@ -0,0 +1,145 @@
+#![allow(unexpected_cfgs)]
+#![allow(deprecated)]
+
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{
+    transfer_checked, Mint, TokenAccount, TokenInterface, TransferChecked,
+};
+
+declare_id!("7obNCw8woiubFYHDvyuUMU641RGDUNwdCEEAjJeziG1Z");
+
+// Rates are quoted in output units per 10^9 input units (one whole SOL)
+const RATE_DIVISOR: u128 = 1_000_000_000;
+
+/// A constant-rate AMM standing in for a real swap router in localnet tests
+#[program]
+pub mod mock_amm {
+    use super::*;
+
+    pub fn initialize_pool(ctx: Context<InitializePool>, rate: u64) -> Result<()> {
+        ctx.accounts.pool.set_inner(Pool {
+            mint_in: ctx.accounts.mint_in.key(),
+            mint_out: ctx.accounts.mint_out.key(),
+            rate,
+            bump: ctx.bumps.pool,
+        });
+        Ok(())
+    }
+
+    pub fn swap(ctx: Context<Swap>, amount_in: u64, minimum_out: u64) -> Result<()> {
+        let amount_out = (amount_in as u128)
+            .checked_mul(ctx.accounts.pool.rate as u128)
+            .and_then(|v| v.checked_div(RATE_DIVISOR))
+            .and_then(|v| u64::try_from(v).ok())
+            .ok_or(MockAmmError::MathOverflow)?;
+        require!(amount_out >= minimum_out, MockAmmError::SlippageExceeded);
+
+        transfer_checked(
+            CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                TransferChecked {
+                    from: ctx.accounts.user_in.to_account_info(),
+                    mint: ctx.accounts.mint_in.to_account_info(),
+                    to: ctx.accounts.pool_in.to_account_info(),
+                    authority: ctx.accounts.user.to_account_info(),
+                },
+            ),
+            amount_in,
+            ctx.accounts.mint_in.decimals,
+        )?;
+
+        let mint_in = ctx.accounts.mint_in.key();
+        let mint_out = ctx.accounts.mint_out.key();
+        let seeds = &[
+            b"pool".as_ref(),
+            mint_in.as_ref(),
+            mint_out.as_ref(),
+            &[ctx.accounts.pool.bump],
+        ];
+
+        transfer_checked(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                TransferChecked {
+                    from: ctx.accounts.pool_out.to_account_info(),
+                    mint: ctx.accounts.mint_out.to_account_info(),
+                    to: ctx.accounts.user_out.to_account_info(),
+                    authority: ctx.accounts.pool.to_account_info(),
+                },
+                &[seeds],
+            ),
+            amount_out,
+            ctx.accounts.mint_out.decimals,
+        )
+    }
+}
+
+#[account]
+pub struct Pool {
+    pub mint_in: Pubkey,
+    pub mint_out: Pubkey,
+    pub rate: u64,
+    pub bump: u8,
+}
+
+impl Pool {
+    pub const LEN: usize = 8 + 32 + 32 + 8 + 1;
+}
+
+#[derive(Accounts)]
+pub struct InitializePool<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub mint_in: InterfaceAccount<'info, Mint>,
+    pub mint_out: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        init,
+        payer = payer,
+        seeds = [b"pool", mint_in.key().as_ref(), mint_out.key().as_ref()],
+        space = Pool::LEN,
+        bump
+    )]
+    pub pool: Account<'info, Pool>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct Swap<'info> {
+    pub user: Signer<'info>,
+
+    #[account(
+        seeds = [b"pool", mint_in.key().as_ref(), mint_out.key().as_ref()],
+        bump = pool.bump,
+        has_one = mint_in,
+        has_one = mint_out)]
+    pub pool: Account<'info, Pool>,
+
+    pub mint_in: InterfaceAccount<'info, Mint>,
+    pub mint_out: InterfaceAccount<'info, Mint>,
+
+    #[account(mut, token::mint = mint_in, token::authority = user)]
+    pub user_in: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(mut, token::mint = mint_out)]
+    pub user_out: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(mut, token::mint = mint_in, token::authority = pool)]
+    pub pool_in: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(mut, token::mint = mint_out, token::authority = pool)]
+    pub pool_out: InterfaceAccount<'info, TokenAccount>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[error_code]
+pub enum MockAmmError {
+    #[msg("Swap output is below the minimum!")]
+    SlippageExceeded,
+
+    #[msg("Arithmetic overflow!")]
+    MathOverflow,
+}
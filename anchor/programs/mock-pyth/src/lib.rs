@@ -0,0 +1,86 @@
+#![allow(unexpected_cfgs)]
+#![allow(deprecated)]
+
+use anchor_lang::prelude::*;
+
+// Loaded at the real Pyth receiver's address in localnet tests, so the accounts it posts pass
+// gotsol's owner check
+declare_id!("rec5EKMGg6MxZYaMdyBfgwp4d5rB9T1VQH5pJv5LtFJ");
+
+/// Posts fully verified PriceUpdateV2 accounts timed against the validator's own clock
+#[program]
+pub mod mock_pyth {
+    use super::*;
+
+    /// `age_seconds` backdates the publish time; a negative age puts it in the future
+    pub fn post_price_update(
+        ctx: Context<PostPriceUpdate>,
+        feed_id: [u8; 32],
+        price: i64,
+        conf: u64,
+        exponent: i32,
+        age_seconds: i64,
+    ) -> Result<()> {
+        let clock = Clock::get()?;
+        let publish_time = clock.unix_timestamp.saturating_sub(age_seconds);
+
+        ctx.accounts.price_update.set_inner(PriceUpdateV2 {
+            write_authority: ctx.accounts.payer.key(),
+            verification_level: VerificationLevel::Full,
+            price_message: PriceFeedMessage {
+                feed_id,
+                price,
+                conf,
+                exponent,
+                publish_time,
+                prev_publish_time: publish_time,
+                ema_price: price,
+                ema_conf: conf,
+            },
+            posted_slot: clock.slot,
+        });
+        Ok(())
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub enum VerificationLevel {
+    Partial { num_signatures: u8 },
+    Full,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct PriceFeedMessage {
+    pub feed_id: [u8; 32],
+    pub price: i64,
+    pub conf: u64,
+    pub exponent: i32,
+    pub publish_time: i64,
+    pub prev_publish_time: i64,
+    pub ema_price: i64,
+    pub ema_conf: u64,
+}
+
+// Same name and layout as the receiver's account, so the discriminator matches too
+#[account]
+pub struct PriceUpdateV2 {
+    pub write_authority: Pubkey,
+    pub verification_level: VerificationLevel,
+    pub price_message: PriceFeedMessage,
+    pub posted_slot: u64,
+}
+
+impl PriceUpdateV2 {
+    pub const LEN: usize = 8 + 32 + 2 + (32 + 8 + 8 + 4 + 8 + 8 + 8 + 8) + 8;
+}
+
+#[derive(Accounts)]
+pub struct PostPriceUpdate<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    #[account(init, payer = payer, space = PriceUpdateV2::LEN)]
+    pub price_update: Account<'info, PriceUpdateV2>,
+
+    pub system_program: Program<'info, System>,
+}
@@ -1,10 +1,13 @@
 import * as anchor from "@coral-xyz/anchor";
 import { Program, web3, BN } from "@coral-xyz/anchor";
 import { Gotsol } from "../target/types/gotsol";
+import { MockAmm } from "../target/types/mock_amm";
+import { MockPyth } from "../target/types/mock_pyth";
 import { PublicKey, SystemProgram, Keypair } from "@solana/web3.js";
 import { assert } from "chai";
-import { TOKEN_PROGRAM_ID, createMint, getOrCreateAssociatedTokenAccount, mintTo, getAccount } from "@solana/spl-token";
+import { TOKEN_PROGRAM_ID, TOKEN_2022_PROGRAM_ID, createMint, getOrCreateAssociatedTokenAccount, getAssociatedTokenAddressSync, mintTo, getAccount, NATIVE_MINT } from "@solana/spl-token";
 import { Buffer } from "buffer";
+import { createHash } from "crypto";
 import wallet from "/home/agent/.config/solana/id.json";
 
 
@@ -1204,6 +1207,2159 @@ describe("gotsol", () => {
   //   }
   // });
     
+  it("creates and pays an invoice", async function () {
+    const invoiceId = new BN(1);
+    const amount = new BN(250_000);
+    const dueDate = new BN(Math.floor(Date.now() / 1000) + 3600);
+    const memoHash = Array.from(Buffer.alloc(32, 1));
+    const [invoice] = PublicKey.findProgramAddressSync([
+      Buffer.from("invoice"),
+      merchant.toBuffer(),
+      invoiceId.toArrayLike(Buffer, "le", 8),
+    ], program.programId);
+
+    await mintTo(
+      provider.connection,
+      stablecoinMintAuthority,
+      stablecoinMint,
+      recipientStablecoinAta,
+      stablecoinMintAuthority,
+      1_000_000
+    );
+
+    const createTx = await program.methods
+      .createInvoice(invoiceId, amount, dueDate, memoHash)
+      .accountsPartial({
+        owner: owner.publicKey,
+        merchant,
+        stablecoinMint,
+        invoice,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([owner])
+      .rpc();
+    console.log("createInvoice tx:", createTx);
+
+    const merchantBalanceBefore = (await getAccount(provider.connection, merchantStablecoinAta)).amount;
+    const payTx = await program.methods
+      .payInvoice(amount, null)
+      .accountsPartial({
+        customer: recipient.publicKey,
+        merchant,
+        invoice,
+        stablecoinMint,
+        customerStablecoinAta: recipientStablecoinAta,
+        merchantStablecoinAta,
+        associatedTokenProgram: anchor.utils.token.ASSOCIATED_PROGRAM_ID,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([recipient])
+      .rpc();
+    console.log("payInvoice tx:", payTx);
+
+    const invoiceAccount = await program.account.invoice.fetch(invoice);
+    const merchantBalanceAfter = (await getAccount(provider.connection, merchantStablecoinAta)).amount;
+    assert.ok(invoiceAccount.status.paid !== undefined, "Invoice should be marked paid");
+    assert.ok(invoiceAccount.payer.equals(recipient.publicKey));
+    assert.equal((merchantBalanceAfter - merchantBalanceBefore).toString(), amount.toString());
+  });
+
+  it("rejects double payment of an invoice", async function () {
+    const invoiceId = new BN(1);
+    const [invoice] = PublicKey.findProgramAddressSync([
+      Buffer.from("invoice"),
+      merchant.toBuffer(),
+      invoiceId.toArrayLike(Buffer, "le", 8),
+    ], program.programId);
+    try {
+      await program.methods
+        .payInvoice(new BN(250_000), null)
+        .accountsPartial({
+          customer: recipient.publicKey,
+          merchant,
+          invoice,
+          stablecoinMint,
+          customerStablecoinAta: recipientStablecoinAta,
+          merchantStablecoinAta,
+          associatedTokenProgram: anchor.utils.token.ASSOCIATED_PROGRAM_ID,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([recipient])
+        .rpc();
+      assert.fail("Expected error due to invoice already paid");
+    } catch (e: any) {
+      console.log("Double invoice payment rejected as expected");
+      assert.ok(e.message.includes("InvoiceNotOpen"));
+    }
+  });
+
+  it("rejects invoice payment with wrong amount", async function () {
+    const invoiceId = new BN(2);
+    const dueDate = new BN(Math.floor(Date.now() / 1000) + 3600);
+    const [invoice] = PublicKey.findProgramAddressSync([
+      Buffer.from("invoice"),
+      merchant.toBuffer(),
+      invoiceId.toArrayLike(Buffer, "le", 8),
+    ], program.programId);
+    await program.methods
+      .createInvoice(invoiceId, new BN(100_000), dueDate, Array.from(Buffer.alloc(32)))
+      .accountsPartial({
+        owner: owner.publicKey,
+        merchant,
+        stablecoinMint,
+        invoice,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([owner])
+      .rpc();
+    try {
+      await program.methods
+        .payInvoice(new BN(99_999), null)
+        .accountsPartial({
+          customer: recipient.publicKey,
+          merchant,
+          invoice,
+          stablecoinMint,
+          customerStablecoinAta: recipientStablecoinAta,
+          merchantStablecoinAta,
+          associatedTokenProgram: anchor.utils.token.ASSOCIATED_PROGRAM_ID,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([recipient])
+        .rpc();
+      assert.fail("Expected error due to invoice amount mismatch");
+    } catch (e: any) {
+      console.log("Wrong invoice amount rejected as expected");
+      assert.ok(e.message.includes("InvoiceAmountMismatch"));
+    }
+  });
+
+  it("cancels an invoice and rejects paying it", async function () {
+    const invoiceId = new BN(2);
+    const [invoice] = PublicKey.findProgramAddressSync([
+      Buffer.from("invoice"),
+      merchant.toBuffer(),
+      invoiceId.toArrayLike(Buffer, "le", 8),
+    ], program.programId);
+    const tx = await program.methods
+      .cancelInvoice()
+      .accountsPartial({
+        owner: owner.publicKey,
+        merchant,
+        invoice,
+      })
+      .signers([owner])
+      .rpc();
+    console.log("cancelInvoice tx:", tx);
+    const invoiceAccount = await program.account.invoice.fetch(invoice);
+    assert.ok(invoiceAccount.status.cancelled !== undefined, "Invoice should be marked cancelled");
+
+    try {
+      await program.methods
+        .payInvoice(new BN(100_000), null)
+        .accountsPartial({
+          customer: recipient.publicKey,
+          merchant,
+          invoice,
+          stablecoinMint,
+          customerStablecoinAta: recipientStablecoinAta,
+          merchantStablecoinAta,
+          associatedTokenProgram: anchor.utils.token.ASSOCIATED_PROGRAM_ID,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([recipient])
+        .rpc();
+      assert.fail("Expected error due to cancelled invoice");
+    } catch (e: any) {
+      console.log("Payment of cancelled invoice rejected as expected");
+      assert.ok(e.message.includes("InvoiceNotOpen"));
+    }
+  });
+
+  it("closes a settled invoice and returns its rent", async function () {
+    const invoiceFor = (id: number) => PublicKey.findProgramAddressSync([
+      Buffer.from("invoice"),
+      merchant.toBuffer(),
+      new BN(id).toArrayLike(Buffer, "le", 8),
+    ], program.programId)[0];
+
+    const openInvoice = invoiceFor(3);
+    await program.methods
+      .createInvoice(new BN(3), new BN(100_000), new BN(Math.floor(Date.now() / 1000) + 3600), Array.from(Buffer.alloc(32)))
+      .accountsPartial({
+        owner: owner.publicKey,
+        merchant,
+        stablecoinMint,
+        invoice: openInvoice,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([owner])
+      .rpc();
+    try {
+      await program.methods
+        .closeInvoice()
+        .accountsPartial({ owner: owner.publicKey, merchant, rentPayer: owner.publicKey, invoice: openInvoice })
+        .signers([owner])
+        .rpc();
+      assert.fail("Expected error due to open invoice");
+    } catch (e: any) {
+      console.log("Closing an open invoice rejected as expected");
+      assert.ok(e.message.includes("InvoiceStillOpen"));
+    }
+
+    const cancelledInvoice = invoiceFor(2);
+    const rent = await provider.connection.getBalance(cancelledInvoice);
+    const ownerBalanceBefore = await provider.connection.getBalance(owner.publicKey);
+    const tx = await program.methods
+      .closeInvoice()
+      .accountsPartial({ owner: owner.publicKey, merchant, rentPayer: owner.publicKey, invoice: cancelledInvoice })
+      .signers([owner])
+      .rpc();
+    console.log("closeInvoice tx:", tx);
+    const ownerBalanceAfter = await provider.connection.getBalance(owner.publicKey);
+    assert.equal(ownerBalanceAfter - ownerBalanceBefore, rent - 5000, "Owner should get the rent back, less the signature fee");
+    assert.isNull(await provider.connection.getAccountInfo(cancelledInvoice));
+  });
+
+  it("pays with a tip credited to the tip pool", async function () {
+    const [tipPool] = PublicKey.findProgramAddressSync([
+      Buffer.from("tip_pool"),
+      merchant.toBuffer(),
+      stablecoinMint.toBuffer(),
+    ], program.programId);
+    const createTx = await program.methods
+      .createTipPool()
+      .accountsPartial({
+        owner: owner.publicKey,
+        merchant,
+        stablecoinMint,
+        tipPool,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([owner])
+      .rpc();
+    console.log("createTipPool tx:", createTx);
+
+    const amount = new BN(200_000);
+    const tip = new BN(30_000);
+    const employee = Keypair.generate();
+    const merchantBalanceBefore = (await getAccount(provider.connection, merchantStablecoinAta)).amount;
+    const payTx = await program.methods
+      .paySpl(amount, tip, employee.publicKey, new BN(0), null)
+      .accountsPartial({
+        customer: recipient.publicKey,
+        merchant,
+        stablecoinMint,
+        customerStablecoinAta: recipientStablecoinAta,
+        merchantStablecoinAta,
+        tipPool,
+        associatedTokenProgram: anchor.utils.token.ASSOCIATED_PROGRAM_ID,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([recipient])
+      .rpc();
+    console.log("paySpl tx:", payTx);
+
+    const merchantBalanceAfter = (await getAccount(provider.connection, merchantStablecoinAta)).amount;
+    const tipPoolAccount = await getAccount(provider.connection, tipPool);
+    assert.equal((merchantBalanceAfter - merchantBalanceBefore).toString(), amount.toString());
+    assert.equal(tipPoolAccount.amount.toString(), tip.toString());
+  });
+
+  it("rejects a tip without a tip pool", async function () {
+    try {
+      await program.methods
+        .paySpl(new BN(100_000), new BN(10_000), null, new BN(0), null)
+        .accountsPartial({
+          customer: recipient.publicKey,
+          merchant,
+          stablecoinMint,
+          customerStablecoinAta: recipientStablecoinAta,
+          merchantStablecoinAta,
+          tipPool: null,
+          associatedTokenProgram: anchor.utils.token.ASSOCIATED_PROGRAM_ID,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([recipient])
+        .rpc();
+      assert.fail("Expected error due to missing tip pool");
+    } catch (e: any) {
+      console.log("Tip without tip pool rejected as expected");
+      assert.ok(e.message.includes("TipPoolRequired"));
+    }
+  });
+
+  it("distributes tips to employees", async function () {
+    const [tipPool] = PublicKey.findProgramAddressSync([
+      Buffer.from("tip_pool"),
+      merchant.toBuffer(),
+      stablecoinMint.toBuffer(),
+    ], program.programId);
+    const employeeA = Keypair.generate();
+    const employeeB = Keypair.generate();
+    const employeeAAta = (await getOrCreateAssociatedTokenAccount(
+      provider.connection,
+      owner,
+      stablecoinMint,
+      employeeA.publicKey
+    )).address;
+    const employeeBAta = (await getOrCreateAssociatedTokenAccount(
+      provider.connection,
+      owner,
+      stablecoinMint,
+      employeeB.publicKey
+    )).address;
+
+    const tx = await program.methods
+      .distributeTips([new BN(20_000), new BN(10_000)])
+      .accountsPartial({
+        owner: owner.publicKey,
+        merchant,
+        stablecoinMint,
+        tipPool,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .remainingAccounts([
+        { pubkey: employeeAAta, isWritable: true, isSigner: false },
+        { pubkey: employeeBAta, isWritable: true, isSigner: false },
+      ])
+      .signers([owner])
+      .rpc();
+    console.log("distributeTips tx:", tx);
+
+    assert.equal((await getAccount(provider.connection, employeeAAta)).amount.toString(), "20000");
+    assert.equal((await getAccount(provider.connection, employeeBAta)).amount.toString(), "10000");
+    assert.equal((await getAccount(provider.connection, tipPool)).amount.toString(), "0");
+  });
+
+  it("authorizes and partially captures a payment", async function () {
+    const authorizationId = new BN(1);
+    const amount = new BN(300_000);
+    const captureAmount = new BN(200_000);
+    const expiresAt = new BN(Math.floor(Date.now() / 1000) + 3600);
+    const [authorization] = PublicKey.findProgramAddressSync([
+      Buffer.from("authorization"),
+      merchant.toBuffer(),
+      recipient.publicKey.toBuffer(),
+      authorizationId.toArrayLike(Buffer, "le", 8),
+    ], program.programId);
+    const [escrow] = PublicKey.findProgramAddressSync([
+      Buffer.from("escrow"),
+      authorization.toBuffer(),
+    ], program.programId);
+
+    const authorizeTx = await program.methods
+      .authorizePayment(authorizationId, amount, expiresAt)
+      .accountsPartial({
+        customer: recipient.publicKey,
+        merchant,
+        stablecoinMint,
+        customerStablecoinAta: recipientStablecoinAta,
+        authorization,
+        escrow,
+        associatedTokenProgram: anchor.utils.token.ASSOCIATED_PROGRAM_ID,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([recipient])
+      .rpc();
+    console.log("authorizePayment tx:", authorizeTx);
+    assert.equal((await getAccount(provider.connection, escrow)).amount.toString(), amount.toString());
+
+    const customerBalanceBefore = (await getAccount(provider.connection, recipientStablecoinAta)).amount;
+    const merchantBalanceBefore = (await getAccount(provider.connection, merchantStablecoinAta)).amount;
+    const captureTx = await program.methods
+      .capturePayment(captureAmount)
+      .accountsPartial({
+        owner: owner.publicKey,
+        merchant,
+        customer: recipient.publicKey,
+        authorization,
+        escrow,
+        stablecoinMint,
+        merchantStablecoinAta,
+        customerStablecoinAta: recipientStablecoinAta,
+        associatedTokenProgram: anchor.utils.token.ASSOCIATED_PROGRAM_ID,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([owner])
+      .rpc();
+    console.log("capturePayment tx:", captureTx);
+
+    const customerBalanceAfter = (await getAccount(provider.connection, recipientStablecoinAta)).amount;
+    const merchantBalanceAfter = (await getAccount(provider.connection, merchantStablecoinAta)).amount;
+    assert.equal((merchantBalanceAfter - merchantBalanceBefore).toString(), captureAmount.toString());
+    assert.equal((customerBalanceAfter - customerBalanceBefore).toString(), amount.sub(captureAmount).toString());
+    assert.isNull(await provider.connection.getAccountInfo(escrow));
+    assert.isNull(await provider.connection.getAccountInfo(authorization));
+  });
+
+  it("voids an authorization back to the customer", async function () {
+    const authorizationId = new BN(2);
+    const amount = new BN(150_000);
+    const expiresAt = new BN(Math.floor(Date.now() / 1000) + 3600);
+    const [authorization] = PublicKey.findProgramAddressSync([
+      Buffer.from("authorization"),
+      merchant.toBuffer(),
+      recipient.publicKey.toBuffer(),
+      authorizationId.toArrayLike(Buffer, "le", 8),
+    ], program.programId);
+    const [escrow] = PublicKey.findProgramAddressSync([
+      Buffer.from("escrow"),
+      authorization.toBuffer(),
+    ], program.programId);
+
+    await program.methods
+      .authorizePayment(authorizationId, amount, expiresAt)
+      .accountsPartial({
+        customer: recipient.publicKey,
+        merchant,
+        stablecoinMint,
+        customerStablecoinAta: recipientStablecoinAta,
+        authorization,
+        escrow,
+        associatedTokenProgram: anchor.utils.token.ASSOCIATED_PROGRAM_ID,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([recipient])
+      .rpc();
+
+    // a third party cannot release a hold that has not expired
+    const stranger = Keypair.generate();
+    try {
+      await program.methods
+        .voidAuthorization()
+        .accountsPartial({
+          authority: stranger.publicKey,
+          merchant,
+          customer: recipient.publicKey,
+          authorization,
+          escrow,
+          stablecoinMint,
+          customerStablecoinAta: recipientStablecoinAta,
+          associatedTokenProgram: anchor.utils.token.ASSOCIATED_PROGRAM_ID,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([stranger])
+        .rpc();
+      assert.fail("Expected error due to unexpired authorization");
+    } catch (e: any) {
+      console.log("Early third-party void rejected as expected");
+      assert.ok(e.message.includes("AuthorizationNotExpired"));
+    }
+
+    const customerBalanceBefore = (await getAccount(provider.connection, recipientStablecoinAta)).amount;
+    const tx = await program.methods
+      .voidAuthorization()
+      .accountsPartial({
+        authority: owner.publicKey,
+        merchant,
+        customer: recipient.publicKey,
+        authorization,
+        escrow,
+        stablecoinMint,
+        customerStablecoinAta: recipientStablecoinAta,
+        associatedTokenProgram: anchor.utils.token.ASSOCIATED_PROGRAM_ID,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([owner])
+      .rpc();
+    console.log("voidAuthorization tx:", tx);
+
+    const customerBalanceAfter = (await getAccount(provider.connection, recipientStablecoinAta)).amount;
+    assert.equal((customerBalanceAfter - customerBalanceBefore).toString(), amount.toString());
+    assert.isNull(await provider.connection.getAccountInfo(authorization));
+  });
+
+  it("creates and charges a subscription", async function () {
+    const amount = new BN(50_000);
+    const interval = new BN(30 * 24 * 60 * 60);
+    const [subscription] = PublicKey.findProgramAddressSync([
+      Buffer.from("subscription"),
+      merchant.toBuffer(),
+      recipient.publicKey.toBuffer(),
+    ], program.programId);
+
+    const createTx = await program.methods
+      .createSubscription(amount, interval, new BN(12))
+      .accountsPartial({
+        customer: recipient.publicKey,
+        merchant,
+        stablecoinMint,
+        customerStablecoinAta: recipientStablecoinAta,
+        subscription,
+        associatedTokenProgram: anchor.utils.token.ASSOCIATED_PROGRAM_ID,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([recipient])
+      .rpc();
+    console.log("createSubscription tx:", createTx);
+
+    const [subscriptionDelegate] = PublicKey.findProgramAddressSync([Buffer.from("subscription_delegate")], program.programId);
+    const customerAta = await getAccount(provider.connection, recipientStablecoinAta);
+    assert.ok(customerAta.delegate.equals(subscriptionDelegate));
+    assert.equal(customerAta.delegatedAmount.toString(), amount.muln(12).toString());
+
+    const merchantBalanceBefore = (await getAccount(provider.connection, merchantStablecoinAta)).amount;
+    const chargeTx = await program.methods
+      .chargeSubscription()
+      .accountsPartial({
+        cranker: provider.wallet.publicKey,
+        merchant,
+        customer: recipient.publicKey,
+        subscription,
+        stablecoinMint,
+        customerStablecoinAta: recipientStablecoinAta,
+        merchantStablecoinAta,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .rpc();
+    console.log("chargeSubscription tx:", chargeTx);
+
+    const merchantBalanceAfter = (await getAccount(provider.connection, merchantStablecoinAta)).amount;
+    const subscriptionAccount = await program.account.subscription.fetch(subscription);
+    assert.equal((merchantBalanceAfter - merchantBalanceBefore).toString(), amount.toString());
+    assert.equal(subscriptionAccount.chargesMade.toNumber(), 1);
+    assert.equal(subscriptionAccount.remainingAllowance.toString(), amount.muln(11).toString());
+  });
+
+  it("rejects a subscription charge before the interval elapses", async function () {
+    const [subscription] = PublicKey.findProgramAddressSync([
+      Buffer.from("subscription"),
+      merchant.toBuffer(),
+      recipient.publicKey.toBuffer(),
+    ], program.programId);
+    try {
+      await program.methods
+        .chargeSubscription()
+        .accountsPartial({
+          cranker: provider.wallet.publicKey,
+          merchant,
+          customer: recipient.publicKey,
+          subscription,
+          stablecoinMint,
+          customerStablecoinAta: recipientStablecoinAta,
+          merchantStablecoinAta,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .rpc();
+      assert.fail("Expected error due to subscription not due");
+    } catch (e: any) {
+      console.log("Early subscription charge rejected as expected");
+      assert.ok(e.message.includes("SubscriptionNotDue"));
+    }
+  });
+
+  it("keeps an existing subscription chargeable when the customer subscribes elsewhere", async function () {
+    const secondMerchantName = "SubscriptionMerchant";
+    const [secondMerchant] = PublicKey.findProgramAddressSync([
+      Buffer.from("merchant"),
+      Buffer.from(secondMerchantName),
+      owner.publicKey.toBuffer(),
+    ], program.programId);
+    const [secondVault] = PublicKey.findProgramAddressSync([Buffer.from("vault"), secondMerchant.toBuffer()], program.programId);
+    await program.methods
+      .createMerchant(secondMerchantName)
+      .accountsPartial({
+        owner: owner.publicKey,
+        merchant: secondMerchant,
+        vault: secondVault,
+        associatedTokenProgram: anchor.utils.token.ASSOCIATED_PROGRAM_ID,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([owner])
+      .rpc();
+    const secondMerchantAta = (await getOrCreateAssociatedTokenAccount(
+      provider.connection,
+      owner,
+      stablecoinMint,
+      secondMerchant,
+      true
+    )).address;
+
+    const [firstSubscription] = PublicKey.findProgramAddressSync([
+      Buffer.from("subscription"),
+      merchant.toBuffer(),
+      recipient.publicKey.toBuffer(),
+    ], program.programId);
+    const [secondSubscription] = PublicKey.findProgramAddressSync([
+      Buffer.from("subscription"),
+      secondMerchant.toBuffer(),
+      recipient.publicKey.toBuffer(),
+    ], program.programId);
+    const firstRemaining = (await program.account.subscription.fetch(firstSubscription)).remainingAllowance;
+    const amount = new BN(20_000);
+
+    await program.methods
+      .createSubscription(amount, new BN(7 * 24 * 60 * 60), new BN(3))
+      .accountsPartial({
+        customer: recipient.publicKey,
+        merchant: secondMerchant,
+        stablecoinMint,
+        customerStablecoinAta: recipientStablecoinAta,
+        subscription: secondSubscription,
+        associatedTokenProgram: anchor.utils.token.ASSOCIATED_PROGRAM_ID,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([recipient])
+      .rpc();
+
+    // The approval covers both subscriptions rather than replacing the first one's
+    const customerAta = await getAccount(provider.connection, recipientStablecoinAta);
+    assert.equal(customerAta.delegatedAmount.toString(), firstRemaining.add(amount.muln(3)).toString());
+
+    await program.methods
+      .chargeSubscription()
+      .accountsPartial({
+        cranker: provider.wallet.publicKey,
+        merchant: secondMerchant,
+        customer: recipient.publicKey,
+        subscription: secondSubscription,
+        stablecoinMint,
+        customerStablecoinAta: recipientStablecoinAta,
+        merchantStablecoinAta: secondMerchantAta,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .rpc();
+    const firstAccount = await program.account.subscription.fetch(firstSubscription);
+    assert.equal(firstAccount.remainingAllowance.toString(), firstRemaining.toString());
+    assert.equal((await getAccount(provider.connection, secondMerchantAta)).amount.toString(), amount.toString());
+  });
+
+  it("delays retries after a failed subscription charge", async function () {
+    const customer = Keypair.generate();
+    const sig = await provider.connection.requestAirdrop(customer.publicKey, web3.LAMPORTS_PER_SOL);
+    await provider.connection.confirmTransaction(sig, "confirmed");
+    const customerAta = (await getOrCreateAssociatedTokenAccount(
+      provider.connection,
+      customer,
+      stablecoinMint,
+      customer.publicKey
+    )).address;
+    const [subscription] = PublicKey.findProgramAddressSync([
+      Buffer.from("subscription"),
+      merchant.toBuffer(),
+      customer.publicKey.toBuffer(),
+    ], program.programId);
+
+    await program.methods
+      .createSubscription(new BN(10_000), new BN(30 * 24 * 60 * 60), new BN(12))
+      .accountsPartial({
+        customer: customer.publicKey,
+        merchant,
+        stablecoinMint,
+        customerStablecoinAta: customerAta,
+        subscription,
+        associatedTokenProgram: anchor.utils.token.ASSOCIATED_PROGRAM_ID,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([customer])
+      .rpc();
+
+    const charge = () => program.methods
+      .chargeSubscription()
+      .accountsPartial({
+        cranker: provider.wallet.publicKey,
+        merchant,
+        customer: customer.publicKey,
+        subscription,
+        stablecoinMint,
+        customerStablecoinAta: customerAta,
+        merchantStablecoinAta,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .rpc();
+
+    // The empty account fails the charge, which is recorded rather than reverted
+    await charge();
+    const subscriptionAccount = await program.account.subscription.fetch(subscription);
+    assert.ok(subscriptionAccount.retryAfter.toNumber() > Math.floor(Date.now() / 1000));
+    assert.equal(subscriptionAccount.chargesMade.toNumber(), 0);
+
+    try {
+      await charge();
+      assert.fail("Expected error due to retry delay");
+    } catch (e: any) {
+      console.log("Immediate retry of a failed charge rejected as expected");
+      assert.ok(e.message.includes("SubscriptionRetryTooSoon"));
+    }
+  });
+
+  it("cancels subscriptions and revokes the delegate with the last one", async function () {
+    const secondMerchant = PublicKey.findProgramAddressSync([
+      Buffer.from("merchant"),
+      Buffer.from("SubscriptionMerchant"),
+      owner.publicKey.toBuffer(),
+    ], program.programId)[0];
+    const cancel = async (subscriptionMerchant: PublicKey) => {
+      const [subscription] = PublicKey.findProgramAddressSync([
+        Buffer.from("subscription"),
+        subscriptionMerchant.toBuffer(),
+        recipient.publicKey.toBuffer(),
+      ], program.programId);
+      const tx = await program.methods
+        .cancelSubscription()
+        .accountsPartial({
+          authority: recipient.publicKey,
+          merchant: subscriptionMerchant,
+          customer: recipient.publicKey,
+          subscription,
+          stablecoinMint,
+          customerStablecoinAta: recipientStablecoinAta,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([recipient])
+        .rpc();
+      console.log("cancelSubscription tx:", tx);
+      assert.isNull(await provider.connection.getAccountInfo(subscription));
+    };
+
+    // Cancelling one subscription leaves the other's allowance approved
+    await cancel(merchant);
+    let customerAta = await getAccount(provider.connection, recipientStablecoinAta);
+    assert.equal(customerAta.delegatedAmount.toString(), "40000");
+
+    await cancel(secondMerchant);
+    customerAta = await getAccount(provider.connection, recipientStablecoinAta);
+    assert.isNull(customerAta.delegate);
+  });
+
+  it("pays with a gift card redeemed once by its code", async function () {
+    const cardId = new BN(1);
+    const amount = new BN(100_000);
+    // The code handed to the giftee is this keypair's secret; only its public key goes on chain
+    const code = Keypair.generate();
+    const giftee = Keypair.generate();
+    const sig = await provider.connection.requestAirdrop(giftee.publicKey, web3.LAMPORTS_PER_SOL);
+    await provider.connection.confirmTransaction(sig, "confirmed");
+    const gifteeAta = (await getOrCreateAssociatedTokenAccount(
+      provider.connection,
+      giftee,
+      stablecoinMint,
+      giftee.publicKey
+    )).address;
+    const [giftCard] = PublicKey.findProgramAddressSync([
+      Buffer.from("gift_card"),
+      merchant.toBuffer(),
+      cardId.toArrayLike(Buffer, "le", 8),
+    ], program.programId);
+
+    const purchaseTx = await program.methods
+      .purchaseGiftCard(cardId, amount, null, code.publicKey, new BN(0))
+      .accountsPartial({
+        buyer: recipient.publicKey,
+        merchant,
+        stablecoinMint,
+        buyerStablecoinAta: recipientStablecoinAta,
+        merchantStablecoinAta,
+        giftCard,
+        associatedTokenProgram: anchor.utils.token.ASSOCIATED_PROGRAM_ID,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([recipient])
+      .rpc();
+    console.log("purchaseGiftCard tx:", purchaseTx);
+
+    const payWithCard = (customer: Keypair, customerAta: PublicKey, payment: BN) => program.methods
+      .paySpl(payment, new BN(0), null, new BN(0), null)
+      .accountsPartial({
+        customer: customer.publicKey,
+        merchant,
+        stablecoinMint,
+        customerStablecoinAta: customerAta,
+        merchantStablecoinAta,
+        giftCard,
+        giftCardCode: code.publicKey,
+        associatedTokenProgram: anchor.utils.token.ASSOCIATED_PROGRAM_ID,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([customer, code])
+      .rpc();
+
+    // The card covers the whole payment; the merchant was paid when the card was bought
+    const merchantBalanceBefore = (await getAccount(provider.connection, merchantStablecoinAta)).amount;
+    const payTx = await payWithCard(giftee, gifteeAta, new BN(40_000));
+    console.log("paySpl (gift card) tx:", payTx);
+
+    const giftCardAccount = await program.account.giftCard.fetch(giftCard);
+    assert.equal(giftCardAccount.balance.toNumber(), 60_000);
+    assert.ok(giftCardAccount.holder.equals(giftee.publicKey), "Card should be bound to the redeemer");
+    assert.equal((await getAccount(provider.connection, merchantStablecoinAta)).amount, merchantBalanceBefore);
+
+    // The code is spent once redeemed, even by someone who learned the secret
+    const thief = Keypair.generate();
+    const thiefSig = await provider.connection.requestAirdrop(thief.publicKey, web3.LAMPORTS_PER_SOL);
+    await provider.connection.confirmTransaction(thiefSig, "confirmed");
+    const thiefAta = (await getOrCreateAssociatedTokenAccount(
+      provider.connection,
+      thief,
+      stablecoinMint,
+      thief.publicKey
+    )).address;
+    try {
+      await payWithCard(thief, thiefAta, new BN(10_000));
+      assert.fail("Expected error due to gift card code reused from another wallet");
+    } catch (e: any) {
+      console.log("Spent gift card code rejected as expected");
+      assert.ok(e.message.includes("UnauthorizedGiftCardRedemption"));
+    }
+  });
+
+  it("splits a payment between a gift card and the wallet", async function () {
+    const cardId = new BN(2);
+    const [giftCard] = PublicKey.findProgramAddressSync([
+      Buffer.from("gift_card"),
+      merchant.toBuffer(),
+      cardId.toArrayLike(Buffer, "le", 8),
+    ], program.programId);
+    await program.methods
+      .purchaseGiftCard(cardId, new BN(10_000), recipient.publicKey, null, new BN(0))
+      .accountsPartial({
+        buyer: recipient.publicKey,
+        merchant,
+        stablecoinMint,
+        buyerStablecoinAta: recipientStablecoinAta,
+        merchantStablecoinAta,
+        giftCard,
+        associatedTokenProgram: anchor.utils.token.ASSOCIATED_PROGRAM_ID,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([recipient])
+      .rpc();
+
+    const merchantBalanceBefore = (await getAccount(provider.connection, merchantStablecoinAta)).amount;
+    await program.methods
+      .paySpl(new BN(15_000), new BN(0), null, new BN(0), null)
+      .accountsPartial({
+        customer: recipient.publicKey,
+        merchant,
+        stablecoinMint,
+        customerStablecoinAta: recipientStablecoinAta,
+        merchantStablecoinAta,
+        giftCard,
+        associatedTokenProgram: anchor.utils.token.ASSOCIATED_PROGRAM_ID,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([recipient])
+      .rpc();
+
+    const merchantBalanceAfter = (await getAccount(provider.connection, merchantStablecoinAta)).amount;
+    assert.equal((await program.account.giftCard.fetch(giftCard)).balance.toNumber(), 0);
+    assert.equal((merchantBalanceAfter - merchantBalanceBefore).toString(), "5000");
+
+    // An empty card can't be used again
+    try {
+      await program.methods
+        .paySpl(new BN(1_000), new BN(0), null, new BN(0), null)
+        .accountsPartial({
+          customer: recipient.publicKey,
+          merchant,
+          stablecoinMint,
+          customerStablecoinAta: recipientStablecoinAta,
+          merchantStablecoinAta,
+          giftCard,
+          associatedTokenProgram: anchor.utils.token.ASSOCIATED_PROGRAM_ID,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([recipient])
+        .rpc();
+      assert.fail("Expected error due to empty gift card");
+    } catch (e: any) {
+      console.log("Empty gift card rejected as expected");
+      assert.ok(e.message.includes("InsufficientGiftCardBalance"));
+    }
+  });
+
+  it("earns and redeems loyalty points on payments", async function () {
+    const [loyaltyProgram] = PublicKey.findProgramAddressSync([
+      Buffer.from("loyalty"),
+      merchant.toBuffer(),
+    ], program.programId);
+    const [pointsMint] = PublicKey.findProgramAddressSync([
+      Buffer.from("points_mint"),
+      merchant.toBuffer(),
+    ], program.programId);
+
+    // Anyone can fund the predictable points mint address first; that must not block creation
+    const griefer = Keypair.generate();
+    const griefSig = await provider.connection.requestAirdrop(griefer.publicKey, web3.LAMPORTS_PER_SOL);
+    await provider.connection.confirmTransaction(griefSig, "confirmed");
+    await provider.sendAndConfirm(
+      new web3.Transaction().add(SystemProgram.transfer({
+        fromPubkey: griefer.publicKey,
+        toPubkey: pointsMint,
+        lamports: 1_000_000,
+      })),
+      [griefer]
+    );
+
+    // 10% back in points, 1 point = 1 unit of discount
+    const createTx = await program.methods
+      .createLoyaltyProgram(new BN(1_000), new BN(10_000), 6, true)
+      .accountsPartial({
+        owner: owner.publicKey,
+        merchant,
+        loyaltyProgram,
+        pointsMint,
+        pointsTokenProgram: TOKEN_2022_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([owner])
+      .rpc();
+    console.log("createLoyaltyProgram tx:", createTx);
+
+    const customerPointsAta = (await getOrCreateAssociatedTokenAccount(
+      provider.connection,
+      recipient,
+      pointsMint,
+      recipient.publicKey,
+      false,
+      undefined,
+      undefined,
+      TOKEN_2022_PROGRAM_ID
+    )).address;
+
+    const loyaltyAccounts = {
+      customer: recipient.publicKey,
+      merchant,
+      stablecoinMint,
+      customerStablecoinAta: recipientStablecoinAta,
+      merchantStablecoinAta,
+      tipPool: null,
+      loyaltyProgram,
+      pointsMint,
+      customerPointsAta,
+      pointsTokenProgram: TOKEN_2022_PROGRAM_ID,
+      associatedTokenProgram: anchor.utils.token.ASSOCIATED_PROGRAM_ID,
+      tokenProgram: TOKEN_PROGRAM_ID,
+      systemProgram: SystemProgram.programId,
+    };
+
+    const earnTx = await program.methods
+      .paySpl(new BN(200_000), new BN(0), null, new BN(0), null)
+      .accountsPartial(loyaltyAccounts)
+      .signers([recipient])
+      .rpc();
+    console.log("paySpl (earn) tx:", earnTx);
+    let pointsAccount = await getAccount(provider.connection, customerPointsAta, undefined, TOKEN_2022_PROGRAM_ID);
+    assert.equal(pointsAccount.amount.toString(), "20000");
+
+    const merchantBalanceBefore = (await getAccount(provider.connection, merchantStablecoinAta)).amount;
+    const redeemTx = await program.methods
+      .paySpl(new BN(100_000), new BN(0), null, new BN(20_000), null)
+      .accountsPartial(loyaltyAccounts)
+      .signers([recipient])
+      .rpc();
+    console.log("paySpl (redeem) tx:", redeemTx);
+
+    const merchantBalanceAfter = (await getAccount(provider.connection, merchantStablecoinAta)).amount;
+    pointsAccount = await getAccount(provider.connection, customerPointsAta, undefined, TOKEN_2022_PROGRAM_ID);
+    assert.equal((merchantBalanceAfter - merchantBalanceBefore).toString(), "80000");
+    // 20,000 burned, then 10% of the 80,000 actually paid earned back
+    assert.equal(pointsAccount.amount.toString(), "8000");
+  });
+
+  it("applies a one-time coupon and rejects its reuse", async function () {
+    const codeHash = Array.from(createHash("sha256").update("WELCOME10").digest());
+    const [coupon] = PublicKey.findProgramAddressSync([
+      Buffer.from("coupon"),
+      merchant.toBuffer(),
+      Buffer.from(codeHash),
+    ], program.programId);
+
+    const createTx = await program.methods
+      .createCoupon(codeHash, { percent: { basisPoints: 1_000 } }, 1, new BN(0))
+      .accountsPartial({
+        owner: owner.publicKey,
+        merchant,
+        coupon,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([owner])
+      .rpc();
+    console.log("createCoupon tx:", createTx);
+
+    const couponAccounts = {
+      customer: recipient.publicKey,
+      merchant,
+      stablecoinMint,
+      customerStablecoinAta: recipientStablecoinAta,
+      merchantStablecoinAta,
+      tipPool: null,
+      coupon,
+      associatedTokenProgram: anchor.utils.token.ASSOCIATED_PROGRAM_ID,
+      tokenProgram: TOKEN_PROGRAM_ID,
+      systemProgram: SystemProgram.programId,
+    };
+
+    // Knowing the coupon's address isn't enough, the code itself has to be presented
+    try {
+      await program.methods
+        .paySpl(new BN(100_000), new BN(0), null, new BN(0), Buffer.from("WELCOME20"))
+        .accountsPartial(couponAccounts)
+        .signers([recipient])
+        .rpc();
+      assert.fail("Expected error due to wrong coupon code");
+    } catch (e: any) {
+      console.log("Wrong coupon code rejected as expected");
+      assert.ok(e.message.includes("InvalidCouponCode"));
+    }
+
+    const merchantBalanceBefore = (await getAccount(provider.connection, merchantStablecoinAta)).amount;
+    const payTx = await program.methods
+      .paySpl(new BN(100_000), new BN(0), null, new BN(0), Buffer.from("WELCOME10"))
+      .accountsPartial(couponAccounts)
+      .signers([recipient])
+      .rpc();
+    console.log("paySpl (coupon) tx:", payTx);
+
+    const merchantBalanceAfter = (await getAccount(provider.connection, merchantStablecoinAta)).amount;
+    const couponAccount = await program.account.coupon.fetch(coupon);
+    assert.equal((merchantBalanceAfter - merchantBalanceBefore).toString(), "90000");
+    assert.equal(couponAccount.redemptionCount, 1);
+
+    try {
+      await program.methods
+        .paySpl(new BN(100_000), new BN(0), null, new BN(0), Buffer.from("WELCOME10"))
+        .accountsPartial(couponAccounts)
+        .signers([recipient])
+        .rpc();
+      assert.fail("Expected error due to exhausted coupon");
+    } catch (e: any) {
+      console.log("Exhausted coupon rejected as expected");
+      assert.ok(e.message.includes("CouponExhausted"));
+    }
+  });
+
+  it("settles an invoice with a fixed coupon", async function () {
+    const codeHash = Array.from(createHash("sha256").update("INVOICE5").digest());
+    const [coupon] = PublicKey.findProgramAddressSync([
+      Buffer.from("coupon"),
+      merchant.toBuffer(),
+      Buffer.from(codeHash),
+    ], program.programId);
+    await program.methods
+      .createCoupon(codeHash, { fixed: { amount: new BN(5_000) } }, 10, new BN(0))
+      .accountsPartial({
+        owner: owner.publicKey,
+        merchant,
+        coupon,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([owner])
+      .rpc();
+
+    const invoiceId = new BN(50);
+    const [invoice] = PublicKey.findProgramAddressSync([
+      Buffer.from("invoice"),
+      merchant.toBuffer(),
+      invoiceId.toArrayLike(Buffer, "le", 8),
+    ], program.programId);
+    await program.methods
+      .createInvoice(invoiceId, new BN(50_000), new BN(Math.floor(Date.now() / 1000) + 3600), Array(32).fill(0))
+      .accountsPartial({
+        owner: owner.publicKey,
+        merchant,
+        stablecoinMint,
+        invoice,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([owner])
+      .rpc();
+
+    const merchantBalanceBefore = (await getAccount(provider.connection, merchantStablecoinAta)).amount;
+    const payTx = await program.methods
+      .payInvoice(new BN(50_000), Buffer.from("INVOICE5"))
+      .accountsPartial({
+        customer: recipient.publicKey,
+        merchant,
+        invoice,
+        stablecoinMint,
+        customerStablecoinAta: recipientStablecoinAta,
+        merchantStablecoinAta,
+        coupon,
+        associatedTokenProgram: anchor.utils.token.ASSOCIATED_PROGRAM_ID,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([recipient])
+      .rpc();
+    console.log("payInvoice (coupon) tx:", payTx);
+
+    const merchantBalanceAfter = (await getAccount(provider.connection, merchantStablecoinAta)).amount;
+    const invoiceAccount = await program.account.invoice.fetch(invoice);
+    assert.ok(invoiceAccount.status.paid !== undefined, "Invoice should be marked paid");
+    assert.equal((merchantBalanceAfter - merchantBalanceBefore).toString(), "45000");
+  });
+
+  it("lets a manager create a product and sells it through an order", async function () {
+    const manager = Keypair.generate();
+    const sig = await provider.connection.requestAirdrop(manager.publicKey, web3.LAMPORTS_PER_SOL);
+    await provider.connection.confirmTransaction(sig, "confirmed");
+    const [merchantManager] = PublicKey.findProgramAddressSync([
+      Buffer.from("manager"),
+      merchant.toBuffer(),
+      manager.publicKey.toBuffer(),
+    ], program.programId);
+    const sku = "LATTE-12OZ";
+    const [product] = PublicKey.findProgramAddressSync([
+      Buffer.from("product"),
+      merchant.toBuffer(),
+      Buffer.from(sku),
+    ], program.programId);
+
+    const addTx = await program.methods
+      .addManager(manager.publicKey)
+      .accountsPartial({
+        owner: owner.publicKey,
+        merchant,
+        merchantManager,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([owner])
+      .rpc();
+    console.log("addManager tx:", addTx);
+
+    const createTx = await program.methods
+      .createProduct(sku, new BN(45_000), new BN(3), 1_000)
+      .accountsPartial({
+        authority: manager.publicKey,
+        merchant,
+        manager: merchantManager,
+        stablecoinMint,
+        product,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([manager])
+      .rpc();
+    console.log("createProduct tx:", createTx);
+
+    const orderId = new BN(1);
+    const [order] = PublicKey.findProgramAddressSync([
+      Buffer.from("order"),
+      merchant.toBuffer(),
+      recipient.publicKey.toBuffer(),
+      orderId.toArrayLike(Buffer, "le", 8),
+    ], program.programId);
+
+    // The merchant runs a loyalty program, so the order earns points too
+    const [loyaltyProgram] = PublicKey.findProgramAddressSync([
+      Buffer.from("loyalty"),
+      merchant.toBuffer(),
+    ], program.programId);
+    const [pointsMint] = PublicKey.findProgramAddressSync([
+      Buffer.from("points_mint"),
+      merchant.toBuffer(),
+    ], program.programId);
+    const customerPointsAta = getAssociatedTokenAddressSync(pointsMint, recipient.publicKey, false, TOKEN_2022_PROGRAM_ID);
+    const pointsBefore = (await getAccount(provider.connection, customerPointsAta, undefined, TOKEN_2022_PROGRAM_ID)).amount;
+
+    const merchantBalanceBefore = (await getAccount(provider.connection, merchantStablecoinAta)).amount;
+    const orderTx = await program.methods
+      .payOrder(orderId, [new BN(2)], new BN(99_000), new BN(0), null)
+      .accountsPartial({
+        customer: recipient.publicKey,
+        merchant,
+        stablecoinMint,
+        customerStablecoinAta: recipientStablecoinAta,
+        merchantStablecoinAta,
+        tipPool: null,
+        order,
+        loyaltyProgram,
+        pointsMint,
+        customerPointsAta,
+        pointsTokenProgram: TOKEN_2022_PROGRAM_ID,
+        associatedTokenProgram: anchor.utils.token.ASSOCIATED_PROGRAM_ID,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+      })
+      .remainingAccounts([{ pubkey: product, isWritable: true, isSigner: false }])
+      .signers([recipient])
+      .rpc();
+    console.log("payOrder tx:", orderTx);
+
+    const merchantBalanceAfter = (await getAccount(provider.connection, merchantStablecoinAta)).amount;
+    const productAccount = await program.account.product.fetch(product);
+    const orderAccount = await program.account.order.fetch(order);
+    assert.equal((merchantBalanceAfter - merchantBalanceBefore).toString(), "99000");
+    assert.equal(productAccount.stock.toNumber(), 1);
+    assert.equal(orderAccount.lineItems.length, 1);
+    assert.equal(orderAccount.subtotal.toNumber(), 90_000);
+    assert.equal(orderAccount.tax.toNumber(), 9_000);
+    const pointsAfter = (await getAccount(provider.connection, customerPointsAta, undefined, TOKEN_2022_PROGRAM_ID)).amount;
+    assert.equal((pointsAfter - pointsBefore).toString(), "9900");
+  });
+
+  it("rejects an order that exceeds stock", async function () {
+    const [product] = PublicKey.findProgramAddressSync([
+      Buffer.from("product"),
+      merchant.toBuffer(),
+      Buffer.from("LATTE-12OZ"),
+    ], program.programId);
+    const orderId = new BN(2);
+    const [order] = PublicKey.findProgramAddressSync([
+      Buffer.from("order"),
+      merchant.toBuffer(),
+      recipient.publicKey.toBuffer(),
+      orderId.toArrayLike(Buffer, "le", 8),
+    ], program.programId);
+    try {
+      // the same product listed twice must be checked against its reduced stock
+      await program.methods
+        .payOrder(orderId, [new BN(1), new BN(1)], new BN(99_000), new BN(0), null)
+        .accountsPartial({
+          customer: recipient.publicKey,
+          merchant,
+          stablecoinMint,
+          customerStablecoinAta: recipientStablecoinAta,
+          merchantStablecoinAta,
+          tipPool: null,
+          order,
+          associatedTokenProgram: anchor.utils.token.ASSOCIATED_PROGRAM_ID,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+        })
+        .remainingAccounts([
+          { pubkey: product, isWritable: true, isSigner: false },
+          { pubkey: product, isWritable: true, isSigner: false },
+        ])
+        .signers([recipient])
+        .rpc();
+      assert.fail("Expected error due to insufficient stock");
+    } catch (e: any) {
+      console.log("Out of stock order rejected as expected");
+      assert.ok(e.message.includes("OutOfStock"));
+    }
+  });
+
+  it("refunds a single line item of an order", async function () {
+    const orderId = new BN(1);
+    const [order] = PublicKey.findProgramAddressSync([
+      Buffer.from("order"),
+      merchant.toBuffer(),
+      recipient.publicKey.toBuffer(),
+      orderId.toArrayLike(Buffer, "le", 8),
+    ], program.programId);
+
+    const customerBalanceBefore = (await getAccount(provider.connection, recipientStablecoinAta)).amount;
+    const tx = await program.methods
+      .refundOrderItems([{ lineIndex: 0, quantity: new BN(1) }])
+      .accountsPartial({
+        owner: owner.publicKey,
+        merchant,
+        order,
+        customer: recipient.publicKey,
+        stablecoinMint,
+        merchantStablecoinAta,
+        customerStablecoinAta: recipientStablecoinAta,
+        associatedTokenProgram: anchor.utils.token.ASSOCIATED_PROGRAM_ID,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([owner])
+      .rpc();
+    console.log("refundOrderItems tx:", tx);
+
+    // one unit at 45,000 plus its half of the line's 9,000 tax
+    const customerBalanceAfter = (await getAccount(provider.connection, recipientStablecoinAta)).amount;
+    const orderAccount = await program.account.order.fetch(order);
+    assert.equal((customerBalanceAfter - customerBalanceBefore).toString(), "49500");
+    assert.equal(orderAccount.lineItems[0].refundedQuantity.toNumber(), 1);
+
+    try {
+      await program.methods
+        .refundOrderItems([{ lineIndex: 0, quantity: new BN(2) }])
+        .accountsPartial({
+          owner: owner.publicKey,
+          merchant,
+          order,
+          customer: recipient.publicKey,
+          stablecoinMint,
+          merchantStablecoinAta,
+          customerStablecoinAta: recipientStablecoinAta,
+          associatedTokenProgram: anchor.utils.token.ASSOCIATED_PROGRAM_ID,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([owner])
+        .rpc();
+      assert.fail("Expected error due to over-refunding a line item");
+    } catch (e: any) {
+      console.log("Line item over-refund rejected as expected");
+      assert.ok(e.message.includes("InvalidLineItemRefund"));
+    }
+
+    // The order has to stay while the merchant can still refund against it
+    try {
+      await program.methods
+        .closeOrder()
+        .accountsPartial({ customer: recipient.publicKey, merchant, order })
+        .signers([recipient])
+        .rpc();
+      assert.fail("Expected error due to a still refundable order");
+    } catch (e: any) {
+      console.log("Refundable order close rejected as expected");
+      assert.ok(e.message.includes("OrderStillRefundable"));
+    }
+
+    await program.methods
+      .refundOrderItems([{ lineIndex: 0, quantity: new BN(1) }])
+      .accountsPartial({
+        owner: owner.publicKey,
+        merchant,
+        order,
+        customer: recipient.publicKey,
+        stablecoinMint,
+        merchantStablecoinAta,
+        customerStablecoinAta: recipientStablecoinAta,
+        associatedTokenProgram: anchor.utils.token.ASSOCIATED_PROGRAM_ID,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([owner])
+      .rpc();
+
+    // Fully refunded, so the customer can take the rent back
+    const orderRent = await provider.connection.getBalance(order);
+    const customerLamportsBefore = await provider.connection.getBalance(recipient.publicKey);
+    const closeTx = await program.methods
+      .closeOrder()
+      .accountsPartial({ customer: recipient.publicKey, merchant, order })
+      .signers([recipient])
+      .rpc();
+    console.log("closeOrder tx:", closeTx);
+
+    const customerLamportsAfter = await provider.connection.getBalance(recipient.publicKey);
+    assert.isNull(await provider.connection.getAccountInfo(order));
+    assert.equal(customerLamportsAfter - customerLamportsBefore, orderRent - 5000);
+  });
+
+  it("rejects product creation by a non-manager", async function () {
+    const stranger = Keypair.generate();
+    const sku = "STOLEN-SKU";
+    const [product] = PublicKey.findProgramAddressSync([
+      Buffer.from("product"),
+      merchant.toBuffer(),
+      Buffer.from(sku),
+    ], program.programId);
+    try {
+      await program.methods
+        .createProduct(sku, new BN(1_000), new BN(1), 0)
+        .accountsPartial({
+          feePayer: provider.wallet.publicKey,
+          authority: stranger.publicKey,
+          merchant,
+          manager: null,
+          stablecoinMint,
+          product,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([stranger])
+        .rpc();
+      assert.fail("Expected error due to unauthorized product manager");
+    } catch (e: any) {
+      console.log("Unauthorized product creation rejected as expected");
+      assert.ok(e.message.includes("UnauthorizedProductManager"));
+    }
+  });
+
+  // Posts a $150.00 SOL/USD price update through the mock Pyth receiver, `ageSeconds` old by the validator's clock
+  const postSolUsdPrice = async (conf: number, ageSeconds: number): Promise<PublicKey> => {
+    const mockPyth = anchor.workspace.MockPyth as Program<MockPyth>;
+    const priceUpdate = Keypair.generate();
+    const feedId = Array.from(Buffer.from("ef0d8b6fda2ceba41da15d4095d1da392a0d2f8ed0c6c7bc0f4cfac8c280b56d", "hex"));
+    await mockPyth.methods
+      .postPriceUpdate(feedId, new BN(15_000_000_000), new BN(conf), -8, new BN(ageSeconds))
+      .accountsPartial({
+        payer: provider.wallet.publicKey,
+        priceUpdate: priceUpdate.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([priceUpdate])
+      .rpc();
+    return priceUpdate.publicKey;
+  };
+
+  it("pays a USD amount in SOL at the oracle price", async function () {
+    const priceUpdate = await postSolUsdPrice(15_000_000, 0);
+    const paymentId = new BN(1);
+    const usdAmount = new BN(3_000_000); // $3.00
+    const expectedLamports = 20_000_000; // $3.00 / $150.00 = 0.02 SOL
+    const [payment] = PublicKey.findProgramAddressSync([
+      Buffer.from("payment"),
+      merchant.toBuffer(),
+      recipient.publicKey.toBuffer(),
+      paymentId.toArrayLike(Buffer, "le", 8),
+    ], program.programId);
+
+    const vaultBefore = await provider.connection.getBalance(vault);
+    const tx = await program.methods
+      .paySolUsd(paymentId, usdAmount, new BN(expectedLamports))
+      .accountsPartial({
+        customer: recipient.publicKey,
+        merchant,
+        vault,
+        priceUpdate,
+        payment,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([recipient])
+      .rpc();
+    console.log("Pay SOL in USD tx:", tx);
+
+    const vaultAfter = await provider.connection.getBalance(vault);
+    assert.equal(vaultAfter - vaultBefore, expectedLamports);
+
+    const paymentAccount = await program.account.payment.fetch(payment);
+    assert.equal(paymentAccount.lamports.toNumber(), expectedLamports);
+    assert.equal(paymentAccount.usdAmount.toNumber(), 3_000_000);
+    assert.equal(paymentAccount.price.toString(), "15000000000");
+    assert.equal(paymentAccount.priceExpo, -8);
+
+    // The receipt is kept for the merchant's books before its rent can be reclaimed
+    try {
+      await program.methods
+        .closePayment()
+        .accountsPartial({ payer: recipient.publicKey, merchant, payment })
+        .signers([recipient])
+        .rpc();
+      assert.fail("Expected error due to a retained payment receipt");
+    } catch (e: any) {
+      console.log("Early payment close rejected as expected");
+      assert.ok(e.message.includes("PaymentStillRetained"));
+    }
+  });
+
+  it("rejects USD payments with a stale, future-dated, low-confidence or slipped price", async function () {
+    const cases = [
+      { priceUpdate: await postSolUsdPrice(15_000_000, 3_600), maxLamports: 20_000_000, error: "StalePrice" },
+      { priceUpdate: await postSolUsdPrice(15_000_000, -3_600), maxLamports: 20_000_000, error: "InvalidPriceTimestamp" },
+      { priceUpdate: await postSolUsdPrice(750_000_000, 0), maxLamports: 20_000_000, error: "PriceConfidenceTooWide" },
+      { priceUpdate: await postSolUsdPrice(15_000_000, 0), maxLamports: 19_999_999, error: "PriceSlippageExceeded" },
+      { priceUpdate: stablecoinMint, maxLamports: 20_000_000, error: "InvalidPriceAccount" },
+    ];
+    for (const [i, c] of cases.entries()) {
+      const paymentId = new BN(100 + i);
+      const [payment] = PublicKey.findProgramAddressSync([
+        Buffer.from("payment"),
+        merchant.toBuffer(),
+        recipient.publicKey.toBuffer(),
+        paymentId.toArrayLike(Buffer, "le", 8),
+      ], program.programId);
+      try {
+        await program.methods
+          .paySolUsd(paymentId, new BN(3_000_000), new BN(c.maxLamports))
+          .accountsPartial({
+            customer: recipient.publicKey,
+            merchant,
+            vault,
+            priceUpdate: c.priceUpdate,
+            payment,
+            systemProgram: SystemProgram.programId,
+          })
+          .signers([recipient])
+          .rpc();
+        assert.fail(`Expected ${c.error}`);
+      } catch (e: any) {
+        assert.ok(e.message.includes(c.error), e.message);
+      }
+    }
+  });
+
+  it("withdraws SOL as stablecoin through the configured swap router", async function () {
+    const mockAmm = anchor.workspace.MockAmm as Program<MockAmm>;
+    const [global] = PublicKey.findProgramAddressSync([Buffer.from("global")], program.programId);
+
+    // Point the global swap router at the mock AMM
+    const globalInfo = await provider.connection.getAccountInfo(global);
+    if (globalInfo === null) {
+      await program.methods
+        .initializeGlobal(mockAmm.programId)
+        .accountsPartial({ auth: auth.publicKey, global, systemProgram: SystemProgram.programId })
+        .signers([auth])
+        .rpc();
+    } else {
+      await program.methods
+        .setSwapRouter(mockAmm.programId)
+        .accountsPartial({ auth: auth.publicKey, global })
+        .signers([auth])
+        .rpc();
+    }
+
+    // 150 stablecoin units (6 decimals) per SOL
+    const [pool] = PublicKey.findProgramAddressSync([
+      Buffer.from("pool"),
+      NATIVE_MINT.toBuffer(),
+      stablecoinMint.toBuffer(),
+    ], mockAmm.programId);
+    await mockAmm.methods
+      .initializePool(new BN(150_000_000))
+      .accountsPartial({ payer: owner.publicKey, mintIn: NATIVE_MINT, mintOut: stablecoinMint, pool })
+      .signers([owner])
+      .rpc();
+    const poolIn = (await getOrCreateAssociatedTokenAccount(provider.connection, owner, NATIVE_MINT, pool, true)).address;
+    const poolOut = (await getOrCreateAssociatedTokenAccount(provider.connection, owner, stablecoinMint, pool, true)).address;
+    await mintTo(provider.connection, stablecoinMintAuthority, stablecoinMint, poolOut, stablecoinMintAuthority, 1_000_000_000);
+
+    const amount = new BN(web3.LAMPORTS_PER_SOL / 10);
+    await provider.sendAndConfirm(new web3.Transaction().add(
+      web3.SystemProgram.transfer({ fromPubkey: owner.publicKey, toPubkey: vault, lamports: amount.toNumber() })
+    ), [owner]);
+
+    // The client builds the router instruction; gotsol signs for the swap authority, which only
+    // ever holds the wrapped amount in a temporary account
+    const [swapAuthority] = PublicKey.findProgramAddressSync([
+      Buffer.from("swap_authority"),
+      merchant.toBuffer(),
+    ], program.programId);
+    const [swapWsol] = PublicKey.findProgramAddressSync([
+      Buffer.from("swap_wsol"),
+      merchant.toBuffer(),
+    ], program.programId);
+    const buildSwap = (minimumOut: BN) => mockAmm.methods
+      .swap(amount, minimumOut)
+      .accountsPartial({
+        user: swapAuthority,
+        pool,
+        mintIn: NATIVE_MINT,
+        mintOut: stablecoinMint,
+        userIn: swapWsol,
+        userOut: merchantStablecoinAta,
+        poolIn,
+        poolOut,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .instruction();
+    const withdraw = async (minimumOut: BN) => {
+      const swapIx = await buildSwap(new BN(0)); // gotsol enforces the minimum itself
+      return program.methods
+        .withdrawSolAsSpl(amount, minimumOut, swapIx.data)
+        .accountsPartial({
+          owner: owner.publicKey,
+          merchant,
+          vault,
+          global,
+          swapProgram: mockAmm.programId,
+          swapAuthority,
+          wsolMint: NATIVE_MINT,
+          swapWsol,
+          stablecoinMint,
+          merchantStablecoinAta,
+          ownerStablecoinAta,
+          house: HOUSE,
+          houseStablecoinAta,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .remainingAccounts(swapIx.keys.map((key) => ({ ...key, isSigner: false })))
+        .signers([owner])
+        .rpc();
+    };
+
+    // 0.1 SOL at 150 yields 15_000_000; demand one unit more
+    try {
+      await withdraw(new BN(15_000_001));
+      assert.fail("Expected SwapSlippageExceeded");
+    } catch (e: any) {
+      assert.ok(e.message.includes("SwapSlippageExceeded"), e.message);
+    }
+
+    const ownerBefore = await getAccount(provider.connection, ownerStablecoinAta);
+    const houseBefore = await getAccount(provider.connection, houseStablecoinAta);
+    const tx = await withdraw(new BN(15_000_000));
+    console.log("Withdraw SOL as SPL tx:", tx);
+
+    const ownerAfter = await getAccount(provider.connection, ownerStablecoinAta);
+    const houseAfter = await getAccount(provider.connection, houseStablecoinAta);
+    assert.equal(Number(ownerAfter.amount - ownerBefore.amount), 14_850_000);
+    assert.equal(Number(houseAfter.amount - houseBefore.amount), 150_000);
+    // The temporary wSOL account doesn't outlive the withdrawal
+    assert.isNull(await provider.connection.getAccountInfo(swapWsol));
+  });
+
+  it("withdraws four mints in one instruction", async function () {
+    // Three classic mints plus one Token-2022 mint
+    const mints: { mint: PublicKey; tokenProgram: PublicKey }[] = [];
+    for (const tokenProgram of [TOKEN_PROGRAM_ID, TOKEN_PROGRAM_ID, TOKEN_PROGRAM_ID, TOKEN_2022_PROGRAM_ID]) {
+      const mint = await createMint(provider.connection, stablecoinMintAuthority, stablecoinMintAuthority.publicKey, null, 6, undefined, undefined, tokenProgram);
+      mints.push({ mint, tokenProgram });
+    }
+
+    const groups: web3.AccountMeta[] = [];
+    const owners: PublicKey[] = [];
+    for (const { mint, tokenProgram } of mints) {
+      const ata = async (authority: PublicKey) =>
+        (await getOrCreateAssociatedTokenAccount(provider.connection, owner, mint, authority, true, undefined, undefined, tokenProgram)).address;
+      const merchantAta = await ata(merchant);
+      const ownerAta = await ata(owner.publicKey);
+      const houseAta = await ata(HOUSE);
+      await mintTo(provider.connection, stablecoinMintAuthority, mint, merchantAta, stablecoinMintAuthority, 1_000_000, [], undefined, tokenProgram);
+      const [withdrawalLimit] = PublicKey.findProgramAddressSync([
+        Buffer.from("withdrawal_limit"),
+        merchant.toBuffer(),
+        mint.toBuffer(),
+      ], program.programId);
+      groups.push(
+        { pubkey: mint, isSigner: false, isWritable: false },
+        { pubkey: merchantAta, isSigner: false, isWritable: true },
+        { pubkey: ownerAta, isSigner: false, isWritable: true },
+        { pubkey: houseAta, isSigner: false, isWritable: true },
+        { pubkey: withdrawalLimit, isSigner: false, isWritable: true },
+      );
+      owners.push(ownerAta);
+    }
+
+    // Fixed amounts for three mints, withdraw everything from the last
+    const tx = await program.methods
+      .withdrawMany([new BN(500_000), new BN(500_000), new BN(1_000_000), null])
+      .accountsPartial({
+        owner: owner.publicKey,
+        merchant,
+        house: HOUSE,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        token2022Program: TOKEN_2022_PROGRAM_ID,
+      })
+      .remainingAccounts(groups)
+      .signers([owner])
+      .rpc();
+    console.log("Withdraw many tx:", tx);
+
+    const expected = [495_000, 495_000, 990_000, 990_000];
+    for (const [i, ownerAta] of owners.entries()) {
+      const account = await getAccount(provider.connection, ownerAta, undefined, mints[i].tokenProgram);
+      assert.equal(Number(account.amount), expected[i]);
+    }
+
+    // Swapping the owner and house ATAs of a group is rejected
+    const swapped = [...groups.slice(0, 2), groups[3], groups[2], groups[4]];
+    try {
+      await program.methods
+        .withdrawMany([new BN(100_000)])
+        .accountsPartial({
+          owner: owner.publicKey,
+          merchant,
+          house: HOUSE,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          token2022Program: TOKEN_2022_PROGRAM_ID,
+        })
+        .remainingAccounts(swapped)
+        .signers([owner])
+        .rpc();
+      assert.fail("Expected InvalidWithdrawGroup");
+    } catch (e: any) {
+      assert.ok(e.message.includes("InvalidWithdrawGroup"), e.message);
+    }
+  });
+
+  it("withdraws all available SOL and SPL", async function () {
+    const rentExempt = await provider.connection.getMinimumBalanceForRentExemption(0);
+    await provider.sendAndConfirm(new web3.Transaction().add(
+      web3.SystemProgram.transfer({ fromPubkey: owner.publicKey, toPubkey: vault, lamports: 1_000_000 })
+    ), [owner]);
+
+    const solTx = await program.methods
+      .withdrawSolAll()
+      .accountsPartial({
+        owner: owner.publicKey,
+        merchant,
+        vault,
+        house: HOUSE,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([owner])
+      .rpc();
+    console.log("withdrawSol (all) tx:", solTx);
+    assert.equal(await provider.connection.getBalance(vault), rentExempt);
+
+    await mintTo(provider.connection, stablecoinMintAuthority, stablecoinMint, merchantStablecoinAta, stablecoinMintAuthority, 1_000_000);
+    const splTx = await program.methods
+      .withdrawSplAll()
+      .accountsPartial({
+        owner: owner.publicKey,
+        merchant,
+        stablecoinMint,
+        merchantStablecoinAta,
+        ownerStablecoinAta,
+        house: HOUSE,
+        houseStablecoinAta,
+        associatedTokenProgram: anchor.utils.token.ASSOCIATED_PROGRAM_ID,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([owner])
+      .rpc();
+    console.log("withdrawSpl (all) tx:", splTx);
+    const merchantAta = await getAccount(provider.connection, merchantStablecoinAta);
+    assert.equal(Number(merchantAta.amount), 0);
+
+    // An empty vault is still held to the minimum withdrawal
+    try {
+      await program.methods
+        .withdrawSolAll()
+        .accountsPartial({
+          owner: owner.publicKey,
+          merchant,
+          vault,
+          house: HOUSE,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([owner])
+        .rpc();
+      assert.fail("Expected BelowMinimumWithdrawal");
+    } catch (e: any) {
+      assert.ok(e.message.includes("BelowMinimumWithdrawal"), e.message);
+    }
+  });
+
+  it("cranks a scheduled payout once the interval elapses", async function () {
+    const [payoutSchedule] = PublicKey.findProgramAddressSync([
+      Buffer.from("payout_schedule"),
+      merchant.toBuffer(),
+      stablecoinMint.toBuffer(),
+    ], program.programId);
+
+    await program.methods
+      .setPayoutSchedule(new BN(2), new BN(500_000), owner.publicKey, 50) // 0.5% bounty
+      .accountsPartial({
+        owner: owner.publicKey,
+        merchant,
+        stablecoinMint,
+        payoutSchedule,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([owner])
+      .rpc();
+    await mintTo(provider.connection, stablecoinMintAuthority, stablecoinMint, merchantStablecoinAta, stablecoinMintAuthority, 1_000_000);
+
+    // Any wallet may crank; here the recipient collects the bounty
+    const crank = () => program.methods
+      .crankPayout()
+      .accountsPartial({
+        cranker: recipient.publicKey,
+        merchant,
+        payoutSchedule,
+        stablecoinMint,
+        merchantStablecoinAta,
+        destination: owner.publicKey,
+        destinationStablecoinAta: ownerStablecoinAta,
+        house: HOUSE,
+        houseStablecoinAta,
+        crankerStablecoinAta: recipientStablecoinAta,
+        associatedTokenProgram: anchor.utils.token.ASSOCIATED_PROGRAM_ID,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([recipient])
+      .rpc();
+
+    try {
+      await crank();
+      assert.fail("Expected PayoutNotDue");
+    } catch (e: any) {
+      assert.ok(e.message.includes("PayoutNotDue"), e.message);
+    }
+
+    await new Promise((resolve) => setTimeout(resolve, 3000));
+
+    const ownerBefore = await getAccount(provider.connection, ownerStablecoinAta);
+    const crankerBefore = await getAccount(provider.connection, recipientStablecoinAta);
+    const tx = await crank();
+    console.log("Crank payout tx:", tx);
+
+    const ownerAfter = await getAccount(provider.connection, ownerStablecoinAta);
+    const crankerAfter = await getAccount(provider.connection, recipientStablecoinAta);
+    const merchantAta = await getAccount(provider.connection, merchantStablecoinAta);
+    assert.equal(Number(ownerAfter.amount - ownerBefore.amount), 985_000);
+    assert.equal(Number(crankerAfter.amount - crankerBefore.amount), 5_000);
+    assert.equal(Number(merchantAta.amount), 0);
+
+    await program.methods
+      .cancelPayoutSchedule()
+      .accountsPartial({ owner: owner.publicKey, merchant, payoutSchedule })
+      .signers([owner])
+      .rpc();
+  });
+
+  it("enforces withdrawal velocity limits and delays raising them", async function () {
+    // A dedicated mint keeps these limits away from the other tests
+    const mint = await createMint(provider.connection, stablecoinMintAuthority, stablecoinMintAuthority.publicKey, null, 6);
+    const merchantAta = (await getOrCreateAssociatedTokenAccount(provider.connection, owner, mint, merchant, true)).address;
+    await mintTo(provider.connection, stablecoinMintAuthority, mint, merchantAta, stablecoinMintAuthority, 1_000_000);
+    const [withdrawalLimit] = PublicKey.findProgramAddressSync([
+      Buffer.from("withdrawal_limit"),
+      merchant.toBuffer(),
+      mint.toBuffer(),
+    ], program.programId);
+
+    const setLimit = (maxPerWithdrawal: number, maxPerDay: number) => program.methods
+      .setSplWithdrawalLimit(new BN(maxPerWithdrawal), new BN(maxPerDay))
+      .accountsPartial({
+        owner: owner.publicKey,
+        merchant,
+        stablecoinMint: mint,
+        withdrawalLimit,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([owner])
+      .rpc();
+    const withdraw = (amount: number) => program.methods
+      .withdrawSpl(new BN(amount))
+      .accountsPartial({
+        owner: owner.publicKey,
+        merchant,
+        stablecoinMint: mint,
+        merchantStablecoinAta: merchantAta,
+        withdrawalLimit,
+        ownerStablecoinAta: getAssociatedTokenAddressSync(mint, owner.publicKey),
+        house: HOUSE,
+        houseStablecoinAta: getAssociatedTokenAddressSync(mint, HOUSE, true),
+        associatedTokenProgram: anchor.utils.token.ASSOCIATED_PROGRAM_ID,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([owner])
+      .rpc();
+    const expectLimitExceeded = async (amount: number) => {
+      try {
+        await withdraw(amount);
+        assert.fail("Expected WithdrawalLimitExceeded");
+      } catch (e: any) {
+        assert.ok(e.message.includes("WithdrawalLimitExceeded"), e.message);
+      }
+    };
+
+    // Setting the first limits is a tightening, so it applies immediately
+    await setLimit(200_000, 300_000);
+    await expectLimitExceeded(250_000);
+    const tx = await withdraw(200_000);
+    console.log("Limited withdrawal tx:", tx);
+    await expectLimitExceeded(150_000); // 350_000 in 24h
+
+    // Removing the limits only takes effect after the delay
+    await setLimit(0, 0);
+    let limit = await program.account.withdrawalLimit.fetch(withdrawalLimit);
+    assert.equal(limit.maxPerDay.toNumber(), 300_000);
+    assert.ok(limit.pendingEffectiveAt.toNumber() > Date.now() / 1000);
+    await expectLimitExceeded(150_000);
+
+    // Lowering is instant and cancels the pending raise
+    await setLimit(100_000, 300_000);
+    limit = await program.account.withdrawalLimit.fetch(withdrawalLimit);
+    assert.equal(limit.maxPerWithdrawal.toNumber(), 100_000);
+    assert.equal(limit.pendingEffectiveAt.toNumber(), 0);
+    await withdraw(100_000);
+
+    // Refunds can pay anyone, so they draw on the same daily allowance
+    const originalTxSig = "mockTxSigLimitedRefund";
+    const [limitedRefundRecord] = PublicKey.findProgramAddressSync([
+      Buffer.from("refund"),
+      Buffer.from(originalTxSig),
+    ], program.programId);
+    try {
+      await program.methods
+        .refundSpl(originalTxSig, new BN(50_000))
+        .accountsPartial({
+          owner: owner.publicKey,
+          merchant,
+          stablecoinMint: mint,
+          merchantStablecoinAta: merchantAta,
+          withdrawalLimit,
+          recipientStablecoinAta: getAssociatedTokenAddressSync(mint, recipient.publicKey),
+          refundRecord: limitedRefundRecord,
+          recipient: recipient.publicKey,
+          associatedTokenProgram: anchor.utils.token.ASSOCIATED_PROGRAM_ID,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([owner])
+        .rpc();
+      assert.fail("Expected WithdrawalLimitExceeded");
+    } catch (e: any) {
+      assert.ok(e.message.includes("WithdrawalLimitExceeded"), e.message);
+    }
+  });
+
+  it("sponsors ATA creation on withdrawal and recoups the rent in stablecoins", async function () {
+    const sponsor = Keypair.generate();
+    const airdrop = await provider.connection.requestAirdrop(sponsor.publicKey, web3.LAMPORTS_PER_SOL);
+    await provider.connection.confirmTransaction(airdrop, "confirmed");
+    const [global] = PublicKey.findProgramAddressSync([Buffer.from("global")], program.programId);
+    await program.methods
+      .setRentRecoupRate(new BN(150_000_000)) // $150 per SOL
+      .accountsPartial({ auth: auth.publicKey, global })
+      .signers([auth])
+      .rpc();
+    await program.methods
+      .setFeePayerApproval(sponsor.publicKey, true)
+      .accountsPartial({ auth: auth.publicKey, global })
+      .signers([auth])
+      .rpc();
+
+    const [sponsorshipBudget] = PublicKey.findProgramAddressSync([
+      Buffer.from("sponsorship_budget"),
+      merchant.toBuffer(),
+    ], program.programId);
+    await program.methods
+      .setSponsorshipBudget(new BN(web3.LAMPORTS_PER_SOL / 10), new BN(86_400))
+      .accountsPartial({ auth: auth.publicKey, merchant, sponsorshipBudget, systemProgram: SystemProgram.programId })
+      .signers([auth])
+      .rpc();
+
+    // A fresh mint, so neither the owner nor the house has an ATA yet
+    const mint = await createMint(provider.connection, stablecoinMintAuthority, stablecoinMintAuthority.publicKey, null, 6);
+    const merchantAta = (await getOrCreateAssociatedTokenAccount(provider.connection, owner, mint, merchant, true)).address;
+    const sponsorAta = (await getOrCreateAssociatedTokenAccount(provider.connection, owner, mint, sponsor.publicKey)).address;
+    await mintTo(provider.connection, stablecoinMintAuthority, mint, merchantAta, stablecoinMintAuthority, 10_000_000);
+
+    const ownerSolBefore = await provider.connection.getBalance(owner.publicKey);
+    const sponsorSolBefore = await provider.connection.getBalance(sponsor.publicKey);
+    const tx = await program.methods
+      .withdrawSplAll()
+      .accountsPartial({
+        feePayer: sponsor.publicKey,
+        owner: owner.publicKey,
+        merchant,
+        sponsorshipBudget,
+        stablecoinMint: mint,
+        merchantStablecoinAta: merchantAta,
+        ownerStablecoinAta: getAssociatedTokenAddressSync(mint, owner.publicKey),
+        house: HOUSE,
+        houseStablecoinAta: getAssociatedTokenAddressSync(mint, HOUSE, true),
+        global,
+        feePayerStablecoinAta: sponsorAta,
+        associatedTokenProgram: anchor.utils.token.ASSOCIATED_PROGRAM_ID,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([owner, sponsor])
+      .rpc();
+    console.log("Sponsored withdrawSpl tx:", tx);
+
+    // The owner spent no SOL; the sponsor fronted both ATAs and was paid back in stablecoins
+    assert.equal(await provider.connection.getBalance(owner.publicKey), ownerSolBefore);
+    const rent = sponsorSolBefore - await provider.connection.getBalance(sponsor.publicKey);
+    assert.equal(rent, 2 * await provider.connection.getMinimumBalanceForRentExemption(165));
+    const recouped = Math.floor(rent * 150_000_000 / web3.LAMPORTS_PER_SOL);
+    const sponsorTokens = await getAccount(provider.connection, sponsorAta);
+    assert.equal(Number(sponsorTokens.amount), recouped);
+
+    // The rest of the balance went through the normal split
+    const ownerTokens = await getAccount(provider.connection, getAssociatedTokenAddressSync(mint, owner.publicKey));
+    assert.equal(Number(ownerTokens.amount), Math.floor((10_000_000 - recouped) * 0.99));
+  });
+
+  it("caps fee payer sponsorship with a per-merchant budget", async function () {
+    const [sponsorshipBudget] = PublicKey.findProgramAddressSync([
+      Buffer.from("sponsorship_budget"),
+      merchant.toBuffer(),
+    ], program.programId);
+
+    // Room for exactly one sponsored refund record plus its signature fee
+    const recordRent = await provider.connection.getMinimumBalanceForRentExemption(8 + 4 + 88 + 32 + 1);
+    await program.methods
+      .setSponsorshipBudget(new BN(recordRent + 5000), new BN(86_400))
+      .accountsPartial({ auth: auth.publicKey, merchant, sponsorshipBudget, systemProgram: SystemProgram.programId })
+      .signers([auth])
+      .rpc();
+    await provider.sendAndConfirm(new web3.Transaction().add(
+      web3.SystemProgram.transfer({ fromPubkey: owner.publicKey, toPubkey: vault, lamports: 100_000 })
+    ), [owner]);
+
+    const [global] = PublicKey.findProgramAddressSync([Buffer.from("global")], program.programId);
+    await program.methods
+      .setFeePayerApproval(provider.wallet.publicKey, true)
+      .accountsPartial({ auth: auth.publicKey, global })
+      .signers([auth])
+      .rpc();
+
+    const sponsoredRefund = (originalTxSig: string) => program.methods
+      .refundSol(originalTxSig, new BN(1000))
+      .accountsPartial({
+        feePayer: provider.wallet.publicKey,
+        owner: owner.publicKey,
+        merchant,
+        sponsorshipBudget,
+        global,
+        vault,
+        refundRecord: PublicKey.findProgramAddressSync([Buffer.from("refund"), Buffer.from(originalTxSig)], program.programId)[0],
+        recipient: recipient.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([owner])
+      .rpc();
+
+    const tx = await sponsoredRefund("budgetTxSig1");
+    console.log("Sponsored refund tx:", tx);
+    let budget = await program.account.sponsorshipBudget.fetch(sponsorshipBudget);
+    assert.equal(budget.consumed.toNumber(), recordRent + 5000);
+
+    try {
+      await sponsoredRefund("budgetTxSig2");
+      assert.fail("Expected SponsorshipBudgetExceeded");
+    } catch (e: any) {
+      assert.ok(e.message.includes("SponsorshipBudgetExceeded"), e.message);
+    }
+
+    // An admin top-up restores headroom for the current period
+    await program.methods
+      .topUpSponsorshipBudget(new BN(recordRent + 5000))
+      .accountsPartial({ auth: auth.publicKey, merchant, sponsorshipBudget })
+      .signers([auth])
+      .rpc();
+    await sponsoredRefund("budgetTxSig2");
+    budget = await program.account.sponsorshipBudget.fetch(sponsorshipBudget);
+    assert.equal(budget.consumed.toNumber(), recordRent + 5000);
+  });
+
+  it("rejects fee payers that are not on the approved list", async function () {
+    const [global] = PublicKey.findProgramAddressSync([Buffer.from("global")], program.programId);
+    const [sponsorshipBudget] = PublicKey.findProgramAddressSync([
+      Buffer.from("sponsorship_budget"),
+      merchant.toBuffer(),
+    ], program.programId);
+    await program.methods
+      .setSponsorshipBudget(new BN(web3.LAMPORTS_PER_SOL / 10), new BN(86_400))
+      .accountsPartial({ auth: auth.publicKey, merchant, sponsorshipBudget, systemProgram: SystemProgram.programId })
+      .signers([auth])
+      .rpc();
+
+    const createInvoice = (invoiceId: number, feePayer: Keypair) => {
+      const id = new BN(invoiceId);
+      const [invoice] = PublicKey.findProgramAddressSync([
+        Buffer.from("invoice"),
+        merchant.toBuffer(),
+        id.toArrayLike(Buffer, "le", 8),
+      ], program.programId);
+      return program.methods
+        .createInvoice(id, new BN(1_000_000), new BN(Math.floor(Date.now() / 1000) + 3600), Array(32).fill(0))
+        .accountsPartial({
+          feePayer: feePayer.publicKey,
+          owner: owner.publicKey,
+          merchant,
+          sponsorshipBudget,
+          global,
+          stablecoinMint,
+          invoice,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([owner, feePayer])
+        .rpc();
+    };
+
+    const stranger = Keypair.generate();
+    const airdrop = await provider.connection.requestAirdrop(stranger.publicKey, web3.LAMPORTS_PER_SOL);
+    await provider.connection.confirmTransaction(airdrop, "confirmed");
+    try {
+      await createInvoice(9001, stranger);
+      assert.fail("Expected UnapprovedFeePayer");
+    } catch (e: any) {
+      assert.ok(e.message.includes("UnapprovedFeePayer"), e.message);
+    }
+
+    // The approved sponsor works until it is removed from the list
+    const tx = await createInvoice(9002, auth);
+    console.log("Approved sponsor tx:", tx);
+    await program.methods
+      .setFeePayerApproval(auth.publicKey, false)
+      .accountsPartial({ auth: auth.publicKey, global })
+      .signers([auth])
+      .rpc();
+    try {
+      await createInvoice(9003, auth);
+      assert.fail("Expected UnapprovedFeePayer");
+    } catch (e: any) {
+      assert.ok(e.message.includes("UnapprovedFeePayer"), e.message);
+    }
+  });
+
+  // Decodes the events a transaction emitted through event CPI
+  async function cpiEvents(tx: string) {
+    const txInfo = await provider.connection.getTransaction(tx, { commitment: "confirmed", maxSupportedTransactionVersion: 0 });
+    const accountKeys = txInfo.transaction.message.getAccountKeys();
+    const events = txInfo.meta.innerInstructions
+      .flatMap((inner) => inner.instructions)
+      .filter((ix) => accountKeys.get(ix.programIdIndex).equals(program.programId))
+      .map((ix) => {
+        // Skip the 8-byte event CPI tag; the rest is the event itself
+        const data = Buffer.from(anchor.utils.bytes.bs58.decode(ix.data)).subarray(8);
+        return program.coder.events.decode(data.toString("base64"));
+      });
+    return { txInfo, events };
+  }
+
+  it("emits withdrawal events through a self-CPI instead of the logs", async function () {
+    await provider.sendAndConfirm(new web3.Transaction().add(
+      web3.SystemProgram.transfer({ fromPubkey: owner.publicKey, toPubkey: vault, lamports: 2_000_000 })
+    ), [owner]);
+
+    const tx = await program.methods
+      .withdrawSol(new BN(1_000_000))
+      .accountsPartial({
+        owner: owner.publicKey,
+        merchant,
+        vault,
+        house: HOUSE,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([owner])
+      .rpc({ commitment: "confirmed" });
+    console.log("withdrawSol (event CPI) tx:", tx);
+
+    const { txInfo, events } = await cpiEvents(tx);
+    assert.equal(events.length, 1);
+    assert.equal(events[0].name.toLowerCase(), "withdrawsolprocessed");
+    assert.equal(events[0].data.amount.toNumber(), 1_000_000);
+    assert.ok(!txInfo.meta.logMessages.some((log) => log.startsWith("Program data:")));
+  });
+
+  it("stamps events with a versioned header", async function () {
+    await provider.sendAndConfirm(new web3.Transaction().add(
+      web3.SystemProgram.transfer({ fromPubkey: owner.publicKey, toPubkey: vault, lamports: 2_000_000 })
+    ), [owner]);
+
+    const tx = await program.methods
+      .withdrawSol(new BN(1_000_000))
+      .accountsPartial({
+        owner: owner.publicKey,
+        merchant,
+        vault,
+        house: HOUSE,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([owner])
+      .rpc({ commitment: "confirmed" });
+    console.log("withdrawSol (header) tx:", tx);
+
+    const { txInfo, events } = await cpiEvents(tx);
+    const header = events[0].data.header;
+    assert.equal(header.version, 1);
+    assert.ok(header.merchant.equals(merchant));
+    assert.ok(header.actor.equals(owner.publicKey));
+    assert.ok(header.mint.equals(NATIVE_MINT));
+    assert.equal(header.slot.toNumber(), txInfo.slot);
+    assert.ok(Math.abs(header.timestamp.toNumber() - txInfo.blockTime) <= 5);
+    assert.equal(header.balance.toNumber(), await provider.connection.getBalance(vault, "confirmed"));
+  });
+
   it("closes merchant", async () => {
     const tx = await program.methods
       .closeMerchant()
@@ -1233,16 +3389,38 @@ describe("gotsol", () => {
       Buffer.from(originalTxSig),
     ], program.programId);
     
+    // The owner paid for this record, so closing it must refund the owner rather than the signer
+    const recordRent = await provider.connection.getBalance(refundRecord);
+    try {
+      await program.methods
+        .closeRefund()
+        .accountsPartial({
+          auth: auth.publicKey,
+          refundRecord,
+          rentPayer: auth.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([auth])
+        .rpc();
+      assert.fail("Expected InvalidRentPayer");
+    } catch (e: any) {
+      assert.ok(e.message.includes("InvalidRentPayer"), e.message);
+    }
+
+    const ownerBalanceBefore = await provider.connection.getBalance(owner.publicKey);
     const closeTx = await program.methods
       .closeRefund()
       .accountsPartial({
         auth: auth.publicKey,
         refundRecord,
+        rentPayer: owner.publicKey,
         systemProgram: SystemProgram.programId,
       })
       .signers([auth])
       .rpc();
     console.log("closeRefund tx:", closeTx);
+    const ownerBalanceAfter = await provider.connection.getBalance(owner.publicKey);
+    assert.equal(ownerBalanceAfter - ownerBalanceBefore, recordRent);
     
     try {
       await program.account.refundRecord.fetch(refundRecord);
@@ -23,6 +23,40 @@ function getRpcUrl(network: string): string {
   }
 }
 
+// The fee payer of the GotSOL transaction that created a legacy refund record: our node's
+// fee payer when it sponsored the refund, otherwise the merchant owner
+async function findLegacyRefundFunder(
+  connection: Connection,
+  refundRecord: PublicKey,
+  programId: PublicKey
+): Promise<PublicKey | null> {
+  // Page back to the oldest signature touching the record
+  const signatures: string[] = [];
+  let before: string | undefined;
+  for (;;) {
+    const page = await connection.getSignaturesForAddress(refundRecord, { before, limit: 1000 });
+    signatures.push(...page.map((entry) => entry.signature));
+    if (page.length < 1000) break;
+    before = page[page.length - 1].signature;
+  }
+
+  for (const signature of signatures.reverse()) {
+    const transaction = await connection.getTransaction(signature, {
+      commitment: 'confirmed',
+      maxSupportedTransactionVersion: 0,
+    });
+    if (!transaction || transaction.meta?.err) continue;
+
+    // Skip plain transfers into the address; only the refund itself invokes GotSOL
+    const message = transaction.transaction.message;
+    const keys = message.getAccountKeys({ accountKeysFromLookups: transaction.meta?.loadedAddresses });
+    const invokesGotsol = message.compiledInstructions.some((ix) => keys.get(ix.programIdIndex)?.equals(programId));
+    if (invokesGotsol) return keys.get(0) ?? null;
+  }
+
+  return null;
+}
+
 // GET handler - returns the close refund action metadata
 export async function GET(request: NextRequest) {
   const url = new URL(request.url);
@@ -115,14 +149,46 @@ export async function POST(request: NextRequest) {
       program.programId
     );
     
-    const closeRefundInstruction = await program.methods
-      .closeRefund()
-      .accounts({
-        auth: authPubkey,
-        refundRecord: refundRecordPda,
-        systemProgram: SystemProgram.programId,
-      })
-      .instruction();
+    const refundRecordAccount = await connection.getAccountInfo(refundRecordPda);
+    if (!refundRecordAccount) {
+      return NextResponse.json(
+        { error: 'Refund record not found' },
+        { status: 404, headers: ACTIONS_CORS_HEADERS }
+      );
+    }
+
+    // Records created before the rent payer was stored don't name it, so find who funded them
+    const LEGACY_REFUND_RECORD_LEN = 8 + 4 + 88 + 1;
+    let closeRefundInstruction;
+    if (refundRecordAccount.data.length === LEGACY_REFUND_RECORD_LEN) {
+      const rentPayer = await findLegacyRefundFunder(connection, refundRecordPda, program.programId);
+      if (!rentPayer) {
+        return NextResponse.json(
+          { error: 'Could not find the transaction that created this refund record' },
+          { status: 404, headers: ACTIONS_CORS_HEADERS }
+        );
+      }
+      closeRefundInstruction = await program.methods
+        .closeLegacyRefund(refundTxSig)
+        .accounts({
+          auth: authPubkey,
+          refundRecord: refundRecordPda,
+          rentPayer,
+          systemProgram: SystemProgram.programId,
+        })
+        .instruction();
+    } else {
+      const refundRecord = program.coder.accounts.decode('refundRecord', refundRecordAccount.data);
+      closeRefundInstruction = await program.methods
+        .closeRefund()
+        .accounts({
+          auth: authPubkey,
+          refundRecord: refundRecordPda,
+          rentPayer: refundRecord.rentPayer,
+          systemProgram: SystemProgram.programId,
+        })
+        .instruction();
+    }
     
     transaction.add(closeRefundInstruction);
 
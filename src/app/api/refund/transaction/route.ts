@@ -400,6 +400,14 @@ export async function POST(request: NextRequest) {
           ],
           program.programId
         );
+        const [withdrawalLimitPda] = PublicKey.findProgramAddressSync(
+          [Buffer.from('withdrawal_limit'), merchantPubkey.toBuffer()],
+          program.programId
+        );
+        const [eventAuthorityPda] = PublicKey.findProgramAddressSync(
+          [Buffer.from('__event_authority')],
+          program.programId
+        );
 
         const refundInstruction = await program.methods
           .refundSol(txSigParam, new anchor.BN(amountLamports))
@@ -407,8 +415,11 @@ export async function POST(request: NextRequest) {
             owner: ownerPubkey,
             merchant: merchantPubkey,
             vault: vaultPda,
+            withdrawalLimit: withdrawalLimitPda,
             refundRecord: refundRecordPda,
             recipient: recipientPubkey,
+            eventAuthority: eventAuthorityPda,
+            program: program.programId,
             systemProgram: SystemProgram.programId,
           })
           .instruction();
@@ -417,6 +428,15 @@ export async function POST(request: NextRequest) {
         console.log('Added refund_sol instruction');
       } else {
         // For SPL token refunds
+        const [withdrawalLimitPda] = PublicKey.findProgramAddressSync(
+          [Buffer.from('withdrawal_limit'), merchantPubkey.toBuffer(), tokenMint!.toBuffer()],
+          program.programId
+        );
+        const [eventAuthorityPda] = PublicKey.findProgramAddressSync(
+          [Buffer.from('__event_authority')],
+          program.programId
+        );
+
         const refundInstruction = await program.methods
           .refundSpl(txSigParam, new anchor.BN(amountLamports))
           .accounts({
@@ -424,11 +444,14 @@ export async function POST(request: NextRequest) {
             merchant: merchantPubkey,
             stablecoinMint: tokenMint!,
             merchantStablecoinAta: merchantTokenAta!,
+            withdrawalLimit: withdrawalLimitPda,
             recipientStablecoinAta: recipientTokenAta!,
             refundRecord: refundRecordPda,
             recipient: recipientPubkey,
             tokenProgram: TOKEN_PROGRAM_ID,
             associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+            eventAuthority: eventAuthorityPda,
+            program: program.programId,
             systemProgram: SystemProgram.programId,
           })
           .instruction();
@@ -397,6 +397,14 @@ export async function POST(request: NextRequest) {
           ],
           program.programId
         );
+        const [withdrawalLimitPda] = PublicKey.findProgramAddressSync(
+          [Buffer.from('withdrawal_limit'), merchantPubkey.toBuffer()],
+          program.programId
+        );
+        const [eventAuthorityPda] = PublicKey.findProgramAddressSync(
+          [Buffer.from('__event_authority')],
+          program.programId
+        );
 
         const withdrawInstruction = await program.methods
           .withdrawSol(new anchor.BN(amountLamports))
@@ -404,7 +412,10 @@ export async function POST(request: NextRequest) {
             owner: ownerPubkey,
             merchant: merchantPubkey,
             vault: vaultPda,
+            withdrawalLimit: withdrawalLimitPda,
             house: HOUSE,
+            eventAuthority: eventAuthorityPda,
+            program: program.programId,
             systemProgram: SystemProgram.programId,
           })
           .instruction();
@@ -413,6 +424,15 @@ export async function POST(request: NextRequest) {
         console.log('Added withdraw_sol instruction');
       } else {
         // For SPL token withdrawals
+        const [withdrawalLimitPda] = PublicKey.findProgramAddressSync(
+          [Buffer.from('withdrawal_limit'), merchantPubkey.toBuffer(), tokenMint!.toBuffer()],
+          program.programId
+        );
+        const [eventAuthorityPda] = PublicKey.findProgramAddressSync(
+          [Buffer.from('__event_authority')],
+          program.programId
+        );
+
         const withdrawInstruction = await program.methods
           .withdrawSpl(new anchor.BN(amountLamports))
           .accounts({
@@ -420,11 +440,14 @@ export async function POST(request: NextRequest) {
             merchant: merchantPubkey,
             stablecoinMint: tokenMint!,
             merchantStablecoinAta: merchantTokenAta!,
+            withdrawalLimit: withdrawalLimitPda,
             ownerStablecoinAta: ownerTokenAta!,
             house: HOUSE,
             houseStablecoinAta: houseTokenAta!,
             tokenProgram: TOKEN_PROGRAM_ID,
             associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+            eventAuthority: eventAuthorityPda,
+            program: program.programId,
             systemProgram: SystemProgram.programId,
           })
           .instruction();
@@ -117,6 +117,14 @@ export function RefundButton({ program, merchantPubkey, payment, onSuccess, isDe
                     ],
                     program.programId
                 );
+                const [withdrawalLimit] = PublicKey.findProgramAddressSync(
+                    [Buffer.from('withdrawal_limit'), merchantPda.toBuffer()],
+                    program.programId
+                );
+                const [eventAuthority] = PublicKey.findProgramAddressSync(
+                    [Buffer.from('__event_authority')],
+                    program.programId
+                );
 
                 // Execute SOL refund
                 tx = await program.methods
@@ -125,13 +133,24 @@ export function RefundButton({ program, merchantPubkey, payment, onSuccess, isDe
                         owner: merchantAccount.owner,
                         merchant: merchantPda,
                         vault: vaultPda,
+                        withdrawalLimit,
                         refundRecord,
                         recipient: payment.recipient,
+                        eventAuthority,
+                        program: program.programId,
                         systemProgram: anchor.web3.SystemProgram.programId
                     })
                     .rpc();
             } else {
                 // Execute SPL token refund (existing logic)
+                const [withdrawalLimit] = PublicKey.findProgramAddressSync(
+                    [Buffer.from('withdrawal_limit'), merchantPda.toBuffer(), tokenMint.toBuffer()],
+                    program.programId
+                );
+                const [eventAuthority] = PublicKey.findProgramAddressSync(
+                    [Buffer.from('__event_authority')],
+                    program.programId
+                );
                 tx = await program.methods
                     .refundSpl(signaturePrefix, refundAmount)
                     .accountsPartial({
@@ -139,11 +158,14 @@ export function RefundButton({ program, merchantPubkey, payment, onSuccess, isDe
                         merchant: merchantPda,
                         stablecoinMint: tokenMint,
                         merchantStablecoinAta: merchantTokenAta,
+                        withdrawalLimit,
                         recipientStablecoinAta: recipientTokenAta,
                         refundRecord,
                         recipient: payment.recipient,
                         associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
                         tokenProgram: TOKEN_PROGRAM_ID,
+                        eventAuthority,
+                        program: program.programId,
                         systemProgram: anchor.web3.SystemProgram.programId
                     })
                     .rpc();
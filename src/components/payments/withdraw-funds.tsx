@@ -330,19 +330,38 @@ export function WithdrawFunds({
         // Direct program call using our consistent token config
         if (selectedToken === 'SOL') {
           // Use withdraw_sol for SOL withdrawals
+          const [withdrawalLimit] = PublicKey.findProgramAddressSync(
+            [Buffer.from('withdrawal_limit'), merchantPubkey.toBuffer()],
+            program.programId
+          );
+          const [eventAuthority] = PublicKey.findProgramAddressSync(
+            [Buffer.from('__event_authority')],
+            program.programId
+          );
           const methodBuilder = program.methods
             .withdrawSol(new anchor.BN(withdrawAmountU64.toString()))
             .accountsPartial({
               owner: ownerPubkey,
               merchant: merchantPubkey,
               vault: tokenAddresses.merchantAta, // For SOL, vault is the merchant's native account
+              withdrawalLimit,
               house: HOUSE,
+              eventAuthority,
+              program: program.programId,
               systemProgram: anchor.web3.SystemProgram.programId,
             });
 
           txid = await methodBuilder.rpc();
         } else {
           // Use withdraw_spl for SPL token withdrawals
+          const [withdrawalLimit] = PublicKey.findProgramAddressSync(
+            [Buffer.from('withdrawal_limit'), merchantPubkey.toBuffer(), tokenConfig.mint.toBuffer()],
+            program.programId
+          );
+          const [eventAuthority] = PublicKey.findProgramAddressSync(
+            [Buffer.from('__event_authority')],
+            program.programId
+          );
           const methodBuilder = program.methods
             .withdrawSpl(new anchor.BN(withdrawAmountU64.toString()))
             .accountsPartial({
@@ -350,11 +369,14 @@ export function WithdrawFunds({
               merchant: merchantPubkey,
               stablecoinMint: tokenConfig.mint,
               merchantStablecoinAta: merchantTokenAta,
+              withdrawalLimit,
               ownerStablecoinAta: ownerTokenAta,
               house: HOUSE,
               houseStablecoinAta: houseTokenAta,
               tokenProgram: TOKEN_PROGRAM_ID,
               associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+              eventAuthority,
+              program: program.programId,
               systemProgram: anchor.web3.SystemProgram.programId,
             });
 